@@ -0,0 +1,13 @@
+CREATE TABLE ScheduledTransactions(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `payee_id` CHAR(32) NOT NULL,
+    `amount` DECIMAL(10, 2) NOT NULL,
+    `bank_account_id` CHAR(32) NOT NULL,
+    `budget_id` CHAR(32) NOT NULL,
+    `schedule_id` CHAR(32) NOT NULL,
+    `next_occurrence` DATE NOT NULL,
+    CONSTRAINT FK_ScheduledTransactions_Payee FOREIGN KEY (`payee_id`) REFERENCES Payees(`id`),
+    CONSTRAINT FK_ScheduledTransactions_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`),
+    CONSTRAINT FK_ScheduledTransactions_Budget FOREIGN KEY (`budget_id`) REFERENCES Budgets(`id`),
+    CONSTRAINT FK_ScheduledTransactions_Schedule FOREIGN KEY (`schedule_id`) REFERENCES Schedules(`id`)
+);
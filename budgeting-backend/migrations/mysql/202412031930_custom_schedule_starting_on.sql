@@ -0,0 +1,6 @@
+-- Custom schedules used to have no start date. A scheduled transaction's next occurrence is
+-- always on its schedule, so use that as the anchor. Anything else is left without one.
+UPDATE Schedules
+INNER JOIN ScheduledTransactions ON ScheduledTransactions.schedule_id = Schedules.id
+SET Schedules.`period_starting_on` = ScheduledTransactions.`next_occurrence`
+WHERE Schedules.`period_type` = 'Custom' AND Schedules.`period_starting_on` IS NULL;
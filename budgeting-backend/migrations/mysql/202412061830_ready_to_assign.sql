@@ -0,0 +1,2 @@
+ALTER TABLE Transactions
+MODIFY COLUMN `budget_id` CHAR(32) NULL;
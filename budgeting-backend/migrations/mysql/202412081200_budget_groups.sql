@@ -0,0 +1,11 @@
+CREATE TABLE BudgetGroups(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `sort_order` INT NOT NULL,
+    `user_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_BudgetGroups_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`)
+);
+
+ALTER TABLE Budgets
+ADD COLUMN `group_id` CHAR(32) NULL,
+ADD CONSTRAINT `FK_Budgets_BudgetGroup` FOREIGN KEY (`group_id`) REFERENCES BudgetGroups(`id`) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE Budgets
+ADD COLUMN `archived_at` DATETIME NULL;
@@ -0,0 +1,61 @@
+CREATE TABLE Plans(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `user_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_Plans_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`)
+);
+
+INSERT INTO Plans(id, name, user_id)
+SELECT id, 'My Plan', id FROM Users;
+
+ALTER TABLE Budgets
+ADD COLUMN `plan_id` CHAR(32) NULL;
+
+UPDATE Budgets SET plan_id = user_id;
+
+ALTER TABLE Budgets
+DROP FOREIGN KEY FK_budget_user_id;
+
+ALTER TABLE Budgets
+DROP COLUMN `user_id`,
+MODIFY COLUMN `plan_id` CHAR(32) NOT NULL,
+ADD CONSTRAINT FK_Budgets_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`);
+
+ALTER TABLE BankAccounts
+ADD COLUMN `plan_id` CHAR(32) NULL;
+
+UPDATE BankAccounts SET plan_id = user_id;
+
+ALTER TABLE BankAccounts
+DROP FOREIGN KEY fk_bank_accounts_user;
+
+ALTER TABLE BankAccounts
+DROP COLUMN `user_id`,
+MODIFY COLUMN `plan_id` CHAR(32) NOT NULL,
+ADD CONSTRAINT FK_BankAccounts_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`);
+
+ALTER TABLE Payees
+ADD COLUMN `plan_id` CHAR(32) NULL;
+
+UPDATE Payees SET plan_id = user_id;
+
+ALTER TABLE Payees
+DROP FOREIGN KEY fk_payees_user;
+
+ALTER TABLE Payees
+DROP COLUMN `user_id`,
+MODIFY COLUMN `plan_id` CHAR(32) NOT NULL,
+ADD CONSTRAINT FK_Payees_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`);
+
+ALTER TABLE BudgetGroups
+ADD COLUMN `plan_id` CHAR(32) NULL;
+
+UPDATE BudgetGroups SET plan_id = user_id;
+
+ALTER TABLE BudgetGroups
+DROP FOREIGN KEY FK_BudgetGroups_User;
+
+ALTER TABLE BudgetGroups
+DROP COLUMN `user_id`,
+MODIFY COLUMN `plan_id` CHAR(32) NOT NULL,
+ADD CONSTRAINT FK_BudgetGroups_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`);
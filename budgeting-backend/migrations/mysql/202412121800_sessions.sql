@@ -0,0 +1,22 @@
+ALTER TABLE Users
+ADD COLUMN `password_hash` VARCHAR(255) NULL;
+
+CREATE TABLE Sessions(
+    `token` CHAR(64) NOT NULL PRIMARY KEY,
+    `user_id` CHAR(32) NOT NULL,
+    `expires_at` DATETIME NOT NULL,
+    CONSTRAINT FK_Sessions_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
+
+-- The seeded super-user has no password so can never sign in.
+-- Remove it unless something has been recorded against its plan.
+DELETE FROM Plans
+WHERE user_id = '33e964459e8a4ca1b8d6390cdfde698f'
+    AND id NOT IN (SELECT plan_id FROM Budgets)
+    AND id NOT IN (SELECT plan_id FROM BankAccounts)
+    AND id NOT IN (SELECT plan_id FROM Payees)
+    AND id NOT IN (SELECT plan_id FROM BudgetGroups);
+
+DELETE FROM Users
+WHERE id = '33e964459e8a4ca1b8d6390cdfde698f'
+    AND id NOT IN (SELECT user_id FROM Plans);
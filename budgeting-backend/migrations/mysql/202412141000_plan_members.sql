@@ -0,0 +1,28 @@
+CREATE TABLE PlanMembers(
+    `plan_id` CHAR(32) NOT NULL,
+    `user_id` CHAR(32) NOT NULL,
+    `role` VARCHAR(16) NOT NULL,
+    PRIMARY KEY (`plan_id`, `user_id`),
+    CONSTRAINT FK_PlanMembers_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_PlanMembers_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
+
+INSERT INTO PlanMembers(plan_id, user_id, role)
+SELECT id, user_id, 'Owner' FROM Plans;
+
+ALTER TABLE Plans
+DROP FOREIGN KEY FK_Plans_User;
+
+ALTER TABLE Plans
+DROP COLUMN `user_id`;
+
+CREATE TABLE Invitations(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `plan_id` CHAR(32) NOT NULL,
+    `email` VARCHAR(255) NOT NULL,
+    `role` VARCHAR(16) NOT NULL,
+    `invited_by_user_id` CHAR(32) NOT NULL,
+    CONSTRAINT UQ_Invitations_Plan_Email UNIQUE (`plan_id`, `email`),
+    CONSTRAINT FK_Invitations_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_Invitations_User FOREIGN KEY (`invited_by_user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
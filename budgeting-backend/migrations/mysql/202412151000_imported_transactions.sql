@@ -0,0 +1,8 @@
+CREATE TABLE ImportedTransactions(
+    `transaction_id` CHAR(32) NOT NULL PRIMARY KEY,
+    `bank_account_id` CHAR(32) NOT NULL,
+    `external_id` VARCHAR(255) NOT NULL,
+    CONSTRAINT UQ_ImportedTransactions_BankAccount_ExternalId UNIQUE (`bank_account_id`, `external_id`),
+    CONSTRAINT FK_ImportedTransactions_Transaction FOREIGN KEY (`transaction_id`) REFERENCES Transactions(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_ImportedTransactions_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+CREATE TABLE CsvMappings(
+    `bank_account_id` CHAR(32) NOT NULL PRIMARY KEY,
+    `delimiter` CHAR(1) NOT NULL,
+    `has_header_row` BOOLEAN NOT NULL,
+    `date_column` INT UNSIGNED NOT NULL,
+    `date_format` VARCHAR(32) NOT NULL,
+    `amount_column` INT UNSIGNED NULL,
+    `debit_column` INT UNSIGNED NULL,
+    `credit_column` INT UNSIGNED NULL,
+    `payee_column` INT UNSIGNED NOT NULL,
+    CONSTRAINT FK_CsvMappings_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Emails identify users when signing in and accepting invitations, so they must be unique.
+-- The column's default collation ignores case, so this also rejects the same email in another
+-- case. It fails if duplicates were already registered, those have to be merged by hand first.
+CREATE UNIQUE INDEX UQ_Users_Email ON Users(`email`);
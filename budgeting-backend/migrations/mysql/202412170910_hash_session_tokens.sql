@@ -0,0 +1,5 @@
+-- Only the SHA-256 of a session's token is stored, so the table can't be used to sign in.
+-- Hashing the existing tokens in place keeps everyone signed in.
+UPDATE Sessions SET `token` = SHA2(`token`, 256);
+
+ALTER TABLE Sessions RENAME COLUMN `token` TO `token_hash`;
@@ -0,0 +1,4 @@
+-- Accepting an invitation takes the token it was created with, stored as its SHA-256.
+-- Invitations from before have none and can't be accepted, so they have to be sent again.
+ALTER TABLE Invitations
+ADD COLUMN `token_hash` CHAR(64) NULL;
@@ -0,0 +1,5 @@
+ALTER TABLE CsvMappings ADD COLUMN decimal_separator CHAR(1) NOT NULL DEFAULT '.';
+
+-- Amounts used to be read with whichever separator came last. Mappings that split columns on
+-- semicolons are almost always for exports that use a decimal comma
+UPDATE CsvMappings SET decimal_separator = ',' WHERE delimiter = ';';
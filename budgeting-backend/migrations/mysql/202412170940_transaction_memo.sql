@@ -0,0 +1,2 @@
+-- The bank's description of an imported transaction, alongside its payee
+ALTER TABLE Transactions ADD COLUMN memo TEXT NULL;
@@ -0,0 +1,156 @@
+-- The schema the MySQL migrations up to 202412170940 build, in one go.
+-- Amounts are whole cents, since SQLite has no DECIMAL type.
+
+CREATE TABLE Users(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `email` VARCHAR(255) NOT NULL COLLATE NOCASE,
+    `pay_schedule_id` CHAR(32) NULL,
+    `password_hash` VARCHAR(255) NULL
+);
+
+-- The column is COLLATE NOCASE, so this also rejects the same email in another case.
+CREATE UNIQUE INDEX UQ_Users_Email ON Users(`email`);
+
+CREATE TABLE Plans(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL
+);
+
+CREATE TABLE PlanMembers(
+    `plan_id` CHAR(32) NOT NULL,
+    `user_id` CHAR(32) NOT NULL,
+    `role` VARCHAR(16) NOT NULL,
+    PRIMARY KEY (`plan_id`, `user_id`),
+    CONSTRAINT FK_PlanMembers_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_PlanMembers_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
+
+CREATE TABLE Invitations(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `plan_id` CHAR(32) NOT NULL,
+    `email` VARCHAR(255) NOT NULL COLLATE NOCASE,
+    `role` VARCHAR(16) NOT NULL,
+    `invited_by_user_id` CHAR(32) NOT NULL,
+    `token_hash` CHAR(64) NOT NULL,
+    CONSTRAINT UQ_Invitations_Plan_Email UNIQUE (`plan_id`, `email`),
+    CONSTRAINT FK_Invitations_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_Invitations_User FOREIGN KEY (`invited_by_user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
+
+CREATE TABLE Sessions(
+    `token_hash` CHAR(64) NOT NULL PRIMARY KEY,
+    `user_id` CHAR(32) NOT NULL,
+    `expires_at` DATETIME NOT NULL,
+    CONSTRAINT FK_Sessions_User FOREIGN KEY (`user_id`) REFERENCES Users(`id`) ON DELETE CASCADE
+);
+
+CREATE TABLE Schedules(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `period_type` VARCHAR(32) NOT NULL,
+    `period_starting_on` DATE NULL,
+    `custom_period_type` VARCHAR(32) NULL,
+    `custom_period_every_count` INT NULL
+);
+
+CREATE TABLE Payees(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `plan_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_Payees_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`)
+);
+
+CREATE TABLE BankAccounts(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `initial_amount` INTEGER NOT NULL DEFAULT 0,
+    `plan_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_BankAccounts_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`)
+);
+
+CREATE TABLE BudgetGroups(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `sort_order` INT NOT NULL,
+    `plan_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_BudgetGroups_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`)
+);
+
+CREATE TABLE Budgets(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL,
+    `target_type` VARCHAR(32) NULL,
+    `repeating_target_type` VARCHAR(32) NULL,
+    `target_amount` INTEGER NULL,
+    `target_schedule_id` CHAR(32) NULL,
+    `group_id` CHAR(32) NULL,
+    `archived_at` DATETIME NULL,
+    `plan_id` CHAR(32) NOT NULL,
+    CONSTRAINT FK_budget_schedule_id FOREIGN KEY (`target_schedule_id`) REFERENCES Schedules(`id`),
+    CONSTRAINT FK_Budgets_BudgetGroup FOREIGN KEY (`group_id`) REFERENCES BudgetGroups(`id`) ON DELETE SET NULL,
+    CONSTRAINT FK_Budgets_Plan FOREIGN KEY (`plan_id`) REFERENCES Plans(`id`)
+);
+
+CREATE TABLE Transactions(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `payee_id` CHAR(32) NOT NULL,
+    `date` DATE NOT NULL,
+    `bank_account_id` CHAR(32) NOT NULL,
+    `amount` INTEGER NOT NULL DEFAULT 0,
+    `budget_id` CHAR(32) NULL,
+    `memo` TEXT NULL,
+    FOREIGN KEY (`payee_id`) REFERENCES Payees(`id`),
+    CONSTRAINT fk_transactions_bank_account FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`),
+    CONSTRAINT FK_Transactions_Budget FOREIGN KEY (`budget_id`) REFERENCES Budgets(`id`)
+);
+
+CREATE TABLE BudgetAssignments(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `amount` INTEGER NOT NULL,
+    `budget_id` CHAR(32) NOT NULL,
+    `date` DATE NOT NULL,
+    `assignment_type` VARCHAR(32) NOT NULL,
+    `from_budget_id` CHAR(32) NULL,
+    `from_transaction_id` CHAR(32) NULL,
+    `link_id` CHAR(32) NULL,
+    FOREIGN KEY (`budget_id`) REFERENCES Budgets(`id`),
+    CONSTRAINT FK_BudgetAssignments_FromBudgetId FOREIGN KEY (`from_budget_id`) REFERENCES Budgets(`id`),
+    CONSTRAINT FK_BudgetAssignments_Transaction FOREIGN KEY (`from_transaction_id`) REFERENCES Transactions(`id`)
+);
+
+CREATE TABLE ScheduledTransactions(
+    `id` CHAR(32) NOT NULL PRIMARY KEY,
+    `payee_id` CHAR(32) NOT NULL,
+    `amount` INTEGER NOT NULL,
+    `bank_account_id` CHAR(32) NOT NULL,
+    `budget_id` CHAR(32) NOT NULL,
+    `schedule_id` CHAR(32) NOT NULL,
+    `next_occurrence` DATE NOT NULL,
+    CONSTRAINT FK_ScheduledTransactions_Payee FOREIGN KEY (`payee_id`) REFERENCES Payees(`id`),
+    CONSTRAINT FK_ScheduledTransactions_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`),
+    CONSTRAINT FK_ScheduledTransactions_Budget FOREIGN KEY (`budget_id`) REFERENCES Budgets(`id`),
+    CONSTRAINT FK_ScheduledTransactions_Schedule FOREIGN KEY (`schedule_id`) REFERENCES Schedules(`id`)
+);
+
+CREATE TABLE ImportedTransactions(
+    `transaction_id` CHAR(32) NOT NULL PRIMARY KEY,
+    `bank_account_id` CHAR(32) NOT NULL,
+    `external_id` VARCHAR(255) NOT NULL,
+    CONSTRAINT UQ_ImportedTransactions_BankAccount_ExternalId UNIQUE (`bank_account_id`, `external_id`),
+    CONSTRAINT FK_ImportedTransactions_Transaction FOREIGN KEY (`transaction_id`) REFERENCES Transactions(`id`) ON DELETE CASCADE,
+    CONSTRAINT FK_ImportedTransactions_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`) ON DELETE CASCADE
+);
+
+CREATE TABLE CsvMappings(
+    `bank_account_id` CHAR(32) NOT NULL PRIMARY KEY,
+    `delimiter` CHAR(1) NOT NULL,
+    `has_header_row` BOOLEAN NOT NULL,
+    `date_column` INT NOT NULL,
+    `date_format` VARCHAR(32) NOT NULL,
+    `amount_column` INT NULL,
+    `debit_column` INT NULL,
+    `credit_column` INT NULL,
+    `payee_column` INT NOT NULL,
+    `decimal_separator` CHAR(1) NOT NULL DEFAULT '.',
+    CONSTRAINT FK_CsvMappings_BankAccount FOREIGN KEY (`bank_account_id`) REFERENCES BankAccounts(`id`) ON DELETE CASCADE
+);
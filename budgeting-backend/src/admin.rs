@@ -0,0 +1,215 @@
+//! Maintenance tasks run from the command line rather than through the API
+
+use anyhow::anyhow;
+use chrono::{Datelike, Local, Months, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::join;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DbPool},
+    import::changes::Changes,
+    models::Plan,
+    AppError,
+};
+
+const DEMO_PLAN_NAME: &str = "Demo";
+/// How many months of history the demo plan gets, including the current one
+const DEMO_MONTHS: u32 = 3;
+
+/// Budget group, budget, amount assigned at the start of each month
+const DEMO_ASSIGNMENTS: [(&str, &str, Decimal); 5] = [
+    ("Bills", "Rent", dec!(1400)),
+    ("Bills", "Power", dec!(130)),
+    ("Everyday", "Groceries", dec!(420)),
+    ("Everyday", "Eating Out", dec!(100)),
+    ("Savings", "Holiday", dec!(300)),
+];
+
+/// Day of the month, payee, budget, amount
+const DEMO_TRANSACTIONS: [(u32, &str, Option<&str>, Decimal); 9] = [
+    (1, "Employer", None, dec!(3200)),
+    (2, "Landlord", Some("Rent"), dec!(-1400)),
+    (3, "Supermarket", Some("Groceries"), dec!(-95.40)),
+    (8, "Cafe", Some("Eating Out"), dec!(-38.50)),
+    (10, "Supermarket", Some("Groceries"), dec!(-112.80)),
+    (15, "Power Company", Some("Power"), dec!(-124.35)),
+    (17, "Supermarket", Some("Groceries"), dec!(-87.15)),
+    (22, "Cafe", Some("Eating Out"), dec!(-64)),
+    (24, "Supermarket", Some("Groceries"), dec!(-104.60)),
+];
+
+/// Creates a plan for `user_id` with a few months of made up bank accounts, budgets and
+/// transactions, for trying the app out. Returns the id of the new plan
+pub async fn seed_demo_data(db_pool: &DbPool, user_id: Uuid) -> Result<Uuid, AppError> {
+    db::users::get_single(db_pool, user_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get user")))?;
+
+    let plan = Plan::new(Uuid::new_v4(), DEMO_PLAN_NAME.into());
+
+    db::plans::create(db_pool, plan.clone(), user_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create plan")))?;
+
+    let mut changes = Changes::get(db_pool, plan.id).await?;
+
+    let everyday_id = changes.add_bank_account("Everyday".into(), dec!(1500));
+    changes.add_bank_account("Savings".into(), dec!(4000));
+
+    let today = Local::now().date_naive();
+    let this_month = today.with_day(1).unwrap_or(today);
+
+    for months_ago in (0..DEMO_MONTHS).rev() {
+        let month = this_month - Months::new(months_ago);
+
+        for (group_name, budget_name, amount) in DEMO_ASSIGNMENTS {
+            let budget_id = changes.budget_id(budget_name, Some(group_name));
+            changes.move_from_ready_to_assign(budget_id, amount, month);
+        }
+
+        for (day, payee_name, budget_name, amount) in DEMO_TRANSACTIONS {
+            let date = NaiveDate::from_ymd_opt(month.year(), month.month(), day).unwrap_or(month);
+            if date > today {
+                continue;
+            }
+
+            let payee_id = changes.payee_id(payee_name);
+            let budget_id = budget_name.map(|budget_name| changes.budget_id(budget_name, None));
+
+            changes.add_transaction(everyday_id, date, amount, payee_id, budget_id, None);
+        }
+    }
+
+    changes.save(db_pool).await?;
+
+    Ok(plan.id)
+}
+
+/// Rebuilds each budget's assignments from transactions, so budget balances match the
+/// transactions again after they've been changed outside the app. Only `plan_id` is checked
+/// when it's set, every plan otherwise. Returns how many budgets were fixed
+pub async fn recalculate_balances(
+    db_pool: &DbPool,
+    plan_id: Option<Uuid>,
+) -> Result<usize, AppError> {
+    let plan_ids: Vec<Uuid> = if let Some(plan_id) = plan_id {
+        db::plans::get_single(db_pool, plan_id)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+        vec![plan_id]
+    } else {
+        db::plans::get_all(db_pool)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not get plans")))?
+            .iter()
+            .map(|plan| plan.id)
+            .collect()
+    };
+
+    let mut fixed_count = 0;
+
+    for plan_id in plan_ids {
+        let (budgets_result, bank_accounts_result) = join!(
+            db::budgets::get(db_pool, plan_id, true),
+            db::bank_accounts::get(db_pool, plan_id)
+        );
+
+        let budgets =
+            budgets_result.map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?;
+        let bank_accounts = bank_accounts_result
+            .map_err(|e| e.to_app_error(anyhow!("Could not get bank accounts")))?;
+
+        let mut transactions = Vec::new();
+        for bank_account in &bank_accounts {
+            transactions.extend(
+                db::transactions::get_all(db_pool, bank_account.id)
+                    .await
+                    .map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))?,
+            );
+        }
+
+        let fixed_budgets: Vec<_> = budgets
+            .into_vec()
+            .into_iter()
+            .filter_map(|mut budget| {
+                budget
+                    .sync_transaction_assignments(&transactions)
+                    .then_some(budget)
+            })
+            .collect();
+
+        let mut db_transaction = db::begin(db_pool)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not recalculate balances")))?;
+
+        for budget in &fixed_budgets {
+            db::budgets::update(&mut db_transaction, budget.clone())
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not update budget")))?;
+        }
+
+        db::commit(db_transaction)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not recalculate balances")))?;
+
+        fixed_count += fixed_budgets.len();
+    }
+
+    Ok(fixed_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::TestPool,
+        models::{BudgetAssignmentSource, User},
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    async fn seed_demo_data_and_recalculate_balances(db_pool: TestPool) {
+        let db_pool = DbPool::from(db_pool);
+        let user_id = Uuid::new_v4();
+        db::users::create(
+            &db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+
+        let plan_id = seed_demo_data(&db_pool, user_id).await.unwrap();
+
+        let bank_accounts = db::bank_accounts::get(&db_pool, plan_id).await.unwrap();
+        assert_eq!(bank_accounts.len(), 2);
+
+        // the demo plan's balances already match its transactions
+        let fixed_count = recalculate_balances(&db_pool, Some(plan_id)).await.unwrap();
+        assert_eq!(fixed_count, 0);
+
+        let budgets = db::budgets::get(&db_pool, plan_id, true).await.unwrap();
+        let mut budget = budgets
+            .iter()
+            .find(|budget| budget.name == "Groceries")
+            .unwrap()
+            .clone();
+        let total_assigned = budget.total_assigned();
+        budget.assignments.retain(|assignment| {
+            !matches!(
+                assignment.source,
+                BudgetAssignmentSource::Transaction { .. }
+            )
+        });
+        db::budgets::update(&db_pool, budget.clone()).await.unwrap();
+
+        let fixed_count = recalculate_balances(&db_pool, None).await.unwrap();
+        assert_eq!(fixed_count, 1);
+
+        let budgets = db::budgets::get(&db_pool, plan_id, true).await.unwrap();
+        let fixed_budget = budgets.iter().find(|x| x.id == budget.id).unwrap();
+        assert_eq!(fixed_budget.total_assigned(), total_assigned);
+    }
+}
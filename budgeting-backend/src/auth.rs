@@ -0,0 +1,197 @@
+use anyhow::anyhow;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use chrono::{SubsecRound, TimeDelta, Utc};
+use email_address::EmailAddress;
+use http::{header::AUTHORIZATION, HeaderMap};
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DbPool},
+    models::{CreateUserRequest, Session, User},
+    AppError,
+};
+
+const SESSION_LENGTH_DAYS: i64 = 30;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub fn hash_password(password: &str) -> Result<String, anyhow::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow!("Could not hash password: {e}"))
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// Creates a user who can sign in with `request`'s email and password.
+/// Emails must be unique and passwords at least [`MIN_PASSWORD_LENGTH`] characters
+pub async fn create_user(db_pool: &DbPool, request: CreateUserRequest) -> Result<Uuid, AppError> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::BadRequest(anyhow!("User name must not be empty")));
+    }
+
+    if !EmailAddress::is_valid(&request.email) {
+        return Err(AppError::BadRequest(anyhow!(
+            "\"{}\" is not a valid email address",
+            request.email
+        )));
+    }
+
+    if request.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::BadRequest(anyhow!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let email = request.email.trim();
+
+    let password_hash = hash_password(&request.password)?;
+
+    let id = Uuid::new_v4();
+
+    let mut db_transaction = db::begin(db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create user")))?;
+
+    db::users::create(
+        &mut db_transaction,
+        User::new(id, request.name.trim().into(), email.into(), None),
+    )
+    .await
+    .map_err(|e| match e {
+        // emails are unique ignoring case
+        db::Error::Duplicate => AppError::BadRequest(anyhow!("\"{email}\" is already registered")),
+        e => e.to_app_error(anyhow!("Could not create user")),
+    })?;
+
+    db::users::set_password_hash(&mut db_transaction, id, &password_hash)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create user")))?;
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create user")))?;
+
+    Ok(id)
+}
+
+/// A random 64 character token, for sessions and invitations
+pub fn new_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Starts a new session for the user with a new token
+pub fn new_session(user_id: Uuid) -> Session {
+    Session::new(
+        new_token(),
+        user_id,
+        Utc::now().trunc_subsecs(0) + TimeDelta::days(SESSION_LENGTH_DAYS),
+    )
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// The user signed in with the request's bearer token.
+/// Rejects the request with 401 if the token is missing, unknown or expired
+#[derive(Clone, Debug)]
+pub struct AuthenticatedUser(pub User);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthenticatedUser
+where
+    DbPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // already looked up by the sign in layer
+        if let Some(user) = parts.extensions.get::<Self>() {
+            return Ok(user.clone());
+        }
+
+        let token = bearer_token(&parts.headers)
+            .ok_or_else(|| AppError::Unauthorized(anyhow!("Sign in to continue")))?;
+
+        let db_pool = DbPool::from_ref(state);
+
+        let session = match db::sessions::get_single(&db_pool, token).await {
+            Ok(session) if session.expires_at > Utc::now() => session,
+            Ok(_) | Err(db::Error::NotFound) => {
+                return Err(AppError::Unauthorized(anyhow!(
+                    "Session has expired, sign in again"
+                )))
+            }
+            Err(e) => return Err(e.to_app_error(anyhow!("Could not check session"))),
+        };
+
+        let user = db::users::get_single(&db_pool, session.user_id)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not get signed in user")))?;
+
+        let authenticated_user = Self(user);
+        parts.extensions.insert(authenticated_user.clone());
+
+        Ok(authenticated_user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn verify_password_test() {
+        let hash = hash_password("correct horse").unwrap();
+
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[test]
+    pub fn bearer_token_test() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), Some("abc"));
+    }
+
+    #[test]
+    pub fn new_session_test() {
+        let user_id = Uuid::new_v4();
+        let session = new_session(user_id);
+
+        assert_eq!(session.token.len(), 64);
+        assert_eq!(session.user_id, user_id);
+        assert!(session.expires_at > Utc::now());
+    }
+}
@@ -1,15 +1,263 @@
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
+use sqlx::{
+    encode::IsNull, error::BoxDynError, Database, Decode, Encode, MySql, MySqlConnection,
+    MySqlPool, Sqlite, SqliteConnection, SqlitePool, Transaction, Type,
+};
+
 use crate::AppError;
 
 pub mod bank_accounts;
+pub mod budget_groups;
 pub mod budgets;
+pub mod csv_mappings;
+pub mod imported_transactions;
+#[cfg(test)]
+pub mod in_memory;
+pub mod invitations;
+pub mod ownership;
 pub mod payees;
+pub mod plan_members;
+pub mod plans;
+pub mod ready_to_assign;
+pub mod repositories;
 pub mod schedule;
+pub mod scheduled_transactions;
+pub mod sessions;
 pub mod transactions;
 pub mod users;
 
+/// A pool for whichever database `DATABASE_URL` names, see [`crate::init_db`].
+///
+/// The two databases have their own sqlx types, so a query is written once inside
+/// [`with_connection`] and built for each of them
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    MySql(MySqlPool),
+    Sqlite(SqlitePool),
+}
+
+impl From<MySqlPool> for DbPool {
+    fn from(value: MySqlPool) -> Self {
+        Self::MySql(value)
+    }
+}
+
+impl From<SqlitePool> for DbPool {
+    fn from(value: SqlitePool) -> Self {
+        Self::Sqlite(value)
+    }
+}
+
+/// The pool the `#[sqlx::test]` suites get, which is for the `MySQL` database at
+/// `DATABASE_URL`, or a throwaway `SQLite` file with the `sqlite-tests` feature
+#[cfg(not(feature = "sqlite-tests"))]
+pub type TestPool = MySqlPool;
+#[cfg(feature = "sqlite-tests")]
+pub type TestPool = SqlitePool;
+
+/// A transaction from [`begin`], on either database
+#[derive(Debug)]
+pub enum DbTransaction {
+    MySql(Transaction<'static, MySql>),
+    Sqlite(Transaction<'static, Sqlite>),
+}
+
+/// Where a query runs: the pool, or a single connection, which is usually in a transaction.
+/// Functions in `db` take `impl Into<DbExecutor>`, so they're called with `&db_pool`,
+/// `&mut db_transaction` or the connection another query runs on
+#[derive(Debug)]
+pub enum DbExecutor<'c> {
+    MySqlPool(&'c MySqlPool),
+    SqlitePool(&'c SqlitePool),
+    MySql(&'c mut MySqlConnection),
+    Sqlite(&'c mut SqliteConnection),
+}
+
+impl<'c> From<&'c DbPool> for DbExecutor<'c> {
+    fn from(value: &'c DbPool) -> Self {
+        match value {
+            DbPool::MySql(pool) => Self::MySqlPool(pool),
+            DbPool::Sqlite(pool) => Self::SqlitePool(pool),
+        }
+    }
+}
+
+impl<'c> From<&'c MySqlPool> for DbExecutor<'c> {
+    fn from(value: &'c MySqlPool) -> Self {
+        Self::MySqlPool(value)
+    }
+}
+
+impl<'c> From<&'c SqlitePool> for DbExecutor<'c> {
+    fn from(value: &'c SqlitePool) -> Self {
+        Self::SqlitePool(value)
+    }
+}
+
+impl<'c> From<&'c mut DbTransaction> for DbExecutor<'c> {
+    fn from(value: &'c mut DbTransaction) -> Self {
+        match value {
+            DbTransaction::MySql(transaction) => Self::MySql(transaction),
+            DbTransaction::Sqlite(transaction) => Self::Sqlite(transaction),
+        }
+    }
+}
+
+impl<'c> From<&'c mut MySqlConnection> for DbExecutor<'c> {
+    fn from(value: &'c mut MySqlConnection) -> Self {
+        Self::MySql(value)
+    }
+}
+
+impl<'c> From<&'c mut SqliteConnection> for DbExecutor<'c> {
+    fn from(value: &'c mut SqliteConnection) -> Self {
+        Self::Sqlite(value)
+    }
+}
+
+/// A connection to either database, see [`with_connection`]
+#[derive(Debug)]
+pub enum DbConnection<'c> {
+    MySql(&'c mut MySqlConnection),
+    Sqlite(&'c mut SqliteConnection),
+}
+
+/// Runs `$body` for whichever database `$executor` is for, with `$connection` bound to a
+/// `&mut` connection to it and `Db` naming its sqlx type. The body is built for both
+/// databases, so its queries have to be valid on each
+macro_rules! with_connection {
+    ($executor:expr, |$connection:ident| $body:expr) => {{
+        let mut mysql_pool_connection;
+        let mut sqlite_pool_connection;
+        let executor: $crate::db::DbExecutor<'_> = $executor.into();
+        let connection = match executor {
+            $crate::db::DbExecutor::MySqlPool(pool) => {
+                mysql_pool_connection = pool.acquire().await?;
+                $crate::db::DbConnection::MySql(&mut *mysql_pool_connection)
+            }
+            $crate::db::DbExecutor::SqlitePool(pool) => {
+                sqlite_pool_connection = pool.acquire().await?;
+                $crate::db::DbConnection::Sqlite(&mut *sqlite_pool_connection)
+            }
+            $crate::db::DbExecutor::MySql(connection) => {
+                $crate::db::DbConnection::MySql(connection)
+            }
+            $crate::db::DbExecutor::Sqlite(connection) => {
+                $crate::db::DbConnection::Sqlite(connection)
+            }
+        };
+        match connection {
+            $crate::db::DbConnection::MySql($connection) => {
+                #[allow(unused)]
+                type Db = sqlx::MySql;
+                $body
+            }
+            $crate::db::DbConnection::Sqlite($connection) => {
+                #[allow(unused)]
+                type Db = sqlx::Sqlite;
+                $body
+            }
+        }
+    }};
+}
+pub(crate) use with_connection;
+
+/// What the SQL of the two databases differs in
+pub trait Backend: Database {
+    /// Ends a `SELECT` in a transaction to lock the rows it reads until the transaction ends.
+    /// `SQLite` has no row locks, instead a transaction that writes after another wrote
+    /// since it read fails, so there are no lost updates there either
+    const FOR_UPDATE: &'static str;
+}
+
+impl Backend for MySql {
+    const FOR_UPDATE: &'static str = " FOR UPDATE";
+}
+
+impl Backend for Sqlite {
+    const FOR_UPDATE: &'static str = "";
+}
+
+/// A decimal amount as it's stored in the database, which is a `DECIMAL(10, 2)` column in
+/// `MySQL`. `SQLite` has no decimal type, so there it's whole cents in an `INTEGER` column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DbDecimal(pub Decimal);
+
+impl From<DbDecimal> for Decimal {
+    fn from(value: DbDecimal) -> Self {
+        value.0
+    }
+}
+
+impl Type<MySql> for DbDecimal {
+    fn type_info() -> <MySql as Database>::TypeInfo {
+        <Decimal as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &<MySql as Database>::TypeInfo) -> bool {
+        <Decimal as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, MySql> for DbDecimal {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <MySql as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <Decimal as Encode<MySql>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, MySql> for DbDecimal {
+    fn decode(value: <MySql as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self(<Decimal as Decode<MySql>>::decode(value)?))
+    }
+}
+
+impl Type<Sqlite> for DbDecimal {
+    fn type_info() -> <Sqlite as Database>::TypeInfo {
+        <i64 as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &<Sqlite as Database>::TypeInfo) -> bool {
+        <i64 as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for DbDecimal {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Sqlite as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        use rust_decimal::{prelude::ToPrimitive, RoundingStrategy};
+
+        // rounds the way MySQL does when storing into a DECIMAL(10, 2)
+        let cents = (self.0 * Decimal::ONE_HUNDRED)
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            .to_i64()
+            .ok_or_else(|| format!("{} is too large to store", self.0))?;
+
+        <i64 as Encode<Sqlite>>::encode_by_ref(&cents, buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for DbDecimal {
+    fn decode(value: <Sqlite as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self(Decimal::new(
+            <i64 as Decode<Sqlite>>::decode(value)?,
+            2,
+        )))
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     NotFound,
+    /// The user can see the resource but their role in its plan doesn't let them change it
+    Forbidden,
+    /// A unique column already has the value, like a registered email
+    Duplicate,
     MappingError { error: anyhow::Error },
     Unknown { error: anyhow::Error },
 }
@@ -19,6 +267,8 @@ impl Error {
     pub fn to_app_error(self, error: anyhow::Error) -> AppError {
         match self {
             Self::NotFound => AppError::NotFound(error),
+            Self::Forbidden => AppError::Forbidden(error),
+            Self::Duplicate => AppError::BadRequest(error),
             Self::Unknown { error }
             | Self::MappingError { error } => AppError::InternalServerError(error),
         }
@@ -28,6 +278,8 @@ impl Error {
         match self {
             Self::NotFound
             | Self::Unknown { .. } => AppError::NotFound(error),
+            Self::Forbidden => AppError::Forbidden(error),
+            Self::Duplicate => AppError::BadRequest(error),
             Self::MappingError { error } => AppError::InternalServerError(error),
         }
     }
@@ -37,7 +289,34 @@ impl From<sqlx::Error> for Error {
     fn from(value: sqlx::Error) -> Self {
         match value {
             sqlx::Error::RowNotFound => Self::NotFound,
+            sqlx::Error::Database(err) if err.is_unique_violation() => Self::Duplicate,
             err => Error::Unknown { error: err.into() },
         }
     }
 }
+
+/// Secret tokens, like a session's, are stored as their SHA-256 so they can't be read back out of
+/// the database
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token))
+}
+
+/// Starts a database transaction for handlers that write to more than one table.
+///
+/// The transaction is rolled back if it is dropped before [`commit`] is called,
+/// so returning early with `?` doesn't leave partial changes behind
+pub async fn begin(db_pool: &DbPool) -> Result<DbTransaction, Error> {
+    Ok(match db_pool {
+        DbPool::MySql(pool) => DbTransaction::MySql(pool.begin().await?),
+        DbPool::Sqlite(pool) => DbTransaction::Sqlite(pool.begin().await?),
+    })
+}
+
+pub async fn commit(db_transaction: DbTransaction) -> Result<(), Error> {
+    match db_transaction {
+        DbTransaction::MySql(transaction) => transaction.commit().await?,
+        DbTransaction::Sqlite(transaction) => transaction.commit().await?,
+    }
+
+    Ok(())
+}
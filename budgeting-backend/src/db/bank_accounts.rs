@@ -1,18 +1,18 @@
 use rust_decimal::Decimal;
-use sqlx::{prelude::FromRow, MySql, MySqlPool};
+use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
 use crate::models::{BankAccount, CreateBankAccountRequest};
 
-use super::Error;
+use super::{with_connection, DbDecimal, DbExecutor, Error};
 
 #[derive(FromRow)]
 struct BankAccountDbModel {
     id: uuid::fmt::Simple,
     name: String,
-    initial_amount: Decimal,
-    user_id: uuid::fmt::Simple,
-    transaction_total: Option<Decimal>,
+    initial_amount: DbDecimal,
+    plan_id: uuid::fmt::Simple,
+    transaction_total: Option<DbDecimal>,
 }
 
 impl TryFrom<BankAccountDbModel> for BankAccount {
@@ -20,100 +20,131 @@ impl TryFrom<BankAccountDbModel> for BankAccount {
 
     fn try_from(value: BankAccountDbModel) -> Result<Self, Self::Error> {
         let id: Uuid = value.id.into_uuid();
-        let user_id: Uuid = value.user_id.into_uuid();
+        let plan_id: Uuid = value.plan_id.into_uuid();
+        let initial_amount: Decimal = value.initial_amount.into();
+        let transaction_total = value.transaction_total.map_or(Decimal::ZERO, Decimal::from);
 
         Ok(BankAccount {
             id,
-            user_id,
-            initial_amount: value.initial_amount,
+            plan_id,
+            initial_amount,
             name: value.name,
-            balance: value.initial_amount + value.transaction_total.unwrap_or(Decimal::ZERO),
+            balance: initial_amount + transaction_total,
         })
     }
 }
 
-pub async fn get(db_pool: &MySqlPool, user_id: Uuid) -> Result<Box<[BankAccount]>, Error> {
-    let bank_accounts: Vec<BankAccount> = sqlx::query_as::<MySql, BankAccountDbModel>(
-        r"
-         SELECT ba.id, ba.name, ba.initial_amount, ba.user_id, SUM(t.amount) as transaction_total
-         FROM BankAccounts ba
-         LEFT JOIN Transactions t ON ba.id = t.bank_account_id
-         WHERE user_id = ?
-         GROUP BY ba.id, ba.name, ba.initial_amount, ba.user_id",
-    )
-    .bind(user_id.simple())
-    .fetch_all(db_pool)
-    .await?
-    .into_iter()
-    .map(|bank_account| bank_account.try_into().unwrap())
-    .collect();
-
-    Ok(bank_accounts.into_boxed_slice())
+pub async fn get(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+) -> Result<Box<[BankAccount]>, Error> {
+    with_connection!(executor, |connection| {
+        let bank_accounts: Vec<BankAccount> = sqlx::query_as::<Db, BankAccountDbModel>(
+            r"
+             SELECT ba.id, ba.name, ba.initial_amount, ba.plan_id, SUM(t.amount) as transaction_total
+             FROM BankAccounts ba
+             LEFT JOIN Transactions t ON ba.id = t.bank_account_id
+             WHERE plan_id = ?
+             GROUP BY ba.id, ba.name, ba.initial_amount, ba.plan_id",
+        )
+        .bind(plan_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|bank_account| bank_account.try_into().unwrap())
+        .collect();
+
+        Ok(bank_accounts.into_boxed_slice())
+    })
 }
 
 pub async fn get_single(
-    db_pool: &MySqlPool,
+    executor: impl Into<DbExecutor<'_>>,
     account_id: Uuid,
-    user_id: Uuid,
+    plan_id: Uuid,
 ) -> Result<BankAccount, Error> {
-    sqlx::query_as::<MySql, BankAccountDbModel>(
-        r"
-        SELECT ba.id, ba.name, ba.initial_amount, ba.user_id, SUM(t.amount) as transaction_total
-         FROM BankAccounts ba
-         LEFT JOIN Transactions t ON ba.id = t.bank_account_id
-         WHERE user_id = ?
-         AND ba.id = ?
-         GROUP BY ba.id, ba.name, ba.initial_amount, ba.user_id",
-    )
-    .bind(user_id.simple())
-    .bind(account_id.simple())
-    .fetch_optional(db_pool)
-    .await?
-    .map(|account| account.try_into().unwrap())
-    .ok_or(Error::NotFound)
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, BankAccountDbModel>(
+            r"
+            SELECT ba.id, ba.name, ba.initial_amount, ba.plan_id, SUM(t.amount) as transaction_total
+             FROM BankAccounts ba
+             LEFT JOIN Transactions t ON ba.id = t.bank_account_id
+             WHERE plan_id = ?
+             AND ba.id = ?
+             GROUP BY ba.id, ba.name, ba.initial_amount, ba.plan_id",
+        )
+        .bind(plan_id.simple())
+        .bind(account_id.simple())
+        .fetch_optional(connection)
+        .await?
+        .map(|account| account.try_into().unwrap())
+        .ok_or(Error::NotFound)
+    })
+}
+
+pub async fn get_plan_id(
+    executor: impl Into<DbExecutor<'_>>,
+    account_id: Uuid,
+) -> Result<Uuid, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_scalar::<Db, uuid::fmt::Simple>("SELECT plan_id FROM BankAccounts WHERE id = ?")
+            .bind(account_id.simple())
+            .fetch_optional(connection)
+            .await?
+            .map(uuid::fmt::Simple::into_uuid)
+            .ok_or(Error::NotFound)
+    })
 }
 
 pub async fn create(
-    db_pool: &MySqlPool,
+    executor: impl Into<DbExecutor<'_>>,
     id: Uuid,
     request: CreateBankAccountRequest,
 ) -> Result<(), Error> {
-    sqlx::query!(
-        "INSERT INTO BankAccounts (id, name, user_id, initial_amount) VALUE(?, ?, ?, ?)",
-        id.as_simple(),
-        request.name,
-        request.user_id.as_simple(),
-        request.initial_amount
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+    with_connection!(executor, |connection| {
+        sqlx::query(
+            "INSERT INTO BankAccounts (id, name, plan_id, initial_amount) VALUES (?, ?, ?, ?)",
+        )
+        .bind(id.simple())
+        .bind(request.name)
+        .bind(request.plan_id.simple())
+        .bind(DbDecimal(request.initial_amount))
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
 }
 
-pub async fn delete(db_pool: &MySqlPool, account_id: Uuid) -> Result<(), Error> {
-    sqlx::query!(
-        "DELETE FROM BankAccounts WHERE id = ?",
-        account_id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, account_id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM BankAccounts WHERE id = ?")
+            .bind(account_id.simple())
+            .execute(connection)
+            .await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, id: Uuid, name: &str) -> Result<(), Error> {
-    sqlx::query!(
-        "UPDATE BankAccounts
-    SET name = ?
-    WHERE id = ?",
-        name,
-        id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+pub async fn update(
+    executor: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+    name: &str,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query(
+            "UPDATE BankAccounts
+        SET name = ?
+        WHERE id = ?",
+        )
+        .bind(name)
+        .bind(id.simple())
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
 }
 
 #[cfg(test)]
@@ -123,20 +154,20 @@ mod tests {
     use chrono::NaiveDate;
     use rust_decimal_macros::dec;
 
+    use crate::models::Transaction;
+    use super::*;
     use crate::{
-        db,
+        db::{self, TestPool},
         extensions::decimal::DecimalExt,
         models::{Budget, CreatePayeeRequest, User},
     };
-    use crate::models::Transaction;
-    use super::*;
 
     static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
     static BANK_ACCOUNT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
     static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
     static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-    async fn test_init(db_pool: &MySqlPool) {
+    async fn test_init(db_pool: &TestPool) {
         let user_id = *USER_ID;
         let payee_id = *PAYEE_ID;
         let budget_id = *BUDGET_ID;
@@ -158,14 +189,14 @@ mod tests {
 
         db::budgets::create(
             db_pool,
-            Budget::new(budget_id, "name".into(), None, user_id, vec![]),
+            Budget::new(budget_id, "name".into(), None, user_id, vec![], None, None),
         )
         .await
         .unwrap();
     }
 
-    #[sqlx::test]
-    pub async fn test_create_and_get_all(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn test_create_and_get_all(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let bank_account_id = *BANK_ACCOUNT_ID;
@@ -214,8 +245,8 @@ mod tests {
         assert!(get_single_balance.approximately_eq(expected_balance, dec!(0.001)));
     }
 
-    #[sqlx::test]
-    pub async fn test_create_and_get_single(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn test_create_and_get_single(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let bank_account_id = *BANK_ACCOUNT_ID;
@@ -236,9 +267,10 @@ mod tests {
                 id: Uuid::new_v4(),
                 bank_account_id,
                 payee_id,
-                budget_id,
+                budget_id: Some(budget_id),
                 amount: dec!(3.13),
-                date: NaiveDate::from_ymd_opt(2024, 10, 6).unwrap()
+                date: NaiveDate::from_ymd_opt(2024, 10, 6).unwrap(),
+                memo: None,
             }
         )
         .await
@@ -280,8 +312,8 @@ mod tests {
         assert!(get_single_balance.approximately_eq(expected_balance, dec!(0.001)));
     }
 
-    #[sqlx::test]
-    pub async fn test_delete(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn test_delete(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let user_id = *USER_ID;
@@ -302,8 +334,8 @@ mod tests {
         assert!(matches!(get_result, Err(Error::NotFound)));
     }
 
-    #[sqlx::test]
-    pub async fn test_update(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn test_update(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let user_id = *USER_ID;
@@ -0,0 +1,187 @@
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::BudgetGroup;
+
+use super::{with_connection, DbExecutor, Error};
+
+#[derive(PartialEq, Debug, FromRow)]
+struct BudgetGroupDbModel {
+    id: uuid::fmt::Simple,
+    name: String,
+    sort_order: i32,
+    plan_id: uuid::fmt::Simple,
+}
+
+impl From<BudgetGroupDbModel> for BudgetGroup {
+    fn from(value: BudgetGroupDbModel) -> Self {
+        Self {
+            id: value.id.into_uuid(),
+            name: value.name,
+            sort_order: value.sort_order,
+            plan_id: value.plan_id.into_uuid(),
+        }
+    }
+}
+
+pub async fn create(
+    executor: impl Into<DbExecutor<'_>>,
+    budget_group: BudgetGroup,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO BudgetGroups(id, name, sort_order, plan_id) VALUES (?, ?, ?, ?)")
+            .bind(budget_group.id.simple())
+            .bind(budget_group.name)
+            .bind(budget_group.sort_order)
+            .bind(budget_group.plan_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+) -> Result<Box<[BudgetGroup]>, Error> {
+    with_connection!(executor, |connection| {
+        let budget_groups = sqlx::query_as::<Db, BudgetGroupDbModel>(
+            "SELECT id, name, sort_order, plan_id FROM BudgetGroups WHERE plan_id = ? ORDER BY sort_order, name",
+        )
+        .bind(plan_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+        Ok(budget_groups)
+    })
+}
+
+pub async fn get_single(
+    executor: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+) -> Result<BudgetGroup, Error> {
+    with_connection!(executor, |connection| {
+        Ok(sqlx::query_as::<Db, BudgetGroupDbModel>(
+            "SELECT id, name, sort_order, plan_id FROM BudgetGroups WHERE id = ?",
+        )
+        .bind(id.simple())
+        .fetch_optional(connection)
+        .await?
+        .ok_or(Error::NotFound)?
+        .into())
+    })
+}
+
+pub async fn update(
+    executor: impl Into<DbExecutor<'_>>,
+    budget_group: BudgetGroup,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE BudgetGroups SET name = ?, sort_order = ? WHERE id = ?")
+            .bind(budget_group.name)
+            .bind(budget_group.sort_order)
+            .bind(budget_group.id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+/// Budgets in the group are kept, they just no longer belong to a group
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM BudgetGroups WHERE id = ?")
+            .bind(id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use crate::{
+        db::{self, TestPool},
+        models::{Budget, User},
+    };
+
+    use super::*;
+
+    static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+    async fn test_init(db_pool: &TestPool) {
+        db::users::create(
+            db_pool,
+            User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let bills = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 1, *USER_ID);
+        let everyday = BudgetGroup::new(Uuid::new_v4(), "Everyday".into(), 0, *USER_ID);
+
+        create(&db_pool, bills.clone()).await.unwrap();
+        create(&db_pool, everyday.clone()).await.unwrap();
+
+        let fetched = get(&db_pool, *USER_ID).await.unwrap();
+        assert_eq!(fetched, vec![everyday, bills.clone()].into_boxed_slice());
+
+        let fetched_single = get_single(&db_pool, bills.id).await.unwrap();
+        assert_eq!(fetched_single, bills);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let mut budget_group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 0, *USER_ID);
+        create(&db_pool, budget_group.clone()).await.unwrap();
+
+        budget_group.name = "Monthly bills".into();
+        budget_group.sort_order = 3;
+        update(&db_pool, budget_group.clone()).await.unwrap();
+
+        let fetched = get_single(&db_pool, budget_group.id).await.unwrap();
+        assert_eq!(fetched, budget_group);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_keeps_budgets(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let budget_group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 0, *USER_ID);
+        create(&db_pool, budget_group.clone()).await.unwrap();
+
+        let budget = Budget::new(
+            Uuid::new_v4(),
+            "Rent".into(),
+            None,
+            *USER_ID,
+            vec![],
+            Some(budget_group.id),
+            None,
+        );
+        db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+        delete(&db_pool, budget_group.id).await.unwrap();
+
+        let result = get_single(&db_pool, budget_group.id).await;
+        assert!(matches!(result, Err(Error::NotFound)));
+
+        let fetched_budget = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+        assert_eq!(fetched_budget.group_id, None);
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
-use sqlx::{prelude::Type, FromRow, MySql, MySqlPool, QueryBuilder};
+use sqlx::{prelude::Type, Acquire, FromRow, QueryBuilder};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 use crate::models::{Budget, BudgetAssignment, BudgetAssignmentSource, BudgetTarget, Schedule};
 
-use super::{schedule, Error};
+use super::{schedule, with_connection, Backend, DbDecimal, DbExecutor, Error};
 
 #[derive(Clone, Debug, PartialEq, FromRow)]
 struct BudgetDbModel {
@@ -17,9 +17,11 @@ struct BudgetDbModel {
     name: String,
     target_type: Option<String>,
     repeating_target_type: Option<String>,
-    target_amount: Option<Decimal>,
+    target_amount: Option<DbDecimal>,
     target_schedule_id: Option<uuid::fmt::Simple>,
-    user_id: uuid::fmt::Simple,
+    plan_id: uuid::fmt::Simple,
+    group_id: Option<uuid::fmt::Simple>,
+    archived_at: Option<DateTime<Utc>>,
     #[sqlx(skip)]
     assignments: Vec<BudgetAssignmentDbModel>,
 }
@@ -28,7 +30,7 @@ struct BudgetDbModel {
 struct BudgetAssignmentDbModel {
     budget_id: uuid::fmt::Simple,
     id: uuid::fmt::Simple,
-    amount: Decimal,
+    amount: DbDecimal,
     date: NaiveDate,
     assignment_type: String,
     from_budget_id: Option<uuid::fmt::Simple>,
@@ -40,6 +42,7 @@ struct BudgetAssignmentDbModel {
 enum BudgetAssignmentType {
     MoveBetweenBudgets,
     FromTransaction,
+    FromReadyToAssign,
 }
 
 impl FromStr for BudgetAssignmentType {
@@ -49,6 +52,7 @@ impl FromStr for BudgetAssignmentType {
         match s {
             "MoveBetweenBudgets" => Ok(BudgetAssignmentType::MoveBetweenBudgets),
             "FromTransaction" => Ok(BudgetAssignmentType::FromTransaction),
+            "FromReadyToAssign" => Ok(BudgetAssignmentType::FromReadyToAssign),
             _ => Err(anyhow!("{s} is not a valid BudgetAssignmentType"))
         }
     }
@@ -59,6 +63,7 @@ impl Display for BudgetAssignmentType {
         match self {
             BudgetAssignmentType::MoveBetweenBudgets => f.write_str("MoveBetweenBudgets"),
             BudgetAssignmentType::FromTransaction => f.write_str("FromTransaction"),
+            BudgetAssignmentType::FromReadyToAssign => f.write_str("FromReadyToAssign"),
         }
     }
 }
@@ -69,7 +74,7 @@ impl From<Budget> for BudgetDbModel {
             match &value.target {
                 None => (None, None, None, None),
                 Some(target @ BudgetTarget::OneTime { target_amount }) => {
-                    (Some(target.to_string()), None, Some(*target_amount), None)
+                    (Some(target.to_string()), None, Some(DbDecimal(*target_amount)), None)
                 }
                 Some(
                     target @ BudgetTarget::Repeating {
@@ -80,7 +85,7 @@ impl From<Budget> for BudgetDbModel {
                 ) => (
                     Some(target.to_string()),
                     Some(repeating_type.to_string()),
-                    Some(*target_amount),
+                    Some(DbDecimal(*target_amount)),
                     Some(schedule.id.simple()),
                 ),
             };
@@ -91,14 +96,16 @@ impl From<Budget> for BudgetDbModel {
             repeating_target_type,
             target_amount,
             target_schedule_id,
-            user_id: value.user_id.simple(),
+            plan_id: value.plan_id.simple(),
+            group_id: value.group_id.map(Uuid::simple),
+            archived_at: value.archived_at,
             assignments: value
                 .assignments
                 .into_iter()
                 .map(|assignment| BudgetAssignmentDbModel {
                     budget_id: value.id.simple(),
                     id: assignment.id.simple(),
-                    amount: assignment.amount,
+                    amount: DbDecimal(assignment.amount),
                     date: assignment.date,
                     assignment_type: match &assignment.source {
                         BudgetAssignmentSource::OtherBudget { .. } => {
@@ -107,6 +114,9 @@ impl From<Budget> for BudgetDbModel {
                         BudgetAssignmentSource::Transaction { .. } => {
                             "FromTransaction".into()
                         }
+                        BudgetAssignmentSource::ReadyToAssign => {
+                            "FromReadyToAssign".into()
+                        }
                     },
                     link_id: if let BudgetAssignmentSource::OtherBudget { link_id, .. } = &assignment.source {
                         Some(link_id.simple())
@@ -167,13 +177,15 @@ impl BudgetDbModel {
         Ok(Budget {
             id: self.id.into_uuid(),
             name: self.name,
-            user_id: self.user_id.into_uuid(),
+            plan_id: self.plan_id.into_uuid(),
+            group_id: self.group_id.map(uuid::fmt::Simple::into_uuid),
+            archived_at: self.archived_at,
             target: self
                 .target_type
                 .map(|target_type| {
                     BudgetTarget::try_new(
                         target_type.as_str(),
-                        self.target_amount,
+                        self.target_amount.map(Decimal::from),
                         self.repeating_target_type.as_deref(),
                         schedule,
                     )
@@ -198,7 +210,7 @@ impl TryInto<BudgetAssignment> for BudgetAssignmentDbModel {
                 Ok(BudgetAssignment {
                     id: self.id.into(),
                     date: self.date,
-                    amount: self.amount,
+                    amount: self.amount.into(),
                     source: BudgetAssignmentSource::Transaction {
                         from_transaction_id: from_transaction_id.into_uuid(),
                     }
@@ -208,7 +220,7 @@ impl TryInto<BudgetAssignment> for BudgetAssignmentDbModel {
                 Ok(BudgetAssignment {
                     id: self.id.into(),
                     date: self.date,
-                    amount: self.amount,
+                    amount: self.amount.into(),
                     source: BudgetAssignmentSource::OtherBudget {
                         from_budget_id: from_budget_id.into(),
                         link_id: link_id.into(),
@@ -216,318 +228,482 @@ impl TryInto<BudgetAssignment> for BudgetAssignmentDbModel {
                 })
             },
             (BudgetAssignmentType::MoveBetweenBudgets, _, _, _) => Err(anyhow!("from_budget_id and link_id must be populated for MoveBetweenBudgets assignment type")),
+            (BudgetAssignmentType::FromReadyToAssign, _, _, _) => Ok(BudgetAssignment {
+                id: self.id.into(),
+                date: self.date,
+                amount: self.amount.into(),
+                source: BudgetAssignmentSource::ReadyToAssign,
+            }),
         }
     }
 }
 
-pub async fn create(db_pool: &MySqlPool, budget: Budget) -> Result<(), Error> {
-    let db_model: BudgetDbModel = budget.into();
+pub async fn create(connection: impl Into<DbExecutor<'_>>, budget: Budget) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        let db_model: BudgetDbModel = budget.into();
+
+        sqlx::query(r"INSERT INTO Budgets(id, name, target_type, repeating_target_type, target_amount, target_schedule_id, plan_id, group_id, archived_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(db_model.id)
+        .bind(db_model.name)
+        .bind(db_model.target_type)
+        .bind(db_model.repeating_target_type)
+        .bind(db_model.target_amount)
+        .bind(db_model.target_schedule_id)
+        .bind(db_model.plan_id)
+        .bind(db_model.group_id)
+        .bind(db_model.archived_at)
+            .execute(&mut *transaction)
+            .await?;
 
-    sqlx::query!(
-r"INSERT INTO Budgets(id, name, target_type, repeating_target_type, target_amount, target_schedule_id, user_id)
-VALUE(?, ?, ?, ?, ?, ?, ?)",
-        db_model.id,
-        db_model.name,
-        db_model.target_type,
-        db_model.repeating_target_type,
-        db_model.target_amount,
-        db_model.target_schedule_id,
-        db_model.user_id)
-        .execute(db_pool)
-        .await?;
+        if !db_model.assignments.is_empty() {
+            let mut query_builder =
+                QueryBuilder::new("INSERT INTO BudgetAssignments (id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id)");
+            query_builder.push_values(db_model.assignments, |mut b, assignment| {
+                b.push_bind(assignment.id)
+                    .push_bind(assignment.amount)
+                    .push_bind(assignment.date)
+                    .push_bind(assignment.budget_id)
+                    .push_bind(assignment.assignment_type.to_string())
+                    .push_bind(assignment.from_budget_id)
+                    .push_bind(assignment.from_transaction_id)
+                    .push_bind(assignment.link_id);
+            });
+
+            query_builder.build().execute(&mut *transaction).await?;
+        }
 
-    if !db_model.assignments.is_empty() {
-        let mut query_builder =
-            QueryBuilder::new("INSERT INTO BudgetAssignments (id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id)");
-        query_builder.push_values(db_model.assignments, |mut b, assignment| {
-            b.push_bind(assignment.id)
-                .push_bind(assignment.amount)
-                .push_bind(assignment.date)
-                .push_bind(assignment.budget_id)
-                .push_bind(assignment.assignment_type.to_string())
-                .push_bind(assignment.from_budget_id)
-                .push_bind(assignment.from_transaction_id)
-                .push_bind(assignment.link_id);
-        });
-
-        query_builder.build().execute(db_pool).await?;
-    }
+        transaction.commit().await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn get_by_ids(db_pool: &MySqlPool, ids: &[Uuid]) -> Result<Box<[Budget]>, Error> {
-    if ids.is_empty() {
-        return Ok(Box::new([]));
-    }
-    let mut query_builder = QueryBuilder::new(
-        "SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, user_id
-        FROM Budgets
-        WHERE id IN (");
-
-    let mut separated = query_builder.separated(',');
-    for id in ids {
-        separated.push_bind(id.as_simple());
-    }
+pub async fn get_by_ids(
+    connection: impl Into<DbExecutor<'_>>,
+    ids: &[Uuid],
+) -> Result<Box<[Budget]>, Error> {
+    with_connection!(connection, |connection| {
+        select_by_ids(connection, ids, "").await
+    })
+}
 
-    separated.push_unseparated(')');
+/// Locks the budgets until `transaction` ends, so they can be changed and saved without another
+/// request saving them in between
+pub async fn get_by_ids_for_update(
+    transaction: impl Into<DbExecutor<'_>>,
+    ids: &[Uuid],
+) -> Result<Box<[Budget]>, Error> {
+    with_connection!(transaction, |transaction| {
+        select_by_ids(transaction, ids, Db::FOR_UPDATE).await
+    })
+}
 
-    let budget_db_models = query_builder
-        .build_query_as::<BudgetDbModel>()
-        .fetch_all(db_pool)
-        .await?;
+/// Locks the budget until `transaction` ends, like [`get_by_ids_for_update`]
+pub async fn get_single_for_update(
+    transaction: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+) -> Result<Budget, Error> {
+    with_connection!(transaction, |transaction| {
+        Vec::from(select_by_ids(transaction, &[id], Db::FOR_UPDATE).await?)
+            .pop()
+            .ok_or(Error::NotFound)
+    })
+}
+
+async fn select_by_ids(
+    connection: impl Into<DbExecutor<'_>>,
+    ids: &[Uuid],
+    lock: &str,
+) -> Result<Box<[Budget]>, Error> {
+    with_connection!(connection, |connection| {
+        if ids.is_empty() {
+            return Ok(Box::new([]));
+        }
 
-    get_budgets_from_db_models(db_pool, budget_db_models).await
+        let mut query_builder = QueryBuilder::new(
+            "SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, plan_id, group_id, archived_at
+            FROM Budgets
+            WHERE id IN (");
+
+        let mut separated = query_builder.separated(',');
+        for id in ids {
+            separated.push_bind(id.as_simple());
+        }
+
+        separated.push_unseparated(')');
+        query_builder.push(lock);
+
+        let budget_db_models = query_builder
+            .build_query_as::<BudgetDbModel>()
+            .fetch_all(&mut *connection)
+            .await?;
+
+        get_budgets_from_db_models(connection, budget_db_models).await
+    })
 }
 
-pub async fn get_single(db_pool: &MySqlPool, id: Uuid) -> Result<Budget, Error> {
-    let mut budget = sqlx::query_as::<MySql, BudgetDbModel>(
-        "SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, user_id
-        FROM Budgets
-        WHERE id = ?").bind(id.simple()).fetch_one(db_pool)
+pub async fn get_single(connection: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<Budget, Error> {
+    with_connection!(connection, |connection| {
+        let mut budget = sqlx::query_as::<Db, BudgetDbModel>(
+            "SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, plan_id, group_id, archived_at
+            FROM Budgets
+            WHERE id = ?").bind(id.simple()).fetch_one(&mut *connection)
+            .await?;
+
+        let assignments = sqlx::query_as::<Db, BudgetAssignmentDbModel>(
+            "SELECT id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id
+            FROM BudgetAssignments
+            WHERE budget_id = ?",
+        )
+        .bind(id.simple())
+        .fetch_all(&mut *connection)
         .await?;
 
-    let assignments = sqlx::query_as::<MySql, BudgetAssignmentDbModel>(
-        "SELECT id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id
-        FROM BudgetAssignments
-        WHERE budget_id = ?",
-    )
-    .bind(id.simple())
-    .fetch_all(db_pool)
-    .await?;
-
-    budget.assignments = assignments;
-
-    let schedule = if let Some(schedule_id) = &budget.target_schedule_id {
-        Some(schedule::get_single(db_pool, schedule_id.into_uuid()).await?)
-    } else {
-        None
-    };
-
-    budget
-        .try_into_budget(schedule)
-        .map_err(|e| Error::MappingError { error: e })
+        budget.assignments = assignments;
+
+        let schedule = if let Some(schedule_id) = &budget.target_schedule_id {
+            Some(schedule::get_single(&mut *connection, schedule_id.into_uuid()).await?)
+        } else {
+            None
+        };
+
+        budget
+            .try_into_budget(schedule)
+            .map_err(|e| Error::MappingError { error: e })
+    })
 }
 
 async fn get_budgets_from_db_models(
-    db_pool: &MySqlPool,
+    connection: impl Into<DbExecutor<'_>>,
     budget_db_models: Vec<BudgetDbModel>,
 ) -> Result<Box<[Budget]>, Error> {
-    let mut schedule_ids = Vec::new();
-    let mut budget_ids = Vec::new();
-    for budget in &budget_db_models {
-        if let Some(target_schedule_id) = &budget.target_schedule_id {
-            schedule_ids.push(target_schedule_id.into_uuid());
+    with_connection!(connection, |connection| {
+        let mut schedule_ids = Vec::new();
+        let mut budget_ids = Vec::new();
+        for budget in &budget_db_models {
+            if let Some(target_schedule_id) = &budget.target_schedule_id {
+                schedule_ids.push(target_schedule_id.into_uuid());
+            }
+
+            budget_ids.push(budget.id);
         }
 
-        budget_ids.push(budget.id);
-    }
+        if budget_db_models.is_empty() {
+            return Ok(Box::new([]));
+        }
 
-    if budget_db_models.is_empty() {
-        return Ok(Box::new([]));
-    }
+        let mut query_builder = QueryBuilder::new(
+            "SELECT id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id FROM BudgetAssignments WHERE budget_id IN (",
+        );
 
-    let mut query_builder = QueryBuilder::new(
-        "SELECT id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id FROM BudgetAssignments WHERE budget_id IN (",
-    );
+        let mut separated = query_builder.separated(",");
+        for budget_id in budget_ids {
+            separated.push_bind(budget_id);
+        }
+        separated.push_unseparated(")");
 
-    let mut separated = query_builder.separated(",");
-    for budget_id in budget_ids {
-        separated.push_bind(budget_id);
-    }
-    separated.push_unseparated(")");
+        let assignments_vec = query_builder
+            .build_query_as::<BudgetAssignmentDbModel>()
+            .fetch_all(&mut *connection)
+            .await?;
 
-    let assignments_vec = query_builder
-        .build_query_as::<BudgetAssignmentDbModel>()
-        .fetch_all(db_pool)
-        .await?;
+        let mut assignments_by_budget_id: HashMap<uuid::fmt::Simple, Vec<_>> = HashMap::new();
 
-    let mut assignments_by_budget_id: HashMap<uuid::fmt::Simple, Vec<_>> = HashMap::new();
+        for assignment in assignments_vec {
+            if let Some(assignments) = assignments_by_budget_id.get_mut(&assignment.budget_id) {
+                assignments.push(assignment);
+            } else {
+                assignments_by_budget_id.insert(assignment.budget_id, vec![assignment]);
+            }
+        }
 
-    for assignment in assignments_vec {
-        if let Some(assignments) = assignments_by_budget_id.get_mut(&assignment.budget_id) {
-            assignments.push(assignment);
+        let schedules = if schedule_ids.is_empty() {
+            Box::new([])
         } else {
-            assignments_by_budget_id.insert(assignment.budget_id, vec![assignment]);
-        }
-    }
+            schedule::get_by_ids(&mut *connection, &schedule_ids).await?
+        };
 
-    let schedules = if schedule_ids.is_empty() {
-        Box::new([])
-    } else {
-        schedule::get_by_ids(db_pool, &schedule_ids).await?
-    };
+        let mut schedules: HashMap<_, _> = schedules
+            .into_vec()
+            .into_iter()
+            .map(|s| (s.id.simple(), s))
+            .collect();
 
-    let mut schedules: HashMap<_, _> = schedules
-        .into_vec()
-        .into_iter()
-        .map(|s| (s.id.simple(), s))
-        .collect();
+        let mut budgets = Vec::new();
 
-    let mut budgets = Vec::new();
+        // I'm not clever enough to do this with just iterators
+        for mut db_model in budget_db_models {
+            let schedule_id = db_model.target_schedule_id;
 
-    // I'm not clever enough to do this with just iterators
-    for mut db_model in budget_db_models {
-        let schedule_id = db_model.target_schedule_id;
+            // a schedule is owned by a single budget, so removing from schedules should be ok
+            let schedule = schedule_id.and_then(|s| schedules.remove(&s));
 
-        // a schedule is owned by a single budget, so removing from schedules should be ok
-        let schedule = schedule_id.and_then(|s| schedules.remove(&s));
+            let assignments = assignments_by_budget_id
+                .remove(&db_model.id)
+                .unwrap_or_default();
 
-        let assignments = assignments_by_budget_id
-            .remove(&db_model.id)
-            .unwrap_or_default();
+            db_model.assignments = assignments;
 
-        db_model.assignments = assignments;
+            let budget: Budget = db_model
+                .try_into_budget(schedule)
+                .map_err(|e| Error::MappingError { error: e })?;
 
-        let budget: Budget = db_model
-            .try_into_budget(schedule)
-            .map_err(|e| Error::MappingError { error: e })?;
+            budgets.push(budget);
+        }
 
-        budgets.push(budget);
-    }
+        Ok(budgets.into_boxed_slice())
+    })
+}
 
-    Ok(budgets.into_boxed_slice())
+/// Archived budgets are only returned when `include_archived` is set
+pub async fn get(
+    connection: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+    include_archived: bool,
+) -> Result<Box<[Budget]>, Error> {
+    with_connection!(connection, |connection| {
+        let budget_db_models = sqlx::query_as::<Db, BudgetDbModel>(
+            r"SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, plan_id, group_id, archived_at
+            FROM Budgets
+            WHERE plan_id = ? AND (? OR archived_at IS NULL)").bind(plan_id.simple())
+            .bind(include_archived)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        get_budgets_from_db_models(&mut *connection, budget_db_models).await
+    })
 }
 
-pub async fn get(db_pool: &MySqlPool, user_id: Uuid) -> Result<Box<[Budget]>, Error> {
-    let budget_db_models = sqlx::query_as::<MySql, BudgetDbModel>(
-        r"SELECT id, name, target_type, repeating_target_type, target_amount, target_schedule_id, user_id
-        FROM Budgets
-        WHERE user_id = ?").bind(user_id.simple())
-        .fetch_all(db_pool)
+pub async fn delete(connection: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        sqlx::query("DELETE FROM BudgetAssignments WHERE budget_id = ?")
+            .bind(id.simple())
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("DELETE FROM Budgets WHERE id = ?")
+            .bind(id.simple())
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    })
+}
+
+/// Whether any transactions, assignments or scheduled transactions refer to the budget
+pub async fn is_in_use(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<bool, Error> {
+    with_connection!(executor, |connection| {
+        let count = sqlx::query_scalar::<Db, i64>(
+            "SELECT (SELECT COUNT(*) FROM Transactions WHERE budget_id = ?)
+                + (SELECT COUNT(*) FROM BudgetAssignments WHERE budget_id = ? OR from_budget_id = ?)
+                + (SELECT COUNT(*) FROM ScheduledTransactions WHERE budget_id = ?)",
+        )
+        .bind(id.simple())
+        .bind(id.simple())
+        .bind(id.simple())
+        .bind(id.simple())
+        .fetch_one(connection)
         .await?;
 
-    get_budgets_from_db_models(db_pool, budget_db_models).await
+        Ok(count > 0)
+    })
 }
 
-pub async fn delete(db_pool: &MySqlPool, id: Uuid) -> Result<(), Error> {
-    sqlx::query!(
-        "DELETE FROM BudgetAssignments WHERE budget_id = ?",
-        id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
+/// Saves the budget's name, target, group and archived date, leaving its assignments as they are
+pub async fn update_details(
+    connection: impl Into<DbExecutor<'_>>,
+    budget: Budget,
+) -> Result<(), Error> {
+    let db_model: BudgetDbModel = budget.into();
+
+    update_row(connection, &db_model).await
+}
 
-    sqlx::query!("DELETE FROM Budgets WHERE id = ?", id.as_simple())
-        .execute(db_pool)
+async fn update_row(
+    connection: impl Into<DbExecutor<'_>>,
+    db_model: &BudgetDbModel,
+) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        sqlx::query(
+            "UPDATE Budgets
+        SET name = ?,
+        target_type = ?,
+        repeating_target_type = ?,
+        target_amount = ?,
+        target_schedule_id = ?,
+        group_id = ?,
+        archived_at = ?
+        WHERE id = ?",
+        )
+        .bind(&db_model.name)
+        .bind(&db_model.target_type)
+        .bind(&db_model.repeating_target_type)
+        .bind(db_model.target_amount)
+        .bind(db_model.target_schedule_id)
+        .bind(db_model.group_id)
+        .bind(db_model.archived_at)
+        .bind(db_model.id)
+        .execute(connection)
         .await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, budget: Budget) -> Result<(), Error> {
-    let db_model: BudgetDbModel = budget.into();
-    sqlx::query!(
-        "UPDATE Budgets
-    SET name = ?,
-    target_type = ?,
-    repeating_target_type = ?,
-    target_amount = ?,
-    target_schedule_id = ?
-    WHERE id = ?",
-        db_model.name,
-        db_model.target_type,
-        db_model.repeating_target_type,
-        db_model.target_amount,
-        db_model.target_schedule_id,
-        db_model.id
-    )
-    .execute(db_pool)
-    .await?;
-
-    let existing_budget: BudgetDbModel = get_single(db_pool, db_model.id.into_uuid())
-        .await?.into();
-
-    let existing_assignments: HashMap<_, _> = existing_budget.assignments.into_iter()
+/// Saves the budget and replaces its assignments with the budget's. Any made since the budget
+/// was read are lost, so it has to be read with [`get_single_for_update`] or one of the other
+/// `_for_update` functions in the same transaction
+pub async fn update(connection: impl Into<DbExecutor<'_>>, budget: Budget) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        let db_model: BudgetDbModel = budget.into();
+        update_row(&mut *transaction, &db_model).await?;
+
+        let existing_assignments: HashMap<_, _> = sqlx::query_as::<Db, BudgetAssignmentDbModel>(
+            "SELECT id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id
+            FROM BudgetAssignments
+            WHERE budget_id = ?",
+        )
+        .bind(db_model.id)
+        .fetch_all(&mut *transaction)
+        .await?
+        .into_iter()
         .map(|assignment| (assignment.id, assignment))
         .collect();
 
-    let mut to_add = Vec::new();
-    let mut to_update = Vec::new();
-    let mut assignment_ids = Vec::new();
+        let mut to_add = Vec::new();
+        let mut to_update = Vec::new();
+        let mut assignment_ids = Vec::new();
 
-    for assignment in db_model.assignments {
-        assignment_ids.push(assignment.id);
-        if let Some(existing_assignment) = existing_assignments.get(&assignment.id) {
-            if existing_assignment != &assignment {
-                to_update.push(assignment);
+        for assignment in db_model.assignments {
+            assignment_ids.push(assignment.id);
+            if let Some(existing_assignment) = existing_assignments.get(&assignment.id) {
+                if existing_assignment != &assignment {
+                    to_update.push(assignment);
+                }
+            } else {
+                to_add.push(assignment);
             }
-        } else {
-            to_add.push(assignment);
         }
-    }
 
-    if !to_add.is_empty() {
-        let mut query_builder =
-            QueryBuilder::new("INSERT INTO BudgetAssignments (id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id)");
+        if !to_add.is_empty() {
+            let mut query_builder =
+                QueryBuilder::new("INSERT INTO BudgetAssignments (id, amount, date, budget_id, assignment_type, from_budget_id, from_transaction_id, link_id)");
             query_builder.push_values(to_add, |mut b, assignment| {
-            b.push_bind(assignment.id)
-                .push_bind(assignment.amount)
-                .push_bind(assignment.date)
-                .push_bind(assignment.budget_id)
-                .push_bind(assignment.assignment_type.to_string())
-                .push_bind(assignment.from_budget_id)
-                .push_bind(assignment.from_transaction_id)
-                .push_bind(assignment.link_id);
-        });
-
-        query_builder.build().execute(db_pool).await?;
-    }
-
-    if !to_update.is_empty() {
-        let mut query_builder = QueryBuilder::default();
-        for assignment in to_update {
-            query_builder.push("UPDATE BudgetAssignments SET ")
-                .push("amount = ").push_bind(assignment.amount)
-                .push(", date = ").push_bind(assignment.date)
-                .push(", assignment_type = ").push_bind(assignment.assignment_type)
-                .push(", from_budget_id = ").push_bind(assignment.from_budget_id)
-                .push(", from_transaction_id = ").push_bind(assignment.from_transaction_id)
-                .push(", link_id = ").push_bind(assignment.link_id)
-                .push(" WHERE id = ").push_bind(assignment.id)
-                .push(";");
+                b.push_bind(assignment.id)
+                    .push_bind(assignment.amount)
+                    .push_bind(assignment.date)
+                    .push_bind(assignment.budget_id)
+                    .push_bind(assignment.assignment_type.to_string())
+                    .push_bind(assignment.from_budget_id)
+                    .push_bind(assignment.from_transaction_id)
+                    .push_bind(assignment.link_id);
+            });
+
+            query_builder.build().execute(&mut *transaction).await?;
         }
 
-        query_builder.build().execute(db_pool).await?;
-    }
+        if !to_update.is_empty() {
+            let mut query_builder = QueryBuilder::default();
+            for assignment in to_update {
+                query_builder
+                    .push("UPDATE BudgetAssignments SET ")
+                    .push("amount = ")
+                    .push_bind(assignment.amount)
+                    .push(", date = ")
+                    .push_bind(assignment.date)
+                    .push(", assignment_type = ")
+                    .push_bind(assignment.assignment_type)
+                    .push(", from_budget_id = ")
+                    .push_bind(assignment.from_budget_id)
+                    .push(", from_transaction_id = ")
+                    .push_bind(assignment.from_transaction_id)
+                    .push(", link_id = ")
+                    .push_bind(assignment.link_id)
+                    .push(" WHERE id = ")
+                    .push_bind(assignment.id)
+                    .push(";");
+            }
 
-    if assignment_ids.is_empty() {
-        // if budget has no assignments, then delete everything from the budget
-        sqlx::query!("DELETE FROM BudgetAssignments WHERE budget_id = ?", db_model.id)
-            .execute(db_pool)
-            .await?;
-    } else {
-        let mut query_builder = QueryBuilder::new("DELETE FROM BudgetAssignments
-        WHERE budget_id = ");
-        query_builder.push_bind(db_model.id)
-            .push(" AND id NOT IN (");
+            query_builder.build().execute(&mut *transaction).await?;
+        }
 
-        let mut separated = query_builder.separated(',');
-        for id in assignment_ids {
-            separated.push_bind(id);
+        if assignment_ids.is_empty() {
+            // if budget has no assignments, then delete everything from the budget
+            sqlx::query("DELETE FROM BudgetAssignments WHERE budget_id = ?")
+                .bind(db_model.id)
+                .execute(&mut *transaction)
+                .await?;
+        } else {
+            let mut query_builder = QueryBuilder::new(
+                "DELETE FROM BudgetAssignments
+            WHERE budget_id = ",
+            );
+            query_builder
+                .push_bind(db_model.id)
+                .push(" AND id NOT IN (");
+
+            let mut separated = query_builder.separated(',');
+            for id in assignment_ids {
+                separated.push_bind(id);
+            }
+            separated.push_unseparated(')');
+
+            query_builder.build().execute(&mut *transaction).await?;
         }
-        separated.push_unseparated(')');
 
-        query_builder.build().execute(db_pool).await?;
-    }
+        transaction.commit().await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn get_by_assignment_transaction_id(db_pool: &MySqlPool, transaction_id: Uuid) -> Result<Option<Budget>, Error> {
-    let maybe_budget = sqlx::query_as::<MySql, BudgetDbModel>("SELECT b.id, b.name, b.target_type, b.repeating_target_type, b.target_amount, b.target_schedule_id, b.user_id
-    FROM Budgets b
-    JOIN BudgetAssignments a ON b.id = a.budget_id
-    WHERE a.from_transaction_id = ?").bind(transaction_id.simple())
-        .fetch_optional(db_pool)
-        .await?;
+pub async fn get_by_assignment_transaction_id(
+    connection: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+) -> Result<Option<Budget>, Error> {
+    select_by_assignment_transaction_id(connection, transaction_id, "").await
+}
 
-    let Some(budget) = maybe_budget else {
-        return Ok(None);
-    };
+/// Locks the budget until `transaction` ends, like [`get_by_ids_for_update`]
+pub async fn get_by_assignment_transaction_id_for_update(
+    transaction: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+) -> Result<Option<Budget>, Error> {
+    with_connection!(transaction, |transaction| {
+        select_by_assignment_transaction_id(transaction, transaction_id, Db::FOR_UPDATE).await
+    })
+}
 
-    get_budgets_from_db_models(db_pool, vec![budget]).await
-        .map(|budgets| Some(Vec::from(budgets).remove(0)))
+async fn select_by_assignment_transaction_id(
+    connection: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+    lock: &str,
+) -> Result<Option<Budget>, Error> {
+    with_connection!(connection, |connection| {
+        let maybe_budget = sqlx::query_as::<Db, BudgetDbModel>(&format!("SELECT b.id, b.name, b.target_type, b.repeating_target_type, b.target_amount, b.target_schedule_id, b.plan_id, b.group_id, b.archived_at
+        FROM Budgets b
+        JOIN BudgetAssignments a ON b.id = a.budget_id
+        WHERE a.from_transaction_id = ?{lock}")).bind(transaction_id.simple())
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        let Some(budget) = maybe_budget else {
+            return Ok(None);
+        };
+
+        let budgets = get_budgets_from_db_models(&mut *connection, vec![budget]).await?;
+
+        Ok(Some(Vec::from(budgets).remove(0)))
+    })
 }
 
 #[cfg(test)]
@@ -569,7 +745,7 @@ mod tests {
 
             let no_target = BudgetDbModel {
                 id: id.simple(),
-                user_id: user_id.simple(),
+                plan_id: user_id.simple(),
                 name: "hi".into(),
                 target_type: None,
                 repeating_target_type: None,
@@ -578,7 +754,7 @@ mod tests {
                 assignments: vec![
                     BudgetAssignmentDbModel {
                         id: assignment_id1.simple(),
-                        amount: assignment_amount,
+                        amount: DbDecimal(assignment_amount),
                         budget_id: id.simple(),
                         date: NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
                         assignment_type: BudgetAssignmentType::FromTransaction.to_string(),
@@ -589,7 +765,7 @@ mod tests {
                     {
                         BudgetAssignmentDbModel {
                             id: assignment_id2.simple(),
-                            amount: assignment_amount,
+                            amount: DbDecimal(assignment_amount),
                             budget_id: id.simple(),
                             date: NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
                             assignment_type: BudgetAssignmentType::MoveBetweenBudgets.to_string(),
@@ -599,17 +775,19 @@ mod tests {
                         }
                     },
                 ],
+                group_id: None,
+                archived_at: None,
             };
 
             let one_time_target = BudgetDbModel {
                 target_type: Some("OneTime".into()),
-                target_amount: Some(amount),
+                target_amount: Some(DbDecimal(amount)),
                 ..no_target.clone()
             };
 
             let repeating_target = BudgetDbModel {
                 target_type: Some("Repeating".into()),
-                target_amount: Some(amount),
+                target_amount: Some(DbDecimal(amount)),
                 repeating_target_type: Some(RepeatingTargetType::BuildUpTo.to_string()),
                 target_schedule_id: Some(schedule_id.simple()),
                 ..no_target.clone()
@@ -619,7 +797,7 @@ mod tests {
                 id,
                 name: "hi".into(),
                 target: None,
-                user_id,
+                plan_id: user_id,
                 assignments: vec![
                     default_assignment.clone(),
                     BudgetAssignment {
@@ -628,6 +806,8 @@ mod tests {
                         ..default_assignment.clone()
                     },
                 ],
+                group_id: None,
+                archived_at: None,
             };
 
             let schedule = Schedule {
@@ -706,13 +886,13 @@ mod tests {
     mod db_tests {
         use std::sync::LazyLock;
 
-        use chrono::NaiveDate;
+        use chrono::{DateTime, NaiveDate};
         use rust_decimal::prelude::FromPrimitive;
         use rust_decimal_macros::dec;
 
         use super::*;
         use crate::{
-            db,
+            db::{self, TestPool},
             models::{
                 CreateBankAccountRequest, CreatePayeeRequest,
                 RepeatingTargetType, SchedulePeriod, User,
@@ -722,7 +902,7 @@ mod tests {
 
         static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-        async fn test_init(db_pool: &MySqlPool) {
+        async fn test_init(db_pool: &TestPool) {
             let user_id = *USER_ID;
 
             db::users::create(
@@ -736,8 +916,8 @@ mod tests {
         mod create_and_get_tests {
             use super::*;
 
-            #[sqlx::test]
-            pub async fn create_and_get_budget_test(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn create_and_get_budget_test(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let without_assignments_id = Uuid::new_v4();
@@ -764,8 +944,10 @@ mod tests {
                         repeating_type: RepeatingTargetType::RequireRepeating,
                         schedule: schedule.clone(),
                     }),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, without_assignments.clone()).await.unwrap();
@@ -794,9 +976,10 @@ mod tests {
                         id: from_transaction_id,
                         bank_account_id,
                         payee_id,
-                        budget_id: without_assignments_id,
+                        budget_id: Some(without_assignments_id),
                         amount: Decimal::ZERO,
-                        date: NaiveDate::from_ymd_opt(2024, 11, 19).unwrap()
+                        date: NaiveDate::from_ymd_opt(2024, 11, 19).unwrap(),
+                        memo: None,
                     }
                 )
                 .await
@@ -828,13 +1011,15 @@ mod tests {
                     id: with_assignments_id,
                     name: "name".into(),
                     target: None,
-                    user_id,
+                    plan_id: user_id,
                     assignments,
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, with_assignments.clone()).await.unwrap();
 
-                let mut fetched = Vec::from(get(&db_pool, user_id).await.unwrap());
+                let mut fetched = Vec::from(get(&db_pool, user_id, false).await.unwrap());
                 let mut expected = vec![without_assignments.clone(), with_assignments.clone()];
                 fetched.sort_by_key(|x| x.id);
                 expected.sort_by_key(|x| x.id);
@@ -855,16 +1040,18 @@ mod tests {
                 assert_eq!(fetched_with_assignments, with_assignments);
             }
 
-            #[sqlx::test]
-            pub async fn get_by_ids_test(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_by_ids_test(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let budget1 = Budget {
                     assignments: vec![],
                     id: Uuid::new_v4(),
-                    user_id: *USER_ID,
+                    plan_id: *USER_ID,
                     name: "name1".into(),
                     target: None,
+                    group_id: None,
+                    archived_at: None,
                 };
                 let budget2 = Budget {
                     id: Uuid::new_v4(),
@@ -889,8 +1076,8 @@ mod tests {
                 assert_eq!(result, expected);
             }
 
-            #[sqlx::test]
-            pub async fn get_by_ids_when_empty_ids(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_by_ids_when_empty_ids(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let result = get_by_ids(&db_pool, &[]).await.unwrap();
@@ -898,8 +1085,8 @@ mod tests {
                 assert!(result.is_empty());
             }
 
-            #[sqlx::test]
-            pub async fn get_budgets_without_schedule(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_budgets_without_schedule(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let user_id = *USER_ID;
@@ -907,10 +1094,12 @@ mod tests {
 
                 create(&db_pool, Budget {
                     id: other_budget_id,
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
                     name: "name".into(),
-                    target: None
+                    target: None,
+                    group_id: None,
+                    archived_at: None,
                 }).await.unwrap();
 
                 let budget = Budget {
@@ -919,13 +1108,15 @@ mod tests {
                     target: Some(BudgetTarget::OneTime {
                         target_amount: Decimal::from_f32(1.1).unwrap(),
                     }),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![BudgetAssignment {
                         id: Uuid::new_v4(),
                         amount: Decimal::ZERO,
                         date: NaiveDate::from_ymd_opt(2024, 11, 30).unwrap(),
                         source: BudgetAssignmentSource::OtherBudget { from_budget_id: other_budget_id, link_id: Uuid::new_v4() }
                     }],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
@@ -934,8 +1125,34 @@ mod tests {
                 assert_eq!(fetched, budget);
             }
 
-            #[sqlx::test]
-            pub async fn get_by_budget_assignment_transaction_id_test(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_excludes_archived_budgets(db_pool: TestPool) {
+                test_init(&db_pool).await;
+                let user_id = *USER_ID;
+
+                let active = Budget::new(Uuid::new_v4(), "active".into(), None, user_id, vec![], None, None);
+                let archived = Budget {
+                    id: Uuid::new_v4(),
+                    name: "archived".into(),
+                    archived_at: DateTime::from_timestamp(1_733_000_000, 0),
+                    ..active.clone()
+                };
+
+                create(&db_pool, active.clone()).await.unwrap();
+                create(&db_pool, archived.clone()).await.unwrap();
+
+                let fetched = get(&db_pool, user_id, false).await.unwrap();
+                assert_eq!(fetched, vec![active.clone()].into_boxed_slice());
+
+                let mut fetched = Vec::from(get(&db_pool, user_id, true).await.unwrap());
+                let mut expected = vec![active, archived];
+                fetched.sort_by_key(|x| x.id);
+                expected.sort_by_key(|x| x.id);
+                assert_eq!(fetched, expected);
+            }
+
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_by_budget_assignment_transaction_id_test(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let payee_id = Uuid::new_v4();
                 let bank_account_id = Uuid::new_v4();
@@ -945,13 +1162,13 @@ mod tests {
                 let user_id = *USER_ID;
 
                 db::bank_accounts::create(&db_pool, bank_account_id, CreateBankAccountRequest {
-                    user_id,
+                    plan_id: user_id,
                     initial_amount: Decimal::ZERO,
                     name: "bank account".into()
                 }).await.unwrap();
                 db::payees::create(&db_pool, payee_id, CreatePayeeRequest {
                     name: "payee".into(),
-                    user_id
+                    plan_id: user_id
                 }).await.unwrap();
 
                 let mut budget_1 = Budget {
@@ -959,16 +1176,19 @@ mod tests {
                     assignments: vec![],
                     name: "budget 1".into(),
                     target: None,
-                    user_id
+                    plan_id: user_id,
+                    group_id: None,
+                    archived_at: None,
                 };
                 create(&db_pool, budget_1.clone()).await.unwrap();
                 db::transactions::create(&db_pool, Transaction {
                     id: transaction_id,
-                    budget_id: budget_1_id,
+                    budget_id: Some(budget_1_id),
                     amount: Decimal::ZERO,
                     date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
                     payee_id,
-                    bank_account_id
+                    bank_account_id,
+                    memo: None,
                 }).await.unwrap();
                 budget_1.assignments.push(BudgetAssignment {
                     id: Uuid::new_v4(),
@@ -992,7 +1212,9 @@ mod tests {
                     }],
                     name: "budget 1".into(),
                     target: None,
-                    user_id
+                    plan_id: user_id,
+                    group_id: None,
+                    archived_at: None,
                 };
                 create(&db_pool, budget_2.clone()).await.unwrap();
 
@@ -1002,8 +1224,8 @@ mod tests {
                 assert_eq!(fetched, Some(budget_1));
             }
 
-            #[sqlx::test]
-            pub async fn get_by_budget_assignment_transaction_id_missing_test(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn get_by_budget_assignment_transaction_id_missing_test(db_pool: TestPool) {
                 let result = get_by_assignment_transaction_id(&db_pool, Uuid::new_v4()).await.unwrap();
                 assert_eq!(result, None);
             }
@@ -1013,8 +1235,8 @@ mod tests {
         mod update_tests {
             use super::*;
 
-            #[sqlx::test]
-            pub async fn update_budget_add_schedule(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_budget_add_schedule(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id1 = Uuid::new_v4();
                 let id2 = Uuid::new_v4();
@@ -1028,8 +1250,10 @@ mod tests {
                         id: id2,
                         name: "name2".into(),
                         target: None,
-                        user_id,
+                        plan_id: user_id,
                         assignments: vec![],
+                        group_id: None,
+                        archived_at: None,
                     },
                 )
                 .await
@@ -1058,10 +1282,11 @@ mod tests {
                     Transaction {
                         id: from_transaction_id,
                         payee_id,
-                        budget_id: id2,
+                        budget_id: Some(id2),
                         date: NaiveDate::from_ymd_opt(2024, 11, 19).unwrap(),
                         amount: Decimal::ZERO,
                         bank_account_id,
+                        memo: None,
                     }
                 )
                 .await
@@ -1071,7 +1296,7 @@ mod tests {
                     id: id1,
                     name: "name".into(),
                     target: None,
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![
                         BudgetAssignment {
                             id: Uuid::new_v4(),
@@ -1091,6 +1316,8 @@ mod tests {
                             },
                         },
                     ],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
@@ -1140,6 +1367,8 @@ mod tests {
                             },
                         ])
                         .collect(),
+                    None,
+                    None,
                 );
 
                 update(&db_pool, updated.clone()).await.unwrap();
@@ -1153,8 +1382,8 @@ mod tests {
                 assert_eq!(fetched, updated);
             }
 
-            #[sqlx::test]
-            pub async fn update_budget_remove_schedule_onetime_target(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_budget_remove_schedule_onetime_target(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let user_id = *USER_ID;
@@ -1179,8 +1408,10 @@ mod tests {
                     id,
                     name: "name".into(),
                     target: Some(target.clone()),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
@@ -1189,7 +1420,7 @@ mod tests {
                     target_amount: dec!(1.2),
                 };
 
-                let updated = Budget::new(id, "newName".into(), Some(updated_target), user_id, vec![]);
+                let updated = Budget::new(id, "newName".into(), Some(updated_target), user_id, vec![], None, None);
 
                 update(&db_pool, updated.clone()).await.unwrap();
 
@@ -1198,8 +1429,8 @@ mod tests {
                 assert_eq!(fetched, updated);
             }
 
-            #[sqlx::test]
-            pub async fn update_budget_remove_schedule_no_target(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_budget_remove_schedule_no_target(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let user_id = *USER_ID;
@@ -1224,13 +1455,15 @@ mod tests {
                     id,
                     name: "name".into(),
                     target: Some(target.clone()),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
 
-                let updated = Budget::new(id, "newName".into(), None, user_id, vec![]);
+                let updated = Budget::new(id, "newName".into(), None, user_id, vec![], None, None);
 
                 update(&db_pool, updated.clone()).await.unwrap();
 
@@ -1239,8 +1472,8 @@ mod tests {
                 assert_eq!(fetched, updated);
             }
 
-            #[sqlx::test]
-            pub async fn update_budget_no_schedule(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_budget_no_schedule(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let user_id = *USER_ID;
@@ -1249,13 +1482,15 @@ mod tests {
                     id,
                     name: "name".into(),
                     target: None,
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
 
-                let updated = Budget::new(id, "newName".into(), None, user_id, vec![]);
+                let updated = Budget::new(id, "newName".into(), None, user_id, vec![], None, None);
 
                 update(&db_pool, updated.clone()).await.unwrap();
 
@@ -1264,8 +1499,8 @@ mod tests {
                 assert_eq!(fetched, updated);
             }
 
-            #[sqlx::test]
-            pub async fn update_budget_schedule(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_budget_schedule(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let user_id = *USER_ID;
@@ -1290,8 +1525,10 @@ mod tests {
                     id,
                     name: "name".into(),
                     target: Some(target.clone()),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget.clone()).await.unwrap();
@@ -1313,7 +1550,7 @@ mod tests {
                     schedule: updated_schedule,
                 };
 
-                let updated = Budget::new(id, "newName".into(), Some(updated_target), user_id, vec![]);
+                let updated = Budget::new(id, "newName".into(), Some(updated_target), user_id, vec![], None, None);
 
                 update(&db_pool, updated.clone()).await.unwrap();
 
@@ -1321,16 +1558,18 @@ mod tests {
 
                 assert_eq!(fetched, updated);
             }
-            #[sqlx::test]
-            pub async fn update_should_update_only_provided_budget(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_should_update_only_provided_budget(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let mut budget1 = Budget {
                     id: Uuid::new_v4(),
                     assignments: vec![],
                     name: "budget 1".into(),
-                    user_id: *USER_ID,
-                    target: None
+                    plan_id: *USER_ID,
+                    target: None,
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 let budget2 = Budget {
@@ -1352,8 +1591,8 @@ mod tests {
 
                 assert_eq!(fetched, expected);
             }
-            #[sqlx::test]
-            pub async fn update_should_update_assignments(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_should_update_assignments(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let budget_id_1 = Uuid::new_v4();
@@ -1367,15 +1606,17 @@ mod tests {
                 let budget_1 = Budget {
                     id: budget_id_1,
                     name: "budget 1".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
-                    assignments: vec![]
+                    assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 let mut budget_2 = Budget {
                     id: budget_id_2,
                     name: "budget 2".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
                     assignments: vec![BudgetAssignment {
                         id: Uuid::new_v4(),
@@ -1385,18 +1626,20 @@ mod tests {
                             from_budget_id: budget_id_1,
                             link_id: Uuid::new_v4()
                         }
-                    }]
+                    }],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget_1.clone()).await.unwrap();
                 create(&db_pool, budget_2.clone()).await.unwrap();
 
                 db::payees::create(&db_pool, payee_id, CreatePayeeRequest {
-                    user_id,
+                    plan_id: user_id,
                     name: "payee".into()
                 }).await.unwrap();
                 db::bank_accounts::create(&db_pool, bank_account_id, CreateBankAccountRequest {
-                    user_id,
+                    plan_id: user_id,
                     name: "bank account".into(),
                     initial_amount: Decimal::ZERO
                 }).await.unwrap();
@@ -1404,9 +1647,10 @@ mod tests {
                     id: transaction_id,
                     payee_id,
                     bank_account_id,
-                    budget_id: budget_id_2,
+                    budget_id: Some(budget_id_2),
                     date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
                     amount: dec!(-14),
+                    memo: None,
                 }).await.unwrap();
 
                 let assignment = &mut budget_2.assignments[0];
@@ -1424,8 +1668,10 @@ mod tests {
                 assert_eq!(fetched, budget_2);
             }
 
-            #[sqlx::test]
-            pub async fn update_should_remove_all_assignments_when_assignments_cleared(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_should_remove_all_assignments_when_assignments_cleared(
+                db_pool: TestPool,
+            ) {
                 test_init(&db_pool).await;
 
                 let budget_id_1 = Uuid::new_v4();
@@ -1435,15 +1681,17 @@ mod tests {
                 let budget_1 = Budget {
                     id: budget_id_1,
                     name: "budget 1".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
-                    assignments: vec![]
+                    assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 let mut budget_2 = Budget {
                     id: budget_id_2,
                     name: "budget 2".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
                     assignments: vec![BudgetAssignment {
                         id: Uuid::new_v4(),
@@ -1453,7 +1701,9 @@ mod tests {
                             from_budget_id: budget_id_1,
                             link_id: Uuid::new_v4()
                         }
-                    }]
+                    }],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget_1.clone()).await.unwrap();
@@ -1467,8 +1717,8 @@ mod tests {
 
                 assert_eq!(fetched, budget_2);
             }
-            #[sqlx::test]
-            pub async fn update_should_remove_only_missing_assignments(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn update_should_remove_only_missing_assignments(db_pool: TestPool) {
                 test_init(&db_pool).await;
 
                 let budget_id_1 = Uuid::new_v4();
@@ -1478,15 +1728,17 @@ mod tests {
                 let budget_1 = Budget {
                     id: budget_id_1,
                     name: "budget 1".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
-                    assignments: vec![]
+                    assignments: vec![],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 let mut budget_2 = Budget {
                     id: budget_id_2,
                     name: "budget 2".into(),
-                    user_id,
+                    plan_id: user_id,
                     target: None,
                     assignments: vec![BudgetAssignment {
                         id: Uuid::new_v4(),
@@ -1504,7 +1756,9 @@ mod tests {
                             from_budget_id: budget_id_1,
                             link_id: Uuid::new_v4()
                         }
-                    }]
+                    }],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget_1.clone()).await.unwrap();
@@ -1523,8 +1777,8 @@ mod tests {
         mod delete_tests {
             use super::*;
 
-            #[sqlx::test]
-            pub async fn delete_budget_test(db_pool: MySqlPool) {
+            #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+            pub async fn delete_budget_test(db_pool: TestPool) {
                 test_init(&db_pool).await;
                 let id = Uuid::new_v4();
                 let id2 = Uuid::new_v4();
@@ -1533,10 +1787,12 @@ mod tests {
 
                 create(&db_pool, Budget {
                     id: id2,
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![],
                     name: "name2".into(),
-                    target: None
+                    target: None,
+                    group_id: None,
+                    archived_at: None,
                 }).await.unwrap();
 
                 let schedule = Schedule {
@@ -1556,13 +1812,15 @@ mod tests {
                         repeating_type: RepeatingTargetType::BuildUpTo,
                         schedule,
                     }),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![BudgetAssignment {
                         id: Uuid::new_v4(),
                         amount: Decimal::ZERO,
                         date: NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
                         source: BudgetAssignmentSource::OtherBudget { from_budget_id: id2, link_id: Uuid::new_v4() }
                     }],
+                    group_id: None,
+                    archived_at: None,
                 };
 
                 create(&db_pool, budget).await.unwrap();
@@ -1572,10 +1830,11 @@ mod tests {
                 let result = get_single(&db_pool, id).await;
                 assert!(matches!(result, Err(Error::NotFound)));
 
-                let assignments_count = sqlx::query_scalar!("SELECT COUNT(*) FROM BudgetAssignments")
-                    .fetch_one(&db_pool)
-                    .await
-                    .unwrap();
+                let assignments_count =
+                    sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM BudgetAssignments")
+                        .fetch_one(&db_pool)
+                        .await
+                        .unwrap();
 
                 assert_eq!(assignments_count, 0);
             }
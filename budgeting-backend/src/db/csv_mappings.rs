@@ -0,0 +1,147 @@
+use anyhow::anyhow;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::models::CsvMapping;
+
+use super::{with_connection, DbExecutor, Error};
+
+#[derive(FromRow)]
+struct CsvMappingModel {
+    delimiter: String,
+    decimal_separator: String,
+    has_header_row: bool,
+    date_column: u32,
+    date_format: String,
+    amount_column: Option<u32>,
+    debit_column: Option<u32>,
+    credit_column: Option<u32>,
+    payee_column: u32,
+}
+
+impl TryFrom<CsvMappingModel> for CsvMapping {
+    type Error = anyhow::Error;
+
+    fn try_from(value: CsvMappingModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            delimiter: value
+                .delimiter
+                .chars()
+                .next()
+                .ok_or(anyhow!("CSV mapping has no delimiter"))?,
+            decimal_separator: value
+                .decimal_separator
+                .chars()
+                .next()
+                .ok_or(anyhow!("CSV mapping has no decimal separator"))?,
+            has_header_row: value.has_header_row,
+            date_column: value.date_column,
+            date_format: value.date_format,
+            amount_column: value.amount_column,
+            debit_column: value.debit_column,
+            credit_column: value.credit_column,
+            payee_column: value.payee_column,
+        })
+    }
+}
+
+pub async fn get(
+    executor: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+) -> Result<CsvMapping, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, CsvMappingModel>(
+            "SELECT delimiter, decimal_separator, has_header_row, date_column, date_format, amount_column, debit_column, credit_column, payee_column
+            FROM CsvMappings
+            WHERE bank_account_id = ?",
+        )
+        .bind(bank_account_id.simple())
+        .fetch_one(connection)
+        .await?
+        .try_into()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+/// Saves the mapping, replacing the bank account's previous one
+pub async fn set(
+    executor: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+    mapping: CsvMapping,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("REPLACE INTO CsvMappings(bank_account_id, delimiter, decimal_separator, has_header_row, date_column, date_format, amount_column, debit_column, credit_column, payee_column)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(bank_account_id.simple())
+        .bind(mapping.delimiter.to_string())
+        .bind(mapping.decimal_separator.to_string())
+        .bind(mapping.has_header_row)
+        .bind(mapping.date_column)
+        .bind(mapping.date_format)
+        .bind(mapping.amount_column)
+        .bind(mapping.debit_column)
+        .bind(mapping.credit_column)
+        .bind(mapping.payee_column)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::{
+        db::{self, TestPool},
+        models::{CreateBankAccountRequest, User},
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn set_and_get_test(db_pool: TestPool) {
+        let user_id = Uuid::new_v4();
+        let bank_account_id = Uuid::new_v4();
+
+        db::users::create(
+            &db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+        db::bank_accounts::create(
+            &db_pool,
+            bank_account_id,
+            CreateBankAccountRequest::new("name".into(), dec!(0), user_id),
+        )
+        .await
+        .unwrap();
+
+        let result = get(&db_pool, bank_account_id).await;
+        assert!(matches!(result, Err(Error::NotFound)));
+
+        let mapping = CsvMapping::new(',', '.', true, 0, "%Y-%m-%d".into(), Some(1), None, None, 2);
+        set(&db_pool, bank_account_id, mapping.clone())
+            .await
+            .unwrap();
+        assert_eq!(get(&db_pool, bank_account_id).await.unwrap(), mapping);
+
+        let mapping = CsvMapping::new(
+            ';',
+            ',',
+            false,
+            1,
+            "%d/%m/%Y".into(),
+            None,
+            Some(3),
+            Some(4),
+            2,
+        );
+        set(&db_pool, bank_account_id, mapping.clone())
+            .await
+            .unwrap();
+        assert_eq!(get(&db_pool, bank_account_id).await.unwrap(), mapping);
+    }
+}
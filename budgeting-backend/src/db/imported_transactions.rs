@@ -0,0 +1,111 @@
+//! Remembers the bank's own id for each imported transaction so importing the same
+//! statement twice doesn't create the transactions again
+
+use uuid::Uuid;
+
+use super::{with_connection, DbExecutor, Error};
+
+pub async fn create(
+    executor: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+    bank_account_id: Uuid,
+    external_id: &str,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO ImportedTransactions(transaction_id, bank_account_id, external_id) VALUES (?, ?, ?)")
+        .bind(transaction_id.simple())
+        .bind(bank_account_id.simple())
+        .bind(external_id)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get_external_ids(
+    executor: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+) -> Result<Box<[String]>, Error> {
+    with_connection!(executor, |connection| {
+        let external_ids = sqlx::query_scalar::<Db, String>(
+            "SELECT external_id FROM ImportedTransactions WHERE bank_account_id = ?",
+        )
+        .bind(bank_account_id.simple())
+        .fetch_all(connection)
+        .await?;
+
+        Ok(external_ids.into_boxed_slice())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    use crate::{
+        db::{self, TestPool},
+        models::{CreateBankAccountRequest, CreatePayeeRequest, Transaction, User},
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
+        let user_id = Uuid::new_v4();
+        let payee_id = Uuid::new_v4();
+        let bank_account_id = Uuid::new_v4();
+
+        db::users::create(
+            &db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+        db::payees::create(
+            &db_pool,
+            payee_id,
+            CreatePayeeRequest::new("name".into(), user_id),
+        )
+        .await
+        .unwrap();
+        db::bank_accounts::create(
+            &db_pool,
+            bank_account_id,
+            CreateBankAccountRequest::new("name".into(), dec!(0), user_id),
+        )
+        .await
+        .unwrap();
+
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            payee_id,
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+            dec!(10),
+            bank_account_id,
+            None,
+            None,
+        );
+        db::transactions::create(&db_pool, transaction.clone())
+            .await
+            .unwrap();
+
+        create(&db_pool, transaction.id, bank_account_id, "2024120501")
+            .await
+            .unwrap();
+
+        let external_ids = get_external_ids(&db_pool, bank_account_id).await.unwrap();
+        assert_eq!(
+            external_ids,
+            vec!["2024120501".to_owned()].into_boxed_slice()
+        );
+
+        db::transactions::delete(&db_pool, transaction.id)
+            .await
+            .unwrap();
+
+        let external_ids = get_external_ids(&db_pool, bank_account_id).await.unwrap();
+        assert!(external_ids.is_empty());
+    }
+}
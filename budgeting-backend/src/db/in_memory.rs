@@ -0,0 +1,851 @@
+//! Repositories kept in maps, for testing route handlers without a database
+
+use std::{cmp::Ordering, collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::models::{
+    BankAccount, Budget, BudgetAssignmentSource, BudgetGroup, CreateBankAccountRequest,
+    CreatePayeeRequest, Payee, PlanRole, ScheduledTransaction, SortOrder, Transaction,
+    TransactionCursor, TransactionFilter, TransactionFlow, TransactionPage, TransactionPageRequest,
+    TransactionSearchPage, TransactionSearchResult, TransactionTotals,
+};
+
+use super::{
+    repositories::{
+        BankAccountRepository, BudgetGroupRepository, BudgetRepository, DueTransactions,
+        OwnershipRepository, PayeeRepository, ScheduledTransactionRepository,
+        TransactionRepository,
+    },
+    Error,
+};
+
+#[derive(Default)]
+pub struct InMemoryRepository {
+    state: Mutex<State>,
+}
+
+/// Writes that can fail part way change a clone, which replaces the state once they succeed
+#[derive(Default, Clone)]
+struct State {
+    roles: HashMap<(Uuid, Uuid), PlanRole>,
+    payees: HashMap<Uuid, Payee>,
+    bank_accounts: HashMap<Uuid, BankAccount>,
+    budget_groups: HashMap<Uuid, BudgetGroup>,
+    budgets: HashMap<Uuid, Budget>,
+    transactions: HashMap<Uuid, Transaction>,
+    scheduled_transactions: HashMap<Uuid, ScheduledTransaction>,
+}
+
+impl State {
+    fn check(&self, user_id: Uuid, plan_id: Option<Uuid>, role: PlanRole) -> Result<(), Error> {
+        let member_role = plan_id
+            .and_then(|plan_id| self.roles.get(&(plan_id, user_id)))
+            .ok_or(Error::NotFound)?;
+
+        if *member_role < role {
+            return Err(Error::Forbidden);
+        }
+
+        Ok(())
+    }
+
+    fn with_balance(&self, bank_account: &BankAccount) -> BankAccount {
+        let transaction_total: Decimal = self
+            .transactions
+            .values()
+            .filter(|transaction| transaction.bank_account_id == bank_account.id)
+            .map(|transaction| transaction.amount)
+            .sum();
+
+        BankAccount {
+            balance: bank_account.initial_amount + transaction_total,
+            ..bank_account.clone()
+        }
+    }
+
+    /// Like the database, transactions whose payee is missing never match
+    fn matches(&self, filter: &TransactionFilter, transaction: &Transaction) -> bool {
+        let Some(payee) = self.payees.get(&transaction.payee_id) else {
+            return false;
+        };
+
+        filter
+            .from_date
+            .is_none_or(|from_date| transaction.date >= from_date)
+            && filter
+                .to_date
+                .is_none_or(|to_date| transaction.date <= to_date)
+            && filter
+                .payee_id
+                .is_none_or(|payee_id| transaction.payee_id == payee_id)
+            && filter
+                .budget_id
+                .is_none_or(|budget_id| transaction.budget_id == Some(budget_id))
+            && filter
+                .min_amount
+                .is_none_or(|min_amount| transaction.amount >= min_amount)
+            && filter
+                .max_amount
+                .is_none_or(|max_amount| transaction.amount <= max_amount)
+            && filter.flow.is_none_or(|flow| match flow {
+                TransactionFlow::Inflow => transaction.amount > Decimal::ZERO,
+                TransactionFlow::Outflow => transaction.amount < Decimal::ZERO,
+            })
+            && filter.search.as_ref().is_none_or(|search| {
+                let search = search.to_lowercase();
+                payee.name.to_lowercase().contains(&search)
+                    || transaction
+                        .memo
+                        .as_ref()
+                        .is_some_and(|memo| memo.to_lowercase().contains(&search))
+            })
+    }
+}
+
+/// Where `transaction` is ordered compared to `cursor`, by the cursor's value then id
+fn compare_to_cursor(transaction: &Transaction, cursor: TransactionCursor) -> Ordering {
+    match cursor {
+        TransactionCursor::Date { date, id } => (transaction.date, transaction.id).cmp(&(date, id)),
+        TransactionCursor::Amount { amount, id } => {
+            (transaction.amount, transaction.id).cmp(&(amount, id))
+        }
+    }
+}
+
+/// Sorts and pages `items` like [`super::transactions::get`], returning the page and the cursor
+/// for the next one
+fn page<T>(
+    mut items: Vec<T>,
+    transaction: impl Fn(&T) -> &Transaction,
+    page: TransactionPageRequest,
+) -> (Box<[T]>, Option<String>) {
+    let in_order = |ordering: Ordering| match page.order {
+        SortOrder::Ascending => ordering,
+        SortOrder::Descending => ordering.reverse(),
+    };
+
+    items.sort_by(|a, b| {
+        in_order(compare_to_cursor(
+            transaction(a),
+            TransactionCursor::new(page.sort, transaction(b)),
+        ))
+    });
+
+    if let Some(after) = page.after {
+        items.retain(|item| in_order(compare_to_cursor(transaction(item), after)).is_gt());
+    }
+
+    let next_cursor = if items.len() > page.limit as usize {
+        items.truncate(page.limit as usize);
+        items
+            .last()
+            .map(|last| TransactionCursor::new(page.sort, transaction(last)).to_string())
+    } else {
+        None
+    };
+
+    (items.into_boxed_slice(), next_cursor)
+}
+
+fn totals<'a>(transactions: impl Iterator<Item = &'a Transaction>) -> TransactionTotals {
+    let mut totals = TransactionTotals::default();
+
+    for transaction in transactions {
+        totals.count += 1;
+        if transaction.amount > Decimal::ZERO {
+            totals.inflow += transaction.amount;
+        } else {
+            totals.outflow += transaction.amount;
+        }
+    }
+
+    totals
+}
+
+impl InMemoryRepository {
+    pub fn add_member(&self, plan_id: Uuid, user_id: Uuid, role: PlanRole) {
+        self.lock().roles.insert((plan_id, user_id), role);
+    }
+
+    pub fn add_payee(&self, payee: Payee) {
+        self.lock().payees.insert(payee.id, payee);
+    }
+
+    pub fn add_bank_account(&self, bank_account: BankAccount) {
+        self.lock()
+            .bank_accounts
+            .insert(bank_account.id, bank_account);
+    }
+
+    pub fn add_budget(&self, budget: Budget) {
+        self.lock().budgets.insert(budget.id, budget);
+    }
+
+    pub fn add_transaction(&self, transaction: Transaction) {
+        self.lock().transactions.insert(transaction.id, transaction);
+    }
+
+    pub fn add_scheduled_transaction(&self, scheduled_transaction: ScheduledTransaction) {
+        self.lock()
+            .scheduled_transactions
+            .insert(scheduled_transaction.id, scheduled_transaction);
+    }
+
+    pub fn payee(&self, payee_id: Uuid) -> Option<Payee> {
+        self.lock().payees.get(&payee_id).cloned()
+    }
+
+    pub fn budget(&self, budget_id: Uuid) -> Option<Budget> {
+        self.lock().budgets.get(&budget_id).cloned()
+    }
+
+    pub fn transaction(&self, transaction_id: Uuid) -> Option<Transaction> {
+        self.lock().transactions.get(&transaction_id).cloned()
+    }
+
+    pub fn scheduled_transaction(
+        &self,
+        scheduled_transaction_id: Uuid,
+    ) -> Option<ScheduledTransaction> {
+        self.lock()
+            .scheduled_transactions
+            .get(&scheduled_transaction_id)
+            .cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl OwnershipRepository for InMemoryRepository {
+    async fn check_plan(&self, user_id: Uuid, plan_id: Uuid, role: PlanRole) -> Result<(), Error> {
+        self.lock().check(user_id, Some(plan_id), role)
+    }
+
+    async fn check_budget(
+        &self,
+        user_id: Uuid,
+        budget_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        let state = self.lock();
+        let plan_id = state.budgets.get(&budget_id).map(|budget| budget.plan_id);
+
+        state.check(user_id, plan_id, role)
+    }
+
+    async fn check_budget_group(
+        &self,
+        user_id: Uuid,
+        budget_group_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        let state = self.lock();
+        let plan_id = state
+            .budget_groups
+            .get(&budget_group_id)
+            .map(|budget_group| budget_group.plan_id);
+
+        state.check(user_id, plan_id, role)
+    }
+
+    async fn check_payee(
+        &self,
+        user_id: Uuid,
+        payee_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        let state = self.lock();
+        let plan_id = state.payees.get(&payee_id).map(|payee| payee.plan_id);
+
+        state.check(user_id, plan_id, role)
+    }
+
+    async fn check_bank_account(
+        &self,
+        user_id: Uuid,
+        bank_account_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        let state = self.lock();
+        let plan_id = state
+            .bank_accounts
+            .get(&bank_account_id)
+            .map(|bank_account| bank_account.plan_id);
+
+        state.check(user_id, plan_id, role)
+    }
+
+    async fn check_transaction(
+        &self,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        let state = self.lock();
+        let plan_id = state
+            .transactions
+            .get(&transaction_id)
+            .and_then(|transaction| state.bank_accounts.get(&transaction.bank_account_id))
+            .map(|bank_account| bank_account.plan_id);
+
+        state.check(user_id, plan_id, role)
+    }
+}
+
+#[async_trait]
+impl PayeeRepository for InMemoryRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[Payee]>, Error> {
+        Ok(self
+            .lock()
+            .payees
+            .values()
+            .filter(|payee| payee.plan_id == plan_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_single(&self, payee_id: Uuid) -> Result<Payee, Error> {
+        self.payee(payee_id).ok_or(Error::NotFound)
+    }
+
+    async fn create(&self, payee_id: Uuid, request: CreatePayeeRequest) -> Result<(), Error> {
+        self.add_payee(Payee::new(payee_id, request.name, request.plan_id));
+
+        Ok(())
+    }
+
+    async fn update(&self, payee: Payee) -> Result<(), Error> {
+        self.add_payee(payee);
+
+        Ok(())
+    }
+
+    async fn delete(&self, payee_id: Uuid) -> Result<(), Error> {
+        self.lock().payees.remove(&payee_id);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BankAccountRepository for InMemoryRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BankAccount]>, Error> {
+        let state = self.lock();
+
+        Ok(state
+            .bank_accounts
+            .values()
+            .filter(|bank_account| bank_account.plan_id == plan_id)
+            .map(|bank_account| state.with_balance(bank_account))
+            .collect())
+    }
+
+    async fn get_single(&self, bank_account_id: Uuid, plan_id: Uuid) -> Result<BankAccount, Error> {
+        let state = self.lock();
+
+        state
+            .bank_accounts
+            .get(&bank_account_id)
+            .filter(|bank_account| bank_account.plan_id == plan_id)
+            .map(|bank_account| state.with_balance(bank_account))
+            .ok_or(Error::NotFound)
+    }
+
+    async fn get_plan_id(&self, bank_account_id: Uuid) -> Result<Uuid, Error> {
+        self.lock()
+            .bank_accounts
+            .get(&bank_account_id)
+            .map(|bank_account| bank_account.plan_id)
+            .ok_or(Error::NotFound)
+    }
+
+    async fn create(
+        &self,
+        bank_account_id: Uuid,
+        request: CreateBankAccountRequest,
+    ) -> Result<(), Error> {
+        self.add_bank_account(BankAccount::new(
+            bank_account_id,
+            request.name,
+            request.initial_amount,
+            request.plan_id,
+            request.initial_amount,
+        ));
+
+        Ok(())
+    }
+
+    async fn update(&self, bank_account_id: Uuid, name: &str) -> Result<(), Error> {
+        if let Some(bank_account) = self.lock().bank_accounts.get_mut(&bank_account_id) {
+            bank_account.name = name.into();
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, bank_account_id: Uuid) -> Result<(), Error> {
+        self.lock().bank_accounts.remove(&bank_account_id);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BudgetGroupRepository for InMemoryRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BudgetGroup]>, Error> {
+        let mut budget_groups: Vec<_> = self
+            .lock()
+            .budget_groups
+            .values()
+            .filter(|budget_group| budget_group.plan_id == plan_id)
+            .cloned()
+            .collect();
+        budget_groups.sort_by(|a, b| (a.sort_order, &a.name).cmp(&(b.sort_order, &b.name)));
+
+        Ok(budget_groups.into_boxed_slice())
+    }
+
+    async fn get_single(&self, budget_group_id: Uuid) -> Result<BudgetGroup, Error> {
+        self.lock()
+            .budget_groups
+            .get(&budget_group_id)
+            .cloned()
+            .ok_or(Error::NotFound)
+    }
+
+    async fn create(&self, budget_group: BudgetGroup) -> Result<(), Error> {
+        self.lock()
+            .budget_groups
+            .insert(budget_group.id, budget_group);
+
+        Ok(())
+    }
+
+    async fn update(&self, budget_group: BudgetGroup) -> Result<(), Error> {
+        self.lock()
+            .budget_groups
+            .insert(budget_group.id, budget_group);
+
+        Ok(())
+    }
+
+    async fn delete(&self, budget_group_id: Uuid) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        state.budget_groups.remove(&budget_group_id);
+        for budget in state.budgets.values_mut() {
+            if budget.group_id == Some(budget_group_id) {
+                budget.group_id = None;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BudgetRepository for InMemoryRepository {
+    async fn get(&self, plan_id: Uuid, include_archived: bool) -> Result<Box<[Budget]>, Error> {
+        Ok(self
+            .lock()
+            .budgets
+            .values()
+            .filter(|budget| {
+                budget.plan_id == plan_id && (include_archived || budget.archived_at.is_none())
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_ids(&self, budget_ids: &[Uuid]) -> Result<Box<[Budget]>, Error> {
+        let state = self.lock();
+
+        Ok(budget_ids
+            .iter()
+            .filter_map(|budget_id| state.budgets.get(budget_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_single(&self, budget_id: Uuid) -> Result<Budget, Error> {
+        self.budget(budget_id).ok_or(Error::NotFound)
+    }
+
+    async fn is_in_use(&self, budget_id: Uuid) -> Result<bool, Error> {
+        let state = self.lock();
+
+        let has_transactions = state
+            .transactions
+            .values()
+            .any(|transaction| transaction.budget_id == Some(budget_id));
+        let has_assignments = state.budgets.values().any(|budget| {
+            (budget.id == budget_id && !budget.assignments.is_empty())
+                || budget.assignments.iter().any(|assignment| {
+                    matches!(
+                        assignment.source,
+                        BudgetAssignmentSource::OtherBudget { from_budget_id, .. }
+                            if from_budget_id == budget_id
+                    )
+                })
+        });
+        let has_scheduled_transactions = state
+            .scheduled_transactions
+            .values()
+            .any(|scheduled_transaction| scheduled_transaction.budget_id == budget_id);
+
+        Ok(has_transactions || has_assignments || has_scheduled_transactions)
+    }
+
+    async fn create(&self, budget: Budget) -> Result<(), Error> {
+        self.add_budget(budget);
+
+        Ok(())
+    }
+
+    async fn assign_from_ready_to_assign(
+        &self,
+        amounts: &[(Uuid, Decimal)],
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut state = self.lock();
+        let mut changed = state.clone();
+
+        for (budget_id, amount) in amounts {
+            changed
+                .budgets
+                .get_mut(budget_id)
+                .ok_or(Error::NotFound)?
+                .move_from_ready_to_assign(*amount, date);
+        }
+
+        *state = changed;
+
+        Ok(())
+    }
+
+    async fn archive(
+        &self,
+        budget_id: Uuid,
+        move_to_budget_id: Option<Uuid>,
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        let mut budget = state
+            .budgets
+            .get(&budget_id)
+            .cloned()
+            .ok_or(Error::NotFound)?;
+
+        if let Some(move_to_budget_id) = move_to_budget_id {
+            let mut move_to = state
+                .budgets
+                .get(&move_to_budget_id)
+                .filter(|_| move_to_budget_id != budget_id)
+                .cloned()
+                .ok_or(Error::NotFound)?;
+            let balance = budget.total_assigned();
+
+            if !balance.is_zero() {
+                Budget::move_between_budgets(&mut budget, &mut move_to, balance, date);
+                state.budgets.insert(move_to.id, move_to);
+            }
+        }
+
+        budget.archived_at = Some(Utc::now());
+        state.budgets.insert(budget.id, budget);
+
+        Ok(())
+    }
+
+    async fn unarchive(&self, budget_id: Uuid) -> Result<(), Error> {
+        self.lock()
+            .budgets
+            .get_mut(&budget_id)
+            .ok_or(Error::NotFound)?
+            .archived_at = None;
+
+        Ok(())
+    }
+
+    async fn update_with_target(
+        &self,
+        budget: Budget,
+        _previous_schedule_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        let assignments = state
+            .budgets
+            .get(&budget.id)
+            .map(|existing| existing.assignments.clone())
+            .unwrap_or_default();
+
+        // the schedule is kept in the budget's target rather than on its own
+        state.budgets.insert(
+            budget.id,
+            Budget {
+                assignments,
+                ..budget
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn delete(&self, budget_id: Uuid, _schedule_id: Option<Uuid>) -> Result<(), Error> {
+        self.lock().budgets.remove(&budget_id);
+
+        Ok(())
+    }
+
+    async fn transfer_between(
+        &self,
+        from_budget_id: Uuid,
+        to_budget_id: Uuid,
+        amount: Decimal,
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        if from_budget_id == to_budget_id {
+            return Err(Error::NotFound);
+        }
+
+        let (Some(mut from), Some(mut to)) = (
+            state.budgets.get(&from_budget_id).cloned(),
+            state.budgets.get(&to_budget_id).cloned(),
+        ) else {
+            return Err(Error::NotFound);
+        };
+
+        Budget::move_between_budgets(&mut from, &mut to, amount, date);
+
+        state.budgets.insert(from.id, from);
+        state.budgets.insert(to.id, to);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for InMemoryRepository {
+    async fn get(
+        &self,
+        bank_account_id: Uuid,
+        filter: &TransactionFilter,
+        page_request: TransactionPageRequest,
+    ) -> Result<TransactionPage, Error> {
+        let state = self.lock();
+
+        let transactions: Vec<_> = state
+            .transactions
+            .values()
+            .filter(|transaction| {
+                transaction.bank_account_id == bank_account_id && state.matches(filter, transaction)
+            })
+            .cloned()
+            .collect();
+
+        let totals = totals(transactions.iter());
+        let (transactions, next_cursor) = page(transactions, |t| t, page_request);
+
+        Ok(TransactionPage {
+            transactions,
+            next_cursor,
+            totals,
+        })
+    }
+
+    async fn get_for_user(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        page_request: TransactionPageRequest,
+    ) -> Result<TransactionSearchPage, Error> {
+        let state = self.lock();
+
+        let results: Vec<_> = state
+            .transactions
+            .values()
+            .filter(|transaction| state.matches(filter, transaction))
+            .filter_map(|transaction| {
+                let bank_account = state.bank_accounts.get(&transaction.bank_account_id)?;
+                if !state.roles.contains_key(&(bank_account.plan_id, user_id)) {
+                    return None;
+                }
+
+                Some(TransactionSearchResult {
+                    transaction: transaction.clone(),
+                    bank_account_name: bank_account.name.clone(),
+                    payee_name: state.payees.get(&transaction.payee_id)?.name.clone(),
+                    budget_name: transaction
+                        .budget_id
+                        .and_then(|budget_id| state.budgets.get(&budget_id))
+                        .map(|budget| budget.name.clone()),
+                })
+            })
+            .collect();
+
+        let totals = totals(results.iter().map(|result| &result.transaction));
+        let (transactions, next_cursor) = page(results, |r| &r.transaction, page_request);
+
+        Ok(TransactionSearchPage {
+            transactions,
+            next_cursor,
+            totals,
+        })
+    }
+
+    async fn get_by_budget_and_payee(
+        &self,
+        budget_id: Uuid,
+        payee_id: Uuid,
+    ) -> Result<Box<[Transaction]>, Error> {
+        Ok(self
+            .lock()
+            .transactions
+            .values()
+            .filter(|transaction| {
+                transaction.budget_id == Some(budget_id) && transaction.payee_id == payee_id
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_single(&self, transaction_id: Uuid) -> Result<Transaction, Error> {
+        self.transaction(transaction_id).ok_or(Error::NotFound)
+    }
+
+    async fn create(&self, transaction: Transaction) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        if let Some(budget_id) = transaction.budget_id {
+            state
+                .budgets
+                .get_mut(&budget_id)
+                .ok_or(Error::NotFound)?
+                .assign_from_transaction(&transaction);
+        }
+
+        state.transactions.insert(transaction.id, transaction);
+
+        Ok(())
+    }
+
+    async fn update(&self, transaction: Transaction) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        if let Some(budget_id) = transaction.budget_id {
+            if !state.budgets.contains_key(&budget_id) {
+                return Err(Error::NotFound);
+            }
+        }
+
+        for budget in state.budgets.values_mut() {
+            budget.sync_transaction_assignment(&transaction);
+        }
+
+        state.transactions.insert(transaction.id, transaction);
+
+        Ok(())
+    }
+
+    async fn delete(&self, transaction_id: Uuid) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        for budget in state.budgets.values_mut() {
+            budget.remove_transaction_assignment(transaction_id);
+        }
+
+        state.transactions.remove(&transaction_id);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ScheduledTransactionRepository for InMemoryRepository {
+    async fn get(&self, bank_account_id: Uuid) -> Result<Box<[ScheduledTransaction]>, Error> {
+        Ok(self
+            .lock()
+            .scheduled_transactions
+            .values()
+            .filter(|scheduled_transaction| {
+                scheduled_transaction.bank_account_id == bank_account_id
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_single(
+        &self,
+        scheduled_transaction_id: Uuid,
+    ) -> Result<ScheduledTransaction, Error> {
+        self.scheduled_transaction(scheduled_transaction_id)
+            .ok_or(Error::NotFound)
+    }
+
+    async fn create(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error> {
+        self.add_scheduled_transaction(scheduled_transaction);
+
+        Ok(())
+    }
+
+    async fn update(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error> {
+        self.add_scheduled_transaction(scheduled_transaction);
+
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        scheduled_transaction_id: Uuid,
+        _schedule_id: Uuid,
+    ) -> Result<(), Error> {
+        self.lock()
+            .scheduled_transactions
+            .remove(&scheduled_transaction_id);
+
+        Ok(())
+    }
+
+    async fn post_due(&self, due: Vec<DueTransactions>) -> Result<Box<[Uuid]>, Error> {
+        let mut state = self.lock();
+        let mut changed = state.clone();
+        let mut created_ids = Vec::new();
+
+        for due in due {
+            let Some(scheduled_transaction) = changed
+                .scheduled_transactions
+                .get_mut(&due.scheduled_transaction_id)
+                .filter(|scheduled_transaction| scheduled_transaction.next_occurrence == due.from)
+            else {
+                continue;
+            };
+            scheduled_transaction.next_occurrence = due.to;
+
+            let budget = changed
+                .budgets
+                .get_mut(&due.budget_id)
+                .ok_or(Error::NotFound)?;
+
+            for transaction in due.transactions {
+                budget.assign_from_transaction(&transaction);
+                created_ids.push(transaction.id);
+                changed.transactions.insert(transaction.id, transaction);
+            }
+        }
+
+        *state = changed;
+
+        Ok(created_ids.into_boxed_slice())
+    }
+}
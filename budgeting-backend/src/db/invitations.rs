@@ -0,0 +1,194 @@
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::Invitation;
+
+use super::{hash_token, with_connection, DbExecutor, Error};
+
+#[derive(FromRow)]
+struct InvitationDbModel {
+    id: uuid::fmt::Simple,
+    plan_id: uuid::fmt::Simple,
+    email: String,
+    role: String,
+    invited_by_user_id: uuid::fmt::Simple,
+}
+
+impl TryFrom<InvitationDbModel> for Invitation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: InvitationDbModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id.into_uuid(),
+            plan_id: value.plan_id.into_uuid(),
+            email: value.email,
+            role: value.role.parse()?,
+            invited_by_user_id: value.invited_by_user_id.into_uuid(),
+        })
+    }
+}
+
+/// Only the hash of `token` is stored, see [`get_with_token`]
+pub async fn create(
+    executor: impl Into<DbExecutor<'_>>,
+    invitation: Invitation,
+    token: &str,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO Invitations(id, plan_id, email, role, invited_by_user_id, token_hash) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(invitation.id.simple())
+        .bind(invitation.plan_id.simple())
+        .bind(invitation.email)
+        .bind(invitation.role.to_string())
+        .bind(invitation.invited_by_user_id.simple())
+        .bind(hash_token(token))
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get_single(
+    executor: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+) -> Result<Invitation, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, InvitationDbModel>(
+            "SELECT id, plan_id, email, role, invited_by_user_id FROM Invitations WHERE id = ?",
+        )
+        .bind(id.simple())
+        .fetch_optional(connection)
+        .await?
+        .ok_or(Error::NotFound)?
+        .try_into()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+/// Gets the invitation only if `token` is the one it was created with
+pub async fn get_with_token(
+    executor: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+    token: &str,
+) -> Result<Invitation, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, InvitationDbModel>(
+            "SELECT id, plan_id, email, role, invited_by_user_id FROM Invitations WHERE id = ? AND token_hash = ?",
+        )
+        .bind(id.simple())
+        .bind(hash_token(token))
+        .fetch_optional(connection)
+        .await?
+        .ok_or(Error::NotFound)?
+        .try_into()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+/// Gets the invitations that haven't been accepted or revoked yet for `plan_id`
+pub async fn get_for_plan(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+) -> Result<Box<[Invitation]>, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, InvitationDbModel>(
+            "SELECT id, plan_id, email, role, invited_by_user_id FROM Invitations WHERE plan_id = ? ORDER BY email",
+        )
+        .bind(plan_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+/// Gets the invitations that haven't been accepted or revoked yet for `email`
+pub async fn get_for_email(
+    executor: impl Into<DbExecutor<'_>>,
+    email: &str,
+) -> Result<Box<[Invitation]>, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, InvitationDbModel>(
+            "SELECT id, plan_id, email, role, invited_by_user_id FROM Invitations WHERE email = ?",
+        )
+        .bind(email)
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Invitations WHERE id = ?")
+            .bind(id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        db::{self, TestPool},
+        models::{PlanRole, User},
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_get_and_delete_test(db_pool: TestPool) {
+        let user_id = Uuid::new_v4();
+
+        db::users::create(
+            &db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+
+        let invitation = Invitation::new(
+            Uuid::new_v4(),
+            user_id,
+            "partner@email.com".into(),
+            PlanRole::Editor,
+            user_id,
+        );
+        create(&db_pool, invitation.clone(), "token").await.unwrap();
+
+        let fetched = get_single(&db_pool, invitation.id).await.unwrap();
+        assert_eq!(fetched, invitation);
+
+        let with_token = get_with_token(&db_pool, invitation.id, "token")
+            .await
+            .unwrap();
+        assert_eq!(with_token, invitation);
+
+        assert!(matches!(
+            get_with_token(&db_pool, invitation.id, "wrong").await,
+            Err(Error::NotFound)
+        ));
+
+        let for_plan = get_for_plan(&db_pool, user_id).await.unwrap();
+        assert_eq!(for_plan, vec![invitation.clone()].into_boxed_slice());
+
+        let for_email = get_for_email(&db_pool, "partner@email.com").await.unwrap();
+        assert_eq!(for_email, vec![invitation.clone()].into_boxed_slice());
+
+        delete(&db_pool, invitation.id).await.unwrap();
+
+        assert!(matches!(
+            get_single(&db_pool, invitation.id).await,
+            Err(Error::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,287 @@
+//! Checks that a resource belongs to a plan the user is a member of, with at least `role`.
+//!
+//! Every check returns [`Error::NotFound`] when the user isn't a member of the resource's plan,
+//! so a caller can't tell someone else's ids apart from ones that don't exist.
+//! A member whose role is too low gets [`Error::Forbidden`] instead
+
+use uuid::Uuid;
+
+use crate::models::PlanRole;
+
+use super::{with_connection, DbExecutor, Error};
+
+async fn check(
+    executor: impl Into<DbExecutor<'_>>,
+    query: &str,
+    user_id: Uuid,
+    id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        let member_role: PlanRole = sqlx::query_scalar::<Db, String>(query)
+            .bind(id.simple())
+            .bind(user_id.simple())
+            .fetch_optional(connection)
+            .await?
+            .ok_or(Error::NotFound)?
+            .parse()
+            .map_err(|e| Error::MappingError { error: e })?;
+
+        if member_role < role {
+            return Err(Error::Forbidden);
+        }
+
+        Ok(())
+    })
+}
+
+pub async fn check_plan(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    plan_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            "SELECT role FROM PlanMembers WHERE plan_id = ? AND user_id = ?",
+            user_id,
+            plan_id,
+            role,
+        )
+        .await
+    })
+}
+
+pub async fn check_budget(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    budget_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            r"
+    SELECT PlanMembers.role
+    FROM Budgets
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = Budgets.plan_id
+    WHERE Budgets.id = ? AND PlanMembers.user_id = ?",
+            user_id,
+            budget_id,
+            role,
+        )
+        .await
+    })
+}
+
+pub async fn check_budget_group(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    budget_group_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            r"
+    SELECT PlanMembers.role
+    FROM BudgetGroups
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = BudgetGroups.plan_id
+    WHERE BudgetGroups.id = ? AND PlanMembers.user_id = ?",
+            user_id,
+            budget_group_id,
+            role,
+        )
+        .await
+    })
+}
+
+pub async fn check_payee(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    payee_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            r"
+    SELECT PlanMembers.role
+    FROM Payees
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = Payees.plan_id
+    WHERE Payees.id = ? AND PlanMembers.user_id = ?",
+            user_id,
+            payee_id,
+            role,
+        )
+        .await
+    })
+}
+
+pub async fn check_bank_account(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    bank_account_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            r"
+    SELECT PlanMembers.role
+    FROM BankAccounts
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = BankAccounts.plan_id
+    WHERE BankAccounts.id = ? AND PlanMembers.user_id = ?",
+            user_id,
+            bank_account_id,
+            role,
+        )
+        .await
+    })
+}
+
+pub async fn check_transaction(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    transaction_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        check(
+            connection,
+            r"
+    SELECT PlanMembers.role
+    FROM Transactions
+    INNER JOIN BankAccounts ON BankAccounts.id = Transactions.bank_account_id
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = BankAccounts.plan_id
+    WHERE Transactions.id = ? AND PlanMembers.user_id = ?",
+            user_id,
+            transaction_id,
+            role,
+        )
+        .await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        db::{self, TestPool},
+        models::{Budget, CreatePayeeRequest, User},
+    };
+
+    use super::*;
+
+    async fn create_user(db_pool: &TestPool) -> Uuid {
+        let user_id = Uuid::new_v4();
+
+        db::users::create(
+            db_pool,
+            User::new(
+                user_id,
+                "name".into(),
+                format!("{}@email.com", user_id.simple()),
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+
+        user_id
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn check_plan_test(db_pool: TestPool) {
+        let user_id = create_user(&db_pool).await;
+        let other_user_id = create_user(&db_pool).await;
+
+        check_plan(&db_pool, user_id, user_id, PlanRole::Owner)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            check_plan(&db_pool, user_id, other_user_id, PlanRole::Viewer).await,
+            Err(Error::NotFound)
+        ));
+        assert!(matches!(
+            check_plan(&db_pool, user_id, Uuid::new_v4(), PlanRole::Viewer).await,
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn check_budget_test(db_pool: TestPool) {
+        let user_id = create_user(&db_pool).await;
+        let other_user_id = create_user(&db_pool).await;
+
+        let budget_id = Uuid::new_v4();
+        db::budgets::create(
+            &db_pool,
+            Budget::new(
+                budget_id,
+                "Groceries".into(),
+                None,
+                user_id,
+                vec![],
+                None,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+
+        check_budget(&db_pool, user_id, budget_id, PlanRole::Editor)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            check_budget(&db_pool, other_user_id, budget_id, PlanRole::Viewer).await,
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn check_payee_test(db_pool: TestPool) {
+        let user_id = create_user(&db_pool).await;
+        let other_user_id = create_user(&db_pool).await;
+
+        let payee_id = Uuid::new_v4();
+        db::payees::create(
+            &db_pool,
+            payee_id,
+            CreatePayeeRequest::new("Shop".into(), user_id),
+        )
+        .await
+        .unwrap();
+
+        check_payee(&db_pool, user_id, payee_id, PlanRole::Editor)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            check_payee(&db_pool, other_user_id, payee_id, PlanRole::Viewer).await,
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn check_plan_role_test(db_pool: TestPool) {
+        let user_id = create_user(&db_pool).await;
+        let viewer_id = create_user(&db_pool).await;
+
+        db::plan_members::create(&db_pool, user_id, viewer_id, PlanRole::Viewer)
+            .await
+            .unwrap();
+
+        check_plan(&db_pool, viewer_id, user_id, PlanRole::Viewer)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            check_plan(&db_pool, viewer_id, user_id, PlanRole::Editor).await,
+            Err(Error::Forbidden)
+        ));
+    }
+}
@@ -1,15 +1,15 @@
-use sqlx::{FromRow, MySql, MySqlPool};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 use crate::models::{CreatePayeeRequest, Payee};
 
-use super::Error;
+use super::{with_connection, DbExecutor, Error};
 
 #[derive(PartialEq, Debug, FromRow)]
 struct PayeeModel {
     id: uuid::fmt::Simple,
     name: String,
-    user_id: uuid::fmt::Simple,
+    plan_id: uuid::fmt::Simple,
 }
 
 impl TryFrom<PayeeModel> for Payee {
@@ -19,83 +19,96 @@ impl TryFrom<PayeeModel> for Payee {
         Ok(Self {
             name: value.name,
             id: value.id.into_uuid(),
-            user_id: value.user_id.into_uuid(),
+            plan_id: value.plan_id.into_uuid(),
         })
     }
 }
 
-pub async fn get(db_pool: &MySqlPool, user_id: Uuid) -> Result<Box<[Payee]>, Error> {
-    let payees: Box<[Payee]> = sqlx::query_as::<MySql, PayeeModel>(
-        "SELECT id, name, user_id FROM Payees WHERE user_id = ?",
-    )
-    .bind(user_id.simple())
-    .fetch_all(db_pool)
-    .await?
-    .into_iter()
-    .map(|payee| payee.try_into().unwrap())
-    .collect();
-
-    Ok(payees)
+pub async fn get(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+) -> Result<Box<[Payee]>, Error> {
+    with_connection!(executor, |connection| {
+        let payees: Box<[Payee]> = sqlx::query_as::<Db, PayeeModel>(
+            "SELECT id, name, plan_id FROM Payees WHERE plan_id = ?",
+        )
+        .bind(plan_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|payee| payee.try_into().unwrap())
+        .collect();
+
+        Ok(payees)
+    })
 }
 
 pub async fn create(
-    db_pool: &MySqlPool,
+    executor: impl Into<DbExecutor<'_>>,
     id: Uuid,
     request: CreatePayeeRequest,
 ) -> Result<(), Error> {
-    sqlx::query!(
-        "INSERT INTO Payees(id, name, user_id) VALUE (?, ?, ?)",
-        id.as_simple(),
-        request.name,
-        request.user_id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO Payees(id, name, plan_id) VALUES (?, ?, ?)")
+            .bind(id.simple())
+            .bind(request.name)
+            .bind(request.plan_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
 }
 
-pub async fn get_single(db_pool: &MySqlPool, id: Uuid) -> Result<Payee, Error> {
-    sqlx::query_as::<MySql, PayeeModel>("SELECT id, name, user_id FROM Payees WHERE id = ?")
-        .bind(id.simple())
-        .fetch_one(db_pool)
-        .await?
-        .try_into()
-        .map_err(|e| Error::MappingError { error: e })
+pub async fn get_single(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<Payee, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, PayeeModel>("SELECT id, name, plan_id FROM Payees WHERE id = ?")
+            .bind(id.simple())
+            .fetch_one(connection)
+            .await?
+            .try_into()
+            .map_err(|e| Error::MappingError { error: e })
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, payee: Payee) -> Result<(), Error> {
-    sqlx::query!(
-        "UPDATE Payees SET name = ? WHERE id = ?",
-        payee.name,
-        payee.id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
+pub async fn update(executor: impl Into<DbExecutor<'_>>, payee: Payee) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE Payees SET name = ? WHERE id = ?")
+            .bind(payee.name)
+            .bind(payee.id.simple())
+            .execute(connection)
+            .await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn delete(db_pool: &MySqlPool, id: Uuid) -> Result<(), Error> {
-    sqlx::query!("DELETE FROM Payees WHERE id = ?", id.as_simple())
-        .execute(db_pool)
-        .await?;
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Payees WHERE id = ?")
+            .bind(id.simple())
+            .execute(connection)
+            .await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::LazyLock;
 
-    use crate::{db::users, models::User};
+    use crate::{
+        db::{users, TestPool},
+        models::User,
+    };
 
     use super::*;
 
     static USER_ID1: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
     static USER_ID2: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-    async fn test_init(db_pool: &MySqlPool) {
+    async fn test_init(db_pool: &TestPool) {
         let user_id1 = *USER_ID1;
         let user_id2 = *USER_ID2;
 
@@ -111,7 +124,7 @@ mod tests {
             User::new(
                 user_id2,
                 "other name".into(),
-                "email@email.com".into(),
+                "other@email.com".into(),
                 None,
             ),
         )
@@ -119,8 +132,8 @@ mod tests {
         .unwrap();
     }
 
-    #[sqlx::test]
-    pub async fn get_payees_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn get_payees_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let id1 = Uuid::new_v4();
@@ -128,19 +141,17 @@ mod tests {
         let user_id1 = *USER_ID1;
         let user_id2 = *USER_ID2;
 
-        sqlx::query!(
-            "INSERT INTO Payees (id, name, user_id)
+        sqlx::query(
+            "INSERT INTO Payees (id, name, plan_id)
             VALUES (?, ?, ?),
                 (?, ?, ?)",
-            // payee 1
-            id1.as_simple(),
-            "name",
-            user_id1.as_simple(),
-            // payee 2
-            id2.as_simple(),
-            "other name",
-            user_id2.as_simple()
         )
+        .bind(id1.as_simple())
+        .bind("name")
+        .bind(user_id1.as_simple())
+        .bind(id2.as_simple())
+        .bind("other name")
+        .bind(user_id2.as_simple())
         .execute(&db_pool)
         .await
         .unwrap();
@@ -153,30 +164,28 @@ mod tests {
         );
     }
 
-    #[sqlx::test]
-    pub async fn get_payee_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn get_payee_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let id = Uuid::new_v4();
         let user_id = *USER_ID1;
 
-        sqlx::query!(
-            "INSERT INTO Payees (id, name, user_id) VALUE (?, ?, ?)",
-            id.as_simple(),
-            "name",
-            user_id.as_simple()
-        )
-        .execute(&db_pool)
-        .await
-        .unwrap();
+        sqlx::query("INSERT INTO Payees (id, name, plan_id) VALUES (?, ?, ?)")
+            .bind(id.simple())
+            .bind("name")
+            .bind(user_id.simple())
+            .execute(&db_pool)
+            .await
+            .unwrap();
 
         let single_result = get_single(&db_pool, id).await.unwrap();
 
         assert_eq!(single_result, Payee::new(id, "name".into(), user_id));
     }
 
-    #[sqlx::test]
-    pub async fn create_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let id = Uuid::new_v4();
@@ -190,87 +199,82 @@ mod tests {
         .await
         .unwrap();
 
-        let fetched = sqlx::query_as::<MySql, PayeeModel>(
-            "SELECT id, name, user_id FROM Payees WHERE id = ?",
-        )
-        .bind(id.simple())
-        .fetch_one(&db_pool)
-        .await
-        .unwrap();
+        let fetched =
+            sqlx::query_as::<_, PayeeModel>("SELECT id, name, plan_id FROM Payees WHERE id = ?")
+                .bind(id.simple())
+                .fetch_one(&db_pool)
+                .await
+                .unwrap();
 
         assert_eq!(
             fetched,
             PayeeModel {
                 id: id.simple(),
                 name: "name".into(),
-                user_id: user_id.simple()
+                plan_id: user_id.simple()
             }
         );
     }
 
-    #[sqlx::test]
-    pub async fn update_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let id = Uuid::new_v4();
         let user_id = *USER_ID1;
 
-        sqlx::query!(
-            "INSERT INTO Payees (id, name, user_id) VALUE (?, ?, ?)",
-            id.as_simple(),
-            "name",
-            user_id.as_simple()
-        )
-        .execute(&db_pool)
-        .await
-        .unwrap();
+        sqlx::query("INSERT INTO Payees (id, name, plan_id) VALUES (?, ?, ?)")
+            .bind(id.simple())
+            .bind("name")
+            .bind(user_id.simple())
+            .execute(&db_pool)
+            .await
+            .unwrap();
 
         let updated = Payee::new(id, "newName".into(), user_id);
 
         update(&db_pool, updated).await.unwrap();
 
-        let fetched = sqlx::query_as::<MySql, PayeeModel>(
-            "SELECT id, name, user_id FROM Payees WHERE id = ?",
-        )
-        .bind(id.simple())
-        .fetch_one(&db_pool)
-        .await
-        .unwrap();
+        let fetched =
+            sqlx::query_as::<_, PayeeModel>("SELECT id, name, plan_id FROM Payees WHERE id = ?")
+                .bind(id.simple())
+                .fetch_one(&db_pool)
+                .await
+                .unwrap();
 
         assert_eq!(
             fetched,
             PayeeModel {
                 id: id.simple(),
                 name: "newName".into(),
-                user_id: user_id.simple()
+                plan_id: user_id.simple()
             }
         );
     }
 
-    #[sqlx::test]
-    pub async fn delete_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let user_id = *USER_ID1;
         let id = Uuid::new_v4();
 
-        sqlx::query!(
-            "INSERT INTO Payees (id, name, user_id) VALUE (?, ?, ?)",
-            id.as_simple(),
-            "name",
-            user_id.as_simple()
-        )
-        .execute(&db_pool)
-        .await
-        .unwrap();
+        sqlx::query("INSERT INTO Payees (id, name, plan_id) VALUES (?, ?, ?)")
+            .bind(id.simple())
+            .bind("name")
+            .bind(user_id.simple())
+            .execute(&db_pool)
+            .await
+            .unwrap();
 
         delete(&db_pool, id).await.unwrap();
 
-        let fetched = sqlx::query!("SELECT COUNT(*) as count FROM Payees WHERE id = ?", id)
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM Payees WHERE id = ?")
+            .bind(id.simple())
             .fetch_one(&db_pool)
             .await
             .unwrap();
 
-        assert_eq!(fetched.count, 0);
+        assert_eq!(count, 0);
     }
 }
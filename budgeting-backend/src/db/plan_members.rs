@@ -0,0 +1,168 @@
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{PlanMember, PlanRole};
+
+use super::{with_connection, DbExecutor, Error};
+
+#[derive(FromRow)]
+struct PlanMemberDbModel {
+    user_id: uuid::fmt::Simple,
+    name: String,
+    email: String,
+    role: String,
+}
+
+impl TryFrom<PlanMemberDbModel> for PlanMember {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PlanMemberDbModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user_id: value.user_id.into_uuid(),
+            name: value.name,
+            email: value.email,
+            role: value.role.parse()?,
+        })
+    }
+}
+
+pub async fn create(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+    user_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO PlanMembers(plan_id, user_id, role) VALUES (?, ?, ?)")
+            .bind(plan_id.simple())
+            .bind(user_id.simple())
+            .bind(role.to_string())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+) -> Result<Box<[PlanMember]>, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, PlanMemberDbModel>(
+            r"
+    SELECT Users.id AS user_id, Users.name, Users.email, PlanMembers.role
+    FROM PlanMembers
+    INNER JOIN Users ON Users.id = PlanMembers.user_id
+    WHERE PlanMembers.plan_id = ?
+    ORDER BY Users.name",
+        )
+        .bind(plan_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+pub async fn update_role(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+    user_id: Uuid,
+    role: PlanRole,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE PlanMembers SET role = ? WHERE plan_id = ? AND user_id = ?")
+            .bind(role.to_string())
+            .bind(plan_id.simple())
+            .bind(user_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn delete(
+    executor: impl Into<DbExecutor<'_>>,
+    plan_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM PlanMembers WHERE plan_id = ? AND user_id = ?")
+            .bind(plan_id.simple())
+            .bind(user_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        db::{self, TestPool},
+        models::User,
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_get_update_and_delete_test(db_pool: TestPool) {
+        let owner_id = Uuid::new_v4();
+        let member_id = Uuid::new_v4();
+
+        db::users::create(
+            &db_pool,
+            User::new(owner_id, "Alex".into(), "alex@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+        db::users::create(
+            &db_pool,
+            User::new(member_id, "Sam".into(), "sam@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+
+        create(&db_pool, owner_id, member_id, PlanRole::Viewer)
+            .await
+            .unwrap();
+
+        let members = get(&db_pool, owner_id).await.unwrap();
+        assert_eq!(
+            members,
+            vec![
+                PlanMember::new(
+                    owner_id,
+                    "Alex".into(),
+                    "alex@email.com".into(),
+                    PlanRole::Owner
+                ),
+                PlanMember::new(
+                    member_id,
+                    "Sam".into(),
+                    "sam@email.com".into(),
+                    PlanRole::Viewer
+                ),
+            ]
+            .into_boxed_slice()
+        );
+
+        update_role(&db_pool, owner_id, member_id, PlanRole::Editor)
+            .await
+            .unwrap();
+
+        let members = get(&db_pool, owner_id).await.unwrap();
+        assert_eq!(members[1].role, PlanRole::Editor);
+
+        delete(&db_pool, owner_id, member_id).await.unwrap();
+
+        let members = get(&db_pool, owner_id).await.unwrap();
+        assert_eq!(members.len(), 1);
+    }
+}
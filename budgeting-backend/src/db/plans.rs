@@ -0,0 +1,226 @@
+use sqlx::{Acquire, FromRow};
+use uuid::Uuid;
+
+use crate::models::{Plan, PlanRole};
+
+use super::{plan_members, with_connection, DbExecutor, Error};
+
+#[derive(PartialEq, Debug, FromRow)]
+struct PlanDbModel {
+    id: uuid::fmt::Simple,
+    name: String,
+}
+
+impl From<PlanDbModel> for Plan {
+    fn from(value: PlanDbModel) -> Self {
+        Self {
+            id: value.id.into_uuid(),
+            name: value.name,
+        }
+    }
+}
+
+/// Creates `plan` with `owner_id` as its only member
+pub async fn create(
+    connection: impl Into<DbExecutor<'_>>,
+    plan: Plan,
+    owner_id: Uuid,
+) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        sqlx::query("INSERT INTO Plans(id, name) VALUES (?, ?)")
+            .bind(plan.id.simple())
+            .bind(plan.name)
+            .execute(&mut *transaction)
+            .await?;
+
+        plan_members::create(&mut *transaction, plan.id, owner_id, PlanRole::Owner).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    })
+}
+
+/// Gets every plan `user_id` is a member of
+pub async fn get(executor: impl Into<DbExecutor<'_>>, user_id: Uuid) -> Result<Box<[Plan]>, Error> {
+    with_connection!(executor, |connection| {
+        let plans = sqlx::query_as::<Db, PlanDbModel>(
+            r"
+    SELECT Plans.id, Plans.name
+    FROM Plans
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = Plans.id
+    WHERE PlanMembers.user_id = ?
+    ORDER BY Plans.name",
+        )
+        .bind(user_id.simple())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+        Ok(plans)
+    })
+}
+
+/// Gets every plan, whoever it belongs to
+pub async fn get_all(executor: impl Into<DbExecutor<'_>>) -> Result<Box<[Plan]>, Error> {
+    with_connection!(executor, |connection| {
+        let plans = sqlx::query_as::<Db, PlanDbModel>("SELECT id, name FROM Plans ORDER BY name")
+            .fetch_all(connection)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(plans)
+    })
+}
+
+/// Gets the plans `user_id` is the owner of
+pub async fn get_owned(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+) -> Result<Box<[Plan]>, Error> {
+    with_connection!(executor, |connection| {
+        let plans = sqlx::query_as::<Db, PlanDbModel>(
+            r"
+    SELECT Plans.id, Plans.name
+    FROM Plans
+    INNER JOIN PlanMembers ON PlanMembers.plan_id = Plans.id
+    WHERE PlanMembers.user_id = ? AND PlanMembers.role = ?
+    ORDER BY Plans.name",
+        )
+        .bind(user_id.simple())
+        .bind(PlanRole::Owner.to_string())
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+        Ok(plans)
+    })
+}
+
+pub async fn get_single(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<Plan, Error> {
+    with_connection!(executor, |connection| {
+        Ok(
+            sqlx::query_as::<Db, PlanDbModel>("SELECT id, name FROM Plans WHERE id = ?")
+                .bind(id.simple())
+                .fetch_optional(connection)
+                .await?
+                .ok_or(Error::NotFound)?
+                .into(),
+        )
+    })
+}
+
+pub async fn update(executor: impl Into<DbExecutor<'_>>, plan: Plan) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE Plans SET name = ? WHERE id = ?")
+            .bind(plan.name)
+            .bind(plan.id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use crate::{
+        db::{self, TestPool},
+        models::User,
+    };
+
+    use super::*;
+
+    static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+    async fn test_init(db_pool: &TestPool) {
+        db::users::create(
+            db_pool,
+            User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn user_has_default_plan(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let plan = get_single(&db_pool, *USER_ID).await.unwrap();
+
+        assert_eq!(plan, Plan::new(*USER_ID, "My Plan".into()));
+
+        let members = plan_members::get(&db_pool, *USER_ID).await.unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].user_id, *USER_ID);
+        assert_eq!(members[0].role, PlanRole::Owner);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let household = Plan::new(Uuid::new_v4(), "Household".into());
+        create(&db_pool, household.clone(), *USER_ID).await.unwrap();
+
+        let fetched = get(&db_pool, *USER_ID).await.unwrap();
+        assert_eq!(
+            fetched,
+            vec![household.clone(), Plan::new(*USER_ID, "My Plan".into())].into_boxed_slice()
+        );
+
+        let fetched_single = get_single(&db_pool, household.id).await.unwrap();
+        assert_eq!(fetched_single, household);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn get_owned_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let other_user_id = Uuid::new_v4();
+        db::users::create(
+            &db_pool,
+            User::new(
+                other_user_id,
+                "other".into(),
+                "other@email.com".into(),
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        plan_members::create(&db_pool, other_user_id, *USER_ID, PlanRole::Editor)
+            .await
+            .unwrap();
+
+        let fetched = get_owned(&db_pool, *USER_ID).await.unwrap();
+        assert_eq!(
+            fetched,
+            vec![Plan::new(*USER_ID, "My Plan".into())].into_boxed_slice()
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let mut plan = Plan::new(Uuid::new_v4(), "Household".into());
+        create(&db_pool, plan.clone(), *USER_ID).await.unwrap();
+
+        plan.name = "Shared".into();
+        update(&db_pool, plan.clone()).await.unwrap();
+
+        let fetched = get_single(&db_pool, plan.id).await.unwrap();
+        assert_eq!(fetched, plan);
+    }
+}
@@ -0,0 +1,116 @@
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{with_connection, DbDecimal, DbExecutor, Error};
+
+/// The money in a plan that hasn't been assigned to a budget yet.
+///
+/// This is every bank account's initial amount plus income transactions (those without a budget),
+/// less whatever has been assigned from the pool into budgets
+pub async fn get(executor: impl Into<DbExecutor<'_>>, plan_id: Uuid) -> Result<Decimal, Error> {
+    with_connection!(executor, |connection| {
+        let amount = sqlx::query_scalar::<Db, DbDecimal>(
+            r"
+    SELECT
+        (SELECT COALESCE(SUM(initial_amount), 0) FROM BankAccounts WHERE plan_id = ?)
+        + (SELECT COALESCE(SUM(t.amount), 0)
+            FROM Transactions t
+            INNER JOIN BankAccounts b ON t.bank_account_id = b.id
+            WHERE b.plan_id = ? AND t.budget_id IS NULL)
+        - (SELECT COALESCE(SUM(a.amount), 0)
+            FROM BudgetAssignments a
+            INNER JOIN Budgets b ON a.budget_id = b.id
+            WHERE b.plan_id = ? AND a.assignment_type = 'FromReadyToAssign')",
+        )
+        .bind(plan_id.simple())
+        .bind(plan_id.simple())
+        .bind(plan_id.simple())
+        .fetch_one(connection)
+        .await?;
+
+        Ok(amount.into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    use crate::{
+        db::{self, TestPool},
+        models::{Budget, CreateBankAccountRequest, CreatePayeeRequest, Transaction, User},
+    };
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn get_test(db_pool: TestPool) {
+        let user_id = Uuid::new_v4();
+        let bank_account_id = Uuid::new_v4();
+        let payee_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 12, 6).unwrap();
+
+        db::users::create(
+            &db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+        db::payees::create(
+            &db_pool,
+            payee_id,
+            CreatePayeeRequest::new("name".into(), user_id),
+        )
+        .await
+        .unwrap();
+        db::bank_accounts::create(
+            &db_pool,
+            bank_account_id,
+            CreateBankAccountRequest::new("name".into(), dec!(100), user_id),
+        )
+        .await
+        .unwrap();
+
+        let mut budget = Budget::new(Uuid::new_v4(), "name".into(), None, user_id, vec![], None, None);
+        db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+        // income
+        db::transactions::create(
+            &db_pool,
+            Transaction::new(
+                Uuid::new_v4(),
+                payee_id,
+                date,
+                dec!(50),
+                bank_account_id,
+                None,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        // spending from a budget doesn't touch the pool
+        db::transactions::create(
+            &db_pool,
+            Transaction::new(
+                Uuid::new_v4(),
+                payee_id,
+                date,
+                dec!(-20),
+                bank_account_id,
+                Some(budget.id),
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+
+        budget.move_from_ready_to_assign(dec!(30), date);
+        db::budgets::update(&db_pool, budget).await.unwrap();
+
+        let amount = get(&db_pool, user_id).await.unwrap();
+
+        assert_eq!(amount, dec!(120));
+    }
+}
@@ -0,0 +1,751 @@
+//! The storage the route handlers need, as traits so their rules can be tested without a database.
+//!
+//! [`SqlRepository`] implements every trait with the functions in [`crate::db`].
+//! Writes that touch more than one table take everything they change, so an implementation can
+//! save it all or nothing. Writes that change budgets' assignments read the budgets themselves,
+//! in the same transaction, so two requests changing a budget at once can't lose either change
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::models::{
+    BankAccount, Budget, BudgetGroup, BudgetTarget, CreateBankAccountRequest, CreatePayeeRequest,
+    Payee, PlanRole, ScheduledTransaction, Transaction, TransactionFilter, TransactionPage,
+    TransactionPageRequest, TransactionSearchPage,
+};
+
+use super::{
+    bank_accounts, budget_groups, budgets, ownership, payees, schedule, scheduled_transactions,
+    transactions, DbPool, Error,
+};
+
+/// Checks that a resource is in a plan the user is a member of, see [`crate::db::ownership`]
+#[async_trait]
+pub trait OwnershipRepository: Send + Sync {
+    async fn check_plan(&self, user_id: Uuid, plan_id: Uuid, role: PlanRole) -> Result<(), Error>;
+
+    async fn check_budget(
+        &self,
+        user_id: Uuid,
+        budget_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error>;
+
+    async fn check_budget_group(
+        &self,
+        user_id: Uuid,
+        budget_group_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error>;
+
+    async fn check_payee(&self, user_id: Uuid, payee_id: Uuid, role: PlanRole)
+        -> Result<(), Error>;
+
+    async fn check_bank_account(
+        &self,
+        user_id: Uuid,
+        bank_account_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error>;
+
+    async fn check_transaction(
+        &self,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait PayeeRepository: Send + Sync {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[Payee]>, Error>;
+
+    async fn get_single(&self, payee_id: Uuid) -> Result<Payee, Error>;
+
+    async fn create(&self, payee_id: Uuid, request: CreatePayeeRequest) -> Result<(), Error>;
+
+    async fn update(&self, payee: Payee) -> Result<(), Error>;
+
+    async fn delete(&self, payee_id: Uuid) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait BankAccountRepository: Send + Sync {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BankAccount]>, Error>;
+
+    /// The bank account, if it's in the plan
+    async fn get_single(&self, bank_account_id: Uuid, plan_id: Uuid) -> Result<BankAccount, Error>;
+
+    async fn get_plan_id(&self, bank_account_id: Uuid) -> Result<Uuid, Error>;
+
+    async fn create(
+        &self,
+        bank_account_id: Uuid,
+        request: CreateBankAccountRequest,
+    ) -> Result<(), Error>;
+
+    async fn update(&self, bank_account_id: Uuid, name: &str) -> Result<(), Error>;
+
+    async fn delete(&self, bank_account_id: Uuid) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait BudgetGroupRepository: Send + Sync {
+    /// The plan's groups, ordered by `sort_order` then name
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BudgetGroup]>, Error>;
+
+    async fn get_single(&self, budget_group_id: Uuid) -> Result<BudgetGroup, Error>;
+
+    async fn create(&self, budget_group: BudgetGroup) -> Result<(), Error>;
+
+    async fn update(&self, budget_group: BudgetGroup) -> Result<(), Error>;
+
+    /// Deletes the group, leaving its budgets without one
+    async fn delete(&self, budget_group_id: Uuid) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait BudgetRepository: Send + Sync {
+    /// Archived budgets are only returned when `include_archived` is set
+    async fn get(&self, plan_id: Uuid, include_archived: bool) -> Result<Box<[Budget]>, Error>;
+
+    /// The budgets that exist out of `budget_ids`
+    async fn get_by_ids(&self, budget_ids: &[Uuid]) -> Result<Box<[Budget]>, Error>;
+
+    async fn get_single(&self, budget_id: Uuid) -> Result<Budget, Error>;
+
+    /// Whether any transactions, assignments or scheduled transactions refer to the budget
+    async fn is_in_use(&self, budget_id: Uuid) -> Result<bool, Error>;
+
+    /// Creates the budget along with the schedule of its target, if it repeats
+    async fn create(&self, budget: Budget) -> Result<(), Error>;
+
+    /// Moves each amount from the plan's Ready to Assign pool into its budget, or back when it's
+    /// negative. [`Error::NotFound`] if any of the budgets is missing
+    async fn assign_from_ready_to_assign(
+        &self,
+        amounts: &[(Uuid, Decimal)],
+        date: NaiveDate,
+    ) -> Result<(), Error>;
+
+    /// Archives the budget, first moving what's left in it to `move_to_budget_id` when set
+    async fn archive(
+        &self,
+        budget_id: Uuid,
+        move_to_budget_id: Option<Uuid>,
+        date: NaiveDate,
+    ) -> Result<(), Error>;
+
+    async fn unarchive(&self, budget_id: Uuid) -> Result<(), Error>;
+
+    /// Saves the budget's name, group and target, but not its assignments. A repeating target's
+    /// schedule is updated when its id is `previous_schedule_id` and created otherwise, and the
+    /// previous schedule is deleted when the budget no longer uses it
+    async fn update_with_target(
+        &self,
+        budget: Budget,
+        previous_schedule_id: Option<Uuid>,
+    ) -> Result<(), Error>;
+
+    /// Deletes the budget and the schedule of its target
+    async fn delete(&self, budget_id: Uuid, schedule_id: Option<Uuid>) -> Result<(), Error>;
+
+    /// Moves `amount` from one budget to the other, reading and saving both together so a
+    /// concurrent change can't be saved over. [`Error::NotFound`] if either budget is missing
+    async fn transfer_between(
+        &self,
+        from_budget_id: Uuid,
+        to_budget_id: Uuid,
+        amount: Decimal,
+        date: NaiveDate,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait TransactionRepository: Send + Sync {
+    /// A page of the bank account's transactions matching `filter`, with totals over all of them
+    async fn get(
+        &self,
+        bank_account_id: Uuid,
+        filter: &TransactionFilter,
+        page: TransactionPageRequest,
+    ) -> Result<TransactionPage, Error>;
+
+    /// A page of the transactions in every bank account of the user's plans matching `filter`,
+    /// with totals over all of them
+    async fn get_for_user(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        page: TransactionPageRequest,
+    ) -> Result<TransactionSearchPage, Error>;
+
+    async fn get_by_budget_and_payee(
+        &self,
+        budget_id: Uuid,
+        payee_id: Uuid,
+    ) -> Result<Box<[Transaction]>, Error>;
+
+    async fn get_single(&self, transaction_id: Uuid) -> Result<Transaction, Error>;
+
+    /// Creates the transaction along with its assignment to its budget
+    async fn create(&self, transaction: Transaction) -> Result<(), Error>;
+
+    /// Updates the transaction and moves or changes its assignment to match, see
+    /// [`Budget::sync_transaction_assignment`]
+    async fn update(&self, transaction: Transaction) -> Result<(), Error>;
+
+    /// Deletes the transaction and its assignment
+    async fn delete(&self, transaction_id: Uuid) -> Result<(), Error>;
+}
+
+/// Occurrences of a scheduled transaction that have come due, see
+/// [`ScheduledTransaction::take_due_transactions`]
+pub struct DueTransactions {
+    pub scheduled_transaction_id: Uuid,
+    pub budget_id: Uuid,
+    /// The `next_occurrence` the transactions were taken from
+    pub from: NaiveDate,
+    /// The `next_occurrence` after the transactions
+    pub to: NaiveDate,
+    pub transactions: Vec<Transaction>,
+}
+
+#[async_trait]
+pub trait ScheduledTransactionRepository: Send + Sync {
+    async fn get(&self, bank_account_id: Uuid) -> Result<Box<[ScheduledTransaction]>, Error>;
+
+    async fn get_single(
+        &self,
+        scheduled_transaction_id: Uuid,
+    ) -> Result<ScheduledTransaction, Error>;
+
+    /// Creates the scheduled transaction along with its schedule
+    async fn create(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error>;
+
+    /// Updates the scheduled transaction along with its schedule
+    async fn update(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error>;
+
+    /// Deletes the scheduled transaction and its schedule
+    async fn delete(&self, scheduled_transaction_id: Uuid, schedule_id: Uuid) -> Result<(), Error>;
+
+    /// Creates the due transactions and assigns them to their budgets, all or nothing.
+    ///
+    /// A concurrent call can read the same `next_occurrence`, so only the call that moves it on
+    /// from `from` to `to` posts those transactions and the other skips them.
+    /// Returns the ids of the transactions that were created
+    async fn post_due(&self, due: Vec<DueTransactions>) -> Result<Box<[Uuid]>, Error>;
+}
+
+#[derive(Clone)]
+pub struct SqlRepository {
+    db_pool: DbPool,
+}
+
+impl SqlRepository {
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl OwnershipRepository for SqlRepository {
+    async fn check_plan(&self, user_id: Uuid, plan_id: Uuid, role: PlanRole) -> Result<(), Error> {
+        ownership::check_plan(&self.db_pool, user_id, plan_id, role).await
+    }
+
+    async fn check_budget(
+        &self,
+        user_id: Uuid,
+        budget_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        ownership::check_budget(&self.db_pool, user_id, budget_id, role).await
+    }
+
+    async fn check_budget_group(
+        &self,
+        user_id: Uuid,
+        budget_group_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        ownership::check_budget_group(&self.db_pool, user_id, budget_group_id, role).await
+    }
+
+    async fn check_payee(
+        &self,
+        user_id: Uuid,
+        payee_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        ownership::check_payee(&self.db_pool, user_id, payee_id, role).await
+    }
+
+    async fn check_bank_account(
+        &self,
+        user_id: Uuid,
+        bank_account_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        ownership::check_bank_account(&self.db_pool, user_id, bank_account_id, role).await
+    }
+
+    async fn check_transaction(
+        &self,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        role: PlanRole,
+    ) -> Result<(), Error> {
+        ownership::check_transaction(&self.db_pool, user_id, transaction_id, role).await
+    }
+}
+
+#[async_trait]
+impl PayeeRepository for SqlRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[Payee]>, Error> {
+        payees::get(&self.db_pool, plan_id).await
+    }
+
+    async fn get_single(&self, payee_id: Uuid) -> Result<Payee, Error> {
+        payees::get_single(&self.db_pool, payee_id).await
+    }
+
+    async fn create(&self, payee_id: Uuid, request: CreatePayeeRequest) -> Result<(), Error> {
+        payees::create(&self.db_pool, payee_id, request).await
+    }
+
+    async fn update(&self, payee: Payee) -> Result<(), Error> {
+        payees::update(&self.db_pool, payee).await
+    }
+
+    async fn delete(&self, payee_id: Uuid) -> Result<(), Error> {
+        payees::delete(&self.db_pool, payee_id).await
+    }
+}
+
+#[async_trait]
+impl BankAccountRepository for SqlRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BankAccount]>, Error> {
+        bank_accounts::get(&self.db_pool, plan_id).await
+    }
+
+    async fn get_single(&self, bank_account_id: Uuid, plan_id: Uuid) -> Result<BankAccount, Error> {
+        bank_accounts::get_single(&self.db_pool, bank_account_id, plan_id).await
+    }
+
+    async fn get_plan_id(&self, bank_account_id: Uuid) -> Result<Uuid, Error> {
+        bank_accounts::get_plan_id(&self.db_pool, bank_account_id).await
+    }
+
+    async fn create(
+        &self,
+        bank_account_id: Uuid,
+        request: CreateBankAccountRequest,
+    ) -> Result<(), Error> {
+        bank_accounts::create(&self.db_pool, bank_account_id, request).await
+    }
+
+    async fn update(&self, bank_account_id: Uuid, name: &str) -> Result<(), Error> {
+        bank_accounts::update(&self.db_pool, bank_account_id, name).await
+    }
+
+    async fn delete(&self, bank_account_id: Uuid) -> Result<(), Error> {
+        bank_accounts::delete(&self.db_pool, bank_account_id).await
+    }
+}
+
+#[async_trait]
+impl BudgetGroupRepository for SqlRepository {
+    async fn get(&self, plan_id: Uuid) -> Result<Box<[BudgetGroup]>, Error> {
+        budget_groups::get(&self.db_pool, plan_id).await
+    }
+
+    async fn get_single(&self, budget_group_id: Uuid) -> Result<BudgetGroup, Error> {
+        budget_groups::get_single(&self.db_pool, budget_group_id).await
+    }
+
+    async fn create(&self, budget_group: BudgetGroup) -> Result<(), Error> {
+        budget_groups::create(&self.db_pool, budget_group).await
+    }
+
+    async fn update(&self, budget_group: BudgetGroup) -> Result<(), Error> {
+        budget_groups::update(&self.db_pool, budget_group).await
+    }
+
+    async fn delete(&self, budget_group_id: Uuid) -> Result<(), Error> {
+        budget_groups::delete(&self.db_pool, budget_group_id).await
+    }
+}
+
+#[async_trait]
+impl BudgetRepository for SqlRepository {
+    async fn get(&self, plan_id: Uuid, include_archived: bool) -> Result<Box<[Budget]>, Error> {
+        budgets::get(&self.db_pool, plan_id, include_archived).await
+    }
+
+    async fn get_by_ids(&self, budget_ids: &[Uuid]) -> Result<Box<[Budget]>, Error> {
+        budgets::get_by_ids(&self.db_pool, budget_ids).await
+    }
+
+    async fn get_single(&self, budget_id: Uuid) -> Result<Budget, Error> {
+        budgets::get_single(&self.db_pool, budget_id).await
+    }
+
+    async fn is_in_use(&self, budget_id: Uuid) -> Result<bool, Error> {
+        budgets::is_in_use(&self.db_pool, budget_id).await
+    }
+
+    async fn create(&self, budget: Budget) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        // the budget has a foreign key to its schedule
+        if let Some(BudgetTarget::Repeating { schedule, .. }) = &budget.target {
+            schedule::create(&mut db_transaction, schedule.clone()).await?;
+        }
+
+        budgets::create(&mut db_transaction, budget).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn assign_from_ready_to_assign(
+        &self,
+        amounts: &[(Uuid, Decimal)],
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let budget_ids: Vec<_> = amounts.iter().map(|(budget_id, _)| *budget_id).collect();
+        let mut changed_budgets: HashMap<_, _> =
+            budgets::get_by_ids_for_update(&mut db_transaction, &budget_ids)
+                .await?
+                .into_vec()
+                .into_iter()
+                .map(|budget| (budget.id, budget))
+                .collect();
+
+        for (budget_id, amount) in amounts {
+            changed_budgets
+                .get_mut(budget_id)
+                .ok_or(Error::NotFound)?
+                .move_from_ready_to_assign(*amount, date);
+        }
+
+        for budget in changed_budgets.into_values() {
+            budgets::update(&mut db_transaction, budget).await?;
+        }
+
+        super::commit(db_transaction).await
+    }
+
+    async fn archive(
+        &self,
+        budget_id: Uuid,
+        move_to_budget_id: Option<Uuid>,
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let budget_ids: Vec<_> = std::iter::once(budget_id)
+            .chain(move_to_budget_id)
+            .collect();
+        let mut found =
+            Vec::from(budgets::get_by_ids_for_update(&mut db_transaction, &budget_ids).await?);
+
+        let mut budget = take_budget(&mut found, budget_id)?;
+
+        if let Some(move_to_budget_id) = move_to_budget_id {
+            let mut move_to = take_budget(&mut found, move_to_budget_id)?;
+            let balance = budget.total_assigned();
+
+            if !balance.is_zero() {
+                Budget::move_between_budgets(&mut budget, &mut move_to, balance, date);
+
+                budgets::update(&mut db_transaction, move_to).await?;
+            }
+        }
+
+        budget.archived_at = Some(Utc::now());
+        budgets::update(&mut db_transaction, budget).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn unarchive(&self, budget_id: Uuid) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let mut budget = budgets::get_single_for_update(&mut db_transaction, budget_id).await?;
+        budget.archived_at = None;
+        budgets::update_details(&mut db_transaction, budget).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn update_with_target(
+        &self,
+        budget: Budget,
+        previous_schedule_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let schedule_id = match &budget.target {
+            Some(BudgetTarget::Repeating { schedule, .. }) => {
+                if previous_schedule_id == Some(schedule.id) {
+                    schedule::update(&mut db_transaction, schedule.clone()).await?;
+                } else {
+                    schedule::create(&mut db_transaction, schedule.clone()).await?;
+                }
+
+                Some(schedule.id)
+            }
+            _ => None,
+        };
+
+        budgets::update_details(&mut db_transaction, budget).await?;
+
+        // the old schedule can only go once the budget no longer refers to it
+        if let Some(previous_schedule_id) = previous_schedule_id {
+            if schedule_id != Some(previous_schedule_id) {
+                schedule::delete(&mut db_transaction, previous_schedule_id).await?;
+            }
+        }
+
+        super::commit(db_transaction).await
+    }
+
+    async fn delete(&self, budget_id: Uuid, schedule_id: Option<Uuid>) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        budgets::delete(&mut db_transaction, budget_id).await?;
+
+        if let Some(schedule_id) = schedule_id {
+            schedule::delete(&mut db_transaction, schedule_id).await?;
+        }
+
+        super::commit(db_transaction).await
+    }
+
+    async fn transfer_between(
+        &self,
+        from_budget_id: Uuid,
+        to_budget_id: Uuid,
+        amount: Decimal,
+        date: NaiveDate,
+    ) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        // read inside the transaction so a concurrent transfer can't save over this one
+        let mut found = Vec::from(
+            budgets::get_by_ids_for_update(&mut db_transaction, &[from_budget_id, to_budget_id])
+                .await?,
+        );
+
+        let mut from = take_budget(&mut found, from_budget_id)?;
+        let mut to = take_budget(&mut found, to_budget_id)?;
+
+        Budget::move_between_budgets(&mut from, &mut to, amount, date);
+
+        budgets::update(&mut db_transaction, from).await?;
+        budgets::update(&mut db_transaction, to).await?;
+
+        super::commit(db_transaction).await
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for SqlRepository {
+    async fn get(
+        &self,
+        bank_account_id: Uuid,
+        filter: &TransactionFilter,
+        page: TransactionPageRequest,
+    ) -> Result<TransactionPage, Error> {
+        transactions::get(&self.db_pool, bank_account_id, filter, page).await
+    }
+
+    async fn get_for_user(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        page: TransactionPageRequest,
+    ) -> Result<TransactionSearchPage, Error> {
+        transactions::get_for_user(&self.db_pool, user_id, filter, page).await
+    }
+
+    async fn get_by_budget_and_payee(
+        &self,
+        budget_id: Uuid,
+        payee_id: Uuid,
+    ) -> Result<Box<[Transaction]>, Error> {
+        transactions::get_by_budget_and_payee(&self.db_pool, budget_id, payee_id).await
+    }
+
+    async fn get_single(&self, transaction_id: Uuid) -> Result<Transaction, Error> {
+        transactions::get_single(&self.db_pool, transaction_id).await
+    }
+
+    async fn create(&self, transaction: Transaction) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let mut budget = match transaction.budget_id {
+            Some(budget_id) => {
+                Some(budgets::get_single_for_update(&mut db_transaction, budget_id).await?)
+            }
+            None => None,
+        };
+
+        if let Some(budget) = &mut budget {
+            budget.assign_from_transaction(&transaction);
+        }
+
+        transactions::create(&mut db_transaction, transaction).await?;
+
+        // budgets are saved after the transaction because their assignments have a foreign key
+        // to it
+        if let Some(budget) = budget {
+            budgets::update(&mut db_transaction, budget).await?;
+        }
+
+        super::commit(db_transaction).await
+    }
+
+    async fn update(&self, transaction: Transaction) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        let assigned_budget = budgets::get_by_assignment_transaction_id_for_update(
+            &mut db_transaction,
+            transaction.id,
+        )
+        .await?;
+        let new_budget = match transaction.budget_id {
+            Some(budget_id)
+                if assigned_budget.as_ref().map(|budget| budget.id) != Some(budget_id) =>
+            {
+                Some(budgets::get_single_for_update(&mut db_transaction, budget_id).await?)
+            }
+            _ => None,
+        };
+
+        for mut budget in assigned_budget.into_iter().chain(new_budget) {
+            if budget.sync_transaction_assignment(&transaction) {
+                budgets::update(&mut db_transaction, budget).await?;
+            }
+        }
+
+        transactions::update(&mut db_transaction, transaction).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn delete(&self, transaction_id: Uuid) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        // the assignment has to go before the transaction it has a foreign key to
+        if let Some(mut budget) = budgets::get_by_assignment_transaction_id_for_update(
+            &mut db_transaction,
+            transaction_id,
+        )
+        .await?
+        {
+            budget.remove_transaction_assignment(transaction_id);
+            budgets::update(&mut db_transaction, budget).await?;
+        }
+
+        transactions::delete(&mut db_transaction, transaction_id).await?;
+
+        super::commit(db_transaction).await
+    }
+}
+
+#[async_trait]
+impl ScheduledTransactionRepository for SqlRepository {
+    async fn get(&self, bank_account_id: Uuid) -> Result<Box<[ScheduledTransaction]>, Error> {
+        scheduled_transactions::get(&self.db_pool, bank_account_id).await
+    }
+
+    async fn get_single(
+        &self,
+        scheduled_transaction_id: Uuid,
+    ) -> Result<ScheduledTransaction, Error> {
+        scheduled_transactions::get_single(&self.db_pool, scheduled_transaction_id).await
+    }
+
+    async fn create(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        schedule::create(&mut db_transaction, scheduled_transaction.schedule.clone()).await?;
+        scheduled_transactions::create(&mut db_transaction, scheduled_transaction).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn update(&self, scheduled_transaction: ScheduledTransaction) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        schedule::update(&mut db_transaction, scheduled_transaction.schedule.clone()).await?;
+        scheduled_transactions::update(&mut db_transaction, scheduled_transaction).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn delete(&self, scheduled_transaction_id: Uuid, schedule_id: Uuid) -> Result<(), Error> {
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        scheduled_transactions::delete(&mut db_transaction, scheduled_transaction_id).await?;
+
+        // schedule must be deleted after the scheduled transaction because of the foreign key
+        schedule::delete(&mut db_transaction, schedule_id).await?;
+
+        super::commit(db_transaction).await
+    }
+
+    async fn post_due(&self, due: Vec<DueTransactions>) -> Result<Box<[Uuid]>, Error> {
+        let mut created_ids = Vec::new();
+        let mut db_transaction = super::begin(&self.db_pool).await?;
+
+        for due in due {
+            let claimed = scheduled_transactions::advance_next_occurrence(
+                &mut db_transaction,
+                due.scheduled_transaction_id,
+                due.from,
+                due.to,
+            )
+            .await?;
+            if !claimed {
+                continue;
+            }
+
+            let mut budget =
+                budgets::get_single_for_update(&mut db_transaction, due.budget_id).await?;
+
+            for transaction in due.transactions {
+                budget.assign_from_transaction(&transaction);
+                created_ids.push(transaction.id);
+
+                transactions::create(&mut db_transaction, transaction).await?;
+            }
+
+            // update budget must happen after transaction create because the budget assignment
+            // has a foreign key to the transaction
+            budgets::update(&mut db_transaction, budget).await?;
+        }
+
+        super::commit(db_transaction).await?;
+
+        Ok(created_ids.into_boxed_slice())
+    }
+}
+
+/// Takes the budget out of the ones read for a write, [`Error::NotFound`] if it's not there
+fn take_budget(budgets: &mut Vec<Budget>, budget_id: Uuid) -> Result<Budget, Error> {
+    let index = budgets
+        .iter()
+        .position(|budget| budget.id == budget_id)
+        .ok_or(Error::NotFound)?;
+
+    Ok(budgets.swap_remove(index))
+}
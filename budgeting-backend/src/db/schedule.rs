@@ -1,11 +1,11 @@
 use anyhow::anyhow;
 use chrono::NaiveDate;
-use sqlx::{prelude::FromRow, MySql, MySqlPool};
+use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
 use crate::models::{Schedule, SchedulePeriod};
 
-use super::Error;
+use super::{with_connection, DbExecutor, Error};
 
 #[derive(FromRow, PartialEq, Debug, Clone)]
 struct ScheduleDbModel {
@@ -44,6 +44,7 @@ impl TryFrom<ScheduleDbModel> for Schedule {
                     ))?,
                 },
                 "Custom" => SchedulePeriod::Custom {
+                    starting_on: value.period_starting_on,
                     period: value
                         .custom_period_type
                         .as_deref()
@@ -68,13 +69,7 @@ impl From<Schedule> for ScheduleDbModel {
         Self {
             id: value.id.simple(),
             period_type: value.period.to_string(),
-            period_starting_on: match value.period {
-                SchedulePeriod::Weekly { starting_on }
-                | SchedulePeriod::Fortnightly { starting_on }
-                | SchedulePeriod::Monthly { starting_on }
-                | SchedulePeriod::Yearly { starting_on } => Some(starting_on),
-                SchedulePeriod::Custom { .. } => None,
-            },
+            period_starting_on: value.period.starting_on(),
             custom_period_type: if let SchedulePeriod::Custom { period, .. } = &value.period {
                 Some(period.to_string())
             } else {
@@ -92,94 +87,106 @@ impl From<Schedule> for ScheduleDbModel {
     }
 }
 
-pub async fn create(db_pool: &MySqlPool, schedule: Schedule) -> Result<(), Error> {
-    let db_model: ScheduleDbModel = schedule.into();
-
-    sqlx::query!(
-        r"
-INSERT INTO Schedules (id, period_type, period_starting_on, custom_period_type, custom_period_every_count)
-VALUE (?, ?, ?, ?, ?)",
-        db_model.id,
-        db_model.period_type,
-        db_model.period_starting_on,
-        db_model.custom_period_type,
-        db_model.custom_period_every_count
-    ).execute(db_pool).await?;
-
-    Ok(())
+pub async fn create(executor: impl Into<DbExecutor<'_>>, schedule: Schedule) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        let db_model: ScheduleDbModel = schedule.into();
+
+        sqlx::query(r"
+    INSERT INTO Schedules (id, period_type, period_starting_on, custom_period_type, custom_period_every_count)
+    VALUES (?, ?, ?, ?, ?)")
+        .bind(db_model.id)
+        .bind(db_model.period_type)
+        .bind(db_model.period_starting_on)
+        .bind(db_model.custom_period_type)
+        .bind(db_model.custom_period_every_count).execute(connection).await?;
+
+        Ok(())
+    })
 }
 
-pub async fn get_single(db_pool: &MySqlPool, id: Uuid) -> Result<Schedule, Error> {
-    sqlx::query_as::<MySql, ScheduleDbModel>(
-        r"
-SELECT id, period_type, period_starting_on, custom_period_type, custom_period_every_count
-FROM Schedules
-WHERE id = ?",
-    )
-    .bind(id.simple())
-    .fetch_optional(db_pool)
-    .await?
-    .map(TryInto::try_into)
-    .ok_or(Error::NotFound)?
-    .map_err(|e| Error::MappingError { error: e })
+pub async fn get_single(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<Schedule, Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query_as::<Db, ScheduleDbModel>(
+            r"
+    SELECT id, period_type, period_starting_on, custom_period_type, custom_period_every_count
+    FROM Schedules
+    WHERE id = ?",
+        )
+        .bind(id.simple())
+        .fetch_optional(connection)
+        .await?
+        .map(TryInto::try_into)
+        .ok_or(Error::NotFound)?
+        .map_err(|e| Error::MappingError { error: e })
+    })
 }
 
-pub async fn get_by_ids(db_pool: &MySqlPool, ids: &[Uuid]) -> Result<Box<[Schedule]>, Error> {
-    if ids.is_empty() {
-        return Ok(Box::new([]));
-    }
+pub async fn get_by_ids(
+    executor: impl Into<DbExecutor<'_>>,
+    ids: &[Uuid],
+) -> Result<Box<[Schedule]>, Error> {
+    with_connection!(executor, |connection| {
+        if ids.is_empty() {
+            return Ok(Box::new([]));
+        }
 
-    let params = vec!["?"; ids.len()];
+        let params = vec!["?"; ids.len()];
 
-    let query_string = format!(
-        r"SELECT id, period_type, period_starting_on, custom_period_type, custom_period_every_count
-FROM Schedules
-WHERE id IN ({})",
-        params.join(", ")
-    );
+        let query_string = format!(
+            r"SELECT id, period_type, period_starting_on, custom_period_type, custom_period_every_count
+    FROM Schedules
+    WHERE id IN ({})",
+            params.join(", ")
+        );
 
-    let mut query = sqlx::query_as::<MySql, ScheduleDbModel>(query_string.as_str());
+        let mut query = sqlx::query_as::<Db, ScheduleDbModel>(query_string.as_str());
 
-    for id in ids {
-        query = query.bind(id.as_simple());
-    }
+        for id in ids {
+            query = query.bind(id.simple());
+        }
 
-    query
-        .fetch_all(db_pool)
-        .await?
-        .into_iter()
-        .map(TryInto::try_into)
-        .collect::<Result<Box<[Schedule]>, _>>()
-        .map_err(|e| Error::MappingError { error: e })
+        query
+            .fetch_all(connection)
+            .await?
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Box<[Schedule]>, _>>()
+            .map_err(|e| Error::MappingError { error: e })
+    })
 }
 
-pub async fn delete(db_pool: &MySqlPool, id: Uuid) -> Result<(), Error> {
-    sqlx::query!("DELETE FROM Schedules WHERE id = ?", id.as_simple())
-        .execute(db_pool)
-        .await?;
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Schedules WHERE id = ?")
+            .bind(id.simple())
+            .execute(connection)
+            .await?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, schedule: Schedule) -> Result<(), Error> {
-    let db_model: ScheduleDbModel = schedule.into();
-    sqlx::query!(
-        "UPDATE Schedules
-    SET period_type = ?,
-    period_starting_on = ?,
-    custom_period_type = ?,
-    custom_period_every_count = ?
-    WHERE Id = ?",
-        db_model.period_type,
-        db_model.period_starting_on,
-        db_model.custom_period_type,
-        db_model.custom_period_every_count,
-        db_model.id
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+pub async fn update(executor: impl Into<DbExecutor<'_>>, schedule: Schedule) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        let db_model: ScheduleDbModel = schedule.into();
+        sqlx::query(
+            "UPDATE Schedules
+        SET period_type = ?,
+        period_starting_on = ?,
+        custom_period_type = ?,
+        custom_period_every_count = ?
+        WHERE Id = ?",
+        )
+        .bind(db_model.period_type)
+        .bind(db_model.period_starting_on)
+        .bind(db_model.custom_period_type)
+        .bind(db_model.custom_period_every_count)
+        .bind(db_model.id)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
 }
 
 #[cfg(test)]
@@ -206,7 +213,7 @@ mod tests {
             let custom_db_model = ScheduleDbModel {
                 id: id.simple(),
                 period_type: "Custom".into(),
-                period_starting_on: None,
+                period_starting_on: Some(started_on),
                 custom_period_type: Some("Weekly".into()),
                 custom_period_every_count: Some(1),
             };
@@ -263,6 +270,7 @@ mod tests {
                     Schedule {
                         id,
                         period: SchedulePeriod::Custom {
+                            starting_on: Some(started_on),
                             period: SchedulePeriodType::Weekly,
                             every_x_periods: 1,
                         },
@@ -276,6 +284,7 @@ mod tests {
                     Schedule {
                         id,
                         period: SchedulePeriod::Custom {
+                            starting_on: Some(started_on),
                             period: SchedulePeriodType::Fortnightly,
                             every_x_periods: 1,
                         },
@@ -289,6 +298,7 @@ mod tests {
                     Schedule {
                         id,
                         period: SchedulePeriod::Custom {
+                            starting_on: Some(started_on),
                             period: SchedulePeriodType::Monthly,
                             every_x_periods: 1,
                         },
@@ -302,6 +312,7 @@ mod tests {
                     Schedule {
                         id,
                         period: SchedulePeriod::Custom {
+                            starting_on: Some(started_on),
                             period: SchedulePeriodType::Yearly,
                             every_x_periods: 1,
                         },
@@ -339,7 +350,7 @@ mod tests {
             let custom_db_model = ScheduleDbModel {
                 id: id.simple(),
                 period_type: "Custom".into(),
-                period_starting_on: None,
+                period_starting_on: Some(started_on),
                 custom_period_type: Some("Weekly".into()),
                 custom_period_every_count: Some(1),
             };
@@ -367,16 +378,32 @@ mod tests {
             }
             .try_into();
             assert!(missing_custom_every_count.is_err());
+            let missing_custom_starting_on: Result<Schedule, _> = ScheduleDbModel {
+                period_starting_on: None,
+                ..custom_db_model.clone()
+            }
+            .try_into();
+            assert!(matches!(
+                missing_custom_starting_on,
+                Ok(Schedule {
+                    period: SchedulePeriod::Custom {
+                        starting_on: None,
+                        ..
+                    },
+                    ..
+                })
+            ));
         }
     }
 
     mod db_tests {
-        use crate::models::SchedulePeriodType;
+
+        use crate::{db::TestPool, models::SchedulePeriodType};
 
         use super::*;
 
-        #[sqlx::test]
-        pub async fn create_schedule_test(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn create_schedule_test(db_pool: TestPool) {
             let id = Uuid::new_v4();
             let result = create(
                 &db_pool,
@@ -391,7 +418,7 @@ mod tests {
 
             assert!(result.is_ok());
 
-            let schedules = sqlx::query_as::<MySql, ScheduleDbModel>(
+            let schedules = sqlx::query_as::<_, ScheduleDbModel>(
                 r"
 SELECT id, period_type, period_starting_on, custom_period_type, custom_period_every_count
 FROM Schedules",
@@ -412,8 +439,8 @@ FROM Schedules",
             );
         }
 
-        #[sqlx::test]
-        pub async fn delete_schedule_test(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn delete_schedule_test(db_pool: TestPool) {
             let id = Uuid::new_v4();
             create(
                 &db_pool,
@@ -435,8 +462,8 @@ FROM Schedules",
             assert!(matches!(find_result, Err(Error::NotFound)));
         }
 
-        #[sqlx::test]
-        pub async fn update_schedule_test(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn update_schedule_test(db_pool: TestPool) {
             let id = Uuid::new_v4();
             create(
                 &db_pool,
@@ -453,6 +480,7 @@ FROM Schedules",
             let updated = Schedule {
                 id,
                 period: SchedulePeriod::Custom {
+                    starting_on: NaiveDate::from_ymd_opt(2024, 9, 27),
                     period: SchedulePeriodType::Monthly,
                     every_x_periods: 4,
                 },
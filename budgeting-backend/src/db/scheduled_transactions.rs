@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::models::{Schedule, ScheduledTransaction};
+
+use super::{schedule, with_connection, DbDecimal, DbExecutor, Error};
+
+#[derive(FromRow, Debug, PartialEq)]
+struct ScheduledTransactionDbModel {
+    id: uuid::fmt::Simple,
+    payee_id: uuid::fmt::Simple,
+    amount: DbDecimal,
+    bank_account_id: uuid::fmt::Simple,
+    budget_id: uuid::fmt::Simple,
+    schedule_id: uuid::fmt::Simple,
+    next_occurrence: NaiveDate,
+}
+
+impl ScheduledTransactionDbModel {
+    fn try_into_scheduled_transaction(
+        self,
+        schedule: Option<Schedule>,
+    ) -> Result<ScheduledTransaction, anyhow::Error> {
+        let schedule = schedule.ok_or(anyhow!(
+            "Missing schedule with id {} for scheduled transaction {}",
+            self.schedule_id,
+            self.id
+        ))?;
+
+        Ok(ScheduledTransaction {
+            id: self.id.into_uuid(),
+            payee_id: self.payee_id.into_uuid(),
+            amount: self.amount.into(),
+            bank_account_id: self.bank_account_id.into_uuid(),
+            budget_id: self.budget_id.into_uuid(),
+            schedule,
+            next_occurrence: self.next_occurrence,
+        })
+    }
+}
+
+pub async fn create(
+    executor: impl Into<DbExecutor<'_>>,
+    scheduled_transaction: ScheduledTransaction,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query(r"
+    INSERT INTO ScheduledTransactions (id, payee_id, amount, bank_account_id, budget_id, schedule_id, next_occurrence)
+    VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(scheduled_transaction.id.simple())
+        .bind(scheduled_transaction.payee_id.simple())
+        .bind(DbDecimal(scheduled_transaction.amount))
+        .bind(scheduled_transaction.bank_account_id.simple())
+        .bind(scheduled_transaction.budget_id.simple())
+        .bind(scheduled_transaction.schedule.id.simple())
+        .bind(scheduled_transaction.next_occurrence)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get(
+    connection: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+) -> Result<Box<[ScheduledTransaction]>, Error> {
+    with_connection!(connection, |connection| {
+        let db_models = sqlx::query_as::<Db, ScheduledTransactionDbModel>(
+            r"
+    SELECT id, payee_id, amount, bank_account_id, budget_id, schedule_id, next_occurrence
+    FROM ScheduledTransactions
+    WHERE bank_account_id = ?",
+        )
+        .bind(bank_account_id.simple())
+        .fetch_all(&mut *connection)
+        .await?;
+
+        let schedule_ids: Vec<_> = db_models
+            .iter()
+            .map(|x| x.schedule_id.into_uuid())
+            .collect();
+
+        let mut schedules: HashMap<_, _> = schedule::get_by_ids(&mut *connection, &schedule_ids)
+            .await?
+            .into_vec()
+            .into_iter()
+            .map(|s| (s.id.simple(), s))
+            .collect();
+
+        db_models
+            .into_iter()
+            .map(|db_model| {
+                // a schedule is owned by a single scheduled transaction, so removing is ok
+                let schedule = schedules.remove(&db_model.schedule_id);
+                db_model.try_into_scheduled_transaction(schedule)
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+pub async fn get_single(
+    connection: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+) -> Result<ScheduledTransaction, Error> {
+    with_connection!(connection, |connection| {
+        let db_model = sqlx::query_as::<Db, ScheduledTransactionDbModel>(
+            r"
+    SELECT id, payee_id, amount, bank_account_id, budget_id, schedule_id, next_occurrence
+    FROM ScheduledTransactions
+    WHERE id = ?",
+        )
+        .bind(id.simple())
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+        let schedule =
+            schedule::get_single(&mut *connection, db_model.schedule_id.into_uuid()).await?;
+
+        db_model
+            .try_into_scheduled_transaction(Some(schedule))
+            .map_err(|e| Error::MappingError { error: e })
+    })
+}
+
+pub async fn update(
+    executor: impl Into<DbExecutor<'_>>,
+    scheduled_transaction: ScheduledTransaction,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query(
+            "UPDATE ScheduledTransactions
+        SET payee_id = ?,
+        amount = ?,
+        budget_id = ?,
+        schedule_id = ?,
+        next_occurrence = ?
+        WHERE id = ?",
+        )
+        .bind(scheduled_transaction.payee_id.simple())
+        .bind(DbDecimal(scheduled_transaction.amount))
+        .bind(scheduled_transaction.budget_id.simple())
+        .bind(scheduled_transaction.schedule.id.simple())
+        .bind(scheduled_transaction.next_occurrence)
+        .bind(scheduled_transaction.id.simple())
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+/// Moves `next_occurrence` from `from` to `to`, unless it's no longer `from` because another
+/// caller already moved it. Returns whether this call moved it
+pub async fn advance_next_occurrence(
+    executor: impl Into<DbExecutor<'_>>,
+    id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<bool, Error> {
+    with_connection!(executor, |connection| {
+        let result = sqlx::query(
+            "UPDATE ScheduledTransactions
+        SET next_occurrence = ?
+        WHERE id = ? AND next_occurrence = ?",
+        )
+        .bind(to)
+        .bind(id.simple())
+        .bind(from)
+        .execute(connection)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    })
+}
+
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, id: Uuid) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM ScheduledTransactions WHERE id = ?")
+            .bind(id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use crate::{
+        db::{self, TestPool},
+        models::{Budget, CreateBankAccountRequest, CreatePayeeRequest, SchedulePeriod, User},
+    };
+
+    use super::*;
+
+    static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+    static BANK_ACCOUNT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+    static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+    static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+    async fn test_init(db_pool: &TestPool) {
+        let user_id = *USER_ID;
+
+        db::users::create(
+            db_pool,
+            User::new(user_id, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+
+        db::payees::create(
+            db_pool,
+            *PAYEE_ID,
+            CreatePayeeRequest::new("name".into(), user_id),
+        )
+        .await
+        .unwrap();
+
+        db::bank_accounts::create(
+            db_pool,
+            *BANK_ACCOUNT_ID,
+            CreateBankAccountRequest::new("name".into(), Decimal::ZERO, user_id),
+        )
+        .await
+        .unwrap();
+
+        db::budgets::create(
+            db_pool,
+            Budget::new(*BUDGET_ID, "name".into(), None, user_id, vec![], None, None),
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn create_scheduled_transaction(db_pool: &TestPool) -> ScheduledTransaction {
+        let schedule = Schedule {
+            id: Uuid::new_v4(),
+            period: SchedulePeriod::Monthly {
+                starting_on: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+            },
+        };
+        db::schedule::create(db_pool, schedule.clone())
+            .await
+            .unwrap();
+
+        let scheduled_transaction = ScheduledTransaction {
+            id: Uuid::new_v4(),
+            payee_id: *PAYEE_ID,
+            amount: dec!(-10.5),
+            bank_account_id: *BANK_ACCOUNT_ID,
+            budget_id: *BUDGET_ID,
+            schedule,
+            next_occurrence: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+        };
+
+        create(db_pool, scheduled_transaction.clone())
+            .await
+            .unwrap();
+
+        scheduled_transaction
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let scheduled_transaction = create_scheduled_transaction(&db_pool).await;
+
+        let fetched = get(&db_pool, *BANK_ACCOUNT_ID).await.unwrap();
+        assert_eq!(
+            fetched,
+            vec![scheduled_transaction.clone()].into_boxed_slice()
+        );
+
+        let fetched_single = get_single(&db_pool, scheduled_transaction.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched_single, scheduled_transaction);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let mut scheduled_transaction = create_scheduled_transaction(&db_pool).await;
+
+        scheduled_transaction.amount = dec!(20);
+        scheduled_transaction.next_occurrence = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        update(&db_pool, scheduled_transaction.clone())
+            .await
+            .unwrap();
+
+        let fetched = get_single(&db_pool, scheduled_transaction.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched, scheduled_transaction);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn advance_next_occurrence_only_once(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let scheduled_transaction = create_scheduled_transaction(&db_pool).await;
+        let from = scheduled_transaction.next_occurrence;
+        let to = from + chrono::Days::new(7);
+
+        assert!(
+            advance_next_occurrence(&db_pool, scheduled_transaction.id, from, to)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !advance_next_occurrence(&db_pool, scheduled_transaction.id, from, to)
+                .await
+                .unwrap()
+        );
+
+        let fetched = get_single(&db_pool, scheduled_transaction.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched.next_occurrence, to);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let scheduled_transaction = create_scheduled_transaction(&db_pool).await;
+
+        delete(&db_pool, scheduled_transaction.id).await.unwrap();
+
+        let result = get_single(&db_pool, scheduled_transaction.id).await;
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+}
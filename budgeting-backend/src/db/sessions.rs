@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::Session;
+
+use super::{hash_token, with_connection, DbExecutor, Error};
+
+#[derive(FromRow)]
+struct SessionDbModel {
+    user_id: uuid::fmt::Simple,
+    expires_at: DateTime<Utc>,
+}
+
+impl SessionDbModel {
+    fn into_session(self, token: &str) -> Session {
+        Session {
+            token: token.to_owned(),
+            user_id: self.user_id.into_uuid(),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+pub async fn create(executor: impl Into<DbExecutor<'_>>, session: Session) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("INSERT INTO Sessions(token_hash, user_id, expires_at) VALUES (?, ?, ?)")
+            .bind(hash_token(&session.token))
+            .bind(session.user_id.simple())
+            .bind(session.expires_at)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn get_single(
+    executor: impl Into<DbExecutor<'_>>,
+    token: &str,
+) -> Result<Session, Error> {
+    with_connection!(executor, |connection| {
+        Ok(sqlx::query_as::<Db, SessionDbModel>(
+            "SELECT user_id, expires_at FROM Sessions WHERE token_hash = ?",
+        )
+        .bind(hash_token(token))
+        .fetch_optional(connection)
+        .await?
+        .ok_or(Error::NotFound)?
+        .into_session(token))
+    })
+}
+
+pub async fn delete(executor: impl Into<DbExecutor<'_>>, token: &str) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Sessions WHERE token_hash = ?")
+            .bind(hash_token(token))
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn delete_expired(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Sessions WHERE user_id = ? AND expires_at <= ?")
+            .bind(user_id.simple())
+            .bind(now)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use chrono::TimeZone;
+
+    use crate::{
+        db::{self, TestPool},
+        models::User,
+    };
+
+    use super::*;
+
+    static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+    async fn test_init(db_pool: &TestPool) {
+        db::users::create(
+            db_pool,
+            User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let session = Session::new(
+            "a".repeat(64),
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 12, 18, 0, 0).unwrap(),
+        );
+        create(&db_pool, session.clone()).await.unwrap();
+
+        let fetched = get_single(&db_pool, &session.token).await.unwrap();
+        assert_eq!(fetched, session);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn stores_token_hash_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let session = Session::new(
+            "a".repeat(64),
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 12, 18, 0, 0).unwrap(),
+        );
+        create(&db_pool, session.clone()).await.unwrap();
+
+        let stored: String = sqlx::query_scalar("SELECT token_hash FROM Sessions")
+            .fetch_one(&db_pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored,
+            "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let session = Session::new(
+            "a".repeat(64),
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 12, 18, 0, 0).unwrap(),
+        );
+        create(&db_pool, session.clone()).await.unwrap();
+
+        delete(&db_pool, &session.token).await.unwrap();
+
+        let result = get_single(&db_pool, &session.token).await;
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_expired_test(db_pool: TestPool) {
+        test_init(&db_pool).await;
+
+        let expired = Session::new(
+            "a".repeat(64),
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap(),
+        );
+        let current = Session::new(
+            "b".repeat(64),
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 31, 0, 0, 0).unwrap(),
+        );
+        create(&db_pool, expired.clone()).await.unwrap();
+        create(&db_pool, current.clone()).await.unwrap();
+
+        delete_expired(
+            &db_pool,
+            *USER_ID,
+            Utc.with_ymd_and_hms(2024, 12, 12, 0, 0, 0).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            get_single(&db_pool, &expired.token).await,
+            Err(Error::NotFound)
+        ));
+        assert_eq!(get_single(&db_pool, &current.token).await.unwrap(), current);
+    }
+}
@@ -1,20 +1,25 @@
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
-use sqlx::{prelude::FromRow, MySql, MySqlPool};
+use sqlx::{prelude::FromRow, Database, Encode, QueryBuilder, Type};
 use uuid::Uuid;
 
-use crate::models::Transaction;
+use crate::models::{
+    SortOrder, Transaction, TransactionCursor, TransactionFilter, TransactionFlow,
+    TransactionPage, TransactionPageRequest, TransactionSearchPage, TransactionSearchResult,
+    TransactionSort, TransactionTotals,
+};
 
-use super::Error;
+use super::{with_connection, DbDecimal, DbExecutor, Error};
 
 #[derive(FromRow)]
 struct TransactionModel {
     id: uuid::fmt::Simple,
     payee_id: uuid::fmt::Simple,
     date: NaiveDate,
-    amount: Decimal,
+    amount: DbDecimal,
     bank_account_id: uuid::fmt::Simple,
-    budget_id: uuid::fmt::Simple,
+    budget_id: Option<uuid::fmt::Simple>,
+    memo: Option<String>,
 }
 
 impl From<TransactionModel> for Transaction {
@@ -23,95 +28,413 @@ impl From<TransactionModel> for Transaction {
             id: value.id.into_uuid(),
             date: value.date,
             payee_id: value.payee_id.into_uuid(),
-            amount: value.amount,
+            amount: value.amount.into(),
             bank_account_id: value.bank_account_id.into(),
-            budget_id: value.budget_id.into(),
+            budget_id: value.budget_id.map(uuid::fmt::Simple::into_uuid),
+            memo: value.memo,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct TransactionSearchResultModel {
+    #[sqlx(flatten)]
+    transaction: TransactionModel,
+    bank_account_name: String,
+    payee_name: String,
+    budget_name: Option<String>,
+}
+
+impl From<TransactionSearchResultModel> for TransactionSearchResult {
+    fn from(value: TransactionSearchResultModel) -> Self {
+        Self {
+            transaction: value.transaction.into(),
+            bank_account_name: value.bank_account_name,
+            payee_name: value.payee_name,
+            budget_name: value.budget_name,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct TransactionTotalsModel {
+    count: i64,
+    inflow: Option<DbDecimal>,
+    outflow: Option<DbDecimal>,
+}
+
+impl From<TransactionTotalsModel> for TransactionTotals {
+    fn from(value: TransactionTotalsModel) -> Self {
+        Self {
+            count: value.count,
+            inflow: value.inflow.map_or(Decimal::ZERO, Decimal::from),
+            outflow: value.outflow.map_or(Decimal::ZERO, Decimal::from),
         }
     }
 }
 
 pub async fn create(
-    db_pool: &MySqlPool,
+    executor: impl Into<DbExecutor<'_>>,
     transaction: Transaction,
 ) -> Result<(), Error> {
-    sqlx::query!(
-        r"
-            INSERT INTO Transactions (id, payee_id, date, amount, bank_account_id, budget_id)
-            VALUE (?, ?, ?, ?, ?, ?)",
-        transaction.id.as_simple(),
-        transaction.payee_id.as_simple(),
-        transaction.date,
-        transaction.amount,
-        transaction.bank_account_id.as_simple(),
-        transaction.budget_id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+    with_connection!(executor, |connection| {
+        sqlx::query(r"
+                INSERT INTO Transactions (id, payee_id, date, amount, bank_account_id, budget_id, memo)
+                VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(transaction.id.simple())
+        .bind(transaction.payee_id.simple())
+        .bind(transaction.date)
+        .bind(DbDecimal(transaction.amount))
+        .bind(transaction.bank_account_id.simple())
+        .bind(transaction.budget_id.map(Uuid::simple))
+        .bind(transaction.memo)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+pub async fn update(
+    executor: impl Into<DbExecutor<'_>>,
+    transaction: Transaction,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query(
+            "UPDATE Transactions
+        SET amount = ?,
+        date = ?,
+        payee_id = ?,
+        budget_id = ?,
+        memo = ?
+        WHERE id = ?",
+        )
+        .bind(DbDecimal(transaction.amount))
+        .bind(transaction.date)
+        .bind(transaction.payee_id.simple())
+        .bind(transaction.budget_id.map(Uuid::simple))
+        .bind(transaction.memo)
+        .bind(transaction.id.simple())
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    })
+}
+
+/// Every transaction in the bank account, in no particular order
+pub async fn get_all(
+    executor: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+) -> Result<Box<[Transaction]>, Error> {
+    with_connection!(executor, |connection| {
+        let transactions = sqlx::query_as::<Db, TransactionModel>(
+            "SELECT id, amount, date, payee_id, bank_account_id, budget_id, memo FROM Transactions WHERE bank_account_id = ?").bind(bank_account_id.simple())
+            .fetch_all(connection)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(transactions)
+    })
+}
+
+/// A page of the bank account's transactions matching `filter`, with totals over all of them
+pub async fn get(
+    connection: impl Into<DbExecutor<'_>>,
+    bank_account_id: Uuid,
+    filter: &TransactionFilter,
+    page: TransactionPageRequest,
+) -> Result<TransactionPage, Error> {
+    with_connection!(connection, |connection| {
+        let mut query = QueryBuilder::new(
+            "SELECT t.id, t.amount, t.date, t.payee_id, t.bank_account_id, t.budget_id, t.memo",
+        );
+        push_bank_account_filter(&mut query, bank_account_id, filter);
+        push_page(&mut query, page);
+
+        let mut transactions: Vec<Transaction> = query
+            .build_query_as::<TransactionModel>()
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        // a page is fetched with one extra transaction to tell whether there's another page
+        let next_cursor = if transactions.len() > page.limit as usize {
+            transactions.truncate(page.limit as usize);
+            transactions
+                .last()
+                .map(|last| TransactionCursor::new(page.sort, last).to_string())
+        } else {
+            None
+        };
+
+        let mut query = QueryBuilder::new(TOTALS_COLUMNS);
+        push_bank_account_filter(&mut query, bank_account_id, filter);
+
+        let totals = query
+            .build_query_as::<TransactionTotalsModel>()
+            .fetch_one(&mut *connection)
+            .await?
+            .into();
+
+        Ok(TransactionPage {
+            transactions: transactions.into_boxed_slice(),
+            next_cursor,
+            totals,
+        })
+    })
+}
+
+/// A page of the transactions in every bank account of the user's plans matching `filter`,
+/// with totals over all of them
+pub async fn get_for_user(
+    connection: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    filter: &TransactionFilter,
+    page: TransactionPageRequest,
+) -> Result<TransactionSearchPage, Error> {
+    with_connection!(connection, |connection| {
+        let mut query = QueryBuilder::new(
+            "SELECT t.id, t.amount, t.date, t.payee_id, t.bank_account_id, t.budget_id, t.memo,
+                ba.name AS bank_account_name, p.name AS payee_name, b.name AS budget_name",
+        );
+        push_user_filter(&mut query, user_id, filter);
+        push_page(&mut query, page);
+
+        let mut transactions: Vec<TransactionSearchResult> = query
+            .build_query_as::<TransactionSearchResultModel>()
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let next_cursor = if transactions.len() > page.limit as usize {
+            transactions.truncate(page.limit as usize);
+            transactions
+                .last()
+                .map(|last| TransactionCursor::new(page.sort, &last.transaction).to_string())
+        } else {
+            None
+        };
+
+        let mut query = QueryBuilder::new(TOTALS_COLUMNS);
+        push_user_filter(&mut query, user_id, filter);
+
+        let totals = query
+            .build_query_as::<TransactionTotalsModel>()
+            .fetch_one(&mut *connection)
+            .await?
+            .into();
+
+        Ok(TransactionSearchPage {
+            transactions: transactions.into_boxed_slice(),
+            next_cursor,
+            totals,
+        })
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, transaction: Transaction) -> Result<(), Error> {
-    sqlx::query!(
-        "UPDATE Transactions
-    SET amount = ?,
-    date = ?,
-    payee_id = ?,
-    budget_id = ?
-    WHERE id = ?",
-        transaction.amount,
-        transaction.date,
-        transaction.payee_id.as_simple(),
-        transaction.budget_id.as_simple(),
-        transaction.id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+const TOTALS_COLUMNS: &str = "SELECT COUNT(*) AS count,
+    SUM(CASE WHEN t.amount > 0 THEN t.amount ELSE 0 END) AS inflow,
+    SUM(CASE WHEN t.amount < 0 THEN t.amount ELSE 0 END) AS outflow";
+
+fn push_bank_account_filter<'args, DB: Database>(
+    query: &mut QueryBuilder<'args, DB>,
+    bank_account_id: Uuid,
+    filter: &TransactionFilter,
+) where
+    NaiveDate: Encode<'args, DB> + Type<DB>,
+    uuid::fmt::Simple: Encode<'args, DB> + Type<DB>,
+    DbDecimal: Encode<'args, DB> + Type<DB>,
+    String: Encode<'args, DB> + Type<DB>,
+{
+    query
+        .push(
+            " FROM Transactions t
+            INNER JOIN Payees p ON p.id = t.payee_id
+            WHERE t.bank_account_id = ",
+        )
+        .push_bind(bank_account_id.simple());
+    push_filter(query, filter);
 }
 
-pub async fn get(db_pool: &MySqlPool, bank_account_id: Uuid) -> Result<Box<[Transaction]>, Error> {
-    let transactions = sqlx::query_as::<MySql, TransactionModel>(
-        "SELECT id, amount, date, payee_id, bank_account_id, budget_id FROM Transactions WHERE bank_account_id = ?").bind(bank_account_id.simple())
-        .fetch_all(db_pool)
-        .await?
-        .into_iter()
-        .map(Into::into)
-        .collect();
+fn push_user_filter<'args, DB: Database>(
+    query: &mut QueryBuilder<'args, DB>,
+    user_id: Uuid,
+    filter: &TransactionFilter,
+) where
+    NaiveDate: Encode<'args, DB> + Type<DB>,
+    uuid::fmt::Simple: Encode<'args, DB> + Type<DB>,
+    DbDecimal: Encode<'args, DB> + Type<DB>,
+    String: Encode<'args, DB> + Type<DB>,
+{
+    query
+        .push(
+            " FROM Transactions t
+            INNER JOIN Payees p ON p.id = t.payee_id
+            INNER JOIN BankAccounts ba ON ba.id = t.bank_account_id
+            INNER JOIN PlanMembers pm ON pm.plan_id = ba.plan_id
+            LEFT JOIN Budgets b ON b.id = t.budget_id
+            WHERE pm.user_id = ",
+        )
+        .push_bind(user_id.simple());
+    push_filter(query, filter);
+}
 
-    Ok(transactions)
+/// Adds a condition for each filter that's set, each starting with `AND`
+fn push_filter<'args, DB: Database>(query: &mut QueryBuilder<'args, DB>, filter: &TransactionFilter)
+where
+    NaiveDate: Encode<'args, DB> + Type<DB>,
+    uuid::fmt::Simple: Encode<'args, DB> + Type<DB>,
+    DbDecimal: Encode<'args, DB> + Type<DB>,
+    String: Encode<'args, DB> + Type<DB>,
+{
+    if let Some(from_date) = filter.from_date {
+        query.push(" AND t.date >= ").push_bind(from_date);
+    }
+    if let Some(to_date) = filter.to_date {
+        query.push(" AND t.date <= ").push_bind(to_date);
+    }
+    if let Some(payee_id) = filter.payee_id {
+        query.push(" AND t.payee_id = ").push_bind(payee_id.simple());
+    }
+    if let Some(budget_id) = filter.budget_id {
+        query.push(" AND t.budget_id = ").push_bind(budget_id.simple());
+    }
+    if let Some(min_amount) = filter.min_amount {
+        query.push(" AND t.amount >= ").push_bind(DbDecimal(min_amount));
+    }
+    if let Some(max_amount) = filter.max_amount {
+        query.push(" AND t.amount <= ").push_bind(DbDecimal(max_amount));
+    }
+    match filter.flow {
+        Some(TransactionFlow::Inflow) => {
+            query.push(" AND t.amount > 0");
+        }
+        Some(TransactionFlow::Outflow) => {
+            query.push(" AND t.amount < 0");
+        }
+        None => (),
+    }
+    if let Some(search) = &filter.search {
+        // `!` rather than a backslash, which MySQL and SQLite treat differently in literals
+        let pattern = format!(
+            "%{}%",
+            search.replace('!', "!!").replace('%', "!%").replace('_', "!_")
+        );
+        query
+            .push(" AND (p.name LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '!' OR t.memo LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '!')");
+    }
 }
 
-pub async fn get_single(db_pool: &MySqlPool, transaction_id: Uuid) -> Result<Transaction, Error> {
-    Ok(sqlx::query_as::<MySql, TransactionModel>(
-        "SELECT id, amount, date, payee_id, bank_account_id, budget_id FROM Transactions WHERE id = ?").bind(transaction_id.simple())
-        .fetch_optional(db_pool)
-        .await?
-        .ok_or(Error::NotFound)?
-        .into())
+/// Orders by the sort column then the id, so a cursor can say exactly where a page ended
+fn push_page<'args, DB: Database>(query: &mut QueryBuilder<'args, DB>, page: TransactionPageRequest)
+where
+    NaiveDate: Encode<'args, DB> + Type<DB>,
+    uuid::fmt::Simple: Encode<'args, DB> + Type<DB>,
+    DbDecimal: Encode<'args, DB> + Type<DB>,
+    i64: Encode<'args, DB> + Type<DB>,
+{
+    let column = match page.sort {
+        TransactionSort::Date => "t.date",
+        TransactionSort::Amount => "t.amount",
+    };
+    let (comparison, direction) = match page.order {
+        SortOrder::Ascending => (">", "ASC"),
+        SortOrder::Descending => ("<", "DESC"),
+    };
+
+    if let Some(after) = page.after {
+        let push_value = |query: &mut QueryBuilder<'args, DB>| match after {
+            TransactionCursor::Date { date, .. } => {
+                query.push_bind(date);
+            }
+            TransactionCursor::Amount { amount, .. } => {
+                query.push_bind(DbDecimal(amount));
+            }
+        };
+
+        query.push(format!(" AND ({column} {comparison} "));
+        push_value(query);
+        query.push(format!(" OR ({column} = "));
+        push_value(query);
+        query
+            .push(format!(" AND t.id {comparison} "))
+            .push_bind(after.id().simple())
+            .push("))");
+    }
+
+    query
+        .push(format!(" ORDER BY {column} {direction}, t.id {direction} LIMIT "))
+        .push_bind(i64::from(page.limit) + 1);
+}
+
+pub async fn get_by_budget_and_payee(
+    executor: impl Into<DbExecutor<'_>>,
+    budget_id: Uuid,
+    payee_id: Uuid,
+) -> Result<Box<[Transaction]>, Error> {
+    with_connection!(executor, |connection| {
+        let transactions = sqlx::query_as::<Db, TransactionModel>(
+            "SELECT id, amount, date, payee_id, bank_account_id, budget_id, memo FROM Transactions WHERE budget_id = ? AND payee_id = ?")
+            .bind(budget_id.simple())
+            .bind(payee_id.simple())
+            .fetch_all(connection)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(transactions)
+    })
 }
 
-pub async fn delete(db_pool: &MySqlPool, transaction_id: Uuid) -> Result<(), Error> {
-    sqlx::query!(
-        "DELETE FROM Transactions WHERE id = ?",
-        transaction_id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
+pub async fn get_single(
+    executor: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+) -> Result<Transaction, Error> {
+    with_connection!(executor, |connection| {
+        Ok(sqlx::query_as::<Db, TransactionModel>(
+            "SELECT id, amount, date, payee_id, bank_account_id, budget_id, memo FROM Transactions WHERE id = ?").bind(transaction_id.simple())
+            .fetch_optional(connection)
+            .await?
+            .ok_or(Error::NotFound)?
+            .into())
+    })
+}
 
-    Ok(())
+pub async fn delete(
+    executor: impl Into<DbExecutor<'_>>,
+    transaction_id: Uuid,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("DELETE FROM Transactions WHERE id = ?")
+            .bind(transaction_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::OnceLock;
 
-    use rust_decimal::prelude::FromPrimitive;
+    use rust_decimal::{prelude::FromPrimitive, Decimal};
     use rust_decimal_macros::dec;
 
     use crate::{
-        db,
+        db::{self, TestPool},
         extensions::decimal::DecimalExt,
         models::{Budget, CreateBankAccountRequest, CreatePayeeRequest, User},
     };
@@ -123,7 +446,7 @@ mod tests {
     static BUDGET_ID: OnceLock<Uuid> = OnceLock::new();
     static PAYEE_ID: OnceLock<Uuid> = OnceLock::new();
 
-    async fn test_init(db_pool: &MySqlPool) {
+    async fn test_init(db_pool: &TestPool) {
         let user_id = *USER_ID.get_or_init(Uuid::new_v4);
         let bank_account_id = *BANK_ACCOUNT_ID.get_or_init(Uuid::new_v4);
         let budget_id = *BUDGET_ID.get_or_init(Uuid::new_v4);
@@ -146,7 +469,7 @@ mod tests {
 
         db::budgets::create(
             db_pool,
-            Budget::new(budget_id, "Budget".into(), None, user_id, vec![]),
+            Budget::new(budget_id, "Budget".into(), None, user_id, vec![], None, None),
         )
         .await
         .unwrap();
@@ -160,8 +483,8 @@ mod tests {
         .unwrap();
     }
 
-    #[sqlx::test]
-    pub async fn create_and_get_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn create_and_get_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let transaction_id = Uuid::new_v4();
@@ -173,18 +496,19 @@ mod tests {
             &db_pool,
             Transaction {
                 id: transaction_id,
-                budget_id,
+                budget_id: Some(budget_id),
                 payee_id,
                 bank_account_id,
                 date: NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
-                amount: dec!(1.2)
+                amount: dec!(1.2),
+                memo: None,
             }
         )
         .await;
 
         assert!(result.is_ok());
 
-        let transactions = get(&db_pool, bank_account_id).await;
+        let transactions = get_all(&db_pool, bank_account_id).await;
 
         assert!(transactions.is_ok());
 
@@ -202,14 +526,15 @@ mod tests {
                 NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
                 Decimal::ZERO,
                 bank_account_id,
-                budget_id
+                Some(budget_id),
+                None,
             )
         );
         assert!(amount.approximately_eq(dec!(1.2), dec!(0.001)));
     }
 
-    #[sqlx::test]
-    pub async fn update_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_test(db_pool: TestPool) {
         test_init(&db_pool).await;
         let transaction_id = Uuid::new_v4();
 
@@ -231,7 +556,7 @@ mod tests {
 
         db::budgets::create(
             &db_pool,
-            Budget::new(budget_id_2, "Budget2".into(), None, user_id, vec![]),
+            Budget::new(budget_id_2, "Budget2".into(), None, user_id, vec![], None, None),
         )
         .await
         .unwrap();
@@ -241,10 +566,11 @@ mod tests {
             Transaction {
                 id: transaction_id,
                 bank_account_id,
-                budget_id: budget_id_1,
+                budget_id: Some(budget_id_1),
                 payee_id: payee_id_1,
                 date: NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
-                amount: dec!(1.2)
+                amount: dec!(1.2),
+                memo: None,
             }
         )
         .await
@@ -256,14 +582,15 @@ mod tests {
             NaiveDate::from_ymd_opt(2024, 10, 4).unwrap(),
             Decimal::from_f32(-1.2).unwrap(),
             bank_account_id,
-            budget_id_2,
+            Some(budget_id_2),
+            None,
         );
 
         let result = update(&db_pool, updated.clone()).await;
 
         assert!(result.is_ok());
 
-        let found_transactions = get(&db_pool, bank_account_id).await.unwrap();
+        let found_transactions = get_all(&db_pool, bank_account_id).await.unwrap();
 
         assert_eq!(found_transactions.len(), 1);
         let mut found_transaction = found_transactions[0].clone();
@@ -278,8 +605,8 @@ mod tests {
         assert!(found_amount.approximately_eq(updated_amount, dec!(0.001)));
     }
 
-    #[sqlx::test]
-    pub async fn delete_test(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_test(db_pool: TestPool) {
         test_init(&db_pool).await;
 
         let transaction_id = Uuid::new_v4();
@@ -293,9 +620,10 @@ mod tests {
                 id: transaction_id,
                 payee_id,
                 bank_account_id,
-                budget_id,
+                budget_id: Some(budget_id),
                 date: NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
-                amount: dec!(1.2)
+                amount: dec!(1.2),
+                memo: None,
             }
         )
         .await
@@ -1,12 +1,14 @@
 use std::{collections::HashMap, hash::RandomState};
 
 use anyhow::bail;
-use sqlx::{prelude::FromRow, MySql, MySqlPool};
+use sqlx::{prelude::FromRow, Acquire};
 use uuid::Uuid;
 
-use crate::models::{Schedule, User};
+use crate::models::{Plan, Schedule, User};
 
-use super::{schedule, Error};
+use super::{plans, schedule, with_connection, DbExecutor, Error};
+
+const DEFAULT_PLAN_NAME: &str = "My Plan";
 
 #[derive(FromRow)]
 struct UserDbModel {
@@ -47,86 +49,152 @@ impl TryInto<User> for (UserDbModel, Option<Schedule>) {
     }
 }
 
-pub async fn get(db_pool: &MySqlPool) -> Result<Box<[User]>, Error> {
-    let user_db_models =
-        sqlx::query_as::<MySql, UserDbModel>("SELECT id, email, name, pay_schedule_id FROM Users")
-            .fetch_all(db_pool)
-            .await?;
+pub async fn get(connection: impl Into<DbExecutor<'_>>) -> Result<Box<[User]>, Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        let user_db_models =
+            sqlx::query_as::<Db, UserDbModel>("SELECT id, email, name, pay_schedule_id FROM Users")
+                .fetch_all(&mut *transaction)
+                .await?;
+
+        let schedule_ids: Vec<_> = user_db_models
+            .iter()
+            .filter_map(|x| x.pay_schedule_id.map(uuid::fmt::Simple::into_uuid))
+            .collect();
+
+        let schedules: Vec<_> = schedule::get_by_ids(&mut *transaction, &schedule_ids)
+            .await?
+            .into();
+
+        transaction.commit().await?;
+
+        let mut schedules_map = schedules
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _, RandomState>>();
+
+        let users = user_db_models
+            .into_iter()
+            .map(|user| {
+                (
+                    user.pay_schedule_id
+                        .and_then(|x| schedules_map.remove(x.as_uuid())),
+                    user,
+                )
+            })
+            .map(TryInto::try_into)
+            .collect::<Result<Box<[User]>, _>>()
+            .map_err(|e| Error::MappingError { error: e })?;
+
+        Ok(users)
+    })
+}
 
-    let schedule_ids: Vec<_> = user_db_models
-        .iter()
-        .filter_map(|x| x.pay_schedule_id.map(uuid::fmt::Simple::into_uuid))
-        .collect();
-
-    let schedules: Vec<_> = schedule::get_by_ids(db_pool, &schedule_ids).await?.into();
-
-    let mut schedules_map = schedules
-        .into_iter()
-        .map(|x| (x.id, x))
-        .collect::<HashMap<_, _, RandomState>>();
-
-    let users = user_db_models
-        .into_iter()
-        .map(|user| {
-            (
-                user.pay_schedule_id
-                    .and_then(|x| schedules_map.remove(x.as_uuid())),
-                user,
+pub async fn get_single(
+    connection: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+) -> Result<User, Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        let db_model = sqlx::query_as::<Db, UserDbModel>(
+            "SELECT id, name, email, pay_schedule_id FROM Users WHERE id = ?",
+        )
+        .bind(user_id.simple())
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+        let schedule = if let Some(schedule_id) = db_model.pay_schedule_id {
+            Some(schedule::get_single(&mut *transaction, schedule_id.into_uuid()).await?)
+        } else {
+            None
+        };
+
+        transaction.commit().await?;
+
+        let user = (db_model, schedule)
+            .try_into()
+            .map_err(|e: anyhow::Error| Error::MappingError { error: e })?;
+
+        Ok(user)
+    })
+}
+
+/// Also creates the user's default plan, which shares the user's id
+pub async fn create(connection: impl Into<DbExecutor<'_>>, user: User) -> Result<(), Error> {
+    with_connection!(connection, |connection| {
+        let mut transaction = connection.begin().await?;
+
+        sqlx::query("INSERT INTO Users(id, name, email, pay_schedule_id) VALUES (?, ?, ?, ?)")
+            .bind(user.id.simple())
+            .bind(user.name)
+            .bind(user.email)
+            .bind(
+                user.pay_frequency
+                    .map(|schedule| uuid::fmt::Simple::from(schedule.id)),
             )
-        })
-        .map(TryInto::try_into)
-        .collect::<Result<Box<[User]>, _>>()
-        .map_err(|e| Error::MappingError { error: e })?;
+            .execute(&mut *transaction)
+            .await?;
 
-    Ok(users)
+        plans::create(
+            &mut *transaction,
+            Plan::new(user.id, DEFAULT_PLAN_NAME.into()),
+            user.id,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    })
 }
 
-pub async fn get_single(db_pool: &MySqlPool, user_id: Uuid) -> Result<User, Error> {
-    let db_model = sqlx::query_as::<MySql, UserDbModel>(
-        "SELECT id, name, email, pay_schedule_id FROM Users WHERE id = ?",
-    )
-    .bind(user_id.simple())
-    .fetch_optional(db_pool)
-    .await?
-    .ok_or(Error::NotFound)?;
-
-    let schedule = if let Some(schedule_id) = db_model.pay_schedule_id {
-        Some(schedule::get_single(db_pool, schedule_id.into_uuid()).await?)
-    } else {
-        None
-    };
-
-    let user = (db_model, schedule)
-        .try_into()
-        .map_err(|e: anyhow::Error| Error::MappingError { error: e })?;
-
-    Ok(user)
+pub async fn update(executor: impl Into<DbExecutor<'_>>, user: User) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE Users SET name = ?, pay_schedule_id = ? WHERE id = ?")
+            .bind(user.name)
+            .bind(user.pay_frequency.map(|x| uuid::fmt::Simple::from(x.id)))
+            .bind(user.id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
 }
 
-pub async fn create(db_pool: &MySqlPool, user: User) -> Result<(), Error> {
-    sqlx::query!(
-        "INSERT INTO Users(id, name, email, pay_schedule_id) VALUE (?, ?, ?, ?)",
-        user.id.as_simple(),
-        user.name,
-        user.email,
-        user.pay_frequency
-            .map(|schedule| uuid::fmt::Simple::from(schedule.id))
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+pub async fn set_password_hash(
+    executor: impl Into<DbExecutor<'_>>,
+    user_id: Uuid,
+    password_hash: &str,
+) -> Result<(), Error> {
+    with_connection!(executor, |connection| {
+        sqlx::query("UPDATE Users SET password_hash = ? WHERE id = ?")
+            .bind(password_hash)
+            .bind(user_id.simple())
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    })
 }
 
-pub async fn update(db_pool: &MySqlPool, user: User) -> Result<(), Error> {
-    sqlx::query!(
-        "UPDATE Users SET name = ?, pay_schedule_id = ? WHERE id = ?",
-        user.name,
-        user.pay_frequency.map(|x| uuid::fmt::Simple::from(x.id)),
-        user.id.as_simple()
-    )
-    .execute(db_pool)
-    .await?;
-
-    Ok(())
+/// Gets the id and password hash of the user with this email.
+/// The hash is `None` for users who have never set a password
+pub async fn get_password_hash(
+    executor: impl Into<DbExecutor<'_>>,
+    email: &str,
+) -> Result<(Uuid, Option<String>), Error> {
+    with_connection!(executor, |connection| {
+        let (id, password_hash) = sqlx::query_as::<Db, (uuid::fmt::Simple, Option<String>)>(
+            "SELECT id, password_hash FROM Users WHERE email = ?",
+        )
+        .bind(email)
+        .fetch_optional(connection)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+        Ok((id.into_uuid(), password_hash))
+    })
 }
@@ -0,0 +1,218 @@
+//! Exports everything belonging to a user as a single [`UserExport`] and restores it again.
+//!
+//! A restore never touches existing plans. Each exported plan is created as a new plan owned
+//! by the user it's restored into, with new ids for everything in it
+
+use anyhow::anyhow;
+use chrono::Utc;
+use tokio::join;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DbPool, DbTransaction},
+    models::{
+        Budget, BudgetTarget, CreateBankAccountRequest, CreatePayeeRequest, Plan, PlanExport,
+        UserExport, EXPORT_VERSION,
+    },
+    AppError,
+};
+
+pub async fn export(db_pool: &DbPool, user_id: Uuid) -> Result<UserExport, AppError> {
+    let (user_result, plans_result) = join!(
+        db::users::get_single(db_pool, user_id),
+        db::plans::get_owned(db_pool, user_id)
+    );
+
+    let user = user_result.map_err(|e| e.to_app_error(anyhow!("Could not get user")))?;
+    let plans = plans_result.map_err(|e| e.to_app_error(anyhow!("Could not get plans")))?;
+
+    let mut plan_exports = Vec::with_capacity(plans.len());
+
+    for plan in plans {
+        plan_exports.push(export_plan(db_pool, plan).await?);
+    }
+
+    Ok(UserExport {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+        user,
+        plans: plan_exports,
+    })
+}
+
+/// Restores the export's plans into `user_id`, along with the exported pay frequency when the
+/// user doesn't have one yet. Returns the ids of the new plans
+pub async fn restore(
+    db_pool: &DbPool,
+    user_id: Uuid,
+    export: UserExport,
+) -> Result<Box<[Uuid]>, AppError> {
+    if export.version > EXPORT_VERSION {
+        return Err(AppError::BadRequest(anyhow!(
+            "Export version {} is newer than the supported version {EXPORT_VERSION}",
+            export.version
+        )));
+    }
+
+    let export = export
+        .with_new_ids()
+        .map_err(|e| AppError::BadRequest(e.context("Could not restore export")))?;
+
+    let mut user = db::users::get_single(db_pool, user_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get user")))?;
+
+    let mut db_transaction = db::begin(db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not restore export")))?;
+
+    let plan_ids = export.plans.iter().map(|plan| plan.plan.id).collect();
+
+    for plan in export.plans {
+        restore_plan(&mut db_transaction, user_id, plan).await?;
+    }
+
+    if let (None, Some(schedule)) = (&user.pay_frequency, export.user.pay_frequency) {
+        db::schedule::create(&mut db_transaction, schedule.clone())
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create pay frequency")))?;
+
+        user.pay_frequency = Some(schedule);
+        db::users::update(&mut db_transaction, user)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not update user")))?;
+    }
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not restore export")))?;
+
+    Ok(plan_ids)
+}
+
+async fn export_plan(db_pool: &DbPool, plan: Plan) -> Result<PlanExport, AppError> {
+    let (bank_accounts_result, payees_result, groups_result, budgets_result) = join!(
+        db::bank_accounts::get(db_pool, plan.id),
+        db::payees::get(db_pool, plan.id),
+        db::budget_groups::get(db_pool, plan.id),
+        db::budgets::get(db_pool, plan.id, true)
+    );
+
+    let bank_accounts =
+        bank_accounts_result.map_err(|e| e.to_app_error(anyhow!("Could not get bank accounts")))?;
+    let payees = payees_result.map_err(|e| e.to_app_error(anyhow!("Could not get payees")))?;
+    let budget_groups =
+        groups_result.map_err(|e| e.to_app_error(anyhow!("Could not get budget groups")))?;
+    let budgets = budgets_result.map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?;
+
+    let mut transactions = Vec::new();
+    let mut scheduled_transactions = Vec::new();
+
+    for bank_account in &bank_accounts {
+        let (transactions_result, scheduled_transactions_result) = join!(
+            db::transactions::get_all(db_pool, bank_account.id),
+            db::scheduled_transactions::get(db_pool, bank_account.id)
+        );
+
+        transactions.extend(
+            transactions_result
+                .map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))?,
+        );
+        scheduled_transactions.extend(
+            scheduled_transactions_result
+                .map_err(|e| e.to_app_error(anyhow!("Could not get scheduled transactions")))?,
+        );
+    }
+
+    Ok(PlanExport {
+        plan,
+        bank_accounts: bank_accounts.into_vec(),
+        payees: payees.into_vec(),
+        budget_groups: budget_groups.into_vec(),
+        budgets: budgets.into_vec(),
+        transactions,
+        scheduled_transactions,
+    })
+}
+
+async fn restore_plan(
+    db_transaction: &mut DbTransaction,
+    user_id: Uuid,
+    plan: PlanExport,
+) -> Result<(), AppError> {
+    let plan_id = plan.plan.id;
+
+    db::plans::create(&mut *db_transaction, plan.plan, user_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create plan")))?;
+
+    for group in plan.budget_groups {
+        db::budget_groups::create(&mut *db_transaction, group)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create budget group")))?;
+    }
+
+    for payee in plan.payees {
+        db::payees::create(
+            &mut *db_transaction,
+            payee.id,
+            CreatePayeeRequest::new(payee.name, plan_id),
+        )
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create payee")))?;
+    }
+
+    for bank_account in plan.bank_accounts {
+        db::bank_accounts::create(
+            &mut *db_transaction,
+            bank_account.id,
+            CreateBankAccountRequest::new(bank_account.name, bank_account.initial_amount, plan_id),
+        )
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create bank account")))?;
+    }
+
+    // budgets are created empty so transactions can refer to them,
+    // their assignments are added once the transactions they refer to exist
+    for budget in &plan.budgets {
+        if let Some(BudgetTarget::Repeating { schedule, .. }) = &budget.target {
+            db::schedule::create(&mut *db_transaction, schedule.clone())
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not create schedule")))?;
+        }
+
+        db::budgets::create(
+            &mut *db_transaction,
+            Budget {
+                assignments: vec![],
+                ..budget.clone()
+            },
+        )
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create budget")))?;
+    }
+
+    for transaction in plan.transactions {
+        db::transactions::create(&mut *db_transaction, transaction)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create transaction")))?;
+    }
+
+    for budget in plan.budgets {
+        db::budgets::update(&mut *db_transaction, budget)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not update budget")))?;
+    }
+
+    for scheduled_transaction in plan.scheduled_transactions {
+        db::schedule::create(&mut *db_transaction, scheduled_transaction.schedule.clone())
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create schedule")))?;
+
+        db::scheduled_transactions::create(&mut *db_transaction, scheduled_transaction)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create scheduled transaction")))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Importers for the files banks and other budgeting tools export.
+//!
+//! Bank statements are parsed into [`ImportedTransaction`](crate::models::ImportedTransaction)s
+//! without touching the database, so the same rows can be previewed and then imported.
+//! Files from other budgeting tools describe a whole plan, so they're written straight to
+//! the database
+
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use rust_decimal::Decimal;
+
+pub(crate) mod changes;
+pub mod csv;
+pub mod ofx;
+pub mod qif;
+pub mod ynab;
+
+/// Outflows must have a budget, so uncategorised ones are put in this budget
+pub(crate) const UNCATEGORISED_BUDGET: &str = "Uncategorised";
+
+/// Payees and budgets in a file are matched to existing ones by name,
+/// ignoring case and surrounding whitespace
+pub(crate) fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Reads an amount written with a decimal point or a decimal comma, ignoring currency symbols.
+/// When a value has both, whichever comes last is taken as the decimal separator
+pub(crate) fn parse_amount(value: &str) -> Result<Decimal, anyhow::Error> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || ['.', ',', '-'].contains(c))
+        .collect();
+
+    let cleaned = match (cleaned.rfind('.'), cleaned.rfind(',')) {
+        (Some(point), Some(comma)) if comma > point => cleaned.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => cleaned.replace(',', ""),
+        (None, Some(_)) => cleaned.replace(',', "."),
+        _ => cleaned,
+    };
+
+    Decimal::from_str(&cleaned).map_err(|_| anyhow!("{value} is not a valid amount"))
+}
+
+/// Reads an amount whose decimal separator is known. The other one of `.` and `,` is taken to
+/// group thousands and ignored, so "1,500" is 1500 with a decimal point and 1.5 with a comma
+pub(crate) fn parse_amount_with_separator(
+    value: &str,
+    decimal_separator: char,
+) -> Result<Decimal, anyhow::Error> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || ['-', decimal_separator].contains(c))
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+
+    Decimal::from_str(&cleaned).map_err(|_| anyhow!("{value} is not a valid amount"))
+}
+
+/// Columns that split inflows and outflows are usually left empty rather than set to 0
+pub(crate) fn parse_optional_amount(value: &str) -> Result<Decimal, anyhow::Error> {
+    if value.trim().is_empty() {
+        Ok(Decimal::ZERO)
+    } else {
+        parse_amount(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn parse_amount_test() {
+        assert_eq!(parse_amount("-42.10").unwrap(), dec!(-42.10));
+        assert_eq!(parse_amount("42,10").unwrap(), dec!(42.10));
+        assert_eq!(parse_amount("1,500.00").unwrap(), dec!(1500));
+        assert_eq!(parse_amount("1.500,00").unwrap(), dec!(1500));
+        assert_eq!(parse_amount("$1,500.00").unwrap(), dec!(1500));
+        assert_eq!(parse_amount("-€ 5,00").unwrap(), dec!(-5));
+        assert!(parse_amount("abc").is_err());
+    }
+
+    #[test]
+    fn parse_amount_with_separator_test() {
+        assert_eq!(
+            parse_amount_with_separator("1,500", '.').unwrap(),
+            dec!(1500)
+        );
+        assert_eq!(
+            parse_amount_with_separator("1,500", ',').unwrap(),
+            dec!(1.5)
+        );
+        assert_eq!(
+            parse_amount_with_separator("-1.500,25", ',').unwrap(),
+            dec!(-1500.25)
+        );
+        assert_eq!(
+            parse_amount_with_separator("$1,500.00", '.').unwrap(),
+            dec!(1500)
+        );
+        assert!(parse_amount_with_separator("abc", '.').is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Collects everything an import adds to a plan so it can all be written in one transaction.
+//!
+//! Payees, budgets and budget groups are matched to the plan's existing ones by name and only
+//! created when there's no match
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use tokio::join;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DbPool},
+    models::{Budget, BudgetGroup, CreateBankAccountRequest, CreatePayeeRequest, Transaction},
+    AppError,
+};
+
+use super::name_key;
+
+pub(crate) struct Changes {
+    plan_id: Uuid,
+    payee_ids: HashMap<String, Uuid>,
+    budget_ids: HashMap<String, Uuid>,
+    budgets: HashMap<Uuid, Budget>,
+    group_ids: HashMap<String, Uuid>,
+    next_group_sort_order: i32,
+    new_payees: Vec<(Uuid, String)>,
+    new_groups: Vec<BudgetGroup>,
+    new_budget_ids: Vec<Uuid>,
+    updated_budget_ids: HashSet<Uuid>,
+    /// Moves from Ready to Assign, applied to the budgets as they are when saving
+    assignments: Vec<(Uuid, Decimal, NaiveDate)>,
+    bank_accounts: Vec<(Uuid, CreateBankAccountRequest)>,
+    transactions: Vec<Transaction>,
+}
+
+impl Changes {
+    /// Starts from the plan's current payees, budgets and budget groups.
+    /// Archived budgets aren't matched, so a new budget is created instead
+    pub(crate) async fn get(db_pool: &DbPool, plan_id: Uuid) -> Result<Self, AppError> {
+        let (payees_result, budgets_result, groups_result) = join!(
+            db::payees::get(db_pool, plan_id),
+            db::budgets::get(db_pool, plan_id, false),
+            db::budget_groups::get(db_pool, plan_id)
+        );
+
+        let payees = payees_result.map_err(|e| e.to_app_error(anyhow!("Could not get payees")))?;
+        let budgets =
+            budgets_result.map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?;
+        let groups =
+            groups_result.map_err(|e| e.to_app_error(anyhow!("Could not get budget groups")))?;
+
+        Ok(Self {
+            plan_id,
+            payee_ids: payees
+                .iter()
+                .map(|payee| (name_key(&payee.name), payee.id))
+                .collect(),
+            budget_ids: budgets
+                .iter()
+                .map(|budget| (name_key(&budget.name), budget.id))
+                .collect(),
+            budgets: budgets
+                .into_vec()
+                .into_iter()
+                .map(|budget| (budget.id, budget))
+                .collect(),
+            group_ids: groups
+                .iter()
+                .map(|group| (name_key(&group.name), group.id))
+                .collect(),
+            next_group_sort_order: groups
+                .iter()
+                .map(|group| group.sort_order + 1)
+                .max()
+                .unwrap_or_default(),
+            new_payees: Vec::new(),
+            new_groups: Vec::new(),
+            new_budget_ids: Vec::new(),
+            updated_budget_ids: HashSet::new(),
+            assignments: Vec::new(),
+            bank_accounts: Vec::new(),
+            transactions: Vec::new(),
+        })
+    }
+
+    pub(crate) fn add_bank_account(&mut self, name: String, initial_amount: Decimal) -> Uuid {
+        let id = Uuid::new_v4();
+        self.bank_accounts.push((
+            id,
+            CreateBankAccountRequest::new(name, initial_amount, self.plan_id),
+        ));
+
+        id
+    }
+
+    pub(crate) fn payee_id(&mut self, name: &str) -> Uuid {
+        *self.payee_ids.entry(name_key(name)).or_insert_with(|| {
+            let id = Uuid::new_v4();
+            self.new_payees.push((id, name.trim().to_owned()));
+            id
+        })
+    }
+
+    /// A new budget is put in the group called `group_name`, which is created if needed.
+    /// Existing budgets are left in their group
+    pub(crate) fn budget_id(&mut self, name: &str, group_name: Option<&str>) -> Uuid {
+        if let Some(id) = self.budget_ids.get(&name_key(name)) {
+            return *id;
+        }
+
+        let group_id = group_name.map(|group_name| self.group_id(group_name));
+        let id = Uuid::new_v4();
+
+        self.budget_ids.insert(name_key(name), id);
+        self.budgets.insert(
+            id,
+            Budget::new(
+                id,
+                name.trim().to_owned(),
+                None,
+                self.plan_id,
+                vec![],
+                group_id,
+                None,
+            ),
+        );
+        self.new_budget_ids.push(id);
+
+        id
+    }
+
+    /// Adds the transaction and, when it has a budget, its assignment to the budget
+    pub(crate) fn add_transaction(
+        &mut self,
+        bank_account_id: Uuid,
+        date: NaiveDate,
+        amount: Decimal,
+        payee_id: Uuid,
+        budget_id: Option<Uuid>,
+        memo: Option<String>,
+    ) {
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            payee_id,
+            date,
+            amount,
+            bank_account_id,
+            budget_id,
+            memo,
+        );
+
+        if let Some(budget_id) = budget_id.filter(|budget_id| self.budgets.contains_key(budget_id))
+        {
+            self.updated_budget_ids.insert(budget_id);
+        }
+
+        self.transactions.push(transaction);
+    }
+
+    pub(crate) fn move_from_ready_to_assign(
+        &mut self,
+        budget_id: Uuid,
+        amount: Decimal,
+        date: NaiveDate,
+    ) {
+        if self.budgets.contains_key(&budget_id) {
+            self.assignments.push((budget_id, amount, date));
+            self.updated_budget_ids.insert(budget_id);
+        }
+    }
+
+    pub(crate) async fn save(self, db_pool: &DbPool) -> Result<(), AppError> {
+        let mut db_transaction = db::begin(db_pool)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not import file")))?;
+
+        for (id, name) in self.new_payees {
+            db::payees::create(
+                &mut db_transaction,
+                id,
+                CreatePayeeRequest::new(name, self.plan_id),
+            )
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not create payee")))?;
+        }
+
+        for group in self.new_groups {
+            db::budget_groups::create(&mut db_transaction, group)
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not create budget group")))?;
+        }
+
+        // new budgets are created empty so the transactions can refer to them,
+        // their assignments are added with the rest below
+        for id in &self.new_budget_ids {
+            db::budgets::create(&mut db_transaction, self.budgets[id].clone())
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not create budget")))?;
+        }
+
+        for (id, request) in self.bank_accounts {
+            db::bank_accounts::create(&mut db_transaction, id, request)
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not create bank account")))?;
+        }
+
+        // read in the transaction so assignments made since the import started aren't lost
+        let budget_ids: Vec<_> = self.updated_budget_ids.into_iter().collect();
+        let mut budgets: HashMap<Uuid, Budget> =
+            db::budgets::get_by_ids_for_update(&mut db_transaction, &budget_ids)
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?
+                .into_vec()
+                .into_iter()
+                .map(|budget| (budget.id, budget))
+                .collect();
+
+        for (budget_id, amount, date) in self.assignments {
+            if let Some(budget) = budgets.get_mut(&budget_id) {
+                budget.move_from_ready_to_assign(amount, date);
+            }
+        }
+
+        for transaction in self.transactions {
+            if let Some(budget) = transaction
+                .budget_id
+                .and_then(|budget_id| budgets.get_mut(&budget_id))
+            {
+                budget.assign_from_transaction(&transaction);
+            }
+
+            db::transactions::create(&mut db_transaction, transaction)
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not create transaction")))?;
+        }
+
+        // update budgets must happen after transaction create because the budget assignments
+        // have a foreign key to the transactions
+        for budget in budgets.into_values() {
+            db::budgets::update(&mut db_transaction, budget)
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Could not update budget")))?;
+        }
+
+        db::commit(db_transaction)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not import file")))?;
+
+        Ok(())
+    }
+
+    fn group_id(&mut self, name: &str) -> Uuid {
+        *self.group_ids.entry(name_key(name)).or_insert_with(|| {
+            let id = Uuid::new_v4();
+            self.new_groups.push(BudgetGroup::new(
+                id,
+                name.trim().to_owned(),
+                self.next_group_sort_order,
+                self.plan_id,
+            ));
+            self.next_group_sort_order += 1;
+            id
+        })
+    }
+}
@@ -0,0 +1,313 @@
+//! Reads transactions from a CSV export using the bank account's saved [`CsvMapping`].
+//!
+//! Amounts are read with the mapping's decimal separator, and the other one of `.` and `,`
+//! is ignored as a thousands separator
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate,
+};
+use csv::{ReaderBuilder, StringRecord, Trim};
+use rust_decimal::Decimal;
+
+use crate::models::{CsvMapping, ImportedTransaction};
+
+use super::parse_amount_with_separator;
+
+pub fn parse(
+    contents: &str,
+    mapping: &CsvMapping,
+) -> Result<Vec<ImportedTransaction>, anyhow::Error> {
+    validate_mapping(mapping)?;
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .has_headers(mapping.has_header_row)
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(contents.as_bytes());
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let line = record.position().map_or(0, csv::Position::line);
+
+            parse_record(&record, mapping).with_context(|| format!("Could not read line {line}"))
+        })
+        .collect()
+}
+
+/// A mapping needs either a single amount column or both debit and credit columns
+pub fn validate_mapping(mapping: &CsvMapping) -> Result<(), anyhow::Error> {
+    if !mapping.delimiter.is_ascii() {
+        bail!("Delimiter must be a single ASCII character");
+    }
+
+    if !['.', ','].contains(&mapping.decimal_separator) {
+        bail!("Decimal separator must be . or ,");
+    }
+
+    if mapping.decimal_separator == mapping.delimiter {
+        bail!("Decimal separator must not be the delimiter");
+    }
+
+    if StrftimeItems::new(&mapping.date_format).any(|item| item == Item::Error) {
+        bail!("{} is not a valid date format", mapping.date_format);
+    }
+
+    match (
+        mapping.amount_column,
+        mapping.debit_column,
+        mapping.credit_column,
+    ) {
+        (Some(_), None, None) | (None, Some(_), Some(_)) => Ok(()),
+        _ => bail!("Set either the amount column or both the debit and credit columns"),
+    }
+}
+
+fn parse_record(
+    record: &StringRecord,
+    mapping: &CsvMapping,
+) -> Result<ImportedTransaction, anyhow::Error> {
+    let date = column(record, mapping.date_column)?;
+    let date = NaiveDate::parse_from_str(date, &mapping.date_format).map_err(|_| {
+        anyhow!(
+            "{date} does not match the date format {}",
+            mapping.date_format
+        )
+    })?;
+
+    let amount = if let Some(amount_column) = mapping.amount_column {
+        parse_amount_with_separator(column(record, amount_column)?, mapping.decimal_separator)?
+    } else {
+        let debit = mapping
+            .debit_column
+            .map_or(Ok(""), |debit_column| column(record, debit_column))?;
+        let credit = mapping
+            .credit_column
+            .map_or(Ok(""), |credit_column| column(record, credit_column))?;
+
+        parse_optional_amount(credit, mapping.decimal_separator)?
+            - parse_optional_amount(debit, mapping.decimal_separator)?.abs()
+    };
+
+    let payee_name = column(record, mapping.payee_column)?;
+    if payee_name.is_empty() {
+        bail!("Payee is empty");
+    }
+
+    Ok(ImportedTransaction {
+        external_id: None,
+        date,
+        amount,
+        payee_name: payee_name.to_owned(),
+        payee_id: None,
+        memo: None,
+    })
+}
+
+/// Columns that split inflows and outflows are usually left empty rather than set to 0
+fn parse_optional_amount(value: &str, decimal_separator: char) -> Result<Decimal, anyhow::Error> {
+    if value.is_empty() {
+        Ok(Decimal::ZERO)
+    } else {
+        parse_amount_with_separator(value, decimal_separator)
+    }
+}
+
+fn column(record: &StringRecord, index: u32) -> Result<&str, anyhow::Error> {
+    record
+        .get(index as usize)
+        .ok_or_else(|| anyhow!("Column {index} is missing"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn parse_with_amount_column() {
+        let mapping = CsvMapping::new(',', '.', true, 0, "%Y-%m-%d".into(), Some(2), None, None, 1);
+        let contents = "Date,Description,Amount
+2024-12-05,Corner Shop,-42.10
+2024-12-15, Employer ,\"1,500.00\"
+";
+
+        let transactions = parse(contents, &mapping).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![
+                ImportedTransaction::new(
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                    dec!(-42.10),
+                    "Corner Shop".into(),
+                    None,
+                    None,
+                ),
+                ImportedTransaction::new(
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                    dec!(1500.00),
+                    "Employer".into(),
+                    None,
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_with_debit_and_credit_columns() {
+        let mapping = CsvMapping::new(
+            ';',
+            ',',
+            false,
+            0,
+            "%d.%m.%Y".into(),
+            None,
+            Some(2),
+            Some(3),
+            1,
+        );
+        let contents = "05.12.2024;Corner Shop;42,10;
+15.12.2024;Employer;;1.500,00
+";
+
+        let transactions = parse(contents, &mapping).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![
+                ImportedTransaction::new(
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                    dec!(-42.10),
+                    "Corner Shop".into(),
+                    None,
+                    None,
+                ),
+                ImportedTransaction::new(
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                    dec!(1500.00),
+                    "Employer".into(),
+                    None,
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_with_decimal_separator() {
+        let mapping = CsvMapping::new(
+            ',',
+            '.',
+            false,
+            0,
+            "%Y-%m-%d".into(),
+            Some(2),
+            None,
+            None,
+            1,
+        );
+        let transactions = parse("2024-12-05,Employer,\"1,500\"", &mapping).unwrap();
+        assert_eq!(transactions[0].amount, dec!(1500));
+
+        let mapping = CsvMapping::new(
+            ';',
+            ',',
+            false,
+            0,
+            "%Y-%m-%d".into(),
+            Some(2),
+            None,
+            None,
+            1,
+        );
+        let transactions = parse("2024-12-05;Employer;1,500", &mapping).unwrap();
+        assert_eq!(transactions[0].amount, dec!(1.5));
+    }
+
+    #[test]
+    fn parse_wrong_date_format() {
+        let mapping = CsvMapping::new(',', '.', true, 0, "%d/%m/%Y".into(), Some(2), None, None, 1);
+        let contents = "Date,Description,Amount
+2024-12-05,Corner Shop,-42.10
+";
+
+        let error = parse(contents, &mapping).unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "Could not read line 2: 2024-12-05 does not match the date format %d/%m/%Y"
+        );
+    }
+
+    #[test]
+    fn parse_missing_column() {
+        let mapping = CsvMapping::new(
+            ',',
+            '.',
+            false,
+            0,
+            "%Y-%m-%d".into(),
+            Some(3),
+            None,
+            None,
+            1,
+        );
+
+        let error = parse("2024-12-05,Corner Shop,-42.10", &mapping).unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "Could not read line 1: Column 3 is missing"
+        );
+    }
+
+    #[test]
+    fn validate_mapping_amount_columns() {
+        let mut mapping =
+            CsvMapping::new(',', '.', true, 0, "%Y-%m-%d".into(), None, None, None, 1);
+        assert!(validate_mapping(&mapping).is_err());
+
+        mapping.debit_column = Some(2);
+        assert!(validate_mapping(&mapping).is_err());
+
+        mapping.credit_column = Some(3);
+        assert!(validate_mapping(&mapping).is_ok());
+
+        mapping.amount_column = Some(4);
+        assert!(validate_mapping(&mapping).is_err());
+    }
+
+    #[test]
+    fn validate_mapping_date_format() {
+        let mut mapping = CsvMapping::new(',', '.', true, 0, "%Q".into(), Some(1), None, None, 2);
+        assert!(validate_mapping(&mapping).is_err());
+
+        mapping.date_format = "%m/%d/%Y".into();
+        assert!(validate_mapping(&mapping).is_ok());
+    }
+
+    #[test]
+    fn validate_mapping_decimal_separator() {
+        let mut mapping =
+            CsvMapping::new(',', ',', true, 0, "%Y-%m-%d".into(), Some(1), None, None, 2);
+        assert!(validate_mapping(&mapping).is_err());
+
+        mapping.decimal_separator = ' ';
+        assert!(validate_mapping(&mapping).is_err());
+
+        mapping.delimiter = ';';
+        mapping.decimal_separator = ',';
+        assert!(validate_mapping(&mapping).is_ok());
+    }
+}
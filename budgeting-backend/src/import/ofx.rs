@@ -0,0 +1,221 @@
+//! Reads the `STMTTRN` entries from an OFX or QFX bank statement.
+//!
+//! Handles both the SGML flavour of OFX 1.x, where leaf elements aren't closed,
+//! and the XML flavour of OFX 2.x. Aggregates other than `STMTTRN` are ignored
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::models::ImportedTransaction;
+
+const TRANSACTION_START: &str = "<STMTTRN>";
+const TRANSACTION_END: &str = "</STMTTRN>";
+
+pub fn parse(contents: &str) -> Result<Vec<ImportedTransaction>, anyhow::Error> {
+    if !contents.contains("<OFX>") {
+        bail!("File is not an OFX or QFX statement");
+    }
+
+    let mut transactions = Vec::new();
+    let mut rest = contents;
+
+    while let Some(start) = rest.find(TRANSACTION_START) {
+        rest = &rest[start + TRANSACTION_START.len()..];
+
+        let end = rest
+            .find(TRANSACTION_END)
+            .ok_or_else(|| anyhow!("STMTTRN {} is never closed", transactions.len() + 1))?;
+
+        let transaction = parse_transaction(&rest[..end])
+            .with_context(|| format!("Could not read STMTTRN {}", transactions.len() + 1))?;
+        transactions.push(transaction);
+
+        rest = &rest[end + TRANSACTION_END.len()..];
+    }
+
+    Ok(transactions)
+}
+
+fn parse_transaction(aggregate: &str) -> Result<ImportedTransaction, anyhow::Error> {
+    let date_posted = field(aggregate, "DTPOSTED").ok_or(anyhow!("DTPOSTED is missing"))?;
+    // dates look like 20241205, optionally followed by a time and timezone, e.g. 20241205120000[-5:EST]
+    let date = date_posted
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| anyhow!("{date_posted} is not a valid DTPOSTED"))?;
+
+    let amount = field(aggregate, "TRNAMT").ok_or(anyhow!("TRNAMT is missing"))?;
+    // some banks write amounts with a decimal comma
+    let amount = Decimal::from_str(&amount.replace(',', "."))
+        .map_err(|_| anyhow!("{amount} is not a valid TRNAMT"))?;
+
+    let (payee_name, memo) = match (field(aggregate, "NAME"), field(aggregate, "MEMO")) {
+        (Some(name), memo) => (name, memo),
+        // without a NAME, the MEMO is what says who the payee was
+        (None, Some(memo)) => (memo, None),
+        (None, None) => bail!("NAME and MEMO are both missing"),
+    };
+
+    Ok(ImportedTransaction {
+        external_id: field(aggregate, "FITID"),
+        date,
+        amount,
+        payee_name,
+        payee_id: None,
+        memo,
+    })
+}
+
+/// The value of the first `<name>` element in `aggregate`, ignoring empty values
+fn field(aggregate: &str, name: &str) -> Option<String> {
+    let start_tag = format!("<{name}>");
+    let start = aggregate.find(&start_tag)? + start_tag.len();
+
+    // the value runs up to the next tag, which is either this element's end tag in XML
+    // or the start of the next element in SGML
+    let value = &aggregate[start..];
+    let value = value[..value.find('<').unwrap_or(value.len())].trim();
+
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn parse_sgml() {
+        let contents = r"OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1>
+<STMTTRNRS>
+<STMTRS>
+<BANKTRANLIST>
+<DTSTART>20241201
+<DTEND>20241231
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20241205120000[-5:EST]
+<TRNAMT>-42.10
+<FITID>2024120501
+<NAME>Corner Shop &amp; Deli
+<MEMO>Card payment
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20241215
+<TRNAMT>1500,00
+<FITID>2024121501
+<MEMO>Salary
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS>
+</STMTTRNRS>
+</BANKMSGSRSV1>
+</OFX>
+";
+
+        let transactions = parse(contents).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![
+                ImportedTransaction::new(
+                    Some("2024120501".into()),
+                    NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                    dec!(-42.10),
+                    "Corner Shop & Deli".into(),
+                    None,
+                    Some("Card payment".into()),
+                ),
+                ImportedTransaction::new(
+                    Some("2024121501".into()),
+                    NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                    dec!(1500.00),
+                    "Salary".into(),
+                    None,
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_xml() {
+        let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX>
+  <BANKMSGSRSV1>
+    <STMTTRNRS>
+      <STMTRS>
+        <BANKTRANLIST>
+          <STMTTRN>
+            <TRNTYPE>DEBIT</TRNTYPE>
+            <DTPOSTED>20241203</DTPOSTED>
+            <TRNAMT>-9.99</TRNAMT>
+            <FITID>abc-123</FITID>
+            <NAME>Streaming</NAME>
+          </STMTTRN>
+        </BANKTRANLIST>
+      </STMTRS>
+    </STMTTRNRS>
+  </BANKMSGSRSV1>
+</OFX>"#;
+
+        let transactions = parse(contents).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![ImportedTransaction::new(
+                Some("abc-123".into()),
+                NaiveDate::from_ymd_opt(2024, 12, 3).unwrap(),
+                dec!(-9.99),
+                "Streaming".into(),
+                None,
+                None,
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_missing_amount() {
+        let contents = r"<OFX>
+<STMTTRN>
+<DTPOSTED>20241203
+<FITID>1
+<NAME>Shop
+</STMTTRN>
+</OFX>";
+
+        let error = parse(contents).unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "Could not read STMTTRN 1: TRNAMT is missing"
+        );
+    }
+
+    #[test]
+    fn parse_not_ofx() {
+        assert!(parse("date,amount\n2024-12-03,-9.99").is_err());
+    }
+}
@@ -0,0 +1,567 @@
+//! Reads and writes QIF, the format older desktop budgeting tools export.
+//!
+//! Only bank-like account types (`Bank`, `Cash`, `CCard`, `Oth A` and `Oth L`) are read;
+//! investment accounts, category lists and memorized transactions are skipped. Split
+//! transactions are imported as a single transaction in their main category
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use tokio::join;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DbPool},
+    AppError,
+};
+
+use super::{changes::Changes, UNCATEGORISED_BUDGET};
+
+/// The payee Quicken gives the transaction that sets an account's starting balance
+const OPENING_BALANCE_PAYEE: &str = "Opening Balance";
+
+/// The date format written on export. Imports also accept two digit years and ISO dates
+const DATE_FORMAT: &str = "%m/%d/%Y";
+
+const BANK_TYPES: [&str; 5] = ["Bank", "Cash", "CCard", "Oth A", "Oth L"];
+
+#[derive(Debug, PartialEq)]
+pub struct QifAccount {
+    /// Only set when the file has an `!Account` header
+    pub name: Option<String>,
+    pub opening_balance: Decimal,
+    pub transactions: Vec<QifTransaction>,
+}
+
+impl QifAccount {
+    fn new(name: Option<String>) -> Self {
+        Self {
+            name,
+            opening_balance: Decimal::ZERO,
+            transactions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QifTransaction {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub payee_name: String,
+    /// The last part of the category, e.g. `Groceries` for `Food:Groceries`.
+    /// Transfers to other accounts have no category
+    pub category: Option<String>,
+    pub memo: Option<String>,
+}
+
+pub fn parse(contents: &str) -> Result<Vec<QifAccount>, anyhow::Error> {
+    let mut accounts: Vec<QifAccount> = Vec::new();
+    let mut current_account = None;
+    let mut section = "";
+    let mut fields: Vec<&str> = Vec::new();
+
+    for line in contents.lines().map(str::trim_end) {
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('!') {
+            section = line;
+            fields.clear();
+            continue;
+        }
+
+        if line != "^" {
+            fields.push(line);
+            continue;
+        }
+
+        if section == "!Account" {
+            let name = field(&fields, 'N').ok_or(anyhow!("Account name is missing"))?;
+
+            // the same account can be listed more than once, e.g. with AutoSwitch
+            current_account = accounts
+                .iter()
+                .position(|account| account.name.as_deref() == Some(name))
+                .or_else(|| {
+                    accounts.push(QifAccount::new(Some(name.to_owned())));
+                    Some(accounts.len() - 1)
+                });
+        } else if is_bank_section(section) {
+            let index = *current_account.get_or_insert_with(|| {
+                accounts.push(QifAccount::new(None));
+                accounts.len() - 1
+            });
+
+            let account = &mut accounts[index];
+            let index = account.transactions.len() + 1;
+
+            match parse_transaction(&fields).with_context(|| {
+                format!(
+                    "Could not read transaction {index} in {}",
+                    account.name.as_deref().unwrap_or("the account")
+                )
+            })? {
+                ParsedTransaction::OpeningBalance(amount) => account.opening_balance += amount,
+                ParsedTransaction::Transaction(transaction) => {
+                    account.transactions.push(transaction);
+                }
+            }
+        }
+
+        fields.clear();
+    }
+
+    if accounts.is_empty() {
+        bail!("File has no bank, cash or credit card transactions");
+    }
+
+    Ok(accounts)
+}
+
+/// Writes `account` as a QIF bank account. The opening balance is dated on the first
+/// transaction, or today when there are none
+pub fn write(account: &QifAccount) -> String {
+    let name = account.name.as_deref().unwrap_or_default();
+    let mut lines = vec![
+        "!Account".to_owned(),
+        format!("N{name}"),
+        "TBank".to_owned(),
+        "^".to_owned(),
+        "!Type:Bank".to_owned(),
+    ];
+
+    if !account.opening_balance.is_zero() {
+        let date = account
+            .transactions
+            .iter()
+            .map(|transaction| transaction.date)
+            .min()
+            .unwrap_or_else(|| Local::now().date_naive());
+
+        lines.extend([
+            format!("D{}", date.format(DATE_FORMAT)),
+            format!("T{}", account.opening_balance),
+            format!("P{OPENING_BALANCE_PAYEE}"),
+            format!("L[{name}]"),
+            "^".to_owned(),
+        ]);
+    }
+
+    for transaction in &account.transactions {
+        lines.push(format!("D{}", transaction.date.format(DATE_FORMAT)));
+        lines.push(format!("T{}", transaction.amount));
+        lines.push(format!("P{}", transaction.payee_name));
+
+        if let Some(category) = &transaction.category {
+            lines.push(format!("L{category}"));
+        }
+
+        if let Some(memo) = &transaction.memo {
+            lines.push(format!("M{memo}"));
+        }
+
+        lines.push("^".to_owned());
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Creates a bank account in the plan for each account in the file, along with any payees
+/// and budgets its transactions need. `account_name` names the account when the file doesn't.
+/// Returns the ids of the new bank accounts
+pub async fn import(
+    db_pool: &DbPool,
+    plan_id: Uuid,
+    contents: &str,
+    account_name: Option<&str>,
+) -> Result<Box<[Uuid]>, AppError> {
+    let accounts =
+        parse(contents).map_err(|e| AppError::BadRequest(e.context("Could not read QIF file")))?;
+
+    let mut changes = Changes::get(db_pool, plan_id).await?;
+    let mut bank_account_ids = Vec::with_capacity(accounts.len());
+
+    for account in accounts {
+        let name = match (account.name, account_name) {
+            (Some(name), _) => name,
+            (None, Some(account_name)) => account_name.to_owned(),
+            (None, None) => {
+                return Err(AppError::BadRequest(anyhow!(
+                    "The file doesn't name its account, so an account name must be given"
+                )));
+            }
+        };
+
+        let bank_account_id = changes.add_bank_account(name, account.opening_balance);
+        bank_account_ids.push(bank_account_id);
+
+        for transaction in account.transactions {
+            let payee_id = changes.payee_id(&transaction.payee_name);
+
+            let budget_name = match transaction.category.as_deref() {
+                Some(category) => Some(category),
+                None if transaction.amount < Decimal::ZERO => Some(UNCATEGORISED_BUDGET),
+                None => None,
+            };
+            let budget_id = budget_name.map(|budget_name| changes.budget_id(budget_name, None));
+
+            changes.add_transaction(
+                bank_account_id,
+                transaction.date,
+                transaction.amount,
+                payee_id,
+                budget_id,
+                transaction.memo,
+            );
+        }
+    }
+
+    changes.save(db_pool).await?;
+
+    Ok(bank_account_ids.into_boxed_slice())
+}
+
+/// Writes the bank account and its transactions as QIF, using budget names as categories
+pub async fn export(db_pool: &DbPool, bank_account_id: Uuid) -> Result<String, AppError> {
+    let plan_id = db::bank_accounts::get_plan_id(db_pool, bank_account_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let (bank_account_result, transactions_result, payees_result, budgets_result) = join!(
+        db::bank_accounts::get_single(db_pool, bank_account_id, plan_id),
+        db::transactions::get_all(db_pool, bank_account_id),
+        db::payees::get(db_pool, plan_id),
+        db::budgets::get(db_pool, plan_id, true)
+    );
+
+    let bank_account =
+        bank_account_result.map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+    let mut transactions =
+        transactions_result.map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))?;
+    let payee_names: HashMap<Uuid, String> = payees_result
+        .map_err(|e| e.to_app_error(anyhow!("Could not get payees")))?
+        .into_vec()
+        .into_iter()
+        .map(|payee| (payee.id, payee.name))
+        .collect();
+    let budget_names: HashMap<Uuid, String> = budgets_result
+        .map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?
+        .into_vec()
+        .into_iter()
+        .map(|budget| (budget.id, budget.name))
+        .collect();
+
+    transactions.sort_by_key(|transaction| transaction.date);
+
+    let account = QifAccount {
+        name: Some(bank_account.name),
+        opening_balance: bank_account.initial_amount,
+        transactions: transactions
+            .iter()
+            .map(|transaction| QifTransaction {
+                date: transaction.date,
+                amount: transaction.amount,
+                payee_name: payee_names
+                    .get(&transaction.payee_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                category: transaction
+                    .budget_id
+                    .and_then(|budget_id| budget_names.get(&budget_id).cloned()),
+                memo: transaction.memo.clone(),
+            })
+            .collect(),
+    };
+
+    Ok(write(&account))
+}
+
+fn is_bank_section(section: &str) -> bool {
+    section
+        .strip_prefix("!Type:")
+        .is_some_and(|account_type| BANK_TYPES.contains(&account_type.trim()))
+}
+
+enum ParsedTransaction {
+    OpeningBalance(Decimal),
+    Transaction(QifTransaction),
+}
+
+fn parse_transaction(fields: &[&str]) -> Result<ParsedTransaction, anyhow::Error> {
+    let date = field(fields, 'D').ok_or(anyhow!("D is missing"))?;
+    let date = parse_date(date)?;
+
+    let amount = field(fields, 'T')
+        .or_else(|| field(fields, 'U'))
+        .ok_or(anyhow!("T is missing"))?;
+    let amount = Decimal::from_str(&amount.replace(',', ""))
+        .map_err(|_| anyhow!("{amount} is not a valid amount"))?;
+
+    let (payee_name, memo) = match (field(fields, 'P'), field(fields, 'M')) {
+        (Some(payee_name), memo) => (payee_name, memo),
+        // without a P, the M is what says who the payee was
+        (None, Some(memo)) => (memo, None),
+        (None, None) => bail!("P and M are both missing"),
+    };
+
+    // transfers are written as [Account name]
+    let category = field(fields, 'L').filter(|category| !category.starts_with('['));
+
+    if payee_name == OPENING_BALANCE_PAYEE && category.is_none() {
+        return Ok(ParsedTransaction::OpeningBalance(amount));
+    }
+
+    Ok(ParsedTransaction::Transaction(QifTransaction {
+        date,
+        amount,
+        payee_name: payee_name.to_owned(),
+        category: category.map(|category| {
+            // drop the class, e.g. Food:Groceries/Holiday, then any parent categories
+            let category = category.split('/').next().unwrap_or_default();
+            category
+                .rsplit(':')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_owned()
+        }),
+        memo: memo.map(str::to_owned),
+    }))
+}
+
+/// The value of the first line starting with `code`, ignoring empty values
+fn field<'a>(fields: &[&'a str], code: char) -> Option<&'a str> {
+    fields
+        .iter()
+        .find_map(|line| line.strip_prefix(code))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// QIF dates are month first, with years written as `2024`, `'24` or ` 24`
+fn parse_date(value: &str) -> Result<NaiveDate, anyhow::Error> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '\'' { '/' } else { c })
+        .collect();
+
+    // %Y would read a two digit year as the first century
+    let two_digit_year = cleaned
+        .rsplit('/')
+        .next()
+        .is_some_and(|year| cleaned.contains('/') && year.len() <= 2);
+    let formats = if two_digit_year {
+        ["%m/%d/%y"].as_slice()
+    } else {
+        [DATE_FORMAT, "%Y-%m-%d"].as_slice()
+    };
+
+    formats
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&cleaned, format).ok())
+        .ok_or_else(|| anyhow!("{value} is not a valid date"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn parse_without_account_header() {
+        let contents = "!Type:Bank
+D12/05/2024
+T-42.10
+PCorner Shop
+LFood:Groceries
+MCard payment
+^
+D12/15'24
+T1,500.00
+PEmployer
+^
+";
+
+        let accounts = parse(contents).unwrap();
+
+        assert_eq!(
+            accounts,
+            vec![QifAccount {
+                name: None,
+                opening_balance: Decimal::ZERO,
+                transactions: vec![
+                    QifTransaction {
+                        date: NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                        amount: dec!(-42.10),
+                        payee_name: "Corner Shop".into(),
+                        category: Some("Groceries".into()),
+                        memo: Some("Card payment".into()),
+                    },
+                    QifTransaction {
+                        date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                        amount: dec!(1500.00),
+                        payee_name: "Employer".into(),
+                        category: None,
+                        memo: None,
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_accounts() {
+        let contents = "!Option:AutoSwitch
+!Account
+NChecking
+TBank
+^
+!Account
+NSavings
+TBank
+^
+!Clear:AutoSwitch
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D12/01/2024
+T100.00
+POpening Balance
+L[Checking]
+^
+D12/02/2024
+T-20.00
+PTransfer
+L[Savings]
+^
+!Type:Cat
+NGroceries
+E
+^
+!Account
+NSavings
+TBank
+^
+!Type:Bank
+D12/02/2024
+T20.00
+PTransfer
+L[Checking]
+^
+";
+
+        let accounts = parse(contents).unwrap();
+
+        assert_eq!(
+            accounts,
+            vec![
+                QifAccount {
+                    name: Some("Checking".into()),
+                    opening_balance: dec!(100),
+                    transactions: vec![QifTransaction {
+                        date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
+                        amount: dec!(-20),
+                        payee_name: "Transfer".into(),
+                        category: None,
+                        memo: None,
+                    }],
+                },
+                QifAccount {
+                    name: Some("Savings".into()),
+                    opening_balance: Decimal::ZERO,
+                    transactions: vec![QifTransaction {
+                        date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
+                        amount: dec!(20),
+                        payee_name: "Transfer".into(),
+                        category: None,
+                        memo: None,
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_missing_date() {
+        let contents = "!Account
+NChecking
+^
+!Type:Bank
+T-42.10
+PCorner Shop
+^
+";
+
+        let error = parse(contents).unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "Could not read transaction 1 in Checking: D is missing"
+        );
+    }
+
+    #[test]
+    fn parse_no_bank_accounts() {
+        assert!(parse("!Type:Cat\nNGroceries\nE\n^\n").is_err());
+    }
+
+    #[test]
+    fn write_and_parse() {
+        let account = QifAccount {
+            name: Some("Checking".into()),
+            opening_balance: dec!(100),
+            transactions: vec![
+                QifTransaction {
+                    date: NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                    amount: dec!(-42.10),
+                    payee_name: "Corner Shop".into(),
+                    category: Some("Groceries".into()),
+                    memo: Some("Card payment".into()),
+                },
+                QifTransaction {
+                    date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                    amount: dec!(1500),
+                    payee_name: "Employer".into(),
+                    category: None,
+                    memo: None,
+                },
+            ],
+        };
+
+        let qif = write(&account);
+
+        assert_eq!(
+            qif,
+            "!Account
+NChecking
+TBank
+^
+!Type:Bank
+D12/05/2024
+T100
+POpening Balance
+L[Checking]
+^
+D12/05/2024
+T-42.10
+PCorner Shop
+LGroceries
+MCard payment
+^
+D12/15/2024
+T1500
+PEmployer
+^
+"
+        );
+        assert_eq!(parse(&qif).unwrap(), vec![account]);
+    }
+}
@@ -0,0 +1,388 @@
+//! Reads YNAB's register and plan CSV exports.
+//!
+//! Each YNAB account becomes a bank account, each category a budget in a budget group named
+//! after its category group, and each month's assigned amount a budget assignment from Ready to
+//! Assign. Like YNAB, a category that ends a month overspent is covered from Ready to Assign at
+//! the start of the next month, so budget balances match what YNAB showed
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{Months, NaiveDate};
+use csv::{ReaderBuilder, StringRecord, Trim};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::{db::DbPool, AppError};
+
+use super::{changes::Changes, parse_amount, parse_optional_amount, UNCATEGORISED_BUDGET};
+
+/// The category group YNAB puts income in
+const INFLOW_GROUP: &str = "Inflow";
+/// YNAB leaves the payee of some transactions empty
+const UNKNOWN_PAYEE: &str = "Unknown payee";
+/// Register dates follow the user's YNAB settings. Unless a format is given, the one of these
+/// that reads every date in the file is used
+const DATE_FORMATS: [&str; 5] = ["%m/%d/%Y", "%d/%m/%Y", "%Y-%m-%d", "%d.%m.%Y", "%Y/%m/%d"];
+
+#[derive(Debug, PartialEq)]
+pub struct YnabTransaction {
+    pub account: String,
+    pub date: NaiveDate,
+    pub payee_name: String,
+    /// Empty for income and for transfers between accounts
+    pub category: Option<YnabCategory>,
+    pub amount: Decimal,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct YnabCategory {
+    pub group: String,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct YnabMonth {
+    /// The first day of the month
+    pub month: NaiveDate,
+    pub category: YnabCategory,
+    pub assigned: Decimal,
+    pub available: Decimal,
+}
+
+pub fn parse_register(
+    contents: &str,
+    date_format: Option<&str>,
+) -> Result<Vec<YnabTransaction>, anyhow::Error> {
+    let (headers, records) = read(contents)?;
+    let account = column_index(&headers, &["Account"])?;
+    let date = column_index(&headers, &["Date"])?;
+    let payee = column_index(&headers, &["Payee"])?;
+    let group = column_index(&headers, &["Category Group"])?;
+    let category = column_index(&headers, &["Category"])?;
+    let outflow = column_index(&headers, &["Outflow"])?;
+    let inflow = column_index(&headers, &["Inflow"])?;
+    let memo = column_index(&headers, &["Memo"]).ok();
+
+    let date_format = match date_format {
+        Some(date_format) => date_format,
+        None => detect_date_format(&records, date)?,
+    };
+
+    records
+        .iter()
+        .map(|record| {
+            let get = |column: usize| record.get(column).unwrap_or_default();
+
+            let amount =
+                parse_optional_amount(get(inflow))? - parse_optional_amount(get(outflow))?.abs();
+            let payee_name = match get(payee) {
+                "" => UNKNOWN_PAYEE,
+                payee_name => payee_name,
+            };
+
+            Ok(YnabTransaction {
+                account: get(account).to_owned(),
+                date: NaiveDate::parse_from_str(get(date), date_format)?,
+                payee_name: payee_name.to_owned(),
+                category: category_of(get(group), get(category)),
+                amount,
+                memo: memo
+                    .map(get)
+                    .filter(|memo| !memo.is_empty())
+                    .map(str::to_owned),
+            })
+        })
+        .enumerate()
+        .map(|(index, result): (usize, Result<_, anyhow::Error>)| {
+            result.with_context(|| format!("Could not read register row {}", index + 1))
+        })
+        .collect()
+}
+
+pub fn parse_plan(contents: &str) -> Result<Vec<YnabMonth>, anyhow::Error> {
+    let (headers, records) = read(contents)?;
+    let month = column_index(&headers, &["Month"])?;
+    let group = column_index(&headers, &["Category Group"])?;
+    let category = column_index(&headers, &["Category"])?;
+    // older exports call the assigned amount budgeted
+    let assigned = column_index(&headers, &["Assigned", "Budgeted"])?;
+    let available = column_index(&headers, &["Available"])?;
+
+    records
+        .iter()
+        .enumerate()
+        .filter_map(|(index, record)| {
+            let get = |column: usize| record.get(column).unwrap_or_default();
+
+            let month = get(month);
+            let result = category_of(get(group), get(category)).map(|category| {
+                Ok(YnabMonth {
+                    month: NaiveDate::parse_from_str(&format!("1 {month}"), "%d %b %Y")
+                        .map_err(|_| anyhow!("{month} is not a valid month"))?,
+                    category,
+                    assigned: parse_amount(get(assigned))?,
+                    available: parse_amount(get(available))?,
+                })
+            });
+
+            result.map(|result: Result<_, anyhow::Error>| {
+                result.with_context(|| format!("Could not read plan row {}", index + 1))
+            })
+        })
+        .collect()
+}
+
+/// Adds YNAB's accounts, payees, categories, transactions and monthly assignments to the plan.
+/// Returns the ids of the new bank accounts
+pub async fn import(
+    db_pool: &DbPool,
+    plan_id: Uuid,
+    register: &str,
+    plan: Option<&str>,
+    date_format: Option<&str>,
+) -> Result<Box<[Uuid]>, AppError> {
+    let transactions = parse_register(register, date_format)
+        .map_err(|e| AppError::BadRequest(e.context("Could not read YNAB register")))?;
+    let months = plan
+        .map(parse_plan)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.context("Could not read YNAB plan")))?
+        .unwrap_or_default();
+
+    let mut changes = Changes::get(db_pool, plan_id).await?;
+    let mut bank_account_ids: HashMap<String, Uuid> = HashMap::new();
+    let mut new_bank_account_ids = Vec::new();
+
+    for transaction in transactions {
+        let bank_account_id = *bank_account_ids
+            .entry(transaction.account.clone())
+            .or_insert_with(|| {
+                let id = changes.add_bank_account(transaction.account, Decimal::ZERO);
+                new_bank_account_ids.push(id);
+                id
+            });
+
+        let payee_id = changes.payee_id(&transaction.payee_name);
+        let budget_id = match transaction.category {
+            Some(category) => Some(changes.budget_id(&category.name, Some(&category.group))),
+            None if transaction.amount < Decimal::ZERO => {
+                Some(changes.budget_id(UNCATEGORISED_BUDGET, None))
+            }
+            None => None,
+        };
+
+        changes.add_transaction(
+            bank_account_id,
+            transaction.date,
+            transaction.amount,
+            payee_id,
+            budget_id,
+            transaction.memo,
+        );
+    }
+
+    let last_month = months.iter().map(|month| month.month).max();
+
+    for month in months {
+        let budget_id = changes.budget_id(&month.category.name, Some(&month.category.group));
+
+        if !month.assigned.is_zero() {
+            changes.move_from_ready_to_assign(budget_id, month.assigned, month.month);
+        }
+
+        // the current month can still be fixed up, so its overspending isn't covered yet
+        if month.available < Decimal::ZERO && Some(month.month) != last_month {
+            let next_month = month.month + Months::new(1);
+            changes.move_from_ready_to_assign(budget_id, -month.available, next_month);
+        }
+    }
+
+    changes.save(db_pool).await?;
+
+    Ok(new_bank_account_ids.into_boxed_slice())
+}
+
+fn read(contents: &str) -> Result<(StringRecord, Vec<StringRecord>), anyhow::Error> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(contents.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers = reader.headers()?.clone();
+    let records = reader.records().collect::<Result<_, _>>()?;
+
+    Ok((headers, records))
+}
+
+/// Dates like 01/02/2024 read as both month first and day first, so a format is only
+/// picked when it's the only one that reads every date
+fn detect_date_format(
+    records: &[StringRecord],
+    date: usize,
+) -> Result<&'static str, anyhow::Error> {
+    let formats: Vec<&str> = DATE_FORMATS
+        .into_iter()
+        .filter(|format| {
+            records.iter().all(|record| {
+                NaiveDate::parse_from_str(record.get(date).unwrap_or_default(), format).is_ok()
+            })
+        })
+        .collect();
+
+    match formats[..] {
+        [format] => Ok(format),
+        [] => bail!("Could not find a date format that reads every date"),
+        _ => bail!(
+            "Dates could be read as any of {}, so the date format must be given",
+            formats.join(", ")
+        ),
+    }
+}
+
+fn column_index(headers: &StringRecord, names: &[&str]) -> Result<usize, anyhow::Error> {
+    headers
+        .iter()
+        .position(|header| names.contains(&header))
+        .ok_or_else(|| anyhow!("{} column is missing", names[0]))
+}
+
+/// Income is put in the Inflow group, which isn't a budget
+fn category_of(group: &str, name: &str) -> Option<YnabCategory> {
+    if group.is_empty() || name.is_empty() || group == INFLOW_GROUP {
+        return None;
+    }
+
+    Some(YnabCategory {
+        group: group.to_owned(),
+        name: name.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn parse_register_test() {
+        let contents = "\u{feff}\"Account\",\"Flag\",\"Date\",\"Payee\",\"Category Group/Category\",\"Category Group\",\"Category\",\"Memo\",\"Outflow\",\"Inflow\",\"Cleared\"
+\"Checking\",\"\",\"15/12/2024\",\"Employer\",\"Inflow: Ready to Assign\",\"Inflow\",\"Ready to Assign\",\"\",\"$0.00\",\"$1,500.00\",\"Cleared\"
+\"Checking\",\"\",\"05/12/2024\",\"Corner Shop\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"Weekly shop\",\"$42.10\",\"$0.00\",\"Cleared\"
+\"Checking\",\"\",\"20/12/2024\",\"Transfer : Savings\",\"\",\"\",\"\",\"\",\"$100.00\",\"$0.00\",\"Cleared\"
+";
+
+        let transactions = parse_register(contents, None).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![
+                YnabTransaction {
+                    account: "Checking".into(),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                    payee_name: "Employer".into(),
+                    category: None,
+                    amount: dec!(1500),
+                    memo: None,
+                },
+                YnabTransaction {
+                    account: "Checking".into(),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                    payee_name: "Corner Shop".into(),
+                    category: Some(YnabCategory {
+                        group: "Everyday".into(),
+                        name: "Groceries".into(),
+                    }),
+                    amount: dec!(-42.10),
+                    memo: Some("Weekly shop".into()),
+                },
+                YnabTransaction {
+                    account: "Checking".into(),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 20).unwrap(),
+                    payee_name: "Transfer : Savings".into(),
+                    category: None,
+                    amount: dec!(-100),
+                    memo: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_register_missing_column() {
+        let error =
+            parse_register("Account,Date,Payee\nChecking,12/05/2024,Shop", None).unwrap_err();
+
+        assert_eq!(error.to_string(), "Category Group column is missing");
+    }
+
+    #[test]
+    fn parse_register_ambiguous_date_format() {
+        let contents = "Account,Date,Payee,Category Group,Category,Outflow,Inflow
+Checking,01/12/2024,Corner Shop,Everyday,Groceries,$42.10,$0.00
+";
+
+        let error = parse_register(contents, None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Dates could be read as any of %m/%d/%Y, %d/%m/%Y, so the date format must be given"
+        );
+
+        let transactions = parse_register(contents, Some("%d/%m/%Y")).unwrap();
+        assert_eq!(
+            transactions[0].date,
+            NaiveDate::from_ymd_opt(2024, 12, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_plan_test() {
+        let contents =
+            "Month,Category Group/Category,Category Group,Category,Budgeted,Activity,Available
+Nov 2024,Everyday: Groceries,Everyday,Groceries,$200.00,-$250.00,-$50.00
+Dec 2024,Everyday: Groceries,Everyday,Groceries,$300.00,-$42.10,$257.90
+";
+
+        let months = parse_plan(contents).unwrap();
+
+        assert_eq!(
+            months,
+            vec![
+                YnabMonth {
+                    month: NaiveDate::from_ymd_opt(2024, 11, 1).unwrap(),
+                    category: YnabCategory {
+                        group: "Everyday".into(),
+                        name: "Groceries".into(),
+                    },
+                    assigned: dec!(200),
+                    available: dec!(-50),
+                },
+                YnabMonth {
+                    month: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    category: YnabCategory {
+                        group: "Everyday".into(),
+                        name: "Groceries".into(),
+                    },
+                    assigned: dec!(300),
+                    available: dec!(257.90),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_plan_invalid_month() {
+        let contents = "Month,Category Group,Category,Assigned,Available
+December,Everyday,Groceries,$200.00,$200.00
+";
+
+        let error = parse_plan(contents).unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "Could not read plan row 1: December is not a valid month"
+        );
+    }
+}
@@ -2,47 +2,148 @@
 #![allow(clippy::missing_panics_doc)]
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::must_use_candidate)]
+pub mod admin;
+pub mod auth;
 pub mod db;
+pub mod export;
 pub mod extensions;
+pub mod import;
+pub mod migrations;
 pub mod models;
 mod routes;
 
+use std::{str::FromStr, sync::Arc};
+
+use anyhow::{bail, Context};
+use auth::AuthenticatedUser;
 use axum::{
-    extract::{MatchedPath, Request},
+    extract::{DefaultBodyLimit, FromRef, MatchedPath, Request},
     http::{HeaderValue, Method, StatusCode},
+    middleware,
     response::IntoResponse,
-    routing::{get, put},
+    routing::{delete, get, post, put},
     Router,
 };
-use http::header::{ACCEPT, CONTENT_TYPE};
+use db::{
+    repositories::{
+        BankAccountRepository, BudgetGroupRepository, BudgetRepository, OwnershipRepository,
+        PayeeRepository, ScheduledTransactionRepository, SqlRepository, TransactionRepository,
+    },
+    DbPool,
+};
+use http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use routes::{
     bank_accounts::{self},
+    budget_groups::{self},
     budgets::{self},
+    exports::{self},
+    imports::{self},
+    invitations::{self},
     payees::{self},
+    plans::{self},
+    qif::{self},
+    scheduled_transactions::{self},
+    schedules::{self},
+    sessions::{self},
     transactions::{self},
     users::{self},
+    ynab::{self},
 };
-use sqlx::MySqlPool;
+use sqlx::{mysql::MySqlConnectOptions, sqlite::SqliteConnectOptions, MySqlPool, SqlitePool};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::info_span;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utoipa::OpenApi;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
 use utoipa_swagger_ui::SwaggerUi;
 
-pub fn new_app(db_pool: MySqlPool) -> Router {
+/// The most a file upload or restored export may be. They hold years of transactions, so they
+/// can be larger than the 2 MB axum allows by default, which other requests keep
+pub const UPLOAD_BODY_LIMIT: usize = 50 * 1024 * 1024;
+
+/// What the handlers can extract with `State`. Handlers that only need the repositories can be
+/// tested against other implementations of them
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub db_pool: DbPool,
+    pub ownership: Arc<dyn OwnershipRepository>,
+    pub payees: Arc<dyn PayeeRepository>,
+    pub bank_accounts: Arc<dyn BankAccountRepository>,
+    pub budget_groups: Arc<dyn BudgetGroupRepository>,
+    pub budgets: Arc<dyn BudgetRepository>,
+    pub transactions: Arc<dyn TransactionRepository>,
+    pub scheduled_transactions: Arc<dyn ScheduledTransactionRepository>,
+}
+
+impl AppState {
+    pub fn new(db_pool: DbPool) -> Self {
+        let repository = Arc::new(SqlRepository::new(db_pool.clone()));
+
+        Self {
+            db_pool,
+            ownership: repository.clone(),
+            payees: repository.clone(),
+            bank_accounts: repository.clone(),
+            budget_groups: repository.clone(),
+            budgets: repository.clone(),
+            transactions: repository.clone(),
+            scheduled_transactions: repository,
+        }
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn new_app(db_pool: DbPool) -> Router {
     let cors_layer = build_cors();
+    let state = AppState::new(db_pool);
 
-    Router::new()
+    let signed_in_routes = Router::new()
         .route("/api/payees", get(payees::get).post(payees::create))
         .route(
             "/api/payees/:payeeId",
             put(payees::update).delete(payees::delete),
         )
-        .route("/api/users", get(users::get).post(users::create))
         .route(
-            "/api/users/:userId",
-            get(users::get_single).put(users::update),
+            "/api/budget-groups",
+            get(budget_groups::get).post(budget_groups::create),
+        )
+        .route(
+            "/api/budget-groups/:groupId",
+            put(budget_groups::update).delete(budget_groups::delete),
+        )
+        .route("/api/users/me", get(users::get_current).put(users::update))
+        .route("/api/users/me/export", get(exports::get))
+        .route(
+            "/api/users/me/restore",
+            post(exports::restore).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/api/plans", get(plans::get).post(plans::create))
+        .route("/api/plans/:planId", put(plans::update))
+        .route("/api/plans/:planId/copy", post(plans::copy))
+        .route(
+            "/api/plans/:planId/ready-to-assign",
+            get(plans::get_ready_to_assign),
+        )
+        .route("/api/plans/:planId/members", get(plans::get_members))
+        .route(
+            "/api/plans/:planId/members/:userId",
+            put(plans::update_member).delete(plans::remove_member),
+        )
+        .route(
+            "/api/plans/:planId/invitations",
+            get(invitations::get_for_plan).post(invitations::create),
+        )
+        .route(
+            "/api/plans/:planId/invitations/:invitationId",
+            delete(invitations::revoke),
+        )
+        .route("/api/invitations", get(invitations::get))
+        .route(
+            "/api/invitations/:invitationId/accept",
+            post(invitations::accept),
         )
         .route(
             "/api/bank-accounts",
@@ -58,20 +159,94 @@ pub fn new_app(db_pool: MySqlPool) -> Router {
             "/api/bank-accounts/:bankAccountId/transactions",
             get(transactions::get).post(transactions::create),
         )
+        .route(
+            "/api/bank-accounts/:bankAccountId/imports",
+            post(imports::create),
+        )
+        .route(
+            "/api/bank-accounts/:bankAccountId/imports/ofx/preview",
+            post(imports::preview_ofx).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route(
+            "/api/bank-accounts/:bankAccountId/imports/csv/preview",
+            post(imports::preview_csv).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route(
+            "/api/plans/:planId/imports/qif",
+            post(qif::import).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/api/bank-accounts/:bankAccountId/qif", get(qif::export))
+        .route(
+            "/api/plans/:planId/imports/ynab",
+            post(ynab::import).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route(
+            "/api/bank-accounts/:bankAccountId/csv-mapping",
+            get(imports::get_csv_mapping).put(imports::update_csv_mapping),
+        )
+        .route("/api/transactions", get(transactions::search))
         .route(
             "/api/transactions/:transactionId",
             put(transactions::update).delete(transactions::delete),
         )
+        .route(
+            "/api/bank-accounts/:bankAccountId/scheduled-transactions",
+            get(scheduled_transactions::get).post(scheduled_transactions::create),
+        )
+        .route(
+            "/api/bank-accounts/:bankAccountId/scheduled-transactions/post-due",
+            post(scheduled_transactions::post_due),
+        )
+        .route(
+            "/api/bank-accounts/:bankAccountId/scheduled-transactions/:scheduledTransactionId",
+            put(scheduled_transactions::update).delete(scheduled_transactions::delete),
+        )
         .route("/api/budgets", get(budgets::get).post(budgets::create))
         .route(
             "/api/budgets/:budgetId",
             put(budgets::update).delete(budgets::delete),
         )
+        .route("/api/budgets/:budgetId/archive", put(budgets::archive))
+        .route("/api/budgets/:budgetId/unarchive", put(budgets::unarchive))
         .route(
             "/api/budgets/:budgetId/transfer-to/:otherBudgetId",
             put(budgets::transfer_between),
         )
-        .with_state(db_pool)
+        .route(
+            "/api/budgets/:budgetId/transfer-to/ready-to-assign",
+            put(budgets::return_to_ready_to_assign),
+        )
+        .route(
+            "/api/budgets/ready-to-assign/transfer-to/:budgetId",
+            put(budgets::assign_from_ready_to_assign),
+        )
+        .route("/api/budgets/months/:month", get(budgets::get_month_for_all))
+        .route(
+            "/api/budgets/suggested-assignments",
+            get(budgets::get_suggested_assignments).post(budgets::apply_suggested_assignments),
+        )
+        .route(
+            "/api/budgets/:budgetId/suggested-target",
+            get(budgets::get_suggested_target),
+        )
+        .route(
+            "/api/budgets/:budgetId/months/:month",
+            get(budgets::get_month),
+        )
+        .route("/api/schedules/preview", post(schedules::preview))
+        .route_layer(middleware::from_extractor_with_state::<
+            AuthenticatedUser,
+            _,
+        >(state.clone()));
+
+    Router::new()
+        .merge(signed_in_routes)
+        .route("/api/users", post(users::create))
+        .route(
+            "/api/sessions",
+            post(sessions::create).delete(sessions::delete),
+        )
+        .with_state(state)
         .layer(
             ServiceBuilder::new()
                 .layer(
@@ -100,21 +275,44 @@ fn build_cors() -> CorsLayer {
 
     CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers([ACCEPT, CONTENT_TYPE])
+        .allow_headers([ACCEPT, AUTHORIZATION, CONTENT_TYPE])
         .allow_origin(allow_origin.parse::<HeaderValue>().unwrap())
 }
 
 #[derive(OpenApi)]
-#[openapi()]
+#[openapi(modifiers(&SessionTokenAddon), security(("session_token" = [])))]
 struct ApiDoc;
 
+struct SessionTokenAddon;
+
+impl Modify for SessionTokenAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "session_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
 pub fn build_swagger_doc() -> utoipa::openapi::OpenApi {
     let mut openapi = ApiDoc::openapi();
     openapi.merge(payees::Api::openapi());
     openapi.merge(transactions::Api::openapi());
+    openapi.merge(exports::Api::openapi());
+    openapi.merge(imports::Api::openapi());
+    openapi.merge(qif::Api::openapi());
+    openapi.merge(ynab::Api::openapi());
     openapi.merge(bank_accounts::Api::openapi());
     openapi.merge(users::Api::openapi());
+    openapi.merge(sessions::Api::openapi());
+    openapi.merge(plans::Api::openapi());
+    openapi.merge(invitations::Api::openapi());
     openapi.merge(budgets::Api::openapi());
+    openapi.merge(budget_groups::Api::openapi());
+    openapi.merge(scheduled_transactions::Api::openapi());
+    openapi.merge(schedules::Api::openapi());
 
     openapi
 }
@@ -125,14 +323,26 @@ pub fn build_swagger_ui() -> SwaggerUi {
     SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", openapi)
 }
 
-pub async fn init_db() -> MySqlPool {
-    let db_url = std::env::var("DATABASE_URL").unwrap();
+/// Connects to `DATABASE_URL`, which picks the database with its scheme, `mysql:`, `mariadb:`
+/// or `sqlite:`. Missing `SQLite` database files are created
+pub async fn init_db() -> Result<DbPool, anyhow::Error> {
+    let db_url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?;
 
     tracing::info!("Connecting to db at {db_url}");
 
-    MySqlPool::connect(&db_url)
-        .await
-        .expect("to be able to connect to the database")
+    let db_pool = if db_url.starts_with("mysql:") || db_url.starts_with("mariadb:") {
+        MySqlPool::connect_with(MySqlConnectOptions::from_str(&db_url)?)
+            .await
+            .map(DbPool::MySql)
+    } else if db_url.starts_with("sqlite:") {
+        SqlitePool::connect_with(SqliteConnectOptions::from_str(&db_url)?.create_if_missing(true))
+            .await
+            .map(DbPool::Sqlite)
+    } else {
+        bail!("DATABASE_URL must start with mysql:, mariadb: or sqlite:");
+    };
+
+    db_pool.context("Could not connect to the database")
 }
 
 pub fn init_logger() {
@@ -156,6 +366,8 @@ pub fn init_logger() {
 pub enum AppError {
     NotFound(anyhow::Error),
     BadRequest(anyhow::Error),
+    Unauthorized(anyhow::Error),
+    Forbidden(anyhow::Error),
     InternalServerError(anyhow::Error),
 }
 
@@ -176,6 +388,8 @@ impl IntoResponse for AppError {
                 format!("Something went wrong: {e}"),
             ),
             AppError::BadRequest(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+            AppError::Unauthorized(e) => (StatusCode::UNAUTHORIZED, e.to_string()),
+            AppError::Forbidden(e) => (StatusCode::FORBIDDEN, e.to_string()),
             AppError::NotFound(e) => (StatusCode::NOT_FOUND, e.to_string()),
         }
         .into_response()
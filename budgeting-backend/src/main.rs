@@ -1,59 +1,327 @@
 #![warn(clippy::pedantic)]
 
-use std::{env::args, path::PathBuf};
+use std::{
+    io::{self, IsTerminal},
+    net::IpAddr,
+    path::PathBuf,
+    process::ExitCode,
+};
 
-use anyhow::bail;
-use budgeting_backend::{build_swagger_doc, build_swagger_ui, init_db, init_logger, new_app};
+use anyhow::{bail, Context};
+use budgeting_backend::{
+    admin, auth, build_swagger_doc, build_swagger_ui,
+    db::DbPool,
+    export, import, init_db, init_logger,
+    migrations::{self, MigrationState},
+    models::CreateUserRequest,
+    new_app, AppError,
+};
+use clap::{Parser, Subcommand};
+use uuid::Uuid;
+
+/// The budgeting API server and the tools for looking after its database.
+///
+/// Commands that use the database connect to `DATABASE_URL`, which can also be set in `.env`
+#[derive(Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// Runs the server when left out
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the API server
+    Serve(ServeArgs),
+    /// Write the Swagger document for the API
+    GenSwagger {
+        /// The file to write, or a directory to write `api-doc.json` into
+        path: PathBuf,
+    },
+    /// Show or apply the database migrations built into this binary
+    Migrate {
+        /// Applies pending migrations when left out
+        #[command(subcommand)]
+        command: Option<MigrateCommand>,
+    },
+    /// Create a user who can sign in with a password.
+    ///
+    /// The password, at least 8 characters, is taken from `USER_PASSWORD` when it's set.
+    /// Otherwise it's prompted for, or read from the first line of stdin when that's piped
+    CreateUser {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        email: String,
+    },
+    /// Add a plan full of made up budgets and transactions to a user
+    SeedDemoData {
+        /// The user who will own the demo plan
+        user_id: Uuid,
+    },
+    /// Rebuild budget balances from the transactions in them
+    RecalculateBalances {
+        /// Only recalculate this plan's budgets
+        #[arg(long)]
+        plan_id: Option<Uuid>,
+    },
+    /// Write everything a user owns to a JSON file
+    Export { user_id: Uuid, path: PathBuf },
+    /// Restore a JSON export's plans into a user
+    Import { user_id: Uuid, path: PathBuf },
+    /// Import a QIF file into a plan
+    ImportQif {
+        plan_id: Uuid,
+        path: PathBuf,
+        /// Names the bank account when the file has no `!Account` header
+        account_name: Option<String>,
+    },
+    /// Write a bank account's transactions to a QIF file
+    ExportQif {
+        bank_account_id: Uuid,
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// List each migration and whether the database has it
+    Status,
+    /// Apply any migrations the database is missing
+    Up,
+}
+
+#[derive(clap::Args)]
+struct ServeArgs {
+    /// The address to listen on
+    #[arg(long, default_value = "0.0.0.0")]
+    address: IpAddr,
+    #[arg(long, short, default_value_t = 3000)]
+    port: u16,
+    /// Don't apply pending migrations on startup. The server still won't start if the
+    /// database schema is newer than it
+    #[arg(long, env = "SKIP_MIGRATIONS")]
+    skip_migrations: bool,
+}
 
 #[tokio::main]
-async fn main() {
-    let args: Vec<_> = args().collect();
-
-    if args.len() == 1 {
-        run_server().await.unwrap();
-    } else if args[1] == "gen-swagger" {
-        gen_swagger(if args.len() > 2 { &args[2] } else { "" })
-            .await
-            .unwrap();
-    } else {
-        // todo: clap
-        panic!("Unknown command
-Available Commands:
-gen-swagger")
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli.command.unwrap_or(Command::Serve(cli.serve))).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            ExitCode::FAILURE
+        }
     }
 }
 
-async fn gen_swagger(path_str: &str) -> Result<(), anyhow::Error> {
-    let api_doc = build_swagger_doc();
+async fn run(command: Command) -> Result<(), anyhow::Error> {
+    match command {
+        Command::Serve(args) => serve(args).await,
+        Command::GenSwagger { path } => gen_swagger(path).await,
+        Command::Migrate { command } => {
+            let db_pool = connect().await?;
 
-    let json = api_doc.to_pretty_json()?;
+            match command.unwrap_or(MigrateCommand::Up) {
+                MigrateCommand::Status => print_migration_status(&db_pool).await,
+                MigrateCommand::Up => {
+                    let applied_count = migrations::run(&db_pool).await?;
+
+                    println!("Applied {applied_count} migrations");
+
+                    Ok(())
+                }
+            }
+        }
+        Command::CreateUser { name, email } => {
+            let password = read_new_password()?;
+            let db_pool = connect().await?;
+
+            let user_id =
+                auth::create_user(&db_pool, CreateUserRequest::new(name, email, password))
+                    .await
+                    .map_err(into_anyhow)?;
+
+            println!("Created user {user_id}");
+
+            Ok(())
+        }
+        Command::SeedDemoData { user_id } => {
+            let db_pool = connect().await?;
+
+            let plan_id = admin::seed_demo_data(&db_pool, user_id)
+                .await
+                .map_err(into_anyhow)?;
+
+            println!("Created demo plan {plan_id}");
+
+            Ok(())
+        }
+        Command::RecalculateBalances { plan_id } => {
+            let db_pool = connect().await?;
+
+            let fixed_count = admin::recalculate_balances(&db_pool, plan_id)
+                .await
+                .map_err(into_anyhow)?;
+
+            println!("Fixed {fixed_count} budgets");
+
+            Ok(())
+        }
+        Command::Export { user_id, path } => {
+            let db_pool = connect().await?;
+
+            let export = export::export(&db_pool, user_id)
+                .await
+                .map_err(into_anyhow)?;
+
+            tokio::fs::write(path, serde_json::to_string_pretty(&export)?.as_bytes()).await?;
+
+            Ok(())
+        }
+        Command::Import { user_id, path } => {
+            let db_pool = connect().await?;
+            let contents = tokio::fs::read_to_string(path).await?;
+
+            let plan_ids = export::restore(&db_pool, user_id, serde_json::from_str(&contents)?)
+                .await
+                .map_err(into_anyhow)?;
+
+            for plan_id in plan_ids {
+                println!("Restored plan {plan_id}");
+            }
+
+            Ok(())
+        }
+        Command::ImportQif {
+            plan_id,
+            path,
+            account_name,
+        } => {
+            let db_pool = connect().await?;
+            let contents = tokio::fs::read_to_string(path).await?;
+
+            let bank_account_ids =
+                import::qif::import(&db_pool, plan_id, &contents, account_name.as_deref())
+                    .await
+                    .map_err(into_anyhow)?;
+
+            for bank_account_id in bank_account_ids {
+                println!("Created bank account {bank_account_id}");
+            }
 
-    if path_str.is_empty() {
-        bail!("path cannot be empty");
+            Ok(())
+        }
+        Command::ExportQif {
+            bank_account_id,
+            path,
+        } => {
+            let db_pool = connect().await?;
+
+            let qif = import::qif::export(&db_pool, bank_account_id)
+                .await
+                .map_err(into_anyhow)?;
+
+            tokio::fs::write(path, qif.as_bytes()).await?;
+
+            Ok(())
+        }
     }
+}
 
-    let mut path = PathBuf::from(path_str);
+async fn gen_swagger(mut path: PathBuf) -> Result<(), anyhow::Error> {
+    let api_doc = build_swagger_doc();
+
+    let json = api_doc.to_pretty_json()?;
 
     if path.is_dir() {
-        path = path.with_file_name("api-doc.json");
+        path.push("api-doc.json");
     }
 
-    tokio::fs::write(path, json.as_bytes()).await?;
+    tokio::fs::write(&path, json.as_bytes())
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))?;
 
     Ok(())
 }
 
-async fn run_server() -> Result<(), anyhow::Error> {
+async fn print_migration_status(db_pool: &DbPool) -> Result<(), anyhow::Error> {
+    for status in migrations::status(db_pool).await? {
+        let state = match status.state {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Unknown => "unknown to this binary",
+        };
+
+        println!("{} {} ({state})", status.version, status.description);
+    }
+
+    Ok(())
+}
+
+/// Passwords aren't taken as arguments, which would leave them in the shell history and the
+/// process list
+fn read_new_password() -> Result<String, anyhow::Error> {
+    if let Ok(password) = std::env::var("USER_PASSWORD") {
+        return Ok(password);
+    }
+
+    if !io::stdin().is_terminal() {
+        let mut password = String::new();
+        io::stdin()
+            .read_line(&mut password)
+            .context("Could not read the password from stdin")?;
+
+        return Ok(password.trim_end_matches(['\r', '\n']).to_owned());
+    }
+
+    let password = rpassword::prompt_password("Password: ")?;
+    if rpassword::prompt_password("Repeat password: ")? != password {
+        bail!("The passwords don't match");
+    }
+
+    Ok(password)
+}
+
+/// Reads `.env` when there is one and connects to the database
+async fn connect() -> Result<DbPool, anyhow::Error> {
+    dotenvy::dotenv().ok();
+
+    init_db().await
+}
+
+fn into_anyhow(error: AppError) -> anyhow::Error {
+    match error {
+        AppError::NotFound(e)
+        | AppError::BadRequest(e)
+        | AppError::Unauthorized(e)
+        | AppError::Forbidden(e)
+        | AppError::InternalServerError(e) => e,
+    }
+}
+
+async fn serve(args: ServeArgs) -> Result<(), anyhow::Error> {
     init_logger();
 
-    dotenvy::dotenv()?;
+    let connection_pool = connect().await?;
 
-    let connection_pool = init_db().await;
+    if args.skip_migrations {
+        migrations::check_schema(&connection_pool).await?;
+    } else {
+        let applied_count = migrations::run(&connection_pool).await?;
+        tracing::info!("applied {applied_count} migrations");
+    }
 
     let app = new_app(connection_pool).merge(build_swagger_ui());
 
-    // run our app with hyper, listening globally on port 3000
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+    let listener = tokio::net::TcpListener::bind((args.address, args.port))
+        .await
+        .with_context(|| format!("Could not listen on {}:{}", args.address, args.port))?;
 
     tracing::info!("listening on {}", listener.local_addr()?);
 
@@ -0,0 +1,163 @@
+//! The database migrations built into the binary. Each database has its own set in
+//! `migrations/<database>`, so a schema change needs a migration in both
+
+use std::collections::HashSet;
+
+use anyhow::bail;
+use sqlx::migrate::{Migrate, Migrator};
+
+use crate::db::{with_connection, DbPool};
+
+pub static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+/// The migrations for [`TestPool`](crate::db::TestPool)'s database, which the `#[sqlx::test]`
+/// suites name as their `migrator`
+#[cfg(not(feature = "sqlite-tests"))]
+pub use MYSQL_MIGRATOR as TEST_MIGRATOR;
+#[cfg(feature = "sqlite-tests")]
+pub use SQLITE_MIGRATOR as TEST_MIGRATOR;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied to the database by a newer version of the server
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    /// Empty for unknown migrations
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Every migration built into the binary or applied to the database, oldest first
+pub async fn status(db_pool: &DbPool) -> Result<Vec<MigrationStatus>, anyhow::Error> {
+    let mut applied_versions = applied_versions(db_pool).await?;
+
+    let mut statuses: Vec<_> = migrator(db_pool)
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state: if applied_versions.remove(&migration.version) {
+                MigrationState::Applied
+            } else {
+                MigrationState::Pending
+            },
+        })
+        .collect();
+
+    statuses.extend(applied_versions.into_iter().map(|version| MigrationStatus {
+        version,
+        description: String::new(),
+        state: MigrationState::Unknown,
+    }));
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Fails if the database has migrations this binary doesn't know about, since the schema
+/// may have changed in ways its queries don't expect
+pub async fn check_schema(db_pool: &DbPool) -> Result<(), anyhow::Error> {
+    let unknown_versions: Vec<_> = status(db_pool)
+        .await?
+        .into_iter()
+        .filter(|status| status.state == MigrationState::Unknown)
+        .map(|status| status.version.to_string())
+        .collect();
+
+    if !unknown_versions.is_empty() {
+        bail!(
+            "The database schema is newer than this server, it has unknown migrations {}",
+            unknown_versions.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Applies the migrations the database is missing and returns how many there were
+pub async fn run(db_pool: &DbPool) -> Result<usize, anyhow::Error> {
+    check_schema(db_pool).await?;
+
+    let pending_count = status(db_pool)
+        .await?
+        .iter()
+        .filter(|status| status.state == MigrationState::Pending)
+        .count();
+
+    match db_pool {
+        DbPool::MySql(pool) => MYSQL_MIGRATOR.run(pool).await?,
+        DbPool::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+    }
+
+    Ok(pending_count)
+}
+
+fn migrator(db_pool: &DbPool) -> &'static Migrator {
+    match db_pool {
+        DbPool::MySql(_) => &MYSQL_MIGRATOR,
+        DbPool::Sqlite(_) => &SQLITE_MIGRATOR,
+    }
+}
+
+async fn applied_versions(db_pool: &DbPool) -> Result<HashSet<i64>, anyhow::Error> {
+    with_connection!(db_pool, |connection| {
+        connection.ensure_migrations_table().await?;
+
+        Ok(connection
+            .list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::TestPool;
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    async fn status_after_migrating(db_pool: TestPool) {
+        let db_pool = DbPool::from(db_pool);
+        let statuses = status(&db_pool).await.unwrap();
+
+        assert_eq!(statuses.len(), TEST_MIGRATOR.iter().count());
+        assert!(statuses
+            .iter()
+            .all(|status| status.state == MigrationState::Applied));
+
+        assert_eq!(run(&db_pool).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    async fn newer_schema(db_pool: TestPool) {
+        let version = i64::MAX;
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+            VALUES (?, 'from the future', TRUE, '', 0)",
+        )
+        .bind(version)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+        let db_pool = DbPool::from(db_pool);
+        let statuses = status(&db_pool).await.unwrap();
+        let last = statuses.last().unwrap();
+        assert_eq!(last.version, version);
+        assert_eq!(last.state, MigrationState::Unknown);
+
+        assert!(check_schema(&db_pool).await.is_err());
+        assert!(run(&db_pool).await.is_err());
+    }
+}
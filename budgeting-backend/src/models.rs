@@ -1,24 +1,149 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
 
 use anyhow::anyhow;
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use derive_more::derive::Constructor;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema)]
-pub struct Payee {
+mod budget_group;
+mod budget_month;
+mod budget_target;
+mod export;
+mod plan;
+mod schedule;
+mod suggested_assignment;
+mod suggested_target;
+mod transaction_query;
+
+pub use budget_month::parse_month;
+pub use export::{PlanExport, UserExport, EXPORT_VERSION};
+pub use plan::PlanCopy;
+pub use suggested_target::suggest_target;
+pub use transaction_query::{
+    SortOrder, TransactionCursor, TransactionFilter, TransactionFlow, TransactionPage,
+    TransactionPageRequest, TransactionSearchPage, TransactionSearchResult, TransactionSort,
+    TransactionTotals,
+};
+
+/// A budget book, owning its own budgets, bank accounts and payees.
+/// Lets a user keep separate books, e.g. a personal one and a household one shared with
+/// the rest of the household through its members
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct Plan {
     pub id: Uuid,
     pub name: String,
+}
+
+/// What a member can do in a plan. Each role can do everything the roles before it can
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema)]
+pub enum PlanRole {
+    /// Can see everything in the plan but not change it
+    Viewer,
+    /// Can also add and change budgets, bank accounts, payees and transactions
+    Editor,
+    /// Can also rename the plan and manage who else is a member
+    Owner,
+}
+
+impl FromStr for PlanRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Viewer" => Ok(Self::Viewer),
+            "Editor" => Ok(Self::Editor),
+            "Owner" => Ok(Self::Owner),
+            other => Err(anyhow!("Unexpected plan role {other}")),
+        }
+    }
+}
+
+impl Display for PlanRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Viewer => write!(f, "Viewer"),
+            Self::Editor => write!(f, "Editor"),
+            Self::Owner => write!(f, "Owner"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct PlanMember {
     pub user_id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub role: PlanRole,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct UpdatePlanMemberRequest {
+    pub role: PlanRole,
+}
+
+/// An invitation for whoever signs in with `email` to join a plan
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub email: String,
+    pub role: PlanRole,
+    pub invited_by_user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    pub role: PlanRole,
+}
+
+/// The token is only ever returned here. The plan's owner passes it on to whoever they invited,
+/// who needs it to accept
+#[derive(Serialize, Deserialize, Constructor, ToSchema, Debug)]
+pub struct CreateInvitationResponse {
+    pub id: Uuid,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct CreatePlanRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct UpdatePlanRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct CopyPlanRequest {
+    /// Name of the new plan
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct Payee {
+    pub id: Uuid,
+    pub name: String,
+    pub plan_id: Uuid,
 }
 
 #[derive(Serialize, Deserialize, Constructor, ToSchema)]
 pub struct CreatePayeeRequest {
     pub name: String,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
 }
 
 #[derive(Deserialize, Serialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
@@ -30,7 +155,10 @@ pub struct Transaction {
     #[serde(with = "rust_decimal::serde::float")]
     pub amount: Decimal,
     pub bank_account_id: Uuid,
-    pub budget_id: Uuid,
+    /// `None` for income, which goes into the plan's Ready to Assign pool
+    pub budget_id: Option<Uuid>,
+    /// The bank's description of an imported transaction. Kept when the transaction is updated
+    pub memo: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Constructor, ToSchema)]
@@ -40,7 +168,8 @@ pub struct CreateTransactionRequest {
     #[serde(with = "rust_decimal::serde::float")]
     pub amount: Decimal,
     pub date: NaiveDate,
-    pub budget_id: Uuid,
+    /// Leave empty to mark an inflow as income
+    pub budget_id: Option<Uuid>,
 }
 
 #[derive(Deserialize, Serialize, Constructor, ToSchema, Debug, PartialEq, Clone)]
@@ -55,6 +184,21 @@ pub struct User {
 pub struct CreateUserRequest {
     pub name: String,
     pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Constructor)]
+pub struct SignInRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// A signed in session. Send the token as `Authorization: Bearer <token>`
+#[derive(Deserialize, Serialize, ToSchema, Constructor, Debug, PartialEq, Clone)]
+pub struct Session {
+    pub token: String,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema, Constructor)]
@@ -70,7 +214,7 @@ pub struct BankAccount {
     #[schema(value_type = f32)]
     #[serde(with = "rust_decimal::serde::float")]
     pub initial_amount: Decimal,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
     #[schema(value_type = f32)]
     #[serde(with = "rust_decimal::serde::float")]
     pub balance: Decimal,
@@ -82,23 +226,25 @@ pub struct CreateBankAccountRequest {
     #[schema(value_type = f32)]
     #[serde(with = "rust_decimal::serde::float")]
     pub initial_amount: Decimal,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
 }
 
 #[derive(Deserialize, Serialize, ToSchema, Constructor)]
 pub struct CreateBudgetRequest {
     pub name: String,
     pub target: Option<CreateBudgetTargetRequest>,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
+    pub group_id: Option<Uuid>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema, Constructor)]
 pub struct UpdateBudgetRequest {
     pub name: String,
     pub target: Option<UpdateBudgetTargetRequest>,
+    pub group_id: Option<Uuid>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, PartialEq, Debug)]
 #[serde(tag = "type")]
 pub enum CreateBudgetTargetRequest {
     OneTime {
@@ -137,31 +283,157 @@ pub struct Budget {
     pub id: Uuid,
     pub name: String,
     pub target: Option<BudgetTarget>,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
     pub assignments: Vec<BudgetAssignment>,
+    pub group_id: Option<Uuid>,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize)]
 pub struct GetBudgetResponse {
     pub id: Uuid,
     pub name: String,
     pub target: Option<BudgetTarget>,
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
     pub assignments: Vec<BudgetAssignment>,
+    pub group_id: Option<Uuid>,
+    pub archived_at: Option<DateTime<Utc>>,
     #[schema(value_type = f32)]
     #[serde(with = "rust_decimal::serde::float")]
     pub total_assigned: Decimal,
+    pub target_status: Option<BudgetTargetStatus>,
+}
+
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct GetBudgetsResponse {
+    pub budgets: Vec<GetBudgetResponse>,
+    /// Sorted by `sort_order`, budgets without a group don't belong to any of these
+    pub groups: Vec<BudgetGroupSummary>,
+}
+
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct BudgetGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub sort_order: i32,
+    pub plan_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Constructor)]
+pub struct CreateBudgetGroupRequest {
+    pub name: String,
+    pub sort_order: i32,
+    pub plan_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Constructor)]
+pub struct UpdateBudgetGroupRequest {
+    pub name: String,
+    pub sort_order: i32,
+}
+
+/// A group along with the totals of the budgets in it
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct BudgetGroupSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub sort_order: i32,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_assigned: Decimal,
+    /// How much more needs to be assigned to meet every target in the group
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub target_needed: Decimal,
+    /// Whether every target in the group is on track
+    pub targets_on_track: bool,
+    /// The earliest due date of the targets in the group
+    pub next_due_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct BudgetTargetStatus {
+    /// What counts towards the target, which depends on the type of target
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub funded: Decimal,
+    /// How much more needs to be assigned to meet the target
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub needed: Decimal,
+    /// Between 0 and 100
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub percent_funded: Decimal,
+    /// Whether enough has been funded so far to meet the target by the next due date
+    pub on_track: bool,
+    /// `None` for one time targets
+    pub next_due_date: Option<NaiveDate>,
+}
+
+/// Where a budget's money came from and went during a single month
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct BudgetMonthSummary {
+    pub budget_id: Uuid,
+    /// The first day of the month
+    pub month: NaiveDate,
+    /// What was left over at the end of the previous month
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub carried_over: Decimal,
+    /// Money moved in from or out to other budgets or Ready to Assign this month
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub assigned: Decimal,
+    /// The total of this month's transactions
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub activity: Decimal,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub available: Decimal,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub overspent: Decimal,
+}
+
+#[derive(Clone, Debug, PartialEq, ToSchema, Serialize, Deserialize, Constructor)]
+pub struct GetMonthResponse {
+    /// The first day of the month
+    pub month: NaiveDate,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub assigned: Decimal,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub activity: Decimal,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub available: Decimal,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub overspent: Decimal,
+    pub budgets: Vec<BudgetMonthSummary>,
 }
 
 impl From<Budget> for GetBudgetResponse {
     fn from(value: Budget) -> Self {
+        Self::from_budget(value, Local::now().date_naive())
+    }
+}
+
+impl GetBudgetResponse {
+    pub fn from_budget(value: Budget, today: NaiveDate) -> Self {
         GetBudgetResponse {
             total_assigned: value.total_assigned(),
+            target_status: value.target_status(today),
             id: value.id,
             target: value.target,
-            user_id: value.user_id,
+            plan_id: value.plan_id,
             assignments: value.assignments,
             name: value.name,
+            group_id: value.group_id,
+            archived_at: value.archived_at,
         }
     }
 }
@@ -182,6 +454,116 @@ impl Budget {
         });
     }
 
+    /// Makes the budget's assignments from transactions match `transactions`, adding the
+    /// missing ones, fixing their amounts and dates and dropping those for transactions that
+    /// are gone or in another budget. Returns whether anything changed
+    pub fn sync_transaction_assignments(&mut self, transactions: &[Transaction]) -> bool {
+        let budget_id = self.id;
+        let transactions_by_id: HashMap<_, _> = transactions
+            .iter()
+            .filter(|transaction| transaction.budget_id == Some(budget_id))
+            .map(|transaction| (transaction.id, transaction))
+            .collect();
+
+        let mut assigned_ids = HashSet::new();
+        let mut changed = false;
+
+        self.assignments.retain_mut(|assignment| {
+            let BudgetAssignmentSource::Transaction {
+                from_transaction_id,
+            } = assignment.source
+            else {
+                return true;
+            };
+
+            match transactions_by_id.get(&from_transaction_id) {
+                Some(transaction) if assigned_ids.insert(from_transaction_id) => {
+                    if assignment.amount != transaction.amount
+                        || assignment.date != transaction.date
+                    {
+                        assignment.amount = transaction.amount;
+                        assignment.date = transaction.date;
+                        changed = true;
+                    }
+                    true
+                }
+                _ => {
+                    changed = true;
+                    false
+                }
+            }
+        });
+
+        for transaction in transactions {
+            if transaction.budget_id == Some(budget_id) && !assigned_ids.contains(&transaction.id) {
+                self.assign_from_transaction(transaction);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Makes the budget's assignment from `transaction` match it, adding it when the transaction
+    /// is in this budget, fixing its amount and date, or dropping it when the transaction moved
+    /// to another budget. Returns whether anything changed
+    pub fn sync_transaction_assignment(&mut self, transaction: &Transaction) -> bool {
+        let in_budget = transaction.budget_id == Some(self.id);
+        let mut found = false;
+        let mut changed = false;
+
+        self.assignments.retain_mut(|assignment| {
+            if assignment.source
+                != (BudgetAssignmentSource::Transaction {
+                    from_transaction_id: transaction.id,
+                })
+            {
+                return true;
+            }
+
+            if !in_budget || found {
+                changed = true;
+                return false;
+            }
+
+            found = true;
+            if assignment.amount != transaction.amount || assignment.date != transaction.date {
+                assignment.amount = transaction.amount;
+                assignment.date = transaction.date;
+                changed = true;
+            }
+            true
+        });
+
+        if in_budget && !found {
+            self.assign_from_transaction(transaction);
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Drops the assignment from the transaction, for when it's deleted
+    pub fn remove_transaction_assignment(&mut self, transaction_id: Uuid) {
+        self.assignments.retain(|assignment| {
+            assignment.source
+                != BudgetAssignmentSource::Transaction {
+                    from_transaction_id: transaction_id,
+                }
+        });
+    }
+
+    /// Moves `amount` from the plan's Ready to Assign pool into this budget.
+    /// A negative amount moves money back into the pool
+    pub fn move_from_ready_to_assign(&mut self, amount: Decimal, date: NaiveDate) {
+        self.assignments.push(BudgetAssignment {
+            id: Uuid::new_v4(),
+            amount,
+            date,
+            source: BudgetAssignmentSource::ReadyToAssign,
+        });
+    }
+
     pub fn move_between_budgets(
         from: &mut Budget,
         to: &mut Budget,
@@ -226,6 +608,7 @@ pub struct BudgetAssignment {
 pub enum BudgetAssignmentSource {
     OtherBudget { from_budget_id: Uuid, link_id: Uuid },
     Transaction { from_transaction_id: Uuid },
+    ReadyToAssign,
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
@@ -287,7 +670,7 @@ pub struct Schedule {
     pub period: SchedulePeriod,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, PartialEq, Debug)]
 pub struct CreateScheduleRequest {
     pub period: SchedulePeriod,
 }
@@ -297,6 +680,13 @@ pub struct UpdateScheduleRequest {
     pub period: SchedulePeriod,
 }
 
+#[derive(Serialize, Deserialize, ToSchema, Constructor)]
+pub struct PreviewScheduleRequest {
+    pub period: SchedulePeriod,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
 #[derive(PartialEq, Debug, Clone, Deserialize, Serialize, ToSchema)]
 #[serde(tag = "type")]
 pub enum SchedulePeriod {
@@ -313,6 +703,9 @@ pub enum SchedulePeriod {
         starting_on: NaiveDate,
     },
     Custom {
+        /// Required when saving. Custom schedules from before they had a start date have
+        /// none and never occur until they're given one
+        starting_on: Option<NaiveDate>,
         period: SchedulePeriodType,
         every_x_periods: u8,
     },
@@ -369,8 +762,112 @@ pub struct UpdateTransactionRequest {
     #[serde(with = "rust_decimal::serde::float")]
     pub amount: Decimal,
     pub payee_id: Uuid,
-    pub budget_id: Uuid,
+    /// Leave empty to mark an inflow as income
+    pub budget_id: Option<Uuid>,
+    pub date: NaiveDate,
+}
+
+/// A transaction read from a bank's export file, before it's been imported
+#[derive(Serialize, Deserialize, ToSchema, Constructor, Debug, PartialEq, Clone)]
+pub struct ImportedTransaction {
+    /// The bank's own id for the transaction, e.g. an OFX `FITID`.
+    /// Used to skip transactions that have already been imported
+    pub external_id: Option<String>,
     pub date: NaiveDate,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub payee_name: String,
+    /// The existing payee with the same name, if there is one. A new payee is created otherwise
+    pub payee_id: Option<Uuid>,
+    /// Whatever else the file says about the transaction, e.g. an OFX `MEMO`
+    pub memo: Option<String>,
+}
+
+/// What importing a file would do, without changing anything yet
+#[derive(Serialize, Deserialize, ToSchema, Constructor, Debug, PartialEq)]
+pub struct ImportPreview {
+    pub transactions: Vec<ImportedTransaction>,
+    /// Transactions in the file that are already in the bank account. Transactions with an
+    /// external id are matched on it, others on their date, amount and payee
+    pub already_imported: Vec<ImportedTransaction>,
+}
+
+/// Where to find each field in a bank account's CSV exports. Columns count from 0
+#[derive(Serialize, Deserialize, ToSchema, Debug, PartialEq, Clone)]
+pub struct CsvMapping {
+    #[schema(value_type = String)]
+    pub delimiter: char,
+    /// `.` or `,`. The other one is taken to group thousands and ignored
+    #[schema(value_type = String)]
+    pub decimal_separator: char,
+    pub has_header_row: bool,
+    pub date_column: u32,
+    /// A chrono format string, e.g. `%d/%m/%Y`
+    pub date_format: String,
+    /// Set when a single column holds signed amounts. Leave empty to use the debit and credit columns
+    pub amount_column: Option<u32>,
+    /// Money leaving the account, written as a positive number
+    pub debit_column: Option<u32>,
+    /// Money coming into the account
+    pub credit_column: Option<u32>,
+    pub payee_column: u32,
+}
+
+impl CsvMapping {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        delimiter: char,
+        decimal_separator: char,
+        has_header_row: bool,
+        date_column: u32,
+        date_format: String,
+        amount_column: Option<u32>,
+        debit_column: Option<u32>,
+        credit_column: Option<u32>,
+        payee_column: u32,
+    ) -> Self {
+        Self {
+            delimiter,
+            decimal_separator,
+            has_header_row,
+            date_column,
+            date_format,
+            amount_column,
+            debit_column,
+            credit_column,
+            payee_column,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Constructor)]
+pub struct ImportTransactionsRequest {
+    pub transactions: Vec<ImportTransactionRequest>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Constructor, Clone)]
+pub struct ImportTransactionRequest {
+    pub external_id: Option<String>,
+    pub date: NaiveDate,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub payee_name: String,
+    /// Leave empty to mark an inflow as income
+    pub budget_id: Option<Uuid>,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Constructor)]
+pub struct YnabImportRequest {
+    /// The register CSV from YNAB's export
+    pub register: String,
+    /// The plan CSV from YNAB's export, leave empty to import only the transactions
+    pub plan: Option<String>,
+    /// The format of the register's dates, such as `%d/%m/%Y`. Leave empty to detect it,
+    /// which fails when the dates could be read with more than one format
+    pub date_format: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Constructor)]
@@ -383,6 +880,13 @@ pub struct UpdatePayeeRequest {
     pub name: String,
 }
 
+#[derive(ToSchema, Serialize, Deserialize, Constructor)]
+pub struct ArchiveBudgetRequest {
+    /// Where to move the budget's remaining balance, required unless the balance is zero
+    pub move_to_budget_id: Option<Uuid>,
+    pub date: NaiveDate,
+}
+
 #[derive(ToSchema, Serialize, Deserialize)]
 pub struct TransferBudgetRequest {
     pub date: NaiveDate,
@@ -391,6 +895,101 @@ pub struct TransferBudgetRequest {
     pub amount: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct SuggestedAssignment {
+    pub budget_id: Uuid,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema)]
+pub struct SuggestedAssignments {
+    /// The date of the paycheck the assignments are suggested for
+    pub pay_date: NaiveDate,
+    pub assignments: Vec<SuggestedAssignment>,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Constructor, ToSchema)]
+pub struct ApplySuggestedAssignmentsRequest {
+    pub plan_id: Uuid,
+    pub date: NaiveDate,
+    pub assignments: Vec<SuggestedAssignment>,
+}
+
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema)]
+pub struct GetReadyToAssignResponse {
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Constructor, PartialEq, Debug, ToSchema, Clone)]
+pub struct ScheduledTransaction {
+    pub id: Uuid,
+    pub payee_id: Uuid,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub bank_account_id: Uuid,
+    pub budget_id: Uuid,
+    pub schedule: Schedule,
+    /// The date the next real transaction will be created for
+    pub next_occurrence: NaiveDate,
+}
+
+impl ScheduledTransaction {
+    /// Creates a transaction for every occurrence on or before `until` that has not been posted yet,
+    /// moving `next_occurrence` past them so they can't be posted twice
+    pub fn take_due_transactions(&mut self, until: NaiveDate) -> Vec<Transaction> {
+        let mut transactions = Vec::new();
+
+        while self.next_occurrence <= until {
+            transactions.push(Transaction {
+                id: Uuid::new_v4(),
+                payee_id: self.payee_id,
+                date: self.next_occurrence,
+                amount: self.amount,
+                bank_account_id: self.bank_account_id,
+                budget_id: Some(self.budget_id),
+                memo: None,
+            });
+
+            let Some(next_occurrence) = self.schedule.next_after(self.next_occurrence) else {
+                // the schedule has run out of dates, so it can never come due again
+                self.next_occurrence = NaiveDate::MAX;
+                break;
+            };
+            self.next_occurrence = next_occurrence;
+        }
+
+        transactions
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Constructor)]
+pub struct CreateScheduledTransactionRequest {
+    pub payee_id: Uuid,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub budget_id: Uuid,
+    pub schedule: CreateScheduleRequest,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Constructor)]
+pub struct UpdateScheduledTransactionRequest {
+    pub payee_id: Uuid,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub budget_id: Uuid,
+    pub schedule: UpdateScheduleRequest,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -401,7 +1000,7 @@ mod tests {
         use std::sync::LazyLock;
 
         static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
-        static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+        static PLAN_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
         #[test]
         #[allow(non_snake_case)]
@@ -409,22 +1008,33 @@ mod tests {
             let budget = Budget {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
                 assignments: vec![],
+                group_id: None,
+                archived_at: None,
             };
 
             let expected = GetBudgetResponse {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
                 assignments: vec![],
                 total_assigned: Decimal::ZERO,
+                target_status: Some(BudgetTargetStatus {
+                    funded: Decimal::ZERO,
+                    needed: Decimal::ZERO,
+                    percent_funded: dec!(100),
+                    on_track: true,
+                    next_due_date: None,
+                }),
+                group_id: None,
+                archived_at: None,
             };
 
             let mapped: GetBudgetResponse = budget.into();
@@ -440,7 +1050,7 @@ mod tests {
             let budget = Budget {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
@@ -453,12 +1063,14 @@ mod tests {
                         link_id,
                     },
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             let expected = GetBudgetResponse {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
@@ -472,6 +1084,15 @@ mod tests {
                     },
                 }],
                 total_assigned: dec!(10),
+                target_status: Some(BudgetTargetStatus {
+                    funded: dec!(10),
+                    needed: Decimal::ZERO,
+                    percent_funded: dec!(100),
+                    on_track: true,
+                    next_due_date: None,
+                }),
+                group_id: None,
+                archived_at: None,
             };
 
             let mapped: GetBudgetResponse = budget.into();
@@ -488,7 +1109,7 @@ mod tests {
             let budget = Budget {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
@@ -512,12 +1133,14 @@ mod tests {
                         },
                     },
                 ],
+                group_id: None,
+                archived_at: None,
             };
 
             let expected = GetBudgetResponse {
                 id: *BUDGET_ID,
                 name: "name".into(),
-                user_id: *USER_ID,
+                plan_id: *PLAN_ID,
                 target: Some(BudgetTarget::OneTime {
                     target_amount: Decimal::ZERO,
                 }),
@@ -542,6 +1165,15 @@ mod tests {
                     },
                 ],
                 total_assigned: dec!(-40),
+                target_status: Some(BudgetTargetStatus {
+                    funded: dec!(-40),
+                    needed: dec!(40),
+                    percent_funded: Decimal::ZERO,
+                    on_track: false,
+                    next_due_date: None,
+                }),
+                group_id: None,
+                archived_at: None,
             };
 
             let mapped: GetBudgetResponse = budget.into();
@@ -549,4 +1181,151 @@ mod tests {
             assert_eq!(mapped, expected);
         }
     }
+
+    mod sync_transaction_assignments_tests {
+        use super::*;
+        use rust_decimal_macros::dec;
+
+        fn date(day: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(2024, 12, day).unwrap()
+        }
+
+        #[test]
+        pub fn sync_transaction_assignments_test() {
+            let mut budget = Budget::new(
+                Uuid::new_v4(),
+                "name".into(),
+                None,
+                Uuid::new_v4(),
+                vec![],
+                None,
+                None,
+            );
+            let transaction = |amount, day, budget_id| {
+                Transaction::new(
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    date(day),
+                    amount,
+                    Uuid::new_v4(),
+                    budget_id,
+                    None,
+                )
+            };
+
+            let unchanged = transaction(dec!(-10), 1, Some(budget.id));
+            let mut changed = transaction(dec!(-20), 2, Some(budget.id));
+            let mut moved = transaction(dec!(-30), 3, Some(budget.id));
+            let deleted = transaction(dec!(-40), 4, Some(budget.id));
+            let missing = transaction(dec!(-50), 5, Some(budget.id));
+            let other_budget = transaction(dec!(-60), 6, Some(Uuid::new_v4()));
+
+            for transaction in [&unchanged, &changed, &moved, &deleted] {
+                budget.assign_from_transaction(transaction);
+            }
+            budget.move_from_ready_to_assign(dec!(100), date(1));
+
+            changed.amount = dec!(-25);
+            changed.date = date(7);
+            moved.budget_id = None;
+
+            let transactions = [unchanged, changed, moved, missing, other_budget];
+
+            assert!(budget.sync_transaction_assignments(&transactions));
+            assert_eq!(budget.total_assigned(), dec!(15));
+            assert_eq!(
+                budget
+                    .assignments
+                    .iter()
+                    .map(|assignment| (assignment.amount, assignment.date))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (dec!(-10), date(1)),
+                    (dec!(-25), date(7)),
+                    (dec!(100), date(1)),
+                    (dec!(-50), date(5)),
+                ]
+            );
+
+            assert!(!budget.sync_transaction_assignments(&transactions));
+        }
+
+        #[test]
+        pub fn sync_transaction_assignment_test() {
+            let mut budget = Budget::new(
+                Uuid::new_v4(),
+                "name".into(),
+                None,
+                Uuid::new_v4(),
+                vec![],
+                None,
+                None,
+            );
+            let mut transaction = Transaction::new(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                date(1),
+                dec!(-10),
+                Uuid::new_v4(),
+                Some(budget.id),
+                None,
+            );
+            budget.move_from_ready_to_assign(dec!(100), date(1));
+
+            assert!(budget.sync_transaction_assignment(&transaction));
+            assert_eq!(budget.total_assigned(), dec!(90));
+
+            transaction.amount = dec!(-20);
+            transaction.date = date(2);
+            assert!(budget.sync_transaction_assignment(&transaction));
+            assert!(!budget.sync_transaction_assignment(&transaction));
+            assert_eq!(budget.assignments.len(), 2);
+            assert_eq!(
+                (budget.assignments[1].amount, budget.assignments[1].date),
+                (dec!(-20), date(2))
+            );
+
+            transaction.budget_id = Some(Uuid::new_v4());
+            assert!(budget.sync_transaction_assignment(&transaction));
+            assert_eq!(budget.total_assigned(), dec!(100));
+        }
+    }
+
+    mod scheduled_transaction_tests {
+        use super::*;
+        use rust_decimal_macros::dec;
+
+        fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(year, month, day).unwrap()
+        }
+
+        #[test]
+        pub fn take_due_transactions_advances_next_occurrence() {
+            let mut scheduled_transaction = ScheduledTransaction {
+                id: Uuid::new_v4(),
+                payee_id: Uuid::new_v4(),
+                amount: dec!(-10),
+                bank_account_id: Uuid::new_v4(),
+                budget_id: Uuid::new_v4(),
+                schedule: Schedule {
+                    id: Uuid::new_v4(),
+                    period: SchedulePeriod::Fortnightly {
+                        starting_on: date(2024, 12, 1),
+                    },
+                },
+                next_occurrence: date(2024, 12, 1),
+            };
+
+            let transactions = scheduled_transaction.take_due_transactions(date(2024, 12, 20));
+
+            assert_eq!(
+                transactions.iter().map(|t| t.date).collect::<Vec<_>>(),
+                vec![date(2024, 12, 1), date(2024, 12, 15)]
+            );
+            assert_eq!(scheduled_transaction.next_occurrence, date(2024, 12, 29));
+            assert!(scheduled_transaction
+                .take_due_transactions(date(2024, 12, 20))
+                .is_empty());
+        }
+    }
 }
@@ -0,0 +1,110 @@
+use super::{BudgetGroup, BudgetGroupSummary, GetBudgetResponse};
+
+impl BudgetGroupSummary {
+    /// Rolls up the budgets in `budgets` that belong to `group`
+    pub fn from_budgets(group: BudgetGroup, budgets: &[GetBudgetResponse]) -> Self {
+        let budgets: Vec<_> = budgets
+            .iter()
+            .filter(|b| b.group_id == Some(group.id))
+            .collect();
+        let target_statuses: Vec<_> = budgets
+            .iter()
+            .filter_map(|b| b.target_status.as_ref())
+            .collect();
+
+        BudgetGroupSummary {
+            id: group.id,
+            name: group.name,
+            sort_order: group.sort_order,
+            total_assigned: budgets.iter().map(|b| b.total_assigned).sum(),
+            target_needed: target_statuses.iter().map(|s| s.needed).sum(),
+            targets_on_track: target_statuses.iter().all(|s| s.on_track),
+            next_due_date: target_statuses.iter().filter_map(|s| s.next_due_date).min(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use crate::models::BudgetTargetStatus;
+
+    use super::*;
+
+    fn budget(
+        group_id: Option<Uuid>,
+        total_assigned: Decimal,
+        target_status: Option<BudgetTargetStatus>,
+    ) -> GetBudgetResponse {
+        GetBudgetResponse {
+            id: Uuid::new_v4(),
+            name: "name".into(),
+            target: None,
+            plan_id: Uuid::nil(),
+            assignments: vec![],
+            total_assigned,
+            target_status,
+            group_id,
+            archived_at: None,
+        }
+    }
+
+    fn status(needed: Decimal, on_track: bool, next_due_date: NaiveDate) -> BudgetTargetStatus {
+        BudgetTargetStatus {
+            funded: Decimal::ZERO,
+            needed,
+            percent_funded: Decimal::ZERO,
+            on_track,
+            next_due_date: Some(next_due_date),
+        }
+    }
+
+    #[test]
+    fn rolls_up_budgets_in_group() {
+        let group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 3, Uuid::nil());
+        let early = NaiveDate::from_ymd_opt(2024, 12, 5).unwrap();
+        let late = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+
+        let budgets = [
+            budget(Some(group.id), dec!(10), Some(status(dec!(5), true, late))),
+            budget(
+                Some(group.id),
+                dec!(20),
+                Some(status(dec!(7), false, early)),
+            ),
+            budget(Some(group.id), dec!(3), None),
+            budget(None, dec!(100), Some(status(dec!(50), true, early))),
+        ];
+
+        let summary = BudgetGroupSummary::from_budgets(group.clone(), &budgets);
+
+        assert_eq!(
+            summary,
+            BudgetGroupSummary {
+                id: group.id,
+                name: group.name,
+                sort_order: 3,
+                total_assigned: dec!(33),
+                target_needed: dec!(12),
+                targets_on_track: false,
+                next_due_date: Some(early),
+            }
+        );
+    }
+
+    #[test]
+    fn empty_group() {
+        let group = BudgetGroup::new(Uuid::new_v4(), "Empty".into(), 0, Uuid::nil());
+
+        let summary = BudgetGroupSummary::from_budgets(group, &[]);
+
+        assert_eq!(summary.total_assigned, Decimal::ZERO);
+        assert_eq!(summary.target_needed, Decimal::ZERO);
+        assert!(summary.targets_on_track);
+        assert_eq!(summary.next_due_date, None);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+
+use super::{Budget, BudgetAssignmentSource, BudgetMonthSummary, GetMonthResponse};
+
+/// Parses a month in the form `yyyy-mm` into the first day of that month
+pub fn parse_month(month: &str) -> Result<NaiveDate, anyhow::Error> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|e| anyhow!("month {month} must be in the form yyyy-mm: {e}"))
+}
+
+fn first_day_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month to have a first day")
+}
+
+impl Budget {
+    /// Summarises the month containing `month`.
+    ///
+    /// Assignments moved from other budgets or Ready to Assign count as assigned and assignments from transactions
+    /// count as activity. Only a positive balance carries over into the next month,
+    /// so an overspent month doesn't eat into the months after it
+    pub fn month_summary(&self, month: NaiveDate) -> BudgetMonthSummary {
+        let month = first_day_of_month(month);
+
+        // (assigned, activity) for every month up to and including `month`
+        let mut totals: BTreeMap<NaiveDate, (Decimal, Decimal)> = BTreeMap::new();
+        for assignment in &self.assignments {
+            let assignment_month = first_day_of_month(assignment.date);
+            if assignment_month > month {
+                continue;
+            }
+
+            let (assigned, activity) = totals.entry(assignment_month).or_default();
+            match assignment.source {
+                BudgetAssignmentSource::OtherBudget { .. }
+                | BudgetAssignmentSource::ReadyToAssign => {
+                    *assigned += assignment.amount;
+                }
+                BudgetAssignmentSource::Transaction { .. } => *activity += assignment.amount,
+            }
+        }
+
+        let (assigned, activity) = totals.remove(&month).unwrap_or_default();
+        let carried_over =
+            totals
+                .values()
+                .fold(Decimal::ZERO, |carried_over, (assigned, activity)| {
+                    (carried_over + assigned + activity).max(Decimal::ZERO)
+                });
+        let available = carried_over + assigned + activity;
+
+        BudgetMonthSummary {
+            budget_id: self.id,
+            month,
+            carried_over,
+            assigned,
+            activity,
+            available,
+            overspent: (-available).max(Decimal::ZERO),
+        }
+    }
+}
+
+impl GetMonthResponse {
+    pub fn from_budgets(budgets: &[Budget], month: NaiveDate) -> Self {
+        let budgets: Vec<_> = budgets.iter().map(|b| b.month_summary(month)).collect();
+
+        GetMonthResponse {
+            month: first_day_of_month(month),
+            assigned: budgets.iter().map(|b| b.assigned).sum(),
+            activity: budgets.iter().map(|b| b.activity).sum(),
+            available: budgets.iter().map(|b| b.available).sum(),
+            overspent: budgets.iter().map(|b| b.overspent).sum(),
+            budgets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use crate::models::BudgetAssignment;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn assigned(amount: Decimal, date: NaiveDate) -> BudgetAssignment {
+        BudgetAssignment {
+            id: Uuid::new_v4(),
+            amount,
+            date,
+            source: BudgetAssignmentSource::OtherBudget {
+                from_budget_id: Uuid::new_v4(),
+                link_id: Uuid::new_v4(),
+            },
+        }
+    }
+
+    fn activity(amount: Decimal, date: NaiveDate) -> BudgetAssignment {
+        BudgetAssignment {
+            id: Uuid::new_v4(),
+            amount,
+            date,
+            source: BudgetAssignmentSource::Transaction {
+                from_transaction_id: Uuid::new_v4(),
+            },
+        }
+    }
+
+    fn budget(assignments: Vec<BudgetAssignment>) -> Budget {
+        Budget {
+            id: Uuid::new_v4(),
+            name: "name".into(),
+            target: None,
+            plan_id: Uuid::new_v4(),
+            assignments,
+            group_id: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    pub fn parse_month_test() {
+        assert_eq!(parse_month("2024-11").unwrap(), date(2024, 11, 1));
+        assert!(parse_month("2024-13").is_err());
+        assert!(parse_month("2024-11-05").is_err());
+        assert!(parse_month("november").is_err());
+    }
+
+    #[test]
+    pub fn empty_budget() {
+        let budget = budget(vec![]);
+
+        assert_eq!(
+            budget.month_summary(date(2024, 11, 15)),
+            BudgetMonthSummary {
+                budget_id: budget.id,
+                month: date(2024, 11, 1),
+                carried_over: Decimal::ZERO,
+                assigned: Decimal::ZERO,
+                activity: Decimal::ZERO,
+                available: Decimal::ZERO,
+                overspent: Decimal::ZERO,
+            }
+        );
+    }
+
+    #[test]
+    pub fn carries_over_from_previous_months() {
+        let budget = budget(vec![
+            assigned(dec!(100), date(2024, 9, 1)),
+            activity(dec!(-30), date(2024, 9, 20)),
+            assigned(dec!(50), date(2024, 11, 1)),
+            activity(dec!(-20), date(2024, 11, 30)),
+            // later months shouldn't be included
+            assigned(dec!(1000), date(2024, 12, 1)),
+        ]);
+
+        assert_eq!(
+            budget.month_summary(date(2024, 11, 1)),
+            BudgetMonthSummary {
+                budget_id: budget.id,
+                month: date(2024, 11, 1),
+                carried_over: dec!(70),
+                assigned: dec!(50),
+                activity: dec!(-20),
+                available: dec!(100),
+                overspent: Decimal::ZERO,
+            }
+        );
+    }
+
+    #[test]
+    pub fn overspending_does_not_carry_over() {
+        let budget = budget(vec![
+            assigned(dec!(10), date(2024, 9, 1)),
+            activity(dec!(-25), date(2024, 9, 2)),
+            assigned(dec!(5), date(2024, 10, 1)),
+        ]);
+
+        let september = budget.month_summary(date(2024, 9, 1));
+        assert_eq!(september.available, dec!(-15));
+        assert_eq!(september.overspent, dec!(15));
+
+        let october = budget.month_summary(date(2024, 10, 1));
+        assert_eq!(october.carried_over, Decimal::ZERO);
+        assert_eq!(october.available, dec!(5));
+        assert_eq!(october.overspent, Decimal::ZERO);
+
+        let november = budget.month_summary(date(2024, 11, 1));
+        assert_eq!(november.carried_over, dec!(5));
+        assert_eq!(november.available, dec!(5));
+    }
+
+    #[test]
+    pub fn month_response_totals() {
+        let budgets = [
+            budget(vec![assigned(dec!(10), date(2024, 11, 1))]),
+            budget(vec![activity(dec!(-4), date(2024, 11, 2))]),
+        ];
+
+        let response = GetMonthResponse::from_budgets(&budgets, date(2024, 11, 20));
+
+        assert_eq!(response.month, date(2024, 11, 1));
+        assert_eq!(response.assigned, dec!(10));
+        assert_eq!(response.activity, dec!(-4));
+        assert_eq!(response.available, dec!(6));
+        assert_eq!(response.overspent, dec!(4));
+        assert_eq!(response.budgets.len(), 2);
+    }
+}
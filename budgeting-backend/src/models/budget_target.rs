@@ -0,0 +1,301 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::{
+    Budget, BudgetAssignmentSource, BudgetTarget, BudgetTargetStatus, RepeatingTargetType,
+};
+
+impl Budget {
+    /// How far along the budget is towards its target as of `today`, or `None` if it has no target.
+    ///
+    /// `OneTime` and `BuildUpTo` targets are funded by the budget's balance, so spending from the
+    /// budget sets them back. `RequireRepeating` targets are funded by what was assigned since the
+    /// previous due date, regardless of what has been spent or carried over
+    pub fn target_status(&self, today: NaiveDate) -> Option<BudgetTargetStatus> {
+        let status = match self.target.as_ref()? {
+            BudgetTarget::OneTime { target_amount } => {
+                let funded = self.total_assigned();
+                let needed = (target_amount - funded).max(Decimal::ZERO);
+
+                BudgetTargetStatus {
+                    funded,
+                    needed,
+                    percent_funded: percent_funded(funded, *target_amount),
+                    // there is no due date to fall behind on, so just check if it's funded
+                    on_track: needed.is_zero(),
+                    next_due_date: None,
+                }
+            }
+            BudgetTarget::Repeating {
+                target_amount,
+                repeating_type,
+                schedule,
+            } => {
+                // without a next due date there's nothing to fall behind on, like a one time
+                // target, and every assignment counts towards it
+                let next_due_date = schedule.first_on_or_after(today);
+                let previous_due_date = next_due_date
+                    .and_then(|date| date.pred_opt())
+                    .and_then(|date| schedule.last_on_or_before(date));
+
+                let funded = match repeating_type {
+                    RepeatingTargetType::BuildUpTo => self.total_assigned(),
+                    RepeatingTargetType::RequireRepeating => self.assigned_since(previous_due_date),
+                };
+
+                BudgetTargetStatus {
+                    funded,
+                    needed: (target_amount - funded).max(Decimal::ZERO),
+                    percent_funded: percent_funded(funded, *target_amount),
+                    on_track: next_due_date.map_or(funded >= *target_amount, |next_due_date| {
+                        funded
+                            >= expected_funding(
+                                *target_amount,
+                                previous_due_date,
+                                next_due_date,
+                                today,
+                            )
+                    }),
+                    next_due_date,
+                }
+            }
+        };
+
+        Some(status)
+    }
+
+    /// The money moved into this budget after `date`, not counting transactions
+    fn assigned_since(&self, date: Option<NaiveDate>) -> Decimal {
+        self.assignments
+            .iter()
+            .filter(|a| !matches!(a.source, BudgetAssignmentSource::Transaction { .. }))
+            .filter(|a| date.is_none_or(|date| a.date > date))
+            .map(|a| a.amount)
+            .sum()
+    }
+}
+
+fn percent_funded(funded: Decimal, target_amount: Decimal) -> Decimal {
+    if target_amount <= Decimal::ZERO {
+        return if funded >= target_amount {
+            Decimal::ONE_HUNDRED
+        } else {
+            Decimal::ZERO
+        };
+    }
+
+    (funded / target_amount * Decimal::ONE_HUNDRED)
+        .clamp(Decimal::ZERO, Decimal::ONE_HUNDRED)
+        .round_dp(2)
+}
+
+/// How much should have been funded by `today` to fully fund the target by `next_due_date`,
+/// assuming money is set aside evenly over the period
+fn expected_funding(
+    target_amount: Decimal,
+    previous_due_date: Option<NaiveDate>,
+    next_due_date: NaiveDate,
+    today: NaiveDate,
+) -> Decimal {
+    if today >= next_due_date {
+        return target_amount;
+    }
+
+    // without a previous due date there's no way to know how long the period is,
+    // so it's only expected to be funded once it's due
+    let Some(previous_due_date) = previous_due_date else {
+        return Decimal::ZERO;
+    };
+
+    let elapsed = Decimal::from((today - previous_due_date).num_days());
+    let length = Decimal::from((next_due_date - previous_due_date).num_days());
+
+    target_amount * elapsed / length
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use crate::models::{BudgetAssignment, Schedule, SchedulePeriod};
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn assigned(amount: Decimal, date: NaiveDate) -> BudgetAssignment {
+        BudgetAssignment {
+            id: Uuid::new_v4(),
+            amount,
+            date,
+            source: BudgetAssignmentSource::ReadyToAssign,
+        }
+    }
+
+    fn spent(amount: Decimal, date: NaiveDate) -> BudgetAssignment {
+        BudgetAssignment {
+            id: Uuid::new_v4(),
+            amount,
+            date,
+            source: BudgetAssignmentSource::Transaction {
+                from_transaction_id: Uuid::new_v4(),
+            },
+        }
+    }
+
+    fn budget(target: Option<BudgetTarget>, assignments: Vec<BudgetAssignment>) -> Budget {
+        Budget {
+            id: Uuid::new_v4(),
+            name: "name".into(),
+            target,
+            plan_id: Uuid::new_v4(),
+            assignments,
+            group_id: None,
+            archived_at: None,
+        }
+    }
+
+    fn monthly(target_amount: Decimal, repeating_type: RepeatingTargetType) -> BudgetTarget {
+        BudgetTarget::Repeating {
+            target_amount,
+            repeating_type,
+            schedule: Schedule {
+                id: Uuid::new_v4(),
+                period: SchedulePeriod::Monthly {
+                    starting_on: date(2024, 1, 31),
+                },
+            },
+        }
+    }
+
+    #[test]
+    pub fn no_target() {
+        let budget = budget(None, vec![assigned(dec!(10), date(2024, 12, 1))]);
+
+        assert_eq!(budget.target_status(date(2024, 12, 1)), None);
+    }
+
+    #[test]
+    pub fn one_time() {
+        let budget = budget(
+            Some(BudgetTarget::OneTime {
+                target_amount: dec!(200),
+            }),
+            vec![
+                assigned(dec!(100), date(2024, 11, 1)),
+                spent(dec!(-50), date(2024, 11, 2)),
+            ],
+        );
+
+        assert_eq!(
+            budget.target_status(date(2024, 12, 1)),
+            Some(BudgetTargetStatus {
+                funded: dec!(50),
+                needed: dec!(150),
+                percent_funded: dec!(25),
+                on_track: false,
+                next_due_date: None,
+            })
+        );
+    }
+
+    #[test]
+    pub fn build_up_to_counts_balance_carried_over() {
+        let budget = budget(
+            Some(monthly(dec!(100), RepeatingTargetType::BuildUpTo)),
+            vec![
+                assigned(dec!(80), date(2024, 10, 1)),
+                spent(dec!(-20), date(2024, 10, 5)),
+                assigned(dec!(10), date(2024, 12, 1)),
+            ],
+        );
+
+        assert_eq!(
+            budget.target_status(date(2024, 12, 15)),
+            Some(BudgetTargetStatus {
+                funded: dec!(70),
+                needed: dec!(30),
+                percent_funded: dec!(70),
+                // half way through the period, 50 is expected
+                on_track: true,
+                next_due_date: Some(date(2024, 12, 31)),
+            })
+        );
+    }
+
+    #[test]
+    pub fn require_repeating_only_counts_this_period() {
+        let budget = budget(
+            Some(monthly(dec!(100), RepeatingTargetType::RequireRepeating)),
+            vec![
+                assigned(dec!(500), date(2024, 10, 1)),
+                assigned(dec!(30), date(2024, 11, 30)),
+                // spending doesn't take away from what has been assigned
+                spent(dec!(-20), date(2024, 12, 5)),
+                assigned(dec!(10), date(2024, 12, 1)),
+            ],
+        );
+
+        assert_eq!(
+            budget.target_status(date(2024, 12, 15)),
+            Some(BudgetTargetStatus {
+                funded: dec!(10),
+                needed: dec!(90),
+                percent_funded: dec!(10),
+                on_track: false,
+                next_due_date: Some(date(2024, 12, 31)),
+            })
+        );
+    }
+
+    #[test]
+    pub fn due_today_must_be_fully_funded() {
+        let budget = budget(
+            Some(monthly(dec!(100), RepeatingTargetType::RequireRepeating)),
+            vec![assigned(dec!(99), date(2024, 12, 1))],
+        );
+
+        let status = budget.target_status(date(2024, 12, 31)).unwrap();
+
+        assert_eq!(status.next_due_date, Some(date(2024, 12, 31)));
+        assert!(!status.on_track);
+    }
+
+    #[test]
+    pub fn before_first_due_date() {
+        let budget = budget(
+            Some(monthly(dec!(100), RepeatingTargetType::RequireRepeating)),
+            vec![assigned(dec!(40), date(2023, 12, 1))],
+        );
+
+        assert_eq!(
+            budget.target_status(date(2024, 1, 15)),
+            Some(BudgetTargetStatus {
+                funded: dec!(40),
+                needed: dec!(60),
+                percent_funded: dec!(40),
+                on_track: true,
+                next_due_date: Some(date(2024, 1, 31)),
+            })
+        );
+    }
+
+    #[test]
+    pub fn overfunded_is_capped_at_one_hundred_percent() {
+        let budget = budget(
+            Some(BudgetTarget::OneTime {
+                target_amount: dec!(10),
+            }),
+            vec![assigned(dec!(25), date(2024, 12, 1))],
+        );
+
+        let status = budget.target_status(date(2024, 12, 1)).unwrap();
+
+        assert_eq!(status.needed, Decimal::ZERO);
+        assert_eq!(status.percent_funded, dec!(100));
+        assert!(status.on_track);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    BankAccount, Budget, BudgetAssignment, BudgetAssignmentSource, BudgetGroup, BudgetTarget,
+    Payee, Plan, Schedule, ScheduledTransaction, Transaction, User,
+};
+
+/// The version of [`UserExport`] written by this server. Bump it whenever the format changes
+/// in a way older servers couldn't restore
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Everything belonging to a user, for backing up or moving to another server
+#[derive(Serialize, Deserialize, ToSchema, PartialEq, Debug, Clone)]
+pub struct UserExport {
+    /// Exports from a newer version than [`EXPORT_VERSION`] can't be restored
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub user: User,
+    /// The plans the user owns
+    pub plans: Vec<PlanExport>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, PartialEq, Debug, Clone)]
+pub struct PlanExport {
+    pub plan: Plan,
+    pub bank_accounts: Vec<BankAccount>,
+    pub payees: Vec<Payee>,
+    pub budget_groups: Vec<BudgetGroup>,
+    /// Archived budgets are included
+    pub budgets: Vec<Budget>,
+    pub transactions: Vec<Transaction>,
+    pub scheduled_transactions: Vec<ScheduledTransaction>,
+}
+
+/// New ids for everything in an export, keyed by the exported ids
+#[derive(Default)]
+struct IdMap(HashMap<Uuid, Uuid>);
+
+impl IdMap {
+    fn insert(&mut self, id: Uuid) -> Uuid {
+        *self.0.entry(id).or_insert_with(Uuid::new_v4)
+    }
+
+    fn get(&self, id: Uuid) -> Result<Uuid, anyhow::Error> {
+        self.0
+            .get(&id)
+            .copied()
+            .ok_or_else(|| anyhow!("Export refers to {id}, which isn't in the export"))
+    }
+}
+
+impl UserExport {
+    /// Gives every plan and everything in it a new id, so the export can be restored
+    /// next to the data it was exported from. Fails if anything refers to an id that isn't
+    /// in the export
+    pub fn with_new_ids(self) -> Result<Self, anyhow::Error> {
+        let mut ids = IdMap::default();
+
+        let plans = self
+            .plans
+            .into_iter()
+            .map(|plan| plan.with_new_ids(&mut ids))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            user: User {
+                pay_frequency: self.user.pay_frequency.map(with_new_id),
+                ..self.user
+            },
+            plans,
+            ..self
+        })
+    }
+}
+
+impl PlanExport {
+    fn with_new_ids(self, ids: &mut IdMap) -> Result<Self, anyhow::Error> {
+        let plan_id = ids.insert(self.plan.id);
+
+        // every id is given its new id up front, since budgets refer to each other
+        for id in self
+            .bank_accounts
+            .iter()
+            .map(|x| x.id)
+            .chain(self.payees.iter().map(|x| x.id))
+            .chain(self.budget_groups.iter().map(|x| x.id))
+            .chain(self.budgets.iter().map(|x| x.id))
+            .chain(self.transactions.iter().map(|x| x.id))
+        {
+            ids.insert(id);
+        }
+
+        let bank_accounts = self
+            .bank_accounts
+            .into_iter()
+            .map(|bank_account| BankAccount {
+                id: ids.insert(bank_account.id),
+                plan_id,
+                ..bank_account
+            })
+            .collect();
+
+        let payees = self
+            .payees
+            .into_iter()
+            .map(|payee| Payee {
+                id: ids.insert(payee.id),
+                plan_id,
+                ..payee
+            })
+            .collect();
+
+        let budget_groups = self
+            .budget_groups
+            .into_iter()
+            .map(|group| BudgetGroup {
+                id: ids.insert(group.id),
+                plan_id,
+                ..group
+            })
+            .collect();
+
+        let budgets = self
+            .budgets
+            .into_iter()
+            .map(|budget| budget_with_new_ids(budget, plan_id, ids))
+            .collect::<Result<_, _>>()?;
+
+        let transactions = self
+            .transactions
+            .into_iter()
+            .map(|transaction| {
+                Ok(Transaction {
+                    id: ids.get(transaction.id)?,
+                    payee_id: ids.get(transaction.payee_id)?,
+                    bank_account_id: ids.get(transaction.bank_account_id)?,
+                    budget_id: transaction.budget_id.map(|id| ids.get(id)).transpose()?,
+                    ..transaction
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
+        let scheduled_transactions = self
+            .scheduled_transactions
+            .into_iter()
+            .map(|scheduled_transaction| {
+                Ok(ScheduledTransaction {
+                    id: Uuid::new_v4(),
+                    payee_id: ids.get(scheduled_transaction.payee_id)?,
+                    bank_account_id: ids.get(scheduled_transaction.bank_account_id)?,
+                    budget_id: ids.get(scheduled_transaction.budget_id)?,
+                    schedule: with_new_id(scheduled_transaction.schedule),
+                    ..scheduled_transaction
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
+        Ok(Self {
+            plan: Plan {
+                id: plan_id,
+                ..self.plan
+            },
+            bank_accounts,
+            payees,
+            budget_groups,
+            budgets,
+            transactions,
+            scheduled_transactions,
+        })
+    }
+}
+
+/// A schedule belongs to a single budget, user or scheduled transaction
+fn with_new_id(schedule: Schedule) -> Schedule {
+    Schedule {
+        id: Uuid::new_v4(),
+        period: schedule.period,
+    }
+}
+
+fn budget_with_new_ids(
+    budget: Budget,
+    plan_id: Uuid,
+    ids: &mut IdMap,
+) -> Result<Budget, anyhow::Error> {
+    Ok(Budget {
+        id: ids.get(budget.id)?,
+        target: budget.target.map(|target| match target {
+            BudgetTarget::Repeating {
+                target_amount,
+                repeating_type,
+                schedule,
+            } => BudgetTarget::Repeating {
+                target_amount,
+                repeating_type,
+                schedule: with_new_id(schedule),
+            },
+            target @ BudgetTarget::OneTime { .. } => target,
+        }),
+        plan_id,
+        assignments: budget
+            .assignments
+            .into_iter()
+            .map(|assignment| {
+                Ok(BudgetAssignment {
+                    id: Uuid::new_v4(),
+                    source: source_with_new_ids(&assignment.source, ids)?,
+                    ..assignment
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?,
+        group_id: budget.group_id.map(|id| ids.get(id)).transpose()?,
+        ..budget
+    })
+}
+
+fn source_with_new_ids(
+    source: &BudgetAssignmentSource,
+    ids: &mut IdMap,
+) -> Result<BudgetAssignmentSource, anyhow::Error> {
+    Ok(match *source {
+        BudgetAssignmentSource::OtherBudget {
+            from_budget_id,
+            link_id,
+        } => BudgetAssignmentSource::OtherBudget {
+            from_budget_id: ids.get(from_budget_id)?,
+            // both sides of a move share the link id, so they must get the same new one
+            link_id: ids.insert(link_id),
+        },
+        BudgetAssignmentSource::Transaction {
+            from_transaction_id,
+        } => BudgetAssignmentSource::Transaction {
+            from_transaction_id: ids.get(from_transaction_id)?,
+        },
+        BudgetAssignmentSource::ReadyToAssign => BudgetAssignmentSource::ReadyToAssign,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 12, 5).unwrap()
+    }
+
+    fn export() -> UserExport {
+        let plan = Plan::new(Uuid::new_v4(), "Personal".into());
+        let bank_account = BankAccount::new(
+            Uuid::new_v4(),
+            "Checking".into(),
+            dec!(100),
+            plan.id,
+            dec!(57.90),
+        );
+        let payee = Payee::new(Uuid::new_v4(), "Corner Shop".into(), plan.id);
+        let group = BudgetGroup::new(Uuid::new_v4(), "Everyday".into(), 0, plan.id);
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            payee.id,
+            date(),
+            dec!(-42.10),
+            bank_account.id,
+            None,
+            None,
+        );
+        let mut groceries = Budget::new(
+            Uuid::new_v4(),
+            "Groceries".into(),
+            None,
+            plan.id,
+            vec![],
+            Some(group.id),
+            None,
+        );
+        let mut fun = Budget::new(
+            Uuid::new_v4(),
+            "Fun".into(),
+            None,
+            plan.id,
+            vec![],
+            None,
+            None,
+        );
+        let transaction = Transaction {
+            budget_id: Some(groceries.id),
+            ..transaction
+        };
+        groceries.assign_from_transaction(&transaction);
+        Budget::move_between_budgets(&mut fun, &mut groceries, dec!(10), date());
+
+        UserExport {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            user: User::new(plan.id, "name".into(), "email@email.com".into(), None),
+            plans: vec![PlanExport {
+                plan,
+                bank_accounts: vec![bank_account],
+                payees: vec![payee],
+                budget_groups: vec![group],
+                budgets: vec![groceries, fun],
+                transactions: vec![transaction],
+                scheduled_transactions: vec![],
+            }],
+        }
+    }
+
+    #[test]
+    fn with_new_ids_test() {
+        let original = export();
+
+        let export = original.clone().with_new_ids().unwrap();
+
+        let [plan] = export.plans.as_slice() else {
+            panic!("expected a single plan");
+        };
+        let original_plan = &original.plans[0];
+        assert_ne!(plan.plan.id, original_plan.plan.id);
+        assert_eq!(plan.plan.name, original_plan.plan.name);
+
+        let bank_account = &plan.bank_accounts[0];
+        let payee = &plan.payees[0];
+        let group = &plan.budget_groups[0];
+        let [groceries, fun] = plan.budgets.as_slice() else {
+            panic!("expected two budgets");
+        };
+        let transaction = &plan.transactions[0];
+
+        assert_ne!(bank_account.id, original_plan.bank_accounts[0].id);
+        assert_eq!(bank_account.plan_id, plan.plan.id);
+        assert_eq!(payee.plan_id, plan.plan.id);
+        assert_eq!(group.plan_id, plan.plan.id);
+        assert_eq!(groceries.group_id, Some(group.id));
+
+        assert_eq!(transaction.payee_id, payee.id);
+        assert_eq!(transaction.bank_account_id, bank_account.id);
+        assert_eq!(transaction.budget_id, Some(groceries.id));
+        assert_eq!(transaction.amount, dec!(-42.10));
+
+        assert_eq!(
+            groceries.assignments[0].source,
+            BudgetAssignmentSource::Transaction {
+                from_transaction_id: transaction.id
+            }
+        );
+
+        let (
+            BudgetAssignmentSource::OtherBudget {
+                from_budget_id: from_fun,
+                link_id,
+            },
+            BudgetAssignmentSource::OtherBudget {
+                from_budget_id: from_groceries,
+                link_id: other_link_id,
+            },
+        ) = (&groceries.assignments[1].source, &fun.assignments[0].source)
+        else {
+            panic!("expected moves between budgets");
+        };
+        assert_eq!(*from_fun, fun.id);
+        assert_eq!(*from_groceries, groceries.id);
+        assert_eq!(link_id, other_link_id);
+        assert_eq!(groceries.total_assigned(), dec!(-32.10));
+    }
+
+    #[test]
+    fn with_new_ids_missing_reference() {
+        let mut export = export();
+        export.plans[0].transactions[0].payee_id = Uuid::new_v4();
+
+        assert!(export.with_new_ids().is_err());
+    }
+}
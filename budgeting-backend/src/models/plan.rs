@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::{Budget, BudgetGroup, BudgetTarget, Plan, Schedule};
+
+/// A new plan along with the budgets and groups copied into it
+pub struct PlanCopy {
+    pub plan: Plan,
+    pub groups: Vec<BudgetGroup>,
+    pub budgets: Vec<Budget>,
+}
+
+impl Plan {
+    /// Copies `groups` and `budgets` (along with their targets) into a new plan named `name`.
+    /// Nothing that involves money is copied, so every budget in the new plan starts out empty
+    pub fn copy(&self, name: String, groups: &[BudgetGroup], budgets: &[Budget]) -> PlanCopy {
+        let plan = Plan {
+            id: Uuid::new_v4(),
+            name,
+        };
+
+        let mut group_ids = HashMap::new();
+        let groups = groups
+            .iter()
+            .map(|group| {
+                let id = Uuid::new_v4();
+                group_ids.insert(group.id, id);
+
+                BudgetGroup {
+                    id,
+                    plan_id: plan.id,
+                    ..group.clone()
+                }
+            })
+            .collect();
+
+        let budgets = budgets
+            .iter()
+            .map(|budget| Budget {
+                id: Uuid::new_v4(),
+                name: budget.name.clone(),
+                target: budget.target.clone().map(|target| match target {
+                    // a schedule belongs to a single budget
+                    BudgetTarget::Repeating {
+                        target_amount,
+                        repeating_type,
+                        schedule,
+                    } => BudgetTarget::Repeating {
+                        target_amount,
+                        repeating_type,
+                        schedule: Schedule {
+                            id: Uuid::new_v4(),
+                            period: schedule.period,
+                        },
+                    },
+                    target @ BudgetTarget::OneTime { .. } => target,
+                }),
+                plan_id: plan.id,
+                assignments: vec![],
+                group_id: budget.group_id.and_then(|id| group_ids.get(&id).copied()),
+                archived_at: None,
+            })
+            .collect();
+
+        PlanCopy {
+            plan,
+            groups,
+            budgets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    use crate::models::{
+        BudgetAssignment, BudgetAssignmentSource, RepeatingTargetType, SchedulePeriod,
+    };
+
+    use super::*;
+
+    #[test]
+    fn copy_plan() {
+        let plan = Plan::new(Uuid::new_v4(), "Personal".into());
+        let group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 2, plan.id);
+        let period = SchedulePeriod::Weekly {
+            starting_on: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
+        };
+        let budget = Budget {
+            id: Uuid::new_v4(),
+            name: "Rent".into(),
+            target: Some(BudgetTarget::Repeating {
+                target_amount: dec!(400),
+                repeating_type: RepeatingTargetType::RequireRepeating,
+                schedule: Schedule {
+                    id: Uuid::new_v4(),
+                    period: period.clone(),
+                },
+            }),
+            plan_id: plan.id,
+            assignments: vec![BudgetAssignment {
+                id: Uuid::new_v4(),
+                amount: dec!(400),
+                date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
+                source: BudgetAssignmentSource::ReadyToAssign,
+            }],
+            group_id: Some(group.id),
+            archived_at: None,
+        };
+
+        let copy = plan.copy(
+            "Household".into(),
+            std::slice::from_ref(&group),
+            std::slice::from_ref(&budget),
+        );
+
+        assert_ne!(copy.plan.id, plan.id);
+        assert_eq!(copy.plan.name, "Household");
+
+        let [copied_group] = copy.groups.as_slice() else {
+            panic!("expected a single group");
+        };
+        assert_ne!(copied_group.id, group.id);
+        assert_eq!(copied_group.plan_id, copy.plan.id);
+        assert_eq!(copied_group.name, group.name);
+        assert_eq!(copied_group.sort_order, group.sort_order);
+
+        let [copied_budget] = copy.budgets.as_slice() else {
+            panic!("expected a single budget");
+        };
+        assert_ne!(copied_budget.id, budget.id);
+        assert_eq!(copied_budget.plan_id, copy.plan.id);
+        assert_eq!(copied_budget.group_id, Some(copied_group.id));
+        assert!(copied_budget.assignments.is_empty());
+
+        let Some(BudgetTarget::Repeating {
+            target_amount,
+            schedule,
+            ..
+        }) = &copied_budget.target
+        else {
+            panic!("expected a repeating target");
+        };
+        assert_eq!(*target_amount, dec!(400));
+        assert_eq!(schedule.period, period);
+        let Some(BudgetTarget::Repeating {
+            schedule: original_schedule,
+            ..
+        }) = &budget.target
+        else {
+            unreachable!()
+        };
+        assert_ne!(schedule.id, original_schedule.id);
+    }
+}
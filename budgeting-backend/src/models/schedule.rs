@@ -0,0 +1,411 @@
+use anyhow::anyhow;
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use super::{Schedule, SchedulePeriod, SchedulePeriodType};
+
+impl SchedulePeriod {
+    /// `None` only for a custom schedule saved before custom schedules had a start date
+    pub fn starting_on(&self) -> Option<NaiveDate> {
+        match self {
+            SchedulePeriod::Weekly { starting_on }
+            | SchedulePeriod::Fortnightly { starting_on }
+            | SchedulePeriod::Monthly { starting_on }
+            | SchedulePeriod::Yearly { starting_on } => Some(*starting_on),
+            SchedulePeriod::Custom { starting_on, .. } => *starting_on,
+        }
+    }
+
+    /// Checks a period sent by a client, which unlike old saved ones has to have a start date
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if let SchedulePeriod::Custom {
+            starting_on,
+            every_x_periods,
+            ..
+        } = self
+        {
+            if starting_on.is_none() {
+                return Err(anyhow!("starting_on must be set"));
+            }
+
+            if *every_x_periods == 0 {
+                return Err(anyhow!("every_x_periods must be at least 1"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The type of period and how many of them there are between each occurrence
+    fn step(&self) -> (SchedulePeriodType, u32) {
+        match self {
+            SchedulePeriod::Weekly { .. } => (SchedulePeriodType::Weekly, 1),
+            SchedulePeriod::Fortnightly { .. } => (SchedulePeriodType::Fortnightly, 1),
+            SchedulePeriod::Monthly { .. } => (SchedulePeriodType::Monthly, 1),
+            SchedulePeriod::Yearly { .. } => (SchedulePeriodType::Yearly, 1),
+            // every_x_periods of 0 would never move forward, so treat it the same as 1
+            SchedulePeriod::Custom {
+                period,
+                every_x_periods,
+                ..
+            } => (*period, u32::from(*every_x_periods).max(1)),
+        }
+    }
+}
+
+impl Schedule {
+    /// The `n`th date this schedule falls on, where the 0th is `starting_on`.
+    /// `None` once the dates go past the last one [`NaiveDate`] can hold,
+    /// or if the schedule has no start date.
+    ///
+    /// Every occurrence is calculated from `starting_on` rather than the previous occurrence,
+    /// so a monthly schedule starting on the 31st lands on the last day of shorter months
+    /// without drifting to the 28th for the rest of the year
+    pub fn nth_occurrence(&self, n: u32) -> Option<NaiveDate> {
+        let starting_on = self.period.starting_on()?;
+        let (period_type, every_x_periods) = self.period.step();
+        let count = n.checked_mul(every_x_periods)?;
+
+        match period_type {
+            SchedulePeriodType::Weekly => {
+                starting_on.checked_add_days(Days::new(7 * u64::from(count)))
+            }
+            SchedulePeriodType::Fortnightly => {
+                starting_on.checked_add_days(Days::new(14 * u64::from(count)))
+            }
+            SchedulePeriodType::Monthly => starting_on.checked_add_months(Months::new(count)),
+            SchedulePeriodType::Yearly => {
+                starting_on.checked_add_months(Months::new(count.checked_mul(12)?))
+            }
+        }
+    }
+
+    /// The first date on or after `date` that this schedule falls on
+    pub fn first_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        self.nth_occurrence(self.first_index_on_or_after(date)?)
+    }
+
+    /// The first date strictly after `date` that this schedule falls on
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        self.first_on_or_after(date.succ_opt()?)
+    }
+
+    /// The last date on or before `date` that this schedule fell on,
+    /// or `None` if `date` is before the schedule starts or too close to the last date there is
+    pub fn last_on_or_before(&self, date: NaiveDate) -> Option<NaiveDate> {
+        if date < self.period.starting_on()? {
+            return None;
+        }
+
+        let index = self.first_index_on_or_after(date)?;
+        let occurrence = self.nth_occurrence(index)?;
+
+        if occurrence == date {
+            Some(occurrence)
+        } else {
+            // date is after starting_on, so the first occurrence on or after it can't be the 0th
+            self.nth_occurrence(index - 1)
+        }
+    }
+
+    /// Every date this schedule falls on between `from` and `to`, inclusive
+    pub fn occurrences_between(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.first_index_on_or_after(from)
+            .into_iter()
+            .flat_map(|first| first..=u32::MAX)
+            .map_while(|n| self.nth_occurrence(n))
+            .take_while(move |occurrence| *occurrence <= to)
+    }
+
+    /// The index of the first occurrence on or after `date`,
+    /// `None` if there's no such occurrence before the dates run out
+    fn first_index_on_or_after(&self, date: NaiveDate) -> Option<u32> {
+        let starting_on = self.period.starting_on()?;
+        if date <= starting_on {
+            return Some(0);
+        }
+
+        let (period_type, every_x_periods) = self.period.step();
+
+        // start with an estimate that is never past `date`, then walk forward
+        #[allow(clippy::cast_sign_loss)]
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_possible_wrap)]
+        let elapsed_periods = match period_type {
+            SchedulePeriodType::Weekly => ((date - starting_on).num_days() / 7) as u32,
+            SchedulePeriodType::Fortnightly => ((date - starting_on).num_days() / 14) as u32,
+            SchedulePeriodType::Monthly => {
+                ((date.year() - starting_on.year()) * 12 + date.month() as i32
+                    - starting_on.month() as i32) as u32
+            }
+            SchedulePeriodType::Yearly => (date.year() - starting_on.year()) as u32,
+        };
+
+        let mut index = (elapsed_periods / every_x_periods).saturating_sub(1);
+        while self.nth_occurrence(index)? < date {
+            index = index.checked_add(1)?;
+        }
+
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn schedule(period: SchedulePeriod) -> Schedule {
+        Schedule {
+            id: Uuid::new_v4(),
+            period,
+        }
+    }
+
+    #[test]
+    pub fn weekly_occurrences() {
+        let schedule = schedule(SchedulePeriod::Weekly {
+            starting_on: date(2024, 12, 2),
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 12, 1), date(2024, 12, 31))
+                .collect::<Vec<_>>(),
+            vec![
+                date(2024, 12, 2),
+                date(2024, 12, 9),
+                date(2024, 12, 16),
+                date(2024, 12, 23),
+                date(2024, 12, 30),
+            ]
+        );
+        assert_eq!(
+            schedule.next_after(date(2024, 12, 9)),
+            Some(date(2024, 12, 16))
+        );
+        assert_eq!(
+            schedule.next_after(date(2024, 12, 10)),
+            Some(date(2024, 12, 16))
+        );
+    }
+
+    #[test]
+    pub fn fortnightly_occurrences() {
+        let schedule = schedule(SchedulePeriod::Fortnightly {
+            starting_on: date(2024, 12, 2),
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2025, 1, 1), date(2025, 1, 31))
+                .collect::<Vec<_>>(),
+            vec![date(2025, 1, 13), date(2025, 1, 27)]
+        );
+    }
+
+    #[test]
+    pub fn monthly_clamps_to_month_end() {
+        let schedule = schedule(SchedulePeriod::Monthly {
+            starting_on: date(2024, 1, 31),
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 1, 1), date(2024, 5, 31))
+                .collect::<Vec<_>>(),
+            vec![
+                date(2024, 1, 31),
+                date(2024, 2, 29),
+                date(2024, 3, 31),
+                date(2024, 4, 30),
+                date(2024, 5, 31),
+            ]
+        );
+        assert_eq!(
+            schedule.first_on_or_after(date(2025, 2, 1)),
+            Some(date(2025, 2, 28))
+        );
+        assert_eq!(
+            schedule.next_after(date(2024, 2, 29)),
+            Some(date(2024, 3, 31))
+        );
+    }
+
+    #[test]
+    pub fn yearly_on_leap_day() {
+        let schedule = schedule(SchedulePeriod::Yearly {
+            starting_on: date(2024, 2, 29),
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 1, 1), date(2028, 12, 31))
+                .collect::<Vec<_>>(),
+            vec![
+                date(2024, 2, 29),
+                date(2025, 2, 28),
+                date(2026, 2, 28),
+                date(2027, 2, 28),
+                date(2028, 2, 29),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn custom_every_x_periods() {
+        let every_three_months = schedule(SchedulePeriod::Custom {
+            starting_on: Some(date(2024, 11, 30)),
+            period: SchedulePeriodType::Monthly,
+            every_x_periods: 3,
+        });
+
+        assert_eq!(
+            every_three_months
+                .occurrences_between(date(2024, 12, 1), date(2025, 12, 31))
+                .collect::<Vec<_>>(),
+            vec![
+                date(2025, 2, 28),
+                date(2025, 5, 30),
+                date(2025, 8, 30),
+                date(2025, 11, 30),
+            ]
+        );
+
+        let every_three_weeks = schedule(SchedulePeriod::Custom {
+            starting_on: Some(date(2024, 12, 2)),
+            period: SchedulePeriodType::Weekly,
+            every_x_periods: 3,
+        });
+
+        assert_eq!(
+            every_three_weeks.next_after(date(2024, 12, 2)),
+            Some(date(2024, 12, 23))
+        );
+
+        let every_two_years = schedule(SchedulePeriod::Custom {
+            starting_on: Some(date(2024, 2, 29)),
+            period: SchedulePeriodType::Yearly,
+            every_x_periods: 2,
+        });
+
+        assert_eq!(
+            every_two_years.first_on_or_after(date(2024, 3, 1)),
+            Some(date(2026, 2, 28))
+        );
+    }
+
+    #[test]
+    pub fn custom_zero_periods_behaves_like_one() {
+        let schedule = schedule(SchedulePeriod::Custom {
+            starting_on: Some(date(2024, 12, 2)),
+            period: SchedulePeriodType::Fortnightly,
+            every_x_periods: 0,
+        });
+
+        assert_eq!(
+            schedule.next_after(date(2024, 12, 2)),
+            Some(date(2024, 12, 16))
+        );
+    }
+
+    #[test]
+    pub fn before_starting_on() {
+        let schedule = schedule(SchedulePeriod::Monthly {
+            starting_on: date(2024, 12, 15),
+        });
+
+        assert_eq!(
+            schedule.first_on_or_after(date(2024, 1, 1)),
+            Some(date(2024, 12, 15))
+        );
+        assert_eq!(schedule.last_on_or_before(date(2024, 12, 14)), None);
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 1, 1), date(2024, 12, 14))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    pub fn last_on_or_before() {
+        let schedule = schedule(SchedulePeriod::Monthly {
+            starting_on: date(2024, 1, 31),
+        });
+
+        assert_eq!(
+            schedule.last_on_or_before(date(2024, 1, 31)),
+            Some(date(2024, 1, 31))
+        );
+        assert_eq!(
+            schedule.last_on_or_before(date(2024, 3, 30)),
+            Some(date(2024, 2, 29))
+        );
+        assert_eq!(
+            schedule.last_on_or_before(date(2024, 3, 31)),
+            Some(date(2024, 3, 31))
+        );
+    }
+
+    #[test]
+    pub fn ends_at_the_last_date() {
+        let schedule = schedule(SchedulePeriod::Custom {
+            starting_on: Some(NaiveDate::MAX - Days::new(40)),
+            period: SchedulePeriodType::Monthly,
+            every_x_periods: u8::MAX,
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(NaiveDate::MIN, NaiveDate::MAX)
+                .collect::<Vec<_>>(),
+            vec![NaiveDate::MAX - Days::new(40)]
+        );
+        assert_eq!(schedule.next_after(NaiveDate::MAX - Days::new(40)), None);
+        assert_eq!(schedule.next_after(NaiveDate::MAX), None);
+        assert_eq!(schedule.nth_occurrence(u32::MAX), None);
+        assert_eq!(schedule.last_on_or_before(NaiveDate::MAX), None);
+    }
+
+    #[test]
+    pub fn custom_without_starting_on_never_occurs() {
+        let schedule = schedule(SchedulePeriod::Custom {
+            starting_on: None,
+            period: SchedulePeriodType::Weekly,
+            every_x_periods: 1,
+        });
+
+        assert_eq!(schedule.first_on_or_after(date(2024, 12, 1)), None);
+        assert_eq!(schedule.last_on_or_before(date(2024, 12, 1)), None);
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 12, 1), date(2024, 12, 31))
+                .count(),
+            0
+        );
+        assert!(schedule.period.validate().is_err());
+    }
+
+    #[test]
+    pub fn empty_when_to_is_before_from() {
+        let schedule = schedule(SchedulePeriod::Weekly {
+            starting_on: date(2024, 12, 2),
+        });
+
+        assert_eq!(
+            schedule
+                .occurrences_between(date(2024, 12, 31), date(2024, 12, 1))
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use super::{Budget, Schedule, SuggestedAssignment, SuggestedAssignments};
+
+impl SuggestedAssignments {
+    /// Suggests how much to assign to each budget from the next paycheck on or after `today`
+    /// so that every target is met by its due date.
+    ///
+    /// What a target still needs is spread evenly over the paychecks left before it's due,
+    /// so a monthly target gets roughly half from each fortnightly paycheck. Targets without a
+    /// due date are suggested in full from the next paycheck.
+    /// `None` if there are no more paychecks, like for a custom pay frequency without a start date
+    pub fn for_next_pay(
+        pay_frequency: &Schedule,
+        budgets: &[Budget],
+        today: NaiveDate,
+    ) -> Option<Self> {
+        let pay_date = pay_frequency.first_on_or_after(today)?;
+
+        let assignments: Vec<_> = budgets
+            .iter()
+            .filter_map(|budget| {
+                let status = budget.target_status(pay_date)?;
+                if status.needed <= Decimal::ZERO {
+                    return None;
+                }
+
+                let paychecks = status.next_due_date.map_or(1, |due_date| {
+                    pay_frequency
+                        .occurrences_between(pay_date, due_date)
+                        .count()
+                        .max(1)
+                });
+
+                let amount = (status.needed / Decimal::from(paychecks))
+                    .round_dp_with_strategy(2, RoundingStrategy::AwayFromZero)
+                    .min(status.needed);
+
+                Some(SuggestedAssignment {
+                    budget_id: budget.id,
+                    amount,
+                })
+            })
+            .collect();
+
+        Some(SuggestedAssignments {
+            pay_date,
+            total: assignments.iter().map(|a| a.amount).sum(),
+            assignments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use crate::models::{
+        BudgetAssignment, BudgetAssignmentSource, BudgetTarget, RepeatingTargetType, SchedulePeriod,
+    };
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn schedule(period: SchedulePeriod) -> Schedule {
+        Schedule {
+            id: Uuid::new_v4(),
+            period,
+        }
+    }
+
+    fn budget(target: Option<BudgetTarget>, assignments: Vec<BudgetAssignment>) -> Budget {
+        Budget {
+            id: Uuid::new_v4(),
+            name: "name".into(),
+            target,
+            plan_id: Uuid::new_v4(),
+            assignments,
+            group_id: None,
+            archived_at: None,
+        }
+    }
+
+    fn fortnightly_pay() -> Schedule {
+        schedule(SchedulePeriod::Fortnightly {
+            starting_on: date(2024, 12, 6),
+        })
+    }
+
+    #[test]
+    pub fn spreads_repeating_targets_across_paychecks() {
+        let rent = budget(
+            Some(BudgetTarget::Repeating {
+                target_amount: dec!(1000),
+                repeating_type: RepeatingTargetType::RequireRepeating,
+                schedule: schedule(SchedulePeriod::Monthly {
+                    starting_on: date(2024, 12, 31),
+                }),
+            }),
+            vec![],
+        );
+
+        let suggested = SuggestedAssignments::for_next_pay(
+            &fortnightly_pay(),
+            std::slice::from_ref(&rent),
+            date(2024, 12, 1),
+        )
+        .unwrap();
+
+        // paid on the 6th and the 20th before rent is due on the 31st
+        assert_eq!(
+            suggested,
+            SuggestedAssignments {
+                pay_date: date(2024, 12, 6),
+                assignments: vec![SuggestedAssignment {
+                    budget_id: rent.id,
+                    amount: dec!(500),
+                }],
+                total: dec!(500),
+            }
+        );
+    }
+
+    #[test]
+    pub fn takes_what_is_already_funded_into_account() {
+        let holiday = budget(
+            Some(BudgetTarget::Repeating {
+                target_amount: dec!(100),
+                repeating_type: RepeatingTargetType::BuildUpTo,
+                schedule: schedule(SchedulePeriod::Monthly {
+                    starting_on: date(2024, 12, 31),
+                }),
+            }),
+            vec![BudgetAssignment {
+                id: Uuid::new_v4(),
+                amount: dec!(10),
+                date: date(2024, 11, 1),
+                source: BudgetAssignmentSource::ReadyToAssign,
+            }],
+        );
+
+        let suggested =
+            SuggestedAssignments::for_next_pay(&fortnightly_pay(), &[holiday], date(2024, 12, 6))
+                .unwrap();
+
+        assert_eq!(suggested.assignments[0].amount, dec!(45));
+    }
+
+    #[test]
+    pub fn rounds_up_to_the_cent() {
+        let weekly_pay = schedule(SchedulePeriod::Weekly {
+            starting_on: date(2024, 12, 2),
+        });
+        let budget = budget(
+            Some(BudgetTarget::Repeating {
+                target_amount: dec!(100),
+                repeating_type: RepeatingTargetType::RequireRepeating,
+                schedule: schedule(SchedulePeriod::Monthly {
+                    starting_on: date(2024, 12, 16),
+                }),
+            }),
+            vec![],
+        );
+
+        // paid on the 2nd, 9th and 16th
+        let suggested =
+            SuggestedAssignments::for_next_pay(&weekly_pay, &[budget], date(2024, 12, 2)).unwrap();
+
+        assert_eq!(suggested.assignments[0].amount, dec!(33.34));
+    }
+
+    #[test]
+    pub fn one_time_targets_are_suggested_in_full() {
+        let one_time = budget(
+            Some(BudgetTarget::OneTime {
+                target_amount: dec!(250),
+            }),
+            vec![],
+        );
+
+        let suggested =
+            SuggestedAssignments::for_next_pay(&fortnightly_pay(), &[one_time], date(2024, 12, 1))
+                .unwrap();
+
+        assert_eq!(suggested.assignments[0].amount, dec!(250));
+    }
+
+    #[test]
+    pub fn skips_budgets_without_anything_needed() {
+        let no_target = budget(None, vec![]);
+        let funded = budget(
+            Some(BudgetTarget::OneTime {
+                target_amount: dec!(10),
+            }),
+            vec![BudgetAssignment {
+                id: Uuid::new_v4(),
+                amount: dec!(10),
+                date: date(2024, 11, 1),
+                source: BudgetAssignmentSource::ReadyToAssign,
+            }],
+        );
+
+        let suggested = SuggestedAssignments::for_next_pay(
+            &fortnightly_pay(),
+            &[no_target, funded],
+            date(2024, 12, 1),
+        )
+        .unwrap();
+
+        assert!(suggested.assignments.is_empty());
+        assert_eq!(suggested.total, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::{
+    CreateBudgetTargetRequest, CreateScheduleRequest, RepeatingTargetType, SchedulePeriod,
+    Transaction,
+};
+
+/// Suggests a repeating target from a payee's past payments, or `None` if they don't recur
+/// weekly, fortnightly, monthly or yearly.
+///
+/// The cadence comes from the median gap between payments so the odd late or skipped
+/// payment doesn't throw it off, as long as at least half of the gaps agree. The target is
+/// the most recent payment's amount and is due on the same day as that payment
+pub fn suggest_target(transactions: &[Transaction]) -> Option<CreateBudgetTargetRequest> {
+    let mut payments: Vec<_> = transactions
+        .iter()
+        .filter(|t| t.amount < Decimal::ZERO)
+        .collect();
+    payments.sort_by_key(|t| t.date);
+
+    let gaps: Vec<_> = payments
+        .windows(2)
+        .map(|pair| (pair[1].date - pair[0].date).num_days())
+        .collect();
+
+    let mut sorted_gaps = gaps.clone();
+    sorted_gaps.sort_unstable();
+    let median_gap = *sorted_gaps.get(sorted_gaps.len() / 2)?;
+
+    let cadence = Cadence::from_gap(median_gap)?;
+    let matching_gaps = gaps
+        .iter()
+        .filter(|gap| Cadence::from_gap(**gap) == Some(cadence))
+        .count();
+    if matching_gaps * 2 < gaps.len() {
+        return None;
+    }
+
+    let last_payment = payments.last()?;
+
+    Some(CreateBudgetTargetRequest::Repeating {
+        target_amount: -last_payment.amount,
+        repeating_type: RepeatingTargetType::RequireRepeating,
+        schedule: CreateScheduleRequest {
+            period: cadence.period_starting_on(last_payment.date),
+        },
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Cadence {
+    Weekly,
+    Fortnightly,
+    Monthly,
+    Yearly,
+}
+
+impl Cadence {
+    /// Allows for payments a few days early or late, and for months being different lengths
+    fn from_gap(days: i64) -> Option<Self> {
+        match days {
+            5..=9 => Some(Self::Weekly),
+            12..=16 => Some(Self::Fortnightly),
+            26..=35 => Some(Self::Monthly),
+            350..=380 => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    fn period_starting_on(self, starting_on: NaiveDate) -> SchedulePeriod {
+        match self {
+            Self::Weekly => SchedulePeriod::Weekly { starting_on },
+            Self::Fortnightly => SchedulePeriod::Fortnightly { starting_on },
+            Self::Monthly => SchedulePeriod::Monthly { starting_on },
+            Self::Yearly => SchedulePeriod::Yearly { starting_on },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn payments(payments: &[(NaiveDate, Decimal)]) -> Vec<Transaction> {
+        payments
+            .iter()
+            .map(|(date, amount)| Transaction {
+                id: Uuid::new_v4(),
+                payee_id: Uuid::nil(),
+                date: *date,
+                amount: *amount,
+                bank_account_id: Uuid::nil(),
+                budget_id: Some(Uuid::nil()),
+                memo: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn monthly_payments() {
+        let transactions = payments(&[
+            (date(2024, 11, 1), dec!(-55)),
+            (date(2024, 9, 2), dec!(-50)),
+            (date(2024, 10, 1), dec!(-50)),
+        ]);
+
+        assert_eq!(
+            suggest_target(&transactions),
+            Some(CreateBudgetTargetRequest::Repeating {
+                target_amount: dec!(55),
+                repeating_type: RepeatingTargetType::RequireRepeating,
+                schedule: CreateScheduleRequest {
+                    period: SchedulePeriod::Monthly {
+                        starting_on: date(2024, 11, 1),
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    pub fn weekly_fortnightly_and_yearly_payments() {
+        let weekly = payments(&[
+            (date(2024, 11, 1), dec!(-10)),
+            (date(2024, 11, 8), dec!(-10)),
+            (date(2024, 11, 16), dec!(-10)),
+        ]);
+        let fortnightly = payments(&[
+            (date(2024, 11, 1), dec!(-10)),
+            (date(2024, 11, 15), dec!(-10)),
+        ]);
+        let yearly = payments(&[
+            (date(2022, 3, 1), dec!(-10)),
+            (date(2023, 3, 1), dec!(-10)),
+            (date(2024, 2, 28), dec!(-10)),
+        ]);
+
+        let period = |transactions: &[Transaction]| match suggest_target(transactions) {
+            Some(CreateBudgetTargetRequest::Repeating { schedule, .. }) => Some(schedule.period),
+            _ => None,
+        };
+
+        assert_eq!(
+            period(&weekly),
+            Some(SchedulePeriod::Weekly {
+                starting_on: date(2024, 11, 16)
+            })
+        );
+        assert_eq!(
+            period(&fortnightly),
+            Some(SchedulePeriod::Fortnightly {
+                starting_on: date(2024, 11, 15)
+            })
+        );
+        assert_eq!(
+            period(&yearly),
+            Some(SchedulePeriod::Yearly {
+                starting_on: date(2024, 2, 28)
+            })
+        );
+    }
+
+    #[test]
+    pub fn tolerates_a_skipped_payment() {
+        let transactions = payments(&[
+            (date(2024, 7, 1), dec!(-50)),
+            (date(2024, 8, 1), dec!(-50)),
+            (date(2024, 10, 1), dec!(-50)),
+            (date(2024, 11, 1), dec!(-50)),
+        ]);
+
+        assert!(suggest_target(&transactions).is_some());
+    }
+
+    #[test]
+    pub fn no_suggestion_for_irregular_payments() {
+        let transactions = payments(&[
+            (date(2024, 1, 1), dec!(-50)),
+            (date(2024, 1, 3), dec!(-50)),
+            (date(2024, 4, 20), dec!(-50)),
+        ]);
+
+        assert_eq!(suggest_target(&transactions), None);
+    }
+
+    #[test]
+    pub fn no_suggestion_for_single_payment() {
+        let transactions = payments(&[(date(2024, 1, 1), dec!(-50))]);
+
+        assert_eq!(suggest_target(&transactions), None);
+    }
+
+    #[test]
+    pub fn ignores_refunds() {
+        let transactions = payments(&[
+            (date(2024, 9, 1), dec!(-50)),
+            (date(2024, 9, 3), dec!(50)),
+            (date(2024, 10, 1), dec!(-50)),
+        ]);
+
+        assert!(suggest_target(&transactions).is_some());
+    }
+}
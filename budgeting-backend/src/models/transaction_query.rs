@@ -0,0 +1,238 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Context};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::Transaction;
+
+/// Narrows down the transactions being listed. Every filter that's set has to match
+#[derive(Deserialize, IntoParams, Debug, Default, Clone, PartialEq)]
+pub struct TransactionFilter {
+    /// The earliest date to include
+    pub from_date: Option<NaiveDate>,
+    /// The latest date to include
+    pub to_date: Option<NaiveDate>,
+    pub payee_id: Option<Uuid>,
+    pub budget_id: Option<Uuid>,
+    /// The smallest amount to include. Outflows are negative
+    #[param(value_type = Option<f32>)]
+    pub min_amount: Option<Decimal>,
+    /// The largest amount to include. Outflows are negative
+    #[param(value_type = Option<f32>)]
+    pub max_amount: Option<Decimal>,
+    pub flow: Option<TransactionFlow>,
+    /// Text the payee's name or the memo has to contain, ignoring case
+    pub search: Option<String>,
+}
+
+impl TransactionFilter {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if let (Some(from_date), Some(to_date)) = (self.from_date, self.to_date) {
+            if from_date > to_date {
+                return Err(anyhow!("from_date must not be after to_date"));
+            }
+        }
+
+        if let (Some(min_amount), Some(max_amount)) = (self.min_amount, self.max_amount) {
+            if min_amount > max_amount {
+                return Err(anyhow!("min_amount must not be more than max_amount"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionFlow {
+    /// Money coming into the account, a positive amount
+    Inflow,
+    /// Money leaving the account, a negative amount
+    Outflow,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionSort {
+    #[default]
+    Date,
+    Amount,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+/// The last transaction of a page, which the next page starts after.
+/// Transactions with the same date or amount are ordered by id
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionCursor {
+    Date { date: NaiveDate, id: Uuid },
+    Amount { amount: Decimal, id: Uuid },
+}
+
+impl TransactionCursor {
+    pub fn new(sort: TransactionSort, transaction: &Transaction) -> Self {
+        match sort {
+            TransactionSort::Date => Self::Date {
+                date: transaction.date,
+                id: transaction.id,
+            },
+            TransactionSort::Amount => Self::Amount {
+                amount: transaction.amount,
+                id: transaction.id,
+            },
+        }
+    }
+
+    /// Reads a cursor written by [`Display`], which only makes sense for the same `sort`
+    pub fn parse(sort: TransactionSort, cursor: &str) -> Result<Self, anyhow::Error> {
+        let (value, id) = cursor.rsplit_once('_').context("Invalid cursor")?;
+        let id = Uuid::try_parse(id).context("Invalid cursor")?;
+
+        Ok(match sort {
+            TransactionSort::Date => Self::Date {
+                date: value
+                    .parse()
+                    .context("Invalid cursor for sorting by date")?,
+                id,
+            },
+            TransactionSort::Amount => Self::Amount {
+                amount: value
+                    .parse()
+                    .context("Invalid cursor for sorting by amount")?,
+                id,
+            },
+        })
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            Self::Date { id, .. } | Self::Amount { id, .. } => *id,
+        }
+    }
+}
+
+impl Display for TransactionCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Date { date, id } => write!(f, "{date}_{}", id.simple()),
+            Self::Amount { amount, id } => write!(f, "{amount}_{}", id.simple()),
+        }
+    }
+}
+
+/// Which page of the filtered transactions to get
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransactionPageRequest {
+    pub sort: TransactionSort,
+    pub order: SortOrder,
+    /// Where the previous page ended, `None` for the first page
+    pub after: Option<TransactionCursor>,
+    pub limit: u32,
+}
+
+/// Adds up every transaction matching the filters, not just those on the page
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone, PartialEq)]
+pub struct TransactionTotals {
+    pub count: i64,
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub inflow: Decimal,
+    /// The sum of the outflows, which is negative
+    #[schema(value_type = f32)]
+    #[serde(with = "rust_decimal::serde::float")]
+    pub outflow: Decimal,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, PartialEq)]
+pub struct TransactionPage {
+    pub transactions: Box<[Transaction]>,
+    /// Pass as `cursor` to get the next page. `None` on the last page
+    pub next_cursor: Option<String>,
+    pub totals: TransactionTotals,
+}
+
+/// A transaction from any of the user's bank accounts, with the names the register would show
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct TransactionSearchResult {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub bank_account_name: String,
+    pub payee_name: String,
+    /// `None` for income, like the transaction's `budget_id`
+    pub budget_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, PartialEq)]
+pub struct TransactionSearchPage {
+    pub transactions: Box<[TransactionSearchResult]>,
+    /// Pass as `cursor` to get the next page. `None` on the last page
+    pub next_cursor: Option<String>,
+    pub totals: TransactionTotals,
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let id = Uuid::new_v4();
+        let date_cursor = TransactionCursor::Date {
+            date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+            id,
+        };
+        let amount_cursor = TransactionCursor::Amount {
+            amount: dec!(-12.50),
+            id,
+        };
+
+        assert_eq!(
+            TransactionCursor::parse(TransactionSort::Date, &date_cursor.to_string()).unwrap(),
+            date_cursor
+        );
+        assert_eq!(
+            TransactionCursor::parse(TransactionSort::Amount, &amount_cursor.to_string()).unwrap(),
+            amount_cursor
+        );
+    }
+
+    #[test]
+    fn cursor_for_other_sort_is_invalid() {
+        let cursor = TransactionCursor::Amount {
+            amount: dec!(3),
+            id: Uuid::new_v4(),
+        };
+
+        assert!(TransactionCursor::parse(TransactionSort::Date, &cursor.to_string()).is_err());
+        assert!(TransactionCursor::parse(TransactionSort::Date, "nonsense").is_err());
+    }
+
+    #[test]
+    fn filter_rejects_empty_ranges() {
+        let filter = TransactionFilter {
+            min_amount: Some(dec!(10)),
+            max_amount: Some(dec!(-10)),
+            ..Default::default()
+        };
+        assert!(filter.validate().is_err());
+
+        let filter = TransactionFilter {
+            from_date: NaiveDate::from_ymd_opt(2024, 12, 2),
+            to_date: NaiveDate::from_ymd_opt(2024, 12, 1),
+            ..Default::default()
+        };
+        assert!(filter.validate().is_err());
+
+        assert!(TransactionFilter::default().validate().is_ok());
+    }
+}
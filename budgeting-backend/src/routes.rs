@@ -1,5 +1,15 @@
 pub mod bank_accounts;
+pub mod budget_groups;
 pub mod budgets;
+pub mod exports;
+pub mod imports;
+pub mod invitations;
 pub mod payees;
+pub mod plans;
+pub mod qif;
+pub mod scheduled_transactions;
+pub mod schedules;
+pub mod sessions;
 pub mod transactions;
 pub mod users;
+pub mod ynab;
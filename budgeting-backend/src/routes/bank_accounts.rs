@@ -5,13 +5,14 @@ use axum::{
 };
 use http::StatusCode;
 use serde::Deserialize;
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use utoipa::{IntoParams, OpenApi};
 use uuid::Uuid;
 
 use crate::{
-    db,
-    models::{BankAccount, CreateBankAccountRequest, UpdateBankAccountRequest},
+    auth::AuthenticatedUser,
+    db::repositories::{BankAccountRepository, OwnershipRepository},
+    models::{BankAccount, CreateBankAccountRequest, PlanRole, UpdateBankAccountRequest},
     AppError,
 };
 
@@ -35,20 +36,28 @@ const API_TAG: &str = "BankAccounts";
     operation_id = "createBankAccount"
 )]
 pub async fn create(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Json(request): Json<CreateBankAccountRequest>,
 ) -> Result<(StatusCode, Json<Uuid>), AppError> {
     if request.name.trim().is_empty() {
         return Err(AppError::BadRequest(anyhow!("Name must not be empty")));
     }
 
-    if request.user_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("User Id must be set")));
+    if request.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Plan Id must be set")));
     }
 
+    ownership
+        .check_plan(user.id, request.plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
     let id = Uuid::new_v4();
 
-    db::bank_accounts::create(&db_pool, id, request)
+    bank_accounts
+        .create(id, request)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Could not create bank account")))?;
 
@@ -57,7 +66,7 @@ pub async fn create(
 
 #[derive(Deserialize, IntoParams)]
 pub struct GetBankAccountsQuery {
-    pub user_id: Uuid,
+    pub plan_id: Uuid,
 }
 
 #[utoipa::path(
@@ -74,10 +83,17 @@ pub struct GetBankAccountsQuery {
 )]
 pub async fn get(
     Query(query): Query<GetBankAccountsQuery>,
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
 ) -> Result<Json<Box<[BankAccount]>>, AppError> {
-    // todo: validate user_id exists
-    db::bank_accounts::get(&db_pool, query.user_id)
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    bank_accounts
+        .get(query.plan_id)
         .await
         .map(Json)
         .map_err(|e| e.to_app_error(anyhow!("Could not get bank accounts")))
@@ -85,17 +101,17 @@ pub async fn get(
 
 #[derive(Deserialize, IntoParams)]
 pub struct GetBankAccountQuery {
-    user_id: Uuid,
+    plan_id: Uuid,
 }
 
 #[derive(Deserialize, IntoParams)]
 pub struct DeleteBankAccountQuery {
-    user_id: Uuid,
+    plan_id: Uuid,
 }
 
 #[derive(Deserialize, IntoParams)]
 pub struct UpdateBankAccountQuery {
-    user_id: Uuid,
+    plan_id: Uuid,
 }
 
 #[utoipa::path(
@@ -113,7 +129,9 @@ pub struct UpdateBankAccountQuery {
 )]
 pub async fn get_single(
     Query(query): Query<GetBankAccountQuery>,
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(account_id): Path<Uuid>,
 ) -> Result<Json<BankAccount>, AppError> {
     if account_id.is_nil() {
@@ -121,11 +139,17 @@ pub async fn get_single(
             "{account_id} is not a valid bank account id"
         )));
     }
-    if query.user_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("user_id must be set")));
+    if query.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("plan_id must be set")));
     }
 
-    db::bank_accounts::get_single(&db_pool, account_id, query.user_id)
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    bank_accounts
+        .get_single(account_id, query.plan_id)
         .await
         .map(Json)
         .map_err(|e| e.to_app_error(anyhow!("Could not get bank_account with id {account_id}")))
@@ -145,15 +169,24 @@ pub async fn get_single(
     operation_id = "deleteBankAccount"
 )]
 pub async fn delete(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(account_id): Path<Uuid>,
-    Query(DeleteBankAccountQuery { user_id }): Query<DeleteBankAccountQuery>,
+    Query(DeleteBankAccountQuery { plan_id }): Query<DeleteBankAccountQuery>,
 ) -> Result<(), AppError> {
-    db::bank_accounts::get_single(&db_pool, account_id, user_id)
+    ownership
+        .check_plan(user.id, plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    bank_accounts
+        .get_single(account_id, plan_id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to find bank account")))?;
 
-    db::bank_accounts::delete(&db_pool, account_id)
+    bank_accounts
+        .delete(account_id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to delete bank account")))?;
 
@@ -174,16 +207,25 @@ pub async fn delete(
     operation_id = "updateBankAccount"
 )]
 pub async fn update(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(account_id): Path<Uuid>,
-    Query(UpdateBankAccountQuery { user_id }): Query<UpdateBankAccountQuery>,
+    Query(UpdateBankAccountQuery { plan_id }): Query<UpdateBankAccountQuery>,
     Json(request): Json<UpdateBankAccountRequest>,
 ) -> Result<(), AppError> {
-    _ = db::bank_accounts::get_single(&db_pool, account_id, user_id)
+    ownership
+        .check_plan(user.id, plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    _ = bank_accounts
+        .get_single(account_id, plan_id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to get bank account")))?;
 
-    db::bank_accounts::update(&db_pool, account_id, &request.name)
+    bank_accounts
+        .update(account_id, &request.name)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update bank account")))?;
 
@@ -0,0 +1,188 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use http::StatusCode;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::repositories::{BudgetGroupRepository, OwnershipRepository},
+    models::{BudgetGroup, CreateBudgetGroupRequest, PlanRole, UpdateBudgetGroupRequest},
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get, create, update, delete),
+    components(schemas(BudgetGroup, CreateBudgetGroupRequest, UpdateBudgetGroupRequest))
+)]
+pub struct Api;
+
+const API_TAG: &str = "Budget Groups";
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetBudgetGroupsQuery {
+    plan_id: Uuid,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/budget-groups",
+    responses(
+        (status = OK, description = "Success", body = Box<[BudgetGroup]>, content_type = "application/json")
+    ),
+    params(
+        GetBudgetGroupsQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "getBudgetGroups"
+)]
+pub async fn get(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<GetBudgetGroupsQuery>,
+) -> Result<Json<Box<[BudgetGroup]>>, AppError> {
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    budget_groups
+        .get(query.plan_id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get budget groups")))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/budget-groups",
+    responses(
+        (status = CREATED, description = "Success", body = Uuid, content_type = "application/json")
+    ),
+    request_body = CreateBudgetGroupRequest,
+    tag = API_TAG,
+    operation_id = "createBudgetGroup"
+)]
+pub async fn create(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<CreateBudgetGroupRequest>,
+) -> Result<(StatusCode, Json<Uuid>), AppError> {
+    if request.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Plan Id must be set")));
+    }
+
+    ownership
+        .check_plan(user.id, request.plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!(
+            "Budget group name cannot be empty"
+        )));
+    }
+
+    let id = Uuid::new_v4();
+
+    budget_groups
+        .create(BudgetGroup::new(
+            id,
+            name.into(),
+            request.sort_order,
+            request.plan_id,
+        ))
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create budget group")))?;
+
+    Ok((StatusCode::CREATED, Json(id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/budget-groups/{groupId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("groupId" = Uuid, Path,),
+    ),
+    request_body = UpdateBudgetGroupRequest,
+    tag = API_TAG,
+    operation_id = "updateBudgetGroup"
+)]
+pub async fn update(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateBudgetGroupRequest>,
+) -> Result<(), AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!(
+            "Budget group name cannot be empty"
+        )));
+    }
+
+    ownership
+        .check_budget_group(user.id, id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not fetch budget group to update")))?;
+
+    let mut budget_group = budget_groups
+        .get_single(id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not fetch budget group to update")))?;
+
+    budget_group.name = name.into();
+    budget_group.sort_order = request.sort_order;
+
+    budget_groups
+        .update(budget_group)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to update budget group")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/budget-groups/{groupId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("groupId" = Uuid, Path,),
+    ),
+    tag = API_TAG,
+    operation_id = "deleteBudgetGroup"
+)]
+pub async fn delete(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<(), AppError> {
+    // also ensures the budget group exists
+    ownership
+        .check_budget_group(user.id, id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget group")))?;
+
+    budget_groups
+        .delete(id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to delete budget group")))?;
+
+    Ok(())
+}
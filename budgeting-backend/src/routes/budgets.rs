@@ -3,26 +3,57 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
+use chrono::Local;
 use http::StatusCode;
 use serde::Deserialize;
-use sqlx::MySqlPool;
+use std::sync::Arc;
+use tokio::join;
 use utoipa::{IntoParams, OpenApi};
 use uuid::Uuid;
 
+use crate::models::{
+    parse_month, suggest_target, BudgetGroupSummary, BudgetMonthSummary, BudgetTargetStatus,
+    GetBudgetResponse, GetBudgetsResponse, GetMonthResponse, TransferBudgetRequest,
+};
+use crate::models::{
+    ApplySuggestedAssignmentsRequest, ArchiveBudgetRequest, SuggestedAssignment,
+    SuggestedAssignments,
+};
 use crate::{
-    db::{self},
+    auth::AuthenticatedUser,
+    db::{
+        self,
+        repositories::{
+            BudgetGroupRepository, BudgetRepository, OwnershipRepository, TransactionRepository,
+        },
+    },
     models::{
-        Budget, BudgetAssignment, BudgetTarget, CreateBudgetRequest, CreateBudgetTargetRequest,
-        CreateScheduleRequest, RepeatingTargetType, Schedule, SchedulePeriod, SchedulePeriodType,
-        UpdateBudgetRequest, UpdateBudgetTargetRequest, UpdateScheduleRequest, BudgetAssignmentSource
+        Budget, BudgetAssignment, BudgetAssignmentSource, BudgetTarget, CreateBudgetRequest,
+        CreateBudgetTargetRequest, CreateScheduleRequest, PlanRole, RepeatingTargetType, Schedule,
+        SchedulePeriod, SchedulePeriodType, UpdateBudgetRequest, UpdateBudgetTargetRequest,
+        UpdateScheduleRequest,
     },
     AppError,
 };
-use crate::models::{GetBudgetResponse, TransferBudgetRequest};
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(get, create, update, delete, transfer_between),
+    paths(
+        get,
+        create,
+        update,
+        delete,
+        archive,
+        unarchive,
+        transfer_between,
+        assign_from_ready_to_assign,
+        return_to_ready_to_assign,
+        get_month,
+        get_month_for_all,
+        get_suggested_assignments,
+        apply_suggested_assignments,
+        get_suggested_target
+    ),
     components(schemas(
         Budget,
         CreateBudgetRequest,
@@ -37,9 +68,18 @@ use crate::models::{GetBudgetResponse, TransferBudgetRequest};
         CreateScheduleRequest,
         UpdateScheduleRequest,
         TransferBudgetRequest,
+        ArchiveBudgetRequest,
         BudgetAssignment,
         BudgetAssignmentSource,
-        GetBudgetResponse
+        GetBudgetResponse,
+        GetBudgetsResponse,
+        BudgetGroupSummary,
+        BudgetTargetStatus,
+        BudgetMonthSummary,
+        GetMonthResponse,
+        SuggestedAssignment,
+        SuggestedAssignments,
+        ApplySuggestedAssignmentsRequest
     ))
 )]
 pub struct Api;
@@ -48,37 +88,97 @@ const API_TAG: &str = "Budgets";
 
 #[derive(Deserialize, IntoParams)]
 pub struct GetBudgetsQuery {
-    user_id: Uuid,
+    plan_id: Uuid,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ListBudgetsQuery {
+    plan_id: Uuid,
+    /// Whether to include archived budgets, defaults to false
+    #[serde(default)]
+    include_archived: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetSuggestedTargetQuery {
+    payee_id: Uuid,
 }
 
 #[utoipa::path(
     get,
     path = "/api/budgets",
     responses(
-        (status = OK, description = "Success", body = Box<[GetBudgetResponse]>, content_type = "application/json")
+        (status = OK, description = "Success", body = GetBudgetsResponse, content_type = "application/json")
     ),
     params(
-        GetBudgetsQuery,
+        ListBudgetsQuery,
     ),
     tag = API_TAG,
     operation_id = "getBudgets"
 )]
 pub async fn get(
-    State(db_pool): State<MySqlPool>,
-    Query(query): Query<GetBudgetsQuery>,
-) -> Result<Json<Box<[GetBudgetResponse]>>, AppError> {
-    if query.user_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("user_id must be set")));
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<ListBudgetsQuery>,
+) -> Result<Json<GetBudgetsResponse>, AppError> {
+    if query.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("plan_id must be set")));
     }
 
-    let budgets: Box<[GetBudgetResponse]> = Vec::from(db::budgets::get(&db_pool, query.user_id)
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
         .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get plan")))?;
+
+    let (budgets_result, groups_result) = join!(
+        budgets.get(query.plan_id, query.include_archived),
+        budget_groups.get(query.plan_id)
+    );
+
+    let budgets: Vec<GetBudgetResponse> = Vec::from(budgets_result
         .map_err(|e| e.to_app_error(anyhow!("Failed to get budgets")))?)
         .into_iter()
         .map(Into::into)
         .collect();
 
-    Ok(Json(budgets))
+    let groups = Vec::from(groups_result
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget groups")))?)
+        .into_iter()
+        .map(|group| BudgetGroupSummary::from_budgets(group, &budgets))
+        .collect();
+
+    Ok(Json(GetBudgetsResponse { budgets, groups }))
+}
+
+/// Makes sure the group a budget is being put in exists and belongs to the budget's plan
+async fn validate_group(
+    budget_groups: &dyn BudgetGroupRepository,
+    group_id: Option<Uuid>,
+    plan_id: Uuid,
+) -> Result<(), AppError> {
+    let Some(group_id) = group_id else {
+        return Ok(());
+    };
+
+    let group = budget_groups
+        .get_single(group_id)
+        .await
+        .map_err(|e| match e {
+            db::Error::NotFound => {
+                AppError::BadRequest(anyhow!("budget group with id {group_id} was not found"))
+            }
+            e => e.to_app_error(anyhow!("Failed to get budget group")),
+        })?;
+
+    if group.plan_id != plan_id {
+        return Err(AppError::BadRequest(anyhow!(
+            "budget group with id {group_id} was not found"
+        )));
+    }
+
+    Ok(())
 }
 
 #[utoipa::path(
@@ -92,11 +192,14 @@ pub async fn get(
     operation_id = "createBudget"
 )]
 pub async fn create(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Json(request): Json<CreateBudgetRequest>,
 ) -> Result<(StatusCode, Json<Uuid>), AppError> {
-    if request.user_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("user_id must be set")));
+    if request.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("plan_id must be set")));
     }
 
     let name = request.name.trim();
@@ -104,35 +207,27 @@ pub async fn create(
         return Err(AppError::BadRequest(anyhow!("Budget name cannot be empty")));
     }
 
-    let user_result = db::users::get_single(&db_pool, request.user_id).await;
-    match user_result {
-        Ok(_) => (),
+    if let Some(CreateBudgetTargetRequest::Repeating { schedule, .. }) = &request.target {
+        schedule.period.validate().map_err(AppError::BadRequest)?;
+    }
+
+    let plan_result = ownership
+        .check_plan(user.id, request.plan_id, PlanRole::Editor)
+        .await;
+    match plan_result {
+        Ok(()) => (),
         Err(db::Error::NotFound) => {
             return Err(AppError::NotFound(anyhow!(
-                "user with id {} was not found",
-                request.user_id
+                "plan with id {} was not found",
+                request.plan_id
             )))
         }
         Err(e) => return Err(e.to_app_error(anyhow!("Failed to create budget"))),
     };
 
-    let budget_id = Uuid::new_v4();
-    let schedule =
-        if let Some(CreateBudgetTargetRequest::Repeating { schedule, .. }) = &request.target {
-            let schedule_id = Uuid::new_v4();
-            let schedule = Schedule {
-                id: schedule_id,
-                period: schedule.period.clone(),
-            };
+    validate_group(&*budget_groups, request.group_id, request.plan_id).await?;
 
-            db::schedule::create(&db_pool, schedule.clone())
-                .await
-                .map_err(|e| e.to_app_error(anyhow!("Failed to create budget")))?;
-
-            Some(schedule)
-        } else {
-            None
-        };
+    let budget_id = Uuid::new_v4();
 
     let budget = Budget {
         id: budget_id,
@@ -144,21 +239,24 @@ pub async fn create(
             CreateBudgetTargetRequest::Repeating {
                 target_amount,
                 repeating_type,
-                ..
-            } if schedule.is_some() => BudgetTarget::Repeating {
+                schedule,
+            } => BudgetTarget::Repeating {
                 target_amount,
                 repeating_type,
-                schedule: schedule.expect("checked by arm guard"),
+                schedule: Schedule {
+                    id: Uuid::new_v4(),
+                    period: schedule.period,
+                },
             },
-            CreateBudgetTargetRequest::Repeating { .. } => {
-                unreachable!("We create schedule above if target is repeating")
-            }
         }),
-        user_id: request.user_id,
+        plan_id: request.plan_id,
         assignments: vec![],
+        group_id: request.group_id,
+        archived_at: None,
     };
 
-    db::budgets::create(&db_pool, budget)
+    budgets
+        .create(budget)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to create budget")))?;
 
@@ -176,62 +274,35 @@ pub async fn create(
     operation_id = "updateBudget"
 )]
 pub async fn update(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(budget_groups): State<Arc<dyn BudgetGroupRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(budget_id): Path<Uuid>,
     Json(request): Json<UpdateBudgetRequest>,
 ) -> Result<(), AppError> {
-    let mut existing_budget = db::budgets::get_single(&db_pool, budget_id)
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!("Budget name cannot be empty")));
+    }
+
+    if let Some(UpdateBudgetTargetRequest::Repeating { schedule, .. }) = &request.target {
+        schedule.period.validate().map_err(AppError::BadRequest)?;
+    }
+
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to get budget")))?;
 
-    let (schedule, schedule_id_to_delete) = match (&existing_budget.target, request.target.clone())
-    {
-        // update schedule
-        (
-            Some(BudgetTarget::Repeating {
-                schedule: existing_schedule,
-                ..
-            }),
-            Some(UpdateBudgetTargetRequest::Repeating {
-                schedule: update_schedule_request,
-                ..
-            }),
-        ) => {
-            let updated_schedule = Schedule {
-                id: existing_schedule.id,
-                period: update_schedule_request.period,
-            };
-            db::schedule::update(&db_pool, updated_schedule.clone())
-                .await
-                .map_err(|e| e.to_app_error(anyhow!("Failed to update schedule")))?;
-
-            (Some(updated_schedule), None)
-        }
-        // delete schedule
-        (
-            Some(BudgetTarget::Repeating { schedule, .. }),
-            None | Some(UpdateBudgetTargetRequest::OneTime { .. }),
-        ) => (None, Some(schedule.id)),
-        // create schedule
-        (
-            None | Some(BudgetTarget::OneTime { .. }),
-            Some(UpdateBudgetTargetRequest::Repeating { schedule, .. }),
-        ) => {
-            let new_schedule_id = Uuid::new_v4();
-
-            let new_schedule = Schedule {
-                id: new_schedule_id,
-                period: schedule.period,
-            };
+    let mut existing_budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget")))?;
 
-            db::schedule::create(&db_pool, new_schedule.clone())
-                .await
-                .map_err(|e| e.to_app_error(anyhow!("Failed to create new schedule")))?;
+    validate_group(&*budget_groups, request.group_id, existing_budget.plan_id).await?;
 
-            (Some(new_schedule), None)
-        }
-        _ => (None, None),
-    };
+    let previous_schedule_id = target_schedule_id(&existing_budget);
 
     let target = request.target.map(|t| match t {
         UpdateBudgetTargetRequest::OneTime { target_amount } => {
@@ -240,30 +311,38 @@ pub async fn update(
         UpdateBudgetTargetRequest::Repeating {
             target_amount,
             repeating_type,
-            ..
+            schedule,
         } => BudgetTarget::Repeating {
             target_amount,
             repeating_type,
-            schedule: schedule.expect("Matched the same arm above"),
+            // a repeating target keeps its schedule, which is updated rather than replaced
+            schedule: Schedule {
+                id: previous_schedule_id.unwrap_or_else(Uuid::new_v4),
+                period: schedule.period,
+            },
         },
     });
 
-    existing_budget.name = request.name;
+    existing_budget.name = name.into();
     existing_budget.target = target;
+    existing_budget.group_id = request.group_id;
 
-    db::budgets::update(&db_pool, existing_budget)
+    budgets
+        .update_with_target(existing_budget, previous_schedule_id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update budget")))?;
 
-    if let Some(schedule_id_to_delete) = schedule_id_to_delete {
-        db::schedule::delete(&db_pool, schedule_id_to_delete)
-            .await
-            .map_err(|e| e.to_app_error(anyhow!("Failed to delete schedule")))?;
-    }
-
     Ok(())
 }
 
+/// The schedule of the budget's target, if it repeats
+fn target_schedule_id(budget: &Budget) -> Option<Uuid> {
+    match &budget.target {
+        Some(BudgetTarget::Repeating { schedule, .. }) => Some(schedule.id),
+        _ => None,
+    }
+}
+
 #[utoipa::path(
     delete,
     path = "/api/budgets/{budget_id}",
@@ -277,23 +356,161 @@ pub async fn update(
     operation_id = "deleteBudget"
 )]
 pub async fn delete(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(budget_id): Path<Uuid>,
 ) -> Result<(), AppError> {
-    let budget = db::budgets::get_single(&db_pool, budget_id)
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to delete")))?;
 
-    db::budgets::delete(&db_pool, budget_id)
+    let budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to delete")))?;
+
+    // deleting a budget that has been used would change past months
+    let in_use = budgets
+        .is_in_use(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to delete")))?;
+    if in_use {
+        return Err(AppError::BadRequest(anyhow!(
+            "Budget {} has transactions or assignments, archive it instead",
+            budget_id
+        )));
+    }
+
+    budgets
+        .delete(budget_id, target_schedule_id(&budget))
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to delete budget")))?;
 
-    if let Some(BudgetTarget::Repeating { schedule, .. }) = budget.target {
-        db::schedule::delete(&db_pool, schedule.id)
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/budgets/{budgetId}/archive",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = ArchiveBudgetRequest,
+    params(
+        ("budgetId" = Uuid, Path,),
+    ),
+    tag = API_TAG,
+    operation_id = "archiveBudget"
+)]
+pub async fn archive(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(budget_id): Path<Uuid>,
+    Json(request): Json<ArchiveBudgetRequest>,
+) -> Result<(), AppError> {
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to archive")))?;
+
+    let budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to archive")))?;
+
+    if budget.archived_at.is_some() {
+        return Err(AppError::BadRequest(anyhow!(
+            "budget with id {budget_id} is already archived"
+        )));
+    }
+
+    let balance = budget.total_assigned();
+
+    // an archived budget can't keep any money, so move it somewhere it can still be spent
+    let move_to_budget_id = if balance.is_zero() {
+        None
+    } else {
+        let Some(move_to_budget_id) = request.move_to_budget_id else {
+            return Err(AppError::BadRequest(anyhow!(
+                "budget with id {budget_id} has a remaining balance of {balance}, move_to_budget_id must be set"
+            )));
+        };
+
+        if move_to_budget_id == budget_id {
+            return Err(AppError::BadRequest(anyhow!(
+                "cannot move the remaining balance to the budget being archived"
+            )));
+        }
+
+        let move_to = budgets
+            .get_single(move_to_budget_id)
             .await
-            .map_err(|e| e.to_app_error(anyhow!("Failed to delete schedule")))?;
+            .map_err(|e| match e {
+                db::Error::NotFound => AppError::BadRequest(anyhow!(
+                    "budget with id {move_to_budget_id} was not found"
+                )),
+                e => e.to_app_error(anyhow!("Failed to get budget to move balance to")),
+            })?;
+
+        if move_to.plan_id != budget.plan_id || move_to.archived_at.is_some() {
+            return Err(AppError::BadRequest(anyhow!(
+                "cannot move the remaining balance to budget with id {move_to_budget_id}"
+            )));
+        }
+
+        Some(move_to_budget_id)
+    };
+
+    budgets
+        .archive(budget_id, move_to_budget_id, request.date)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to archive budget")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/budgets/{budgetId}/unarchive",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("budgetId" = Uuid, Path,),
+    ),
+    tag = API_TAG,
+    operation_id = "unarchiveBudget"
+)]
+pub async fn unarchive(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(budget_id): Path<Uuid>,
+) -> Result<(), AppError> {
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to unarchive")))?;
+
+    let budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to unarchive")))?;
+
+    if budget.archived_at.is_none() {
+        return Err(AppError::BadRequest(anyhow!(
+            "budget with id {budget_id} is not archived"
+        )));
     }
 
+    budgets
+        .unarchive(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to unarchive budget")))?;
+
     Ok(())
 }
 
@@ -312,17 +529,31 @@ pub async fn delete(
     operation_id = "transferBetweenBudgets"
 )]
 pub async fn transfer_between(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path((from_budget_id, to_budget_id)): Path<(Uuid, Uuid)>,
     Json(request): Json<TransferBudgetRequest>,
 ) -> Result<(), AppError> {
-    let mut budgets = Vec::from(
-        db::budgets::get_by_ids(&db_pool, &[from_budget_id, to_budget_id])
-            .await
-            .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to assign to")))?,
+    if from_budget_id == to_budget_id {
+        return Err(AppError::BadRequest(anyhow!(
+            "cannot transfer from a budget to itself"
+        )));
+    }
+
+    let (from_check, to_check) = join!(
+        ownership.check_budget(user.id, from_budget_id, PlanRole::Editor),
+        ownership.check_budget(user.id, to_budget_id, PlanRole::Editor)
     );
+    from_check.map_err(|e| e.to_app_error(anyhow!("Failed to get budget to transfer from")))?;
+    to_check.map_err(|e| e.to_app_error(anyhow!("Failed to get budget to transfer to")))?;
+
+    let found = budgets
+        .get_by_ids(&[from_budget_id, to_budget_id])
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budgets to transfer between")))?;
 
-    if budgets.len() != 2 {
+    if found.len() != 2 {
         return Err(AppError::NotFound(anyhow!(
             "Could not find either one or both budgets with ids {} and {}",
             from_budget_id,
@@ -330,27 +561,329 @@ pub async fn transfer_between(
         )));
     }
 
-    let (mut from, mut to) = if budgets[0].id == from_budget_id {
-        (budgets.remove(0), budgets.remove(0))
-    } else {
-        (budgets.remove(1), budgets.remove(0))
-    };
+    if found[0].plan_id != found[1].plan_id {
+        return Err(AppError::BadRequest(anyhow!(
+            "Cannot transfer between budgets in different plans"
+        )));
+    }
 
-    Budget::move_between_budgets(&mut from, &mut to, request.amount, request.date);
+    budgets
+        .transfer_between(from_budget_id, to_budget_id, request.amount, request.date)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to transfer between budgets")))?;
 
-    db::budgets::update(&db_pool, from)
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/budgets/ready-to-assign/transfer-to/{budgetId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = TransferBudgetRequest,
+    params(
+        ("budgetId" = Uuid, Path,),
+    ),
+    tag = API_TAG,
+    operation_id = "assignFromReadyToAssign"
+)]
+pub async fn assign_from_ready_to_assign(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(budget_id): Path<Uuid>,
+    Json(request): Json<TransferBudgetRequest>,
+) -> Result<(), AppError> {
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to assign to")))?;
+
+    let budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to assign to")))?;
+
+    if budget.archived_at.is_some() {
+        return Err(AppError::BadRequest(anyhow!(
+            "budget with id {budget_id} is archived"
+        )));
+    }
+
+    budgets
+        .assign_from_ready_to_assign(&[(budget_id, request.amount)], request.date)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update budget")))?;
-    db::budgets::update(&db_pool, to)
+
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/budgets/{budgetId}/transfer-to/ready-to-assign",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = TransferBudgetRequest,
+    params(
+        ("budgetId" = Uuid, Path,),
+    ),
+    tag = API_TAG,
+    operation_id = "returnToReadyToAssign"
+)]
+pub async fn return_to_ready_to_assign(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(budget_id): Path<Uuid>,
+    Json(request): Json<TransferBudgetRequest>,
+) -> Result<(), AppError> {
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget to return from")))?;
+
+    budgets
+        .assign_from_ready_to_assign(&[(budget_id, -request.amount)], request.date)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update budget")))?;
 
     Ok(())
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/budgets/{budgetId}/months/{month}",
+    responses(
+        (status = OK, description = "Success", body = BudgetMonthSummary, content_type = "application/json")
+    ),
+    params(
+        ("budgetId" = Uuid, Path,),
+        ("month" = String, Path, description = "The month in the form yyyy-mm"),
+    ),
+    tag = API_TAG,
+    operation_id = "getBudgetMonth"
+)]
+pub async fn get_month(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((budget_id, month)): Path<(Uuid, String)>,
+) -> Result<Json<BudgetMonthSummary>, AppError> {
+    let month = parse_month(&month).map_err(AppError::BadRequest)?;
+
+    ownership
+        .check_budget(user.id, budget_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget")))?;
+
+    let budget = budgets
+        .get_single(budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget")))?;
+
+    Ok(Json(budget.month_summary(month)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/budgets/months/{month}",
+    responses(
+        (status = OK, description = "Success", body = GetMonthResponse, content_type = "application/json")
+    ),
+    params(
+        ("month" = String, Path, description = "The month in the form yyyy-mm"),
+        GetBudgetsQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "getMonth"
+)]
+pub async fn get_month_for_all(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(month): Path<String>,
+    Query(query): Query<GetBudgetsQuery>,
+) -> Result<Json<GetMonthResponse>, AppError> {
+    if query.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("plan_id must be set")));
+    }
+
+    let month = parse_month(&month).map_err(AppError::BadRequest)?;
+
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get plan")))?;
+
+    // archived budgets still count towards the months they were used in
+    let budgets = budgets
+        .get(query.plan_id, true)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budgets")))?;
+
+    Ok(Json(GetMonthResponse::from_budgets(&budgets, month)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/budgets/suggested-assignments",
+    responses(
+        (status = OK, description = "Success", body = SuggestedAssignments, content_type = "application/json")
+    ),
+    params(
+        GetBudgetsQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "getSuggestedAssignments"
+)]
+pub async fn get_suggested_assignments(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<GetBudgetsQuery>,
+) -> Result<Json<SuggestedAssignments>, AppError> {
+    if query.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("plan_id must be set")));
+    }
+
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get plan")))?;
+
+    let budgets = budgets
+        .get(query.plan_id, false)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budgets")))?;
+
+    let Some(pay_frequency) = user.pay_frequency else {
+        return Err(AppError::BadRequest(anyhow!(
+            "user {} must have a pay frequency to suggest assignments",
+            user.id
+        )));
+    };
+
+    SuggestedAssignments::for_next_pay(&pay_frequency, &budgets, Local::now().date_naive())
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::BadRequest(anyhow!(
+                "user {}'s pay frequency has no upcoming pay day",
+                user.id
+            ))
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/budgets/suggested-assignments",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = ApplySuggestedAssignmentsRequest,
+    tag = API_TAG,
+    operation_id = "applySuggestedAssignments"
+)]
+pub async fn apply_suggested_assignments(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<ApplySuggestedAssignmentsRequest>,
+) -> Result<(), AppError> {
+    ownership
+        .check_plan(user.id, request.plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get plan")))?;
+
+    let budget_ids: Vec<_> = request.assignments.iter().map(|a| a.budget_id).collect();
+
+    let found = budgets
+        .get_by_ids(&budget_ids)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get budgets to assign to")))?;
+
+    for assignment in &request.assignments {
+        let Some(budget) = found
+            .iter()
+            .find(|b| b.id == assignment.budget_id && b.plan_id == request.plan_id)
+        else {
+            return Err(AppError::NotFound(anyhow!(
+                "Could not find budget with id {}",
+                assignment.budget_id
+            )));
+        };
+
+        if budget.archived_at.is_some() {
+            return Err(AppError::BadRequest(anyhow!(
+                "budget with id {} is archived",
+                budget.id
+            )));
+        }
+    }
+
+    let amounts: Vec<_> = request
+        .assignments
+        .iter()
+        .map(|a| (a.budget_id, a.amount))
+        .collect();
+
+    budgets
+        .assign_from_ready_to_assign(&amounts, request.date)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to apply suggested assignments")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/budgets/{budgetId}/suggested-target",
+    responses(
+        (status = OK, description = "Success", body = CreateBudgetTargetRequest, content_type = "application/json")
+    ),
+    params(
+        ("budgetId" = Uuid, Path,),
+        GetSuggestedTargetQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "getSuggestedTarget"
+)]
+pub async fn get_suggested_target(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(budget_id): Path<Uuid>,
+    Query(query): Query<GetSuggestedTargetQuery>,
+) -> Result<Json<CreateBudgetTargetRequest>, AppError> {
+    if query.payee_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("payee_id must be set")));
+    }
+
+    let (budget_check, payee_check) = join!(
+        ownership.check_budget(user.id, budget_id, PlanRole::Viewer),
+        ownership.check_payee(user.id, query.payee_id, PlanRole::Viewer)
+    );
+    budget_check.map_err(|e| e.to_app_error(anyhow!("Failed to get budget")))?;
+    payee_check.map_err(|e| e.to_app_error(anyhow!("Failed to get payee")))?;
+
+    let transactions = transactions
+        .get_by_budget_and_payee(budget_id, query.payee_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get transactions")))?;
+
+    suggest_target(&transactions).map(Json).ok_or_else(|| {
+        AppError::NotFound(anyhow!(
+            "No recurring payments to payee {} found in budget {budget_id}",
+            query.payee_id
+        ))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{db::TestPool, AppState};
 
     mod update_budget_tests {
         use std::sync::{LazyLock, OnceLock};
@@ -376,7 +909,8 @@ mod tests {
         static OTHER_BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
         static ASSIGNMENT_LINK_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-        async fn test_init(db_pool: &MySqlPool) {
+        #[allow(clippy::too_many_lines)]
+        async fn test_init(db_pool: &TestPool) {
             let budget_no_target_id = *BUDGET_NO_TARGET_ID;
             let budget_onetime_target_id = *BUDGET_ONETIME_TARGET_ID;
             let budget_repeating_target_id = *BUDGET_REPEATING_TARGET_ID;
@@ -400,7 +934,9 @@ mod tests {
                     assignments: vec![],
                     name: "name".into(),
                     target: None,
-                    user_id,
+                    plan_id: user_id,
+                    group_id: None,
+                    archived_at: None,
                 },
             ).await.unwrap();
 
@@ -409,7 +945,7 @@ mod tests {
                     id: budget_no_target_id,
                     name: "name".into(),
                     target: None,
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![BudgetAssignment {
                         id: assignment_id1,
                         amount: dec!(10),
@@ -420,6 +956,8 @@ mod tests {
                             link_id: assignment_link_id
                         }
                     }],
+                    group_id: None,
+                    archived_at: None,
                 })
                 .clone();
 
@@ -430,7 +968,7 @@ mod tests {
                     target: Some(BudgetTarget::OneTime {
                         target_amount: dec!(1.2),
                     }),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![BudgetAssignment {
                         id: assignment_id2,
                         amount: dec!(10),
@@ -441,6 +979,8 @@ mod tests {
                             link_id: assignment_link_id
                         }
                     }],
+                    group_id: None,
+                    archived_at: None,
                 })
                 .clone();
 
@@ -460,7 +1000,7 @@ mod tests {
                         repeating_type: RepeatingTargetType::BuildUpTo,
                         schedule: schedule.clone(),
                     }),
-                    user_id,
+                    plan_id: user_id,
                     assignments: vec![BudgetAssignment {
                         id: assignment_id3,
                         amount: dec!(10),
@@ -471,6 +1011,8 @@ mod tests {
                             link_id: assignment_link_id
                         }
                     }],
+                    group_id: None,
+                    archived_at: None,
                 })
                 .clone();
 
@@ -484,19 +1026,24 @@ mod tests {
                 .unwrap();
         }
 
-        #[sqlx::test]
-        pub async fn no_schedule_to_no_schedule(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn no_schedule_to_no_schedule(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let budget = BUDGET_NO_TARGET.get().unwrap().clone();
             let user_id = *USER_ID;
 
+            let state = AppState::new(db_pool.clone().into());
             update(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                State(state.budget_groups.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path(budget.id),
                 Json(UpdateBudgetRequest {
                     name: "newName".into(),
                     target: None,
+                    group_id: None,
                 }),
             )
             .await
@@ -506,7 +1053,7 @@ mod tests {
                 id: budget.id,
                 name: "newName".into(),
                 target: None,
-                user_id,
+                plan_id: user_id,
                 assignments: vec![BudgetAssignment {
                     id: *ASSIGNMENT_ID1,
                     amount: dec!(10),
@@ -516,6 +1063,8 @@ mod tests {
                         link_id: *ASSIGNMENT_LINK_ID
                     }
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
@@ -523,15 +1072,19 @@ mod tests {
             assert_eq!(fetched, expected);
         }
 
-        #[sqlx::test]
-        pub async fn onetime_to_repeating(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn onetime_to_repeating(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let budget = BUDGET_ONETIME_TARGET.get().unwrap().clone();
             let user_id = *USER_ID;
 
+            let state = AppState::new(db_pool.clone().into());
             update(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                State(state.budget_groups.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path(budget.id),
                 Json(UpdateBudgetRequest {
                     name: "newName".into(),
@@ -544,6 +1097,7 @@ mod tests {
                             },
                         },
                     }),
+                    group_id: None,
                 }),
             )
             .await
@@ -563,7 +1117,7 @@ mod tests {
                     repeating_type: RepeatingTargetType::BuildUpTo,
                     schedule: schedule.clone(),
                 }),
-                user_id,
+                plan_id: user_id,
                 assignments: vec![BudgetAssignment {
                     id: *ASSIGNMENT_ID2,
                     amount: dec!(10),
@@ -573,20 +1127,26 @@ mod tests {
                         link_id: *ASSIGNMENT_LINK_ID
                     }
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             assert_eq!(fetched, expected);
         }
 
-        #[sqlx::test]
-        pub async fn no_target_to_repeating(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn no_target_to_repeating(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let budget = BUDGET_NO_TARGET.get().unwrap().clone();
             let user_id = *USER_ID;
 
+            let state = AppState::new(db_pool.clone().into());
             update(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                State(state.budget_groups.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path(budget.id),
                 Json(UpdateBudgetRequest {
                     name: "newName".into(),
@@ -599,6 +1159,7 @@ mod tests {
                             },
                         },
                     }),
+                    group_id: None,
                 }),
             )
             .await
@@ -618,7 +1179,7 @@ mod tests {
                     repeating_type: RepeatingTargetType::BuildUpTo,
                     schedule: schedule.clone(),
                 }),
-                user_id,
+                plan_id: user_id,
                 assignments: vec![BudgetAssignment {
                     id: *ASSIGNMENT_ID1,
                     amount: dec!(10),
@@ -628,24 +1189,31 @@ mod tests {
                         link_id: *ASSIGNMENT_LINK_ID
                     }
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             assert_eq!(fetched, expected);
         }
 
-        #[sqlx::test]
-        pub async fn repeating_to_no_target(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn repeating_to_no_target(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let budget = BUDGET_REPEATING_TARGET.get().unwrap().clone();
             let user_id = *USER_ID;
 
+            let state = AppState::new(db_pool.clone().into());
             update(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                State(state.budget_groups.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path(budget.id),
                 Json(UpdateBudgetRequest {
                     name: "newName".into(),
                     target: None,
+                    group_id: None,
                 }),
             )
             .await
@@ -657,7 +1225,7 @@ mod tests {
                 id: budget.id,
                 name: "newName".into(),
                 target: None,
-                user_id,
+                plan_id: user_id,
                 assignments: vec![BudgetAssignment {
                     id: *ASSIGNMENT_ID3,
                     amount: dec!(10),
@@ -667,26 +1235,33 @@ mod tests {
                         link_id: *ASSIGNMENT_LINK_ID
                     }
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             assert_eq!(fetched, expected);
         }
 
-        #[sqlx::test]
-        pub async fn repeating_to_one_time(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn repeating_to_one_time(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let budget = BUDGET_REPEATING_TARGET.get().unwrap().clone();
             let user_id = *USER_ID;
 
+            let state = AppState::new(db_pool.clone().into());
             update(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                State(state.budget_groups.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path(budget.id),
                 Json(UpdateBudgetRequest {
                     name: "newName".into(),
                     target: Some(UpdateBudgetTargetRequest::OneTime {
                         target_amount: dec!(1.2),
                     }),
+                    group_id: None,
                 }),
             )
             .await
@@ -700,7 +1275,7 @@ mod tests {
                 target: Some(BudgetTarget::OneTime {
                     target_amount: dec!(1.2),
                 }),
-                user_id,
+                plan_id: user_id,
                 assignments: vec![BudgetAssignment {
                     id: *ASSIGNMENT_ID3,
                     amount: dec!(10),
@@ -710,6 +1285,8 @@ mod tests {
                         link_id: *ASSIGNMENT_LINK_ID
                     }
                 }],
+                group_id: None,
+                archived_at: None,
             };
 
             assert_eq!(fetched, expected);
@@ -728,7 +1305,7 @@ mod tests {
         static BUDGET1_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
         static BUDGET2_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-        async fn test_init(db_pool: &MySqlPool) {
+        async fn test_init(db_pool: &TestPool) {
             let user_id = *USER_ID;
 
             db::users::create(
@@ -743,7 +1320,9 @@ mod tests {
                 assignments: vec![],
                 name: "name".into(),
                 target: None,
-                user_id
+                plan_id: user_id,
+                group_id: None,
+                archived_at: None,
             }).await.unwrap();
 
             db::budgets::create(db_pool, Budget {
@@ -751,12 +1330,14 @@ mod tests {
                 assignments: vec![],
                 name: "name".into(),
                 target: None,
-                user_id
+                plan_id: user_id,
+                group_id: None,
+                archived_at: None,
             }).await.unwrap();
         }
 
-        #[sqlx::test]
-        pub async fn existing_assignment_test(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn existing_assignment_test(db_pool: TestPool) {
             test_init(&db_pool).await;
 
             let link_id = Uuid::new_v4();
@@ -785,8 +1366,11 @@ mod tests {
             db::budgets::update(&db_pool, budget1).await.unwrap();
             db::budgets::update(&db_pool, budget2).await.unwrap();
 
+            let state = AppState::new(db_pool.clone().into());
             transfer_between(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path((*BUDGET1_ID, *BUDGET2_ID)),
                 Json(TransferBudgetRequest {
                     amount: Decimal::ZERO,
@@ -820,12 +1404,15 @@ mod tests {
             assert_eq!(fetched2.assignments[1].amount, Decimal::ZERO);
         }
 
-        #[sqlx::test]
-        pub async fn empty_assignment_test(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn empty_assignment_test(db_pool: TestPool) {
             test_init(&db_pool).await;
 
+            let state = AppState::new(db_pool.clone().into());
             transfer_between(
-                State(db_pool.clone()),
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, *USER_ID).await.unwrap()),
                 Path((*BUDGET1_ID, *BUDGET2_ID)),
                 Json(TransferBudgetRequest {
                     amount: Decimal::ZERO,
@@ -869,8 +1456,8 @@ mod tests {
 
         use super::*;
 
-        #[sqlx::test]
-        pub async fn delete_budget_should_delete_schedule(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn delete_budget_should_delete_schedule(db_pool: TestPool) {
             let user_id = Uuid::new_v4();
 
             db::users::create(
@@ -903,15 +1490,23 @@ mod tests {
                     repeating_type: RepeatingTargetType::BuildUpTo,
                     schedule: schedule.clone(),
                 }),
-                user_id,
+                plan_id: user_id,
                 assignments: vec![],
+                group_id: None,
+                archived_at: None,
             };
 
             db::budgets::create(&db_pool, budget).await.unwrap();
 
-            delete(State(db_pool.clone()), Path(budget_id))
-                .await
-                .unwrap();
+            let state = AppState::new(db_pool.clone().into());
+            delete(
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+                Path(budget_id),
+            )
+            .await
+            .unwrap();
 
             let fetch_result = db::budgets::get_single(&db_pool, budget_id).await;
 
@@ -921,8 +1516,8 @@ mod tests {
             assert!(matches!(fetch_schedule_result, Err(Error::NotFound)));
         }
 
-        #[sqlx::test]
-        pub async fn delete_budget_should_succeed_when_no_schedule(db_pool: MySqlPool) {
+        #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+        pub async fn delete_budget_should_succeed_when_no_schedule(db_pool: TestPool) {
             let user_id = Uuid::new_v4();
 
             db::users::create(
@@ -938,19 +1533,234 @@ mod tests {
                 id: budget_id,
                 name: "name".into(),
                 target: None,
-                user_id,
+                plan_id: user_id,
                 assignments: vec![],
+                group_id: None,
+                archived_at: None,
             };
 
             db::budgets::create(&db_pool, budget).await.unwrap();
 
-            delete(State(db_pool.clone()), Path(budget_id))
-                .await
-                .unwrap();
+            let state = AppState::new(db_pool.clone().into());
+            delete(
+                State(state.ownership.clone()),
+                State(state.budgets.clone()),
+                AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+                Path(budget_id),
+            )
+            .await
+            .unwrap();
 
             let fetch_result = db::budgets::get_single(&db_pool, budget_id).await;
 
             assert!(matches!(fetch_result, Err(Error::NotFound)));
         }
     }
+
+    mod in_memory_tests {
+        use chrono::{NaiveDate, Utc};
+        use rust_decimal_macros::dec;
+
+        use crate::{db::in_memory::InMemoryRepository, models::User};
+
+        use super::*;
+
+        fn setup(repository: &InMemoryRepository, role: PlanRole) -> (User, Uuid) {
+            let user = User::new(Uuid::new_v4(), "name".into(), "email@email.com".into(), None);
+            let plan_id = Uuid::new_v4();
+
+            repository.add_member(plan_id, user.id, role);
+
+            (user, plan_id)
+        }
+
+        #[tokio::test]
+        async fn delete_should_reject_budget_in_use() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let budget = Budget::new(
+                Uuid::new_v4(),
+                "budget".into(),
+                None,
+                plan_id,
+                vec![BudgetAssignment {
+                    id: Uuid::new_v4(),
+                    amount: dec!(10),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    source: BudgetAssignmentSource::ReadyToAssign,
+                }],
+                None,
+                None,
+            );
+            repository.add_budget(budget.clone());
+
+            let result = delete(
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path(budget.id),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(repository.budget(budget.id).is_some());
+        }
+
+        #[tokio::test]
+        async fn update_should_trim_name() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let budget = Budget::new(
+                Uuid::new_v4(),
+                "budget".into(),
+                None,
+                plan_id,
+                vec![],
+                None,
+                None,
+            );
+            repository.add_budget(budget.clone());
+
+            update(
+                State(repository.clone()),
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path(budget.id),
+                Json(UpdateBudgetRequest {
+                    name: "  renamed  ".into(),
+                    target: None,
+                    group_id: None,
+                }),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(repository.budget(budget.id).unwrap().name, "renamed");
+        }
+
+        #[tokio::test]
+        async fn update_should_reject_empty_name() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let budget = Budget::new(
+                Uuid::new_v4(),
+                "budget".into(),
+                None,
+                plan_id,
+                vec![],
+                None,
+                None,
+            );
+            repository.add_budget(budget.clone());
+
+            let result = update(
+                State(repository.clone()),
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path(budget.id),
+                Json(UpdateBudgetRequest {
+                    name: "   ".into(),
+                    target: None,
+                    group_id: None,
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert_eq!(repository.budget(budget.id).unwrap().name, "budget");
+        }
+
+        #[tokio::test]
+        async fn transfer_between_should_reject_budgets_in_different_plans() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let other_plan_id = Uuid::new_v4();
+            repository.add_member(other_plan_id, user.id, PlanRole::Editor);
+            let from = Budget::new(Uuid::new_v4(), "from".into(), None, plan_id, vec![], None, None);
+            let to = Budget::new(Uuid::new_v4(), "to".into(), None, other_plan_id, vec![], None, None);
+            repository.add_budget(from.clone());
+            repository.add_budget(to.clone());
+
+            let result = transfer_between(
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path((from.id, to.id)),
+                Json(TransferBudgetRequest {
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    amount: dec!(10),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(repository.budget(from.id).unwrap().assignments.is_empty());
+            assert!(repository.budget(to.id).unwrap().assignments.is_empty());
+        }
+
+        #[tokio::test]
+        async fn transfer_between_should_reject_same_budget() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let budget = Budget::new(
+                Uuid::new_v4(),
+                "budget".into(),
+                None,
+                plan_id,
+                vec![],
+                None,
+                None,
+            );
+            repository.add_budget(budget.clone());
+
+            let result = transfer_between(
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path((budget.id, budget.id)),
+                Json(TransferBudgetRequest {
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    amount: dec!(10),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(repository.budget(budget.id).unwrap().assignments.is_empty());
+        }
+
+        #[tokio::test]
+        async fn assign_from_ready_to_assign_should_reject_archived_budget() {
+            let repository = Arc::new(InMemoryRepository::default());
+            let (user, plan_id) = setup(&repository, PlanRole::Editor);
+            let mut budget = Budget::new(
+                Uuid::new_v4(),
+                "budget".into(),
+                None,
+                plan_id,
+                vec![],
+                None,
+                None,
+            );
+            budget.archived_at = Some(Utc::now());
+            repository.add_budget(budget.clone());
+
+            let result = assign_from_ready_to_assign(
+                State(repository.clone()),
+                State(repository.clone()),
+                AuthenticatedUser(user),
+                Path(budget.id),
+                Json(TransferBudgetRequest {
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    amount: dec!(10),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(repository.budget(budget.id).unwrap().assignments.is_empty());
+        }
+    }
 }
@@ -0,0 +1,58 @@
+use axum::{extract::State, Json};
+use http::StatusCode;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::DbPool,
+    export,
+    models::{PlanExport, UserExport},
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(get, restore), components(schemas(UserExport, PlanExport)))]
+pub struct Api;
+
+const API_TAG: &str = "Exports";
+
+/// Exports the signed in user along with every plan they own
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export",
+    responses(
+        (status = OK, description = "Success", body = UserExport, content_type = "application/json")
+    ),
+    tag = API_TAG,
+    operation_id = "exportUser"
+)]
+pub async fn get(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<UserExport>, AppError> {
+    export::export(&db_pool, user.id).await.map(Json)
+}
+
+/// Restores each exported plan as a new plan owned by the signed in user.
+/// Returns the ids of the new plans
+#[utoipa::path(
+    post,
+    path = "/api/users/me/restore",
+    responses(
+        (status = CREATED, description = "Success", body = Box<[Uuid]>, content_type = "application/json"),
+        (status = PAYLOAD_TOO_LARGE, description = "The file is larger than 50 MB")
+    ),
+    request_body = UserExport,
+    tag = API_TAG,
+    operation_id = "restoreUser"
+)]
+pub async fn restore(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<UserExport>,
+) -> Result<(StatusCode, Json<Box<[Uuid]>>), AppError> {
+    let plan_ids = export::restore(&db_pool, user.id, request).await?;
+
+    Ok((StatusCode::CREATED, Json(plan_ids)))
+}
@@ -0,0 +1,445 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    Json,
+};
+use chrono::NaiveDate;
+use http::StatusCode;
+use rust_decimal::Decimal;
+use tokio::join;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::{self, DbPool, Error},
+    import::{self, name_key},
+    models::{
+        Budget, CreatePayeeRequest, CsvMapping, ImportPreview, ImportTransactionRequest,
+        ImportTransactionsRequest, ImportedTransaction, PlanRole, Transaction,
+    },
+    AppError,
+};
+
+use super::transactions::{validate_budget, validate_not_archived};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(preview_ofx, preview_csv, get_csv_mapping, update_csv_mapping, create),
+    components(schemas(
+        CsvMapping,
+        ImportPreview,
+        ImportedTransaction,
+        ImportTransactionsRequest,
+        ImportTransactionRequest
+    ))
+)]
+pub struct Api;
+
+const API_TAG: &str = "Imports";
+
+#[utoipa::path(
+    post,
+    path = "/api/bank-accounts/{bankAccountId}/imports/ofx/preview",
+    responses(
+        (status = OK, description = "Success", body = ImportPreview, content_type = "application/json"),
+        (status = PAYLOAD_TOO_LARGE, description = "The file is larger than 50 MB")
+    ),
+    request_body(content = String, description = "The OFX or QFX statement", content_type = "text/plain"),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "previewOfxImport"
+)]
+pub async fn preview_ofx(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+    body: Bytes,
+) -> Result<Json<ImportPreview>, AppError> {
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let transactions = import::ofx::parse(&String::from_utf8_lossy(&body))
+        .map_err(|e| AppError::BadRequest(e.context("Could not read OFX file")))?;
+
+    preview(&db_pool, bank_account_id, transactions)
+        .await
+        .map(Json)
+}
+
+/// A dry run of importing a CSV export with the bank account's saved CSV mapping
+#[utoipa::path(
+    post,
+    path = "/api/bank-accounts/{bankAccountId}/imports/csv/preview",
+    responses(
+        (status = OK, description = "Success", body = ImportPreview, content_type = "application/json"),
+        (status = PAYLOAD_TOO_LARGE, description = "The file is larger than 50 MB")
+    ),
+    request_body(content = String, description = "The CSV export", content_type = "text/csv"),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "previewCsvImport"
+)]
+pub async fn preview_csv(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+    body: Bytes,
+) -> Result<Json<ImportPreview>, AppError> {
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let mapping = match db::csv_mappings::get(&db_pool, bank_account_id).await {
+        Ok(mapping) => mapping,
+        Err(Error::NotFound) => {
+            return Err(AppError::BadRequest(anyhow!(
+                "Save a CSV mapping for the bank account before importing"
+            )));
+        }
+        Err(e) => return Err(e.to_app_error(anyhow!("Could not get CSV mapping"))),
+    };
+
+    let transactions = import::csv::parse(&String::from_utf8_lossy(&body), &mapping)
+        .map_err(|e| AppError::BadRequest(e.context("Could not read CSV file")))?;
+
+    preview(&db_pool, bank_account_id, transactions)
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/bank-accounts/{bankAccountId}/csv-mapping",
+    responses(
+        (status = OK, description = "Success", body = CsvMapping, content_type = "application/json"),
+        (status = NOT_FOUND, description = "No mapping has been saved for the bank account")
+    ),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "getCsvMapping"
+)]
+pub async fn get_csv_mapping(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+) -> Result<Json<CsvMapping>, AppError> {
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    db::csv_mappings::get(&db_pool, bank_account_id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get CSV mapping")))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/bank-accounts/{bankAccountId}/csv-mapping",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = CsvMapping,
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "updateCsvMapping"
+)]
+pub async fn update_csv_mapping(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+    Json(mapping): Json<CsvMapping>,
+) -> Result<(), AppError> {
+    import::csv::validate_mapping(&mapping).map_err(AppError::BadRequest)?;
+
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    db::csv_mappings::set(&db_pool, bank_account_id, mapping)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not save CSV mapping")))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/bank-accounts/{bankAccountId}/imports",
+    responses(
+        (status = CREATED, description = "Success", body = Box<[Uuid]>, content_type = "application/json")
+    ),
+    request_body = ImportTransactionsRequest,
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "importTransactions"
+)]
+pub async fn create(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+    Json(request): Json<ImportTransactionsRequest>,
+) -> Result<(StatusCode, Json<Box<[Uuid]>>), AppError> {
+    for transaction in &request.transactions {
+        validate_budget(transaction.budget_id, transaction.amount)?;
+    }
+
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let plan_id = db::bank_accounts::get_plan_id(&db_pool, bank_account_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let budget_ids = get_budget_ids(&db_pool, plan_id, &request.transactions).await?;
+
+    let mut payee_ids = get_payee_ids_by_name(&db_pool, plan_id).await?;
+    let mut existing = ExistingTransactions::get(&db_pool, bank_account_id).await?;
+
+    let mut new_payees = Vec::new();
+    let mut transactions = Vec::new();
+
+    for request in request.transactions {
+        // skip anything already in the account, or listed twice with the same external id
+        if existing.contains(
+            request.external_id.as_ref(),
+            request.date,
+            request.amount,
+            payee_ids.get(&name_key(&request.payee_name)).copied(),
+        ) {
+            continue;
+        }
+
+        if let Some(external_id) = &request.external_id {
+            existing.external_ids.insert(external_id.clone());
+        }
+
+        let payee_id = *payee_ids
+            .entry(name_key(&request.payee_name))
+            .or_insert_with(|| {
+                let id = Uuid::new_v4();
+                new_payees.push((id, request.payee_name.trim().to_owned()));
+                id
+            });
+
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            payee_id,
+            request.date,
+            request.amount,
+            bank_account_id,
+            request.budget_id,
+            request.memo,
+        );
+
+        transactions.push((transaction, request.external_id));
+    }
+
+    let mut db_transaction = db::begin(&db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not import transactions")))?;
+
+    // read in the transaction so assignments made since the budgets were checked aren't lost
+    let mut budgets: HashMap<Uuid, Budget> =
+        db::budgets::get_by_ids_for_update(&mut db_transaction, &budget_ids)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?
+            .into_vec()
+            .into_iter()
+            .map(|budget| (budget.id, budget))
+            .collect();
+
+    for (id, name) in new_payees {
+        db::payees::create(
+            &mut db_transaction,
+            id,
+            CreatePayeeRequest::new(name, plan_id),
+        )
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create payee")))?;
+    }
+
+    let mut ids = Vec::with_capacity(transactions.len());
+
+    for (transaction, external_id) in transactions {
+        let id = transaction.id;
+
+        if let Some(budget) = transaction
+            .budget_id
+            .and_then(|budget_id| budgets.get_mut(&budget_id))
+        {
+            budget.assign_from_transaction(&transaction);
+        }
+
+        db::transactions::create(&mut db_transaction, transaction)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not import transactions")))?;
+
+        if let Some(external_id) = external_id {
+            db::imported_transactions::create(
+                &mut db_transaction,
+                id,
+                bank_account_id,
+                &external_id,
+            )
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not import transactions")))?;
+        }
+
+        ids.push(id);
+    }
+
+    // update budgets must happen after transaction create because the budget assignments
+    // have a foreign key to the transactions
+    for budget in budgets.into_values() {
+        db::budgets::update(&mut db_transaction, budget)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Could not update budget")))?;
+    }
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not import transactions")))?;
+
+    Ok((StatusCode::CREATED, Json(ids.into_boxed_slice())))
+}
+
+/// Matches the parsed transactions to existing payees and sets aside the ones that are
+/// already in the bank account
+pub(crate) async fn preview(
+    db_pool: &DbPool,
+    bank_account_id: Uuid,
+    transactions: Vec<ImportedTransaction>,
+) -> Result<ImportPreview, AppError> {
+    let plan_id = db::bank_accounts::get_plan_id(db_pool, bank_account_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let payee_ids = get_payee_ids_by_name(db_pool, plan_id).await?;
+    let existing = ExistingTransactions::get(db_pool, bank_account_id).await?;
+
+    let (already_imported, transactions) = transactions
+        .into_iter()
+        .map(|transaction| ImportedTransaction {
+            payee_id: payee_ids.get(&name_key(&transaction.payee_name)).copied(),
+            ..transaction
+        })
+        .partition(|transaction| {
+            existing.contains(
+                transaction.external_id.as_ref(),
+                transaction.date,
+                transaction.amount,
+                transaction.payee_id,
+            )
+        });
+
+    Ok(ImportPreview::new(transactions, already_imported))
+}
+
+/// The ids of the budgets the transactions are assigned to. Every budget must belong to the plan
+async fn get_budget_ids(
+    db_pool: &DbPool,
+    plan_id: Uuid,
+    transactions: &[ImportTransactionRequest],
+) -> Result<Box<[Uuid]>, AppError> {
+    let budget_ids: Box<[Uuid]> = transactions
+        .iter()
+        .filter_map(|transaction| transaction.budget_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let budgets: HashMap<Uuid, Budget> = db::budgets::get_by_ids(db_pool, &budget_ids)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?
+        .into_vec()
+        .into_iter()
+        .filter(|budget| budget.plan_id == plan_id)
+        .map(|budget| (budget.id, budget))
+        .collect();
+
+    if let Some(budget_id) = budget_ids.iter().find(|id| !budgets.contains_key(id)) {
+        return Err(AppError::NotFound(anyhow!(
+            "Budget not found with id {budget_id}"
+        )));
+    }
+
+    for budget in budgets.values() {
+        validate_not_archived(Some(budget))?;
+    }
+
+    Ok(budget_ids)
+}
+
+async fn get_payee_ids_by_name(
+    db_pool: &DbPool,
+    plan_id: Uuid,
+) -> Result<HashMap<String, Uuid>, AppError> {
+    let payees = db::payees::get(db_pool, plan_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get payees")))?;
+
+    Ok(payees
+        .iter()
+        .map(|payee| (name_key(&payee.name), payee.id))
+        .collect())
+}
+
+/// What's already in a bank account, to tell which imported transactions are duplicates
+struct ExistingTransactions {
+    external_ids: HashSet<String>,
+    /// The date, amount and payee of every transaction in the account
+    transactions: HashSet<(NaiveDate, Decimal, Uuid)>,
+}
+
+impl ExistingTransactions {
+    async fn get(db_pool: &DbPool, bank_account_id: Uuid) -> Result<Self, AppError> {
+        let (external_ids_result, transactions_result) = join!(
+            db::imported_transactions::get_external_ids(db_pool, bank_account_id),
+            db::transactions::get_all(db_pool, bank_account_id)
+        );
+
+        let external_ids = external_ids_result
+            .map_err(|e| e.to_app_error(anyhow!("Could not get imported transactions")))?;
+        let transactions = transactions_result
+            .map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))?;
+
+        Ok(Self {
+            external_ids: external_ids.into_vec().into_iter().collect(),
+            transactions: transactions
+                .iter()
+                .map(|transaction| (transaction.date, transaction.amount, transaction.payee_id))
+                .collect(),
+        })
+    }
+
+    /// Transactions with an external id are matched on it. Others are matched on their
+    /// date, amount and payee, so a transaction with a new payee is never a duplicate
+    fn contains(
+        &self,
+        external_id: Option<&String>,
+        date: NaiveDate,
+        amount: Decimal,
+        payee_id: Option<Uuid>,
+    ) -> bool {
+        match external_id {
+            Some(external_id) => self.external_ids.contains(external_id),
+            None => payee_id
+                .is_some_and(|payee_id| self.transactions.contains(&(date, amount, payee_id))),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use email_address::EmailAddress;
+use http::StatusCode;
+use tokio::join;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::{self, AuthenticatedUser},
+    db::{self, DbPool},
+    models::{
+        AcceptInvitationRequest, CreateInvitationRequest, CreateInvitationResponse, Invitation,
+        PlanRole,
+    },
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get, get_for_plan, create, revoke, accept),
+    components(schemas(
+        Invitation,
+        CreateInvitationRequest,
+        CreateInvitationResponse,
+        AcceptInvitationRequest,
+        PlanRole
+    ))
+)]
+pub struct Api;
+
+const API_TAG: &str = "Invitations";
+
+#[utoipa::path(
+    get,
+    path = "/api/invitations",
+    responses(
+        (status = OK, description = "Invitations for the signed in user's email", body = Box<[Invitation]>, content_type = "application/json")
+    ),
+    tag = API_TAG,
+    operation_id = "getInvitations"
+)]
+pub async fn get(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Box<[Invitation]>>, AppError> {
+    db::invitations::get_for_email(&db_pool, &user.email)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get invitations")))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/plans/{planId}/invitations",
+    responses(
+        (status = OK, description = "Success", body = Box<[Invitation]>, content_type = "application/json")
+    ),
+    params(
+        ("planId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "getPlanInvitations"
+)]
+pub async fn get_for_plan(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<Box<[Invitation]>>, AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Owner)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    db::invitations::get_for_plan(&db_pool, plan_id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get invitations")))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/plans/{planId}/invitations",
+    responses(
+        (status = CREATED, description = "Success", body = CreateInvitationResponse, content_type = "application/json")
+    ),
+    params(
+        ("planId" = Uuid, Path,)
+    ),
+    request_body = CreateInvitationRequest,
+    tag = API_TAG,
+    operation_id = "createInvitation"
+)]
+pub async fn create(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+    Json(request): Json<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<CreateInvitationResponse>), AppError> {
+    let email = request.email.trim();
+    if !EmailAddress::is_valid(email) {
+        return Err(AppError::BadRequest(anyhow!(
+            "\"{email}\" is not a valid email address"
+        )));
+    }
+
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Owner)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let (members_result, invitations_result) = join!(
+        db::plan_members::get(&db_pool, plan_id),
+        db::invitations::get_for_plan(&db_pool, plan_id)
+    );
+
+    let members =
+        members_result.map_err(|e| e.to_app_error(anyhow!("Could not get plan members")))?;
+    let invitations =
+        invitations_result.map_err(|e| e.to_app_error(anyhow!("Could not get invitations")))?;
+
+    if members
+        .iter()
+        .any(|member| member.email.eq_ignore_ascii_case(email))
+    {
+        return Err(AppError::BadRequest(anyhow!(
+            "{email} is already a member of this plan"
+        )));
+    }
+
+    if invitations
+        .iter()
+        .any(|invitation| invitation.email.eq_ignore_ascii_case(email))
+    {
+        return Err(AppError::BadRequest(anyhow!(
+            "{email} has already been invited to this plan"
+        )));
+    }
+
+    let id = Uuid::new_v4();
+    let token = auth::new_token();
+
+    db::invitations::create(
+        &db_pool,
+        Invitation::new(id, plan_id, email.into(), request.role, user.id),
+        &token,
+    )
+    .await
+    .map_err(|e| e.to_app_error(anyhow!("Could not create invitation")))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateInvitationResponse::new(id, token)),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/plans/{planId}/invitations/{invitationId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("planId" = Uuid, Path,),
+        ("invitationId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "revokeInvitation"
+)]
+pub async fn revoke(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((plan_id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<(), AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Owner)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let invitation = db::invitations::get_single(&db_pool, invitation_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get invitation")))?;
+
+    if invitation.plan_id != plan_id {
+        return Err(AppError::NotFound(anyhow!(
+            "Invitation not found with id {invitation_id}"
+        )));
+    }
+
+    db::invitations::delete(&db_pool, invitation_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to revoke invitation")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/invitations/{invitationId}/accept",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("invitationId" = Uuid, Path,)
+    ),
+    request_body = AcceptInvitationRequest,
+    tag = API_TAG,
+    operation_id = "acceptInvitation"
+)]
+pub async fn accept(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(invitation_id): Path<Uuid>,
+    Json(request): Json<AcceptInvitationRequest>,
+) -> Result<(), AppError> {
+    let invitation = db::invitations::get_with_token(&db_pool, invitation_id, &request.token)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get invitation")))?;
+
+    // the token shows the plan's owner passed the invitation on, and the email, which only one
+    // user can have, that it reached who they meant. An invitation for someone else looks the
+    // same as one that doesn't exist
+    if !invitation.email.eq_ignore_ascii_case(&user.email) {
+        return Err(AppError::NotFound(anyhow!(
+            "Invitation not found with id {invitation_id}"
+        )));
+    }
+
+    let members = db::plan_members::get(&db_pool, invitation.plan_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan members")))?;
+
+    if members.iter().any(|member| member.user_id == user.id) {
+        return Err(AppError::BadRequest(anyhow!(
+            "Already a member of plan {}",
+            invitation.plan_id
+        )));
+    }
+
+    let mut db_transaction = db::begin(&db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to accept invitation")))?;
+
+    db::plan_members::create(
+        &mut db_transaction,
+        invitation.plan_id,
+        user.id,
+        invitation.role,
+    )
+    .await
+    .map_err(|e| e.to_app_error(anyhow!("Failed to add plan member")))?;
+
+    db::invitations::delete(&mut db_transaction, invitation_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to accept invitation")))?;
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to accept invitation")))?;
+
+    Ok(())
+}
@@ -5,13 +5,14 @@ use axum::{
 };
 use http::StatusCode;
 use serde::Deserialize;
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use utoipa::{IntoParams, OpenApi};
 use uuid::Uuid;
 
 use crate::{
-    db,
-    models::{CreatePayeeRequest, Payee, UpdatePayeeRequest},
+    auth::AuthenticatedUser,
+    db::repositories::{OwnershipRepository, PayeeRepository},
+    models::{CreatePayeeRequest, Payee, PlanRole, UpdatePayeeRequest},
     AppError,
 };
 
@@ -26,7 +27,7 @@ const API_TAG: &str = "Payees";
 
 #[derive(Deserialize, IntoParams)]
 pub struct GetPayeesQuery {
-    user_id: Uuid,
+    plan_id: Uuid,
 }
 
 #[utoipa::path(
@@ -42,10 +43,18 @@ pub struct GetPayeesQuery {
     operation_id = "getPayees"
 )]
 pub async fn get(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Query(query): Query<GetPayeesQuery>,
 ) -> Result<Json<Box<[Payee]>>, AppError> {
-    db::payees::get(&db_pool, query.user_id)
+    ownership
+        .check_plan(user.id, query.plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    payees
+        .get(query.plan_id)
         .await
         .map(Json)
         .map_err(|e| e.to_app_error(anyhow!("Could not get payees")))
@@ -62,16 +71,24 @@ pub async fn get(
     operation_id = "createPayee"
 )]
 pub async fn create(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Json(request): Json<CreatePayeeRequest>,
 ) -> Result<(StatusCode, Json<Uuid>), AppError> {
-    if request.user_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("User Id must be set")));
+    if request.plan_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Plan Id must be set")));
     }
 
+    ownership
+        .check_plan(user.id, request.plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
     let id = Uuid::new_v4();
 
-    db::payees::create(&db_pool, id, request)
+    payees
+        .create(id, request)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Could not create payee")))?;
 
@@ -91,17 +108,26 @@ pub async fn create(
     operation_id = "updatePayee"
 )]
 pub async fn update(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdatePayeeRequest>,
 ) -> Result<(), AppError> {
-    let mut payee = db::payees::get_single(&db_pool, id)
+    ownership
+        .check_payee(user.id, id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not fetch payee to update")))?;
+
+    let mut payee = payees
+        .get_single(id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Could not fetch payee to update")))?;
 
     payee.name = request.name;
 
-    db::payees::update(&db_pool, payee)
+    payees
+        .update(payee)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update payee")))?;
 
@@ -121,15 +147,19 @@ pub async fn update(
     operation_id = "deletePayee"
 )]
 pub async fn delete(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<Uuid>,
 ) -> Result<(), AppError> {
-    // fetch to ensure payee exists
-    db::payees::get_single(&db_pool, id)
+    // also ensures the payee exists
+    ownership
+        .check_payee(user.id, id, PlanRole::Editor)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to get payee")))?;
 
-    db::payees::delete(&db_pool, id)
+    payees
+        .delete(id)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to delete payee")))?;
 
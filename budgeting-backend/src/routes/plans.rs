@@ -0,0 +1,376 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use tokio::join;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::{self, DbPool},
+    models::{
+        BudgetTarget, CopyPlanRequest, CreatePlanRequest, GetReadyToAssignResponse, Plan, PlanCopy,
+        PlanMember, PlanRole, UpdatePlanMemberRequest, UpdatePlanRequest,
+    },
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get,
+        create,
+        update,
+        copy,
+        get_ready_to_assign,
+        get_members,
+        update_member,
+        remove_member
+    ),
+    components(schemas(
+        Plan,
+        PlanMember,
+        PlanRole,
+        UpdatePlanMemberRequest,
+        CreatePlanRequest,
+        UpdatePlanRequest,
+        CopyPlanRequest,
+        GetReadyToAssignResponse
+    ))
+)]
+pub struct Api;
+
+const API_TAG: &str = "Plans";
+
+#[utoipa::path(
+    get,
+    path = "/api/plans",
+    responses(
+        (status = OK, description = "Success", body = Box<[Plan]>, content_type = "application/json")
+    ),
+    tag = API_TAG,
+    operation_id = "getPlans"
+)]
+pub async fn get(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Box<[Plan]>>, AppError> {
+    db::plans::get(&db_pool, user.id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plans")))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/plans",
+    responses(
+        (status = CREATED, description = "Success", body = Uuid, content_type = "application/json")
+    ),
+    request_body = CreatePlanRequest,
+    tag = API_TAG,
+    operation_id = "createPlan"
+)]
+pub async fn create(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<CreatePlanRequest>,
+) -> Result<(StatusCode, Json<Uuid>), AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!("Plan name cannot be empty")));
+    }
+
+    let id = Uuid::new_v4();
+
+    db::plans::create(&db_pool, Plan::new(id, name.into()), user.id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create plan")))?;
+
+    Ok((StatusCode::CREATED, Json(id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/plans/{planId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("planId" = Uuid, Path,),
+    ),
+    request_body = UpdatePlanRequest,
+    tag = API_TAG,
+    operation_id = "updatePlan"
+)]
+pub async fn update(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdatePlanRequest>,
+) -> Result<(), AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!("Plan name cannot be empty")));
+    }
+
+    db::ownership::check_plan(&db_pool, user.id, id, PlanRole::Owner)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not fetch plan to update")))?;
+
+    let mut plan = db::plans::get_single(&db_pool, id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not fetch plan to update")))?;
+
+    plan.name = name.into();
+
+    db::plans::update(&db_pool, plan)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to update plan")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/plans/{planId}/copy",
+    responses(
+        (status = CREATED, description = "Success", body = Uuid, content_type = "application/json")
+    ),
+    params(
+        ("planId" = Uuid, Path,),
+    ),
+    request_body = CopyPlanRequest,
+    tag = API_TAG,
+    operation_id = "copyPlan"
+)]
+pub async fn copy(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CopyPlanRequest>,
+) -> Result<(StatusCode, Json<Uuid>), AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(anyhow!("Plan name cannot be empty")));
+    }
+
+    db::ownership::check_plan(&db_pool, user.id, id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan to copy")))?;
+
+    let (plan_result, groups_result, budgets_result) = join!(
+        db::plans::get_single(&db_pool, id),
+        db::budget_groups::get(&db_pool, id),
+        db::budgets::get(&db_pool, id, false)
+    );
+
+    let plan = plan_result.map_err(|e| e.to_app_error(anyhow!("Could not get plan to copy")))?;
+    let groups =
+        groups_result.map_err(|e| e.to_app_error(anyhow!("Could not get budget groups")))?;
+    let budgets = budgets_result.map_err(|e| e.to_app_error(anyhow!("Could not get budgets")))?;
+
+    let PlanCopy {
+        plan,
+        groups,
+        budgets,
+    } = plan.copy(name.into(), &groups, &budgets);
+
+    let mut db_transaction = db::begin(&db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to copy plan")))?;
+
+    db::plans::create(&mut db_transaction, plan.clone(), user.id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to create plan")))?;
+
+    for group in groups {
+        db::budget_groups::create(&mut db_transaction, group)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Failed to create budget group")))?;
+    }
+
+    for budget in budgets {
+        if let Some(BudgetTarget::Repeating { schedule, .. }) = &budget.target {
+            db::schedule::create(&mut db_transaction, schedule.clone())
+                .await
+                .map_err(|e| e.to_app_error(anyhow!("Failed to create schedule")))?;
+        }
+
+        db::budgets::create(&mut db_transaction, budget)
+            .await
+            .map_err(|e| e.to_app_error(anyhow!("Failed to create budget")))?;
+    }
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to copy plan")))?;
+
+    Ok((StatusCode::CREATED, Json(plan.id)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/plans/{planId}/ready-to-assign",
+    responses(
+        (status = OK, description = "Success", body = GetReadyToAssignResponse, content_type = "application/json")
+    ),
+    params(
+        ("planId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "getReadyToAssign"
+)]
+pub async fn get_ready_to_assign(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<GetReadyToAssignResponse>, AppError> {
+    // the pool of a plan that doesn't exist would just be 0, so check it exists first
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let amount = db::ready_to_assign::get(&db_pool, plan_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get ready to assign")))?;
+
+    Ok(Json(GetReadyToAssignResponse { amount }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/plans/{planId}/members",
+    responses(
+        (status = OK, description = "Success", body = Box<[PlanMember]>, content_type = "application/json")
+    ),
+    params(
+        ("planId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "getPlanMembers"
+)]
+pub async fn get_members(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<Box<[PlanMember]>>, AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    db::plan_members::get(&db_pool, plan_id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan members")))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/plans/{planId}/members/{userId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    request_body = UpdatePlanMemberRequest,
+    params(
+        ("planId" = Uuid, Path,),
+        ("userId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "updatePlanMember"
+)]
+pub async fn update_member(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((plan_id, member_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdatePlanMemberRequest>,
+) -> Result<(), AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Owner)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let members = db::plan_members::get(&db_pool, plan_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan members")))?;
+
+    let Some(member) = members.iter().find(|member| member.user_id == member_id) else {
+        return Err(AppError::NotFound(anyhow!(
+            "user with id {member_id} is not a member of plan {plan_id}"
+        )));
+    };
+
+    let owners = members
+        .iter()
+        .filter(|member| member.role == PlanRole::Owner)
+        .count();
+    if member.role == PlanRole::Owner && request.role != PlanRole::Owner && owners == 1 {
+        return Err(AppError::BadRequest(anyhow!(
+            "A plan must keep at least one owner"
+        )));
+    }
+
+    db::plan_members::update_role(&db_pool, plan_id, member_id, request.role)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to update plan member")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/plans/{planId}/members/{userId}",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    params(
+        ("planId" = Uuid, Path,),
+        ("userId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "removePlanMember"
+)]
+pub async fn remove_member(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((plan_id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<(), AppError> {
+    // anyone can leave a plan, but only an owner can remove someone else
+    let role = if member_id == user.id {
+        PlanRole::Viewer
+    } else {
+        PlanRole::Owner
+    };
+
+    db::ownership::check_plan(&db_pool, user.id, plan_id, role)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let members = db::plan_members::get(&db_pool, plan_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan members")))?;
+
+    let Some(member) = members.iter().find(|member| member.user_id == member_id) else {
+        return Err(AppError::NotFound(anyhow!(
+            "user with id {member_id} is not a member of plan {plan_id}"
+        )));
+    };
+
+    let owners = members
+        .iter()
+        .filter(|member| member.role == PlanRole::Owner)
+        .count();
+    if member.role == PlanRole::Owner && owners == 1 {
+        return Err(AppError::BadRequest(anyhow!(
+            "A plan must keep at least one owner"
+        )));
+    }
+
+    db::plan_members::delete(&db_pool, plan_id, member_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to remove plan member")))?;
+
+    Ok(())
+}
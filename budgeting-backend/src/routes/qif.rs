@@ -0,0 +1,96 @@
+use anyhow::anyhow;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    Json,
+};
+use http::{
+    header::{HeaderName, CONTENT_TYPE},
+    StatusCode,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::{self, DbPool},
+    import,
+    models::PlanRole,
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(import, export))]
+pub struct Api;
+
+const API_TAG: &str = "QIF";
+
+#[derive(Deserialize, IntoParams)]
+pub struct ImportQifQuery {
+    /// Names the bank account when the file has no `!Account` header
+    account_name: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/plans/{planId}/imports/qif",
+    responses(
+        (status = CREATED, description = "Success", body = Box<[Uuid]>, content_type = "application/json"),
+        (status = PAYLOAD_TOO_LARGE, description = "The file is larger than 50 MB")
+    ),
+    request_body(content = String, description = "The QIF file", content_type = "application/qif"),
+    params(
+        ("planId" = Uuid, Path,),
+        ImportQifQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "importQif"
+)]
+pub async fn import(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<ImportQifQuery>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Box<[Uuid]>>), AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let bank_account_ids = import::qif::import(
+        &db_pool,
+        plan_id,
+        &String::from_utf8_lossy(&body),
+        query.account_name.as_deref(),
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(bank_account_ids)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/bank-accounts/{bankAccountId}/qif",
+    responses(
+        (status = OK, description = "Success", body = String, content_type = "application/qif")
+    ),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "exportQif"
+)]
+pub async fn export(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+) -> Result<([(HeaderName, &'static str); 1], String), AppError> {
+    db::ownership::check_bank_account(&db_pool, user.id, bank_account_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let qif = import::qif::export(&db_pool, bank_account_id).await?;
+
+    Ok(([(CONTENT_TYPE, "application/qif")], qif))
+}
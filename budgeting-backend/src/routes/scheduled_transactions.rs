@@ -0,0 +1,536 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{Local, NaiveDate};
+use http::StatusCode;
+use std::sync::Arc;
+use tokio::join;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::{
+        repositories::{
+            BankAccountRepository, BudgetRepository, DueTransactions, OwnershipRepository,
+            PayeeRepository, ScheduledTransactionRepository,
+        },
+        Error,
+    },
+    models::{
+        CreateScheduledTransactionRequest, PlanRole, Schedule, ScheduledTransaction,
+        UpdateScheduledTransactionRequest,
+    },
+    AppError,
+};
+
+use super::transactions::validate_same_plan;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get, create, update, delete, post_due),
+    components(schemas(
+        ScheduledTransaction,
+        CreateScheduledTransactionRequest,
+        UpdateScheduledTransactionRequest
+    ))
+)]
+pub struct Api;
+
+const API_TAG: &str = "ScheduledTransactions";
+
+#[utoipa::path(
+    get,
+    path = "/api/bank-accounts/{bankAccountId}/scheduled-transactions",
+    responses(
+        (status = OK, description = "Success", body = Box<[ScheduledTransaction]>, content_type = "application/json")
+    ),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "getScheduledTransactions"
+)]
+pub async fn get(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(scheduled_transactions): State<Arc<dyn ScheduledTransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+) -> Result<Json<Box<[ScheduledTransaction]>>, AppError> {
+    if bank_account_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Bank account id must be set")));
+    }
+
+    ownership
+        .check_bank_account(user.id, bank_account_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    scheduled_transactions
+        .get(bank_account_id)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get scheduled transactions")))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/bank-accounts/{bankAccountId}/scheduled-transactions",
+    responses(
+        (status = CREATED, description = "Success", body = Uuid, content_type = "application/json")
+    ),
+    request_body = CreateScheduledTransactionRequest,
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "createScheduledTransaction"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(scheduled_transactions): State<Arc<dyn ScheduledTransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+    Json(request): Json<CreateScheduledTransactionRequest>,
+) -> Result<(StatusCode, Json<Uuid>), AppError> {
+    if bank_account_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Bank Account Id must be set")));
+    }
+
+    ownership
+        .check_bank_account(user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    validate_references(&*ownership, user.id, request.payee_id, request.budget_id).await?;
+
+    let budget = budgets
+        .get_single(request.budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get budget")))?;
+    validate_same_plan(
+        &*bank_accounts,
+        &*payees,
+        bank_account_id,
+        request.payee_id,
+        Some(&budget),
+    )
+    .await?;
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: request.schedule.period,
+    };
+    let next_occurrence = next_occurrence_from_today(&schedule)?;
+
+    let id = Uuid::new_v4();
+
+    let scheduled_transaction = ScheduledTransaction {
+        id,
+        payee_id: request.payee_id,
+        amount: request.amount,
+        bank_account_id,
+        budget_id: request.budget_id,
+        next_occurrence,
+        schedule,
+    };
+
+    scheduled_transactions
+        .create(scheduled_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not create scheduled transaction")))?;
+
+    Ok((StatusCode::CREATED, Json(id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/bank-accounts/{bankAccountId}/scheduled-transactions/{scheduledTransactionId}",
+    responses(
+        (status = OK, description = "Success",)
+    ),
+    request_body = UpdateScheduledTransactionRequest,
+    params(
+        ("bankAccountId" = Uuid, Path,),
+        ("scheduledTransactionId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "updateScheduledTransaction"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn update(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(scheduled_transactions): State<Arc<dyn ScheduledTransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((bank_account_id, scheduled_transaction_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateScheduledTransactionRequest>,
+) -> Result<(), AppError> {
+    let mut scheduled_transaction = get_for_bank_account(
+        &*ownership,
+        &*scheduled_transactions,
+        user.id,
+        bank_account_id,
+        scheduled_transaction_id,
+    )
+    .await?;
+
+    validate_references(&*ownership, user.id, request.payee_id, request.budget_id).await?;
+
+    let budget = budgets
+        .get_single(request.budget_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get budget")))?;
+    validate_same_plan(
+        &*bank_accounts,
+        &*payees,
+        bank_account_id,
+        request.payee_id,
+        Some(&budget),
+    )
+    .await?;
+
+    if scheduled_transaction.schedule.period != request.schedule.period {
+        scheduled_transaction.schedule.period = request.schedule.period;
+        scheduled_transaction.next_occurrence =
+            next_occurrence_from_today(&scheduled_transaction.schedule)?;
+    }
+
+    scheduled_transaction.payee_id = request.payee_id;
+    scheduled_transaction.amount = request.amount;
+    scheduled_transaction.budget_id = request.budget_id;
+
+    scheduled_transactions
+        .update(scheduled_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to update scheduled transaction")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/bank-accounts/{bankAccountId}/scheduled-transactions/{scheduledTransactionId}",
+    responses(
+        (status = OK, description = "Success",)
+    ),
+    params(
+        ("bankAccountId" = Uuid, Path,),
+        ("scheduledTransactionId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "deleteScheduledTransaction"
+)]
+pub async fn delete(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(scheduled_transactions): State<Arc<dyn ScheduledTransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((bank_account_id, scheduled_transaction_id)): Path<(Uuid, Uuid)>,
+) -> Result<(), AppError> {
+    let scheduled_transaction = get_for_bank_account(
+        &*ownership,
+        &*scheduled_transactions,
+        user.id,
+        bank_account_id,
+        scheduled_transaction_id,
+    )
+    .await?;
+
+    scheduled_transactions
+        .delete(scheduled_transaction_id, scheduled_transaction.schedule.id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to delete scheduled transaction")))?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/bank-accounts/{bankAccountId}/scheduled-transactions/post-due",
+    responses(
+        (status = OK, description = "Ids of the transactions that were created", body = Box<[Uuid]>, content_type = "application/json")
+    ),
+    params(
+        ("bankAccountId" = Uuid, Path,)
+    ),
+    tag = API_TAG,
+    operation_id = "postDueScheduledTransactions"
+)]
+pub async fn post_due(
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(scheduled_transactions): State<Arc<dyn ScheduledTransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(bank_account_id): Path<Uuid>,
+) -> Result<Json<Box<[Uuid]>>, AppError> {
+    ownership
+        .check_bank_account(user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let today = Local::now().date_naive();
+
+    let due = scheduled_transactions
+        .get(bank_account_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get scheduled transactions")))?
+        .into_vec()
+        .into_iter()
+        .filter_map(|mut scheduled_transaction| {
+            let from = scheduled_transaction.next_occurrence;
+            let transactions = scheduled_transaction.take_due_transactions(today);
+
+            (!transactions.is_empty()).then_some(DueTransactions {
+                scheduled_transaction_id: scheduled_transaction.id,
+                budget_id: scheduled_transaction.budget_id,
+                from,
+                to: scheduled_transaction.next_occurrence,
+                transactions,
+            })
+        })
+        .collect();
+
+    // every due occurrence is posted together, so a failure part way through doesn't
+    // leave transactions behind that would be posted again next time
+    scheduled_transactions
+        .post_due(due)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not post scheduled transactions")))
+}
+
+async fn get_for_bank_account(
+    ownership: &dyn OwnershipRepository,
+    scheduled_transactions: &dyn ScheduledTransactionRepository,
+    user_id: Uuid,
+    bank_account_id: Uuid,
+    scheduled_transaction_id: Uuid,
+) -> Result<ScheduledTransaction, AppError> {
+    ownership
+        .check_bank_account(user_id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    let scheduled_transaction = scheduled_transactions
+        .get_single(scheduled_transaction_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get scheduled transaction")))?;
+
+    if scheduled_transaction.bank_account_id != bank_account_id {
+        return Err(AppError::NotFound(anyhow!(
+            "Scheduled transaction not found with id {scheduled_transaction_id}"
+        )));
+    }
+
+    Ok(scheduled_transaction)
+}
+
+fn next_occurrence_from_today(schedule: &Schedule) -> Result<NaiveDate, AppError> {
+    schedule.period.validate().map_err(AppError::BadRequest)?;
+
+    schedule
+        .first_on_or_after(Local::now().date_naive())
+        .ok_or_else(|| AppError::BadRequest(anyhow!("Schedule has no occurrences from today")))
+}
+
+async fn validate_references(
+    ownership: &dyn OwnershipRepository,
+    user_id: Uuid,
+    payee_id: Uuid,
+    budget_id: Uuid,
+) -> Result<(), AppError> {
+    if payee_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Payee Id must be set")));
+    }
+
+    if budget_id.is_nil() {
+        return Err(AppError::BadRequest(anyhow!("Budget Id must be set")));
+    }
+
+    let (budget_result, payee_result) = join!(
+        ownership.check_budget(user_id, budget_id, PlanRole::Editor),
+        ownership.check_payee(user_id, payee_id, PlanRole::Editor)
+    );
+
+    match budget_result {
+        Ok(()) => (),
+        Err(Error::NotFound) => {
+            return Err(AppError::NotFound(anyhow!(
+                "Budget not found with id {budget_id}"
+            )));
+        }
+        Err(e) => return Err(e.to_app_error(anyhow!("Could not get budget"))),
+    }
+
+    match payee_result {
+        Ok(()) => Ok(()),
+        Err(Error::NotFound) => Err(AppError::NotFound(anyhow!(
+            "Payee not found with id {payee_id}"
+        ))),
+        Err(e) => Err(e.to_app_error(anyhow!("Could not get payee"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Days;
+    use rust_decimal_macros::dec;
+
+    use crate::{
+        db::in_memory::InMemoryRepository,
+        models::{
+            BankAccount, Budget, BudgetAssignmentSource, CreateScheduleRequest, Payee,
+            SchedulePeriod, User,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn post_due_should_create_due_transactions_and_assign_them() {
+        let repository = Arc::new(InMemoryRepository::default());
+        let user = User::new(
+            Uuid::new_v4(),
+            "name".into(),
+            "email@email.com".into(),
+            None,
+        );
+        let plan_id = Uuid::new_v4();
+        let bank_account_id = Uuid::new_v4();
+        let payee_id = Uuid::new_v4();
+        let budget = Budget::new(
+            Uuid::new_v4(),
+            "budget".into(),
+            None,
+            plan_id,
+            vec![],
+            None,
+            None,
+        );
+        let yesterday = Local::now().date_naive() - Days::new(1);
+        let scheduled_transaction = ScheduledTransaction {
+            id: Uuid::new_v4(),
+            payee_id,
+            amount: dec!(-10),
+            bank_account_id,
+            budget_id: budget.id,
+            schedule: Schedule {
+                id: Uuid::new_v4(),
+                period: SchedulePeriod::Yearly {
+                    starting_on: yesterday,
+                },
+            },
+            next_occurrence: yesterday,
+        };
+        repository.add_member(plan_id, user.id, PlanRole::Editor);
+        repository.add_bank_account(BankAccount::new(
+            bank_account_id,
+            "account".into(),
+            dec!(0),
+            plan_id,
+            dec!(0),
+        ));
+        repository.add_payee(Payee::new(payee_id, "payee".into(), plan_id));
+        repository.add_budget(budget.clone());
+        repository.add_scheduled_transaction(scheduled_transaction.clone());
+
+        let Json(ids) = post_due(
+            State(repository.clone()),
+            State(repository.clone()),
+            AuthenticatedUser(user),
+            Path(bank_account_id),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ids.len(), 1);
+        let transaction = repository.transaction(ids[0]).unwrap();
+        assert_eq!(transaction.date, yesterday);
+        assert_eq!(transaction.budget_id, Some(budget.id));
+
+        let budget = repository.budget(budget.id).unwrap();
+        assert_eq!(budget.assignments.len(), 1);
+        assert_eq!(
+            budget.assignments[0].source,
+            BudgetAssignmentSource::Transaction {
+                from_transaction_id: transaction.id
+            }
+        );
+
+        let scheduled_transaction = repository
+            .scheduled_transaction(scheduled_transaction.id)
+            .unwrap();
+        assert!(scheduled_transaction.next_occurrence > Local::now().date_naive());
+    }
+
+    #[tokio::test]
+    async fn create_should_reject_budgets_in_other_plans() {
+        let repository = Arc::new(InMemoryRepository::default());
+        let user = User::new(
+            Uuid::new_v4(),
+            "name".into(),
+            "email@email.com".into(),
+            None,
+        );
+        let plan_id = Uuid::new_v4();
+        let other_plan_id = Uuid::new_v4();
+        let bank_account_id = Uuid::new_v4();
+        let payee_id = Uuid::new_v4();
+        let other_budget = Budget::new(
+            Uuid::new_v4(),
+            "other budget".into(),
+            None,
+            other_plan_id,
+            vec![],
+            None,
+            None,
+        );
+        repository.add_member(plan_id, user.id, PlanRole::Editor);
+        repository.add_member(other_plan_id, user.id, PlanRole::Editor);
+        repository.add_bank_account(BankAccount::new(
+            bank_account_id,
+            "account".into(),
+            dec!(0),
+            plan_id,
+            dec!(0),
+        ));
+        repository.add_payee(Payee::new(payee_id, "payee".into(), plan_id));
+        repository.add_budget(other_budget.clone());
+
+        let result = create(
+            State(repository.clone()),
+            State(repository.clone()),
+            State(repository.clone()),
+            State(repository.clone()),
+            State(repository.clone()),
+            AuthenticatedUser(user),
+            Path(bank_account_id),
+            Json(CreateScheduledTransactionRequest {
+                payee_id,
+                amount: dec!(-10),
+                budget_id: other_budget.id,
+                schedule: CreateScheduleRequest {
+                    period: SchedulePeriod::Yearly {
+                        starting_on: Local::now().date_naive(),
+                    },
+                },
+            }),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(
+            ScheduledTransactionRepository::get(&*repository, bank_account_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
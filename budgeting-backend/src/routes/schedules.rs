@@ -0,0 +1,148 @@
+use anyhow::anyhow;
+use axum::Json;
+use chrono::NaiveDate;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    models::{PreviewScheduleRequest, Schedule},
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(preview), components(schemas(PreviewScheduleRequest)))]
+pub struct Api;
+
+const API_TAG: &str = "Schedules";
+
+const MAX_PREVIEW_OCCURRENCES: usize = 100;
+
+#[utoipa::path(
+    post,
+    path = "/api/schedules/preview",
+    responses(
+        (status = OK, description = "Success", body = Box<[NaiveDate]>, content_type = "application/json")
+    ),
+    request_body = PreviewScheduleRequest,
+    tag = API_TAG,
+    operation_id = "previewSchedule"
+)]
+pub async fn preview(
+    Json(request): Json<PreviewScheduleRequest>,
+) -> Result<Json<Box<[NaiveDate]>>, AppError> {
+    if request.to < request.from {
+        return Err(AppError::BadRequest(anyhow!("to must not be before from")));
+    }
+
+    request.period.validate().map_err(AppError::BadRequest)?;
+
+    let schedule = Schedule {
+        id: Uuid::nil(),
+        period: request.period,
+    };
+
+    Ok(Json(
+        schedule
+            .occurrences_between(request.from, request.to)
+            .take(MAX_PREVIEW_OCCURRENCES)
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Days;
+
+    use crate::models::{SchedulePeriod, SchedulePeriodType};
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[tokio::test]
+    pub async fn preview_returns_occurrences() {
+        let Json(occurrences) = preview(Json(PreviewScheduleRequest::new(
+            SchedulePeriod::Monthly {
+                starting_on: date(2024, 1, 31),
+            },
+            date(2024, 2, 1),
+            date(2024, 4, 30),
+        )))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            occurrences,
+            vec![date(2024, 2, 29), date(2024, 3, 31), date(2024, 4, 30)].into_boxed_slice()
+        );
+    }
+
+    #[tokio::test]
+    pub async fn preview_limits_occurrences() {
+        let Json(occurrences) = preview(Json(PreviewScheduleRequest::new(
+            SchedulePeriod::Weekly {
+                starting_on: date(2024, 1, 1),
+            },
+            date(2024, 1, 1),
+            date(2099, 1, 1),
+        )))
+        .await
+        .unwrap();
+
+        assert_eq!(occurrences.len(), MAX_PREVIEW_OCCURRENCES);
+    }
+
+    #[tokio::test]
+    pub async fn preview_rejects_zero_periods() {
+        let result = preview(Json(PreviewScheduleRequest::new(
+            SchedulePeriod::Custom {
+                starting_on: Some(date(2024, 1, 1)),
+                period: SchedulePeriodType::Weekly,
+                every_x_periods: 0,
+            },
+            date(2024, 1, 1),
+            date(2024, 2, 1),
+        )))
+        .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    pub async fn preview_ends_at_the_last_date() {
+        let Json(occurrences) = preview(Json(PreviewScheduleRequest::new(
+            SchedulePeriod::Custom {
+                starting_on: Some(NaiveDate::MAX - Days::new(1)),
+                period: SchedulePeriodType::Yearly,
+                every_x_periods: u8::MAX,
+            },
+            NaiveDate::MAX - Days::new(1),
+            NaiveDate::MAX,
+        )))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            occurrences,
+            vec![NaiveDate::MAX - Days::new(1)].into_boxed_slice()
+        );
+    }
+
+    #[tokio::test]
+    pub async fn preview_rejects_custom_without_starting_on() {
+        let result = preview(Json(PreviewScheduleRequest::new(
+            SchedulePeriod::Custom {
+                starting_on: None,
+                period: SchedulePeriodType::Weekly,
+                every_x_periods: 1,
+            },
+            date(2024, 1, 1),
+            date(2024, 2, 1),
+        )))
+        .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+}
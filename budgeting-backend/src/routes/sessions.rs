@@ -0,0 +1,91 @@
+use anyhow::anyhow;
+use axum::{extract::State, Json};
+use chrono::Utc;
+use http::{HeaderMap, StatusCode};
+use utoipa::OpenApi;
+
+use crate::{
+    auth::{self, AuthenticatedUser},
+    db::{self, DbPool},
+    models::{Session, SignInRequest},
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(create, delete), components(schemas(Session, SignInRequest)))]
+pub struct Api;
+
+const API_TAG: &str = "Sessions";
+
+#[utoipa::path(
+    post,
+    path = "/api/sessions",
+    responses(
+        (status = CREATED, description = "Success", body = Session, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Incorrect email or password")
+    ),
+    request_body = SignInRequest,
+    security(()),
+    tag = API_TAG,
+    operation_id = "signIn"
+)]
+pub async fn create(
+    State(db_pool): State<DbPool>,
+    Json(request): Json<SignInRequest>,
+) -> Result<(StatusCode, Json<Session>), AppError> {
+    let user_id = match db::users::get_password_hash(&db_pool, request.email.trim()).await {
+        Ok((user_id, Some(password_hash)))
+            if auth::verify_password(&request.password, &password_hash) =>
+        {
+            user_id
+        }
+        Ok(_) | Err(db::Error::NotFound) => {
+            return Err(AppError::Unauthorized(anyhow!(
+                "Incorrect email or password"
+            )))
+        }
+        Err(e) => return Err(e.to_app_error(anyhow!("Could not sign in"))),
+    };
+
+    let session = auth::new_session(user_id);
+
+    let mut db_transaction = db::begin(&db_pool)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not sign in")))?;
+
+    db::sessions::delete_expired(&mut db_transaction, user_id, Utc::now())
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not sign in")))?;
+
+    db::sessions::create(&mut db_transaction, session.clone())
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not sign in")))?;
+
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not sign in")))?;
+
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/sessions",
+    responses(
+        (status = OK, description = "Success")
+    ),
+    tag = API_TAG,
+    operation_id = "signOut"
+)]
+pub async fn delete(
+    State(db_pool): State<DbPool>,
+    _user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> Result<(), AppError> {
+    let token = auth::bearer_token(&headers)
+        .ok_or_else(|| AppError::Unauthorized(anyhow!("Sign in to continue")))?;
+
+    db::sessions::delete(&db_pool, token)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not sign out")))
+}
@@ -1,51 +1,163 @@
 use anyhow::anyhow;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use http::StatusCode;
-use sqlx::MySqlPool;
+use serde::Deserialize;
+use std::sync::Arc;
 use tokio::join;
-use utoipa::OpenApi;
+use utoipa::{IntoParams, OpenApi};
 use uuid::Uuid;
 
-use crate::models::BudgetAssignmentSource;
+use rust_decimal::Decimal;
+
+use crate::models::Budget;
 use crate::{
-    db::{self, Error},
-    models::{CreateTransactionRequest, Transaction, UpdateTransactionRequest},
+    auth::AuthenticatedUser,
+    db::{
+        repositories::{
+            BankAccountRepository, BudgetRepository, OwnershipRepository, PayeeRepository,
+            TransactionRepository,
+        },
+        Error,
+    },
+    models::{
+        CreateTransactionRequest, PlanRole, SortOrder, Transaction, TransactionCursor,
+        TransactionFilter, TransactionFlow, TransactionPage, TransactionPageRequest,
+        TransactionSearchPage, TransactionSearchResult, TransactionSort, TransactionTotals,
+        UpdateTransactionRequest,
+    },
     AppError,
 };
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(get, create, update, delete),
-    components(schemas(Transaction, CreateTransactionRequest, UpdateTransactionRequest))
+    paths(get, search, create, update, delete),
+    components(schemas(
+        Transaction,
+        CreateTransactionRequest,
+        UpdateTransactionRequest,
+        TransactionPage,
+        TransactionSearchPage,
+        TransactionSearchResult,
+        TransactionTotals,
+        TransactionFlow,
+        TransactionSort,
+        SortOrder
+    ))
 )]
 pub struct Api;
 
 const API_TAG: &str = "Transactions";
 
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetTransactionsQuery {
+    /// Defaults to `Date`
+    #[serde(default)]
+    sort: TransactionSort,
+    /// Defaults to `Descending`, newest or largest first
+    #[serde(default)]
+    order: SortOrder,
+    /// The `next_cursor` of the previous page, with the same filters and sort
+    cursor: Option<String>,
+    /// Defaults to 100, at most 500
+    limit: Option<u32>,
+}
+
+impl GetTransactionsQuery {
+    fn page_request(&self) -> Result<TransactionPageRequest, AppError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(AppError::BadRequest(anyhow!(
+                "limit must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+
+        let after = self
+            .cursor
+            .as_deref()
+            .map(|cursor| TransactionCursor::parse(self.sort, cursor))
+            .transpose()
+            .map_err(AppError::BadRequest)?;
+
+        Ok(TransactionPageRequest {
+            sort: self.sort,
+            order: self.order,
+            after,
+            limit,
+        })
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/api/bank-accounts/{bankAccountId}/transactions",
     responses(
-        (status = OK, description = "Success", body = Box<[Transaction]>, content_type = "application/json")
+        (status = OK, description = "Success", body = TransactionPage, content_type = "application/json")
     ),
     params(
-        ("bankAccountId" = Uuid, Path,)
+        ("bankAccountId" = Uuid, Path,),
+        TransactionFilter,
+        GetTransactionsQuery,
     ),
     tag = API_TAG,
     operation_id = "getTransactions"
 )]
 pub async fn get(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(bank_account_id): Path<Uuid>,
-) -> Result<Json<Box<[Transaction]>>, AppError> {
+    Query(filter): Query<TransactionFilter>,
+    Query(query): Query<GetTransactionsQuery>,
+) -> Result<Json<TransactionPage>, AppError> {
     if bank_account_id.is_nil() {
         return Err(AppError::BadRequest(anyhow!("Bank account id must be set")));
     }
 
-    db::transactions::get(&db_pool, bank_account_id)
+    filter.validate().map_err(AppError::BadRequest)?;
+    let page = query.page_request()?;
+
+    ownership
+        .check_bank_account(user.id, bank_account_id, PlanRole::Viewer)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+
+    transactions
+        .get(bank_account_id, &filter, page)
+        .await
+        .map(Json)
+        .map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/transactions",
+    responses(
+        (status = OK, description = "Success", body = TransactionSearchPage, content_type = "application/json")
+    ),
+    params(
+        TransactionFilter,
+        GetTransactionsQuery,
+    ),
+    tag = API_TAG,
+    operation_id = "searchTransactions"
+)]
+pub async fn search(
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(filter): Query<TransactionFilter>,
+    Query(query): Query<GetTransactionsQuery>,
+) -> Result<Json<TransactionSearchPage>, AppError> {
+    filter.validate().map_err(AppError::BadRequest)?;
+    let page = query.page_request()?;
+
+    transactions
+        .get_for_user(user.id, &filter, page)
         .await
         .map(Json)
         .map_err(|e| e.to_app_error(anyhow!("Could not get transactions")))
@@ -64,8 +176,14 @@ pub async fn get(
     tag = API_TAG,
     operation_id = "createTransaction"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(bank_account_id): Path<Uuid>,
     Json(request): Json<CreateTransactionRequest>,
 ) -> Result<(StatusCode, Json<Uuid>), AppError> {
@@ -77,21 +195,24 @@ pub async fn create(
         return Err(AppError::BadRequest(anyhow!("Bank Account Id must be set")));
     }
 
-    if request.budget_id.is_nil() {
-        return Err(AppError::BadRequest(anyhow!("Budget Id must be set")));
-    }
+    validate_budget(request.budget_id, request.amount)?;
+
+    ownership
+        .check_bank_account(user.id, bank_account_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
 
     let id = Uuid::new_v4();
 
     let (budget_result, payee_result) = join!(
-        db::budgets::get_single(&db_pool, request.budget_id),
-        db::payees::get_single(&db_pool, request.payee_id)
+        get_budget(&*ownership, &*budgets, user.id, request.budget_id),
+        ownership.check_payee(user.id, request.payee_id, PlanRole::Editor)
     );
 
-    let mut budget = budget_result.map_err(|e| e.to_app_error(anyhow!("Could not get budget")))?;
+    let budget = budget_result.map_err(|e| e.to_app_error(anyhow!("Could not get budget")))?;
 
     match payee_result {
-        Ok(_) => (),
+        Ok(()) => (),
         Err(Error::NotFound) => {
             return Err(AppError::NotFound(anyhow::anyhow!(
                 "Payee not found with id {}",
@@ -101,6 +222,16 @@ pub async fn create(
         Err(e) => return Err(e.to_app_error(anyhow!("Could not create transaction"))),
     }
 
+    validate_same_plan(
+        &*bank_accounts,
+        &*payees,
+        bank_account_id,
+        request.payee_id,
+        budget.as_ref(),
+    )
+    .await?;
+    validate_not_archived(budget.as_ref())?;
+
     let transaction = Transaction {
         id,
         date: request.date,
@@ -108,20 +239,14 @@ pub async fn create(
         payee_id: request.payee_id,
         bank_account_id,
         budget_id: request.budget_id,
+        memo: None,
     };
 
-    budget.assign_from_transaction(&transaction);
-
-    db::transactions::create(&db_pool, transaction)
+    transactions
+        .create(transaction)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Could not create transaction")))?;
 
-    // update budget must happen after transaction create because the budget assignment
-    // has a foreign key to the transaction
-    db::budgets::update(&db_pool, budget)
-        .await
-        .map_err(|e| e.to_app_error(anyhow!("Could not update budget")))?;
-
     Ok((StatusCode::CREATED, Json(id)))
 }
 
@@ -139,61 +264,53 @@ pub async fn create(
     tag = API_TAG,
     operation_id = "updateTransaction"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn update(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(bank_accounts): State<Arc<dyn BankAccountRepository>>,
+    State(payees): State<Arc<dyn PayeeRepository>>,
+    State(budgets): State<Arc<dyn BudgetRepository>>,
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(transaction_id): Path<Uuid>,
     Json(request): Json<UpdateTransactionRequest>,
 ) -> Result<(), AppError> {
-    let (transaction_result, budget_by_transaction_id_result, budget_by_id_result) = join!(
-        db::transactions::get_single(&db_pool, transaction_id),
-        db::budgets::get_by_assignment_transaction_id(&db_pool, transaction_id),
-        db::budgets::get_single(&db_pool, request.budget_id)
+    validate_budget(request.budget_id, request.amount)?;
+
+    let (transaction_check, payee_check) = join!(
+        ownership.check_transaction(user.id, transaction_id, PlanRole::Editor),
+        ownership.check_payee(user.id, request.payee_id, PlanRole::Editor)
+    );
+    transaction_check.map_err(|e| e.to_app_error(anyhow!("Failed to update transaction")))?;
+    payee_check.map_err(|e| e.to_app_error(anyhow!("Payee not found with id {}", request.payee_id)))?;
+
+    let (transaction_result, budget_by_id_result) = join!(
+        transactions.get_single(transaction_id),
+        get_budget(&*ownership, &*budgets, user.id, request.budget_id)
     );
 
     let mut transaction =
         transaction_result.map_err(|e| e.to_app_error(anyhow!("Failed to update transaction")))?;
-    let budget_by_transaction_id = budget_by_transaction_id_result
-        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget with transaction assignment")))?;
-    let mut budget_by_id =
+    let budget_by_id =
         budget_by_id_result.map_err(|e| e.to_app_error(anyhow!("Failed to get budget by id")))?;
 
-    let original_budget_id = transaction.budget_id;
-    
+    validate_same_plan(
+        &*bank_accounts,
+        &*payees,
+        transaction.bank_account_id,
+        request.payee_id,
+        budget_by_id.as_ref(),
+    )
+    .await?;
+    validate_not_archived(budget_by_id.as_ref())?;
+
     transaction.amount = request.amount;
     transaction.date = request.date;
     transaction.payee_id = request.payee_id;
     transaction.budget_id = request.budget_id;
 
-    if let Some(mut budget_by_transaction_id) = budget_by_transaction_id {
-        if original_budget_id != request.budget_id {
-            budget_by_transaction_id.assignments.retain(|assignment| !matches!(
-                assignment.source,
-                BudgetAssignmentSource::Transaction { from_transaction_id } if from_transaction_id == transaction_id
-            ));
-
-            budget_by_id.assign_from_transaction(&transaction);
-
-            let (result1, result2) = join!(
-                db::budgets::update(&db_pool, budget_by_transaction_id),
-                db::budgets::update(&db_pool, budget_by_id),
-            );
-
-            result1.map_err(|e| e.to_app_error(anyhow!("Failed to update budget")))?;
-            result2.map_err(|e| e.to_app_error(anyhow!("Failed to update budget")))?;
-        } else if let Some(assignment) = budget_by_transaction_id.assignments.iter_mut()
-            .find(|assignment| matches!(
-                assignment.source,
-                BudgetAssignmentSource::Transaction { from_transaction_id } if from_transaction_id == transaction_id
-            )) {
-            assignment.amount = transaction.amount;
-            assignment.date = transaction.date;
-            
-            db::budgets::update(&db_pool, budget_by_transaction_id).await.map_err(|e| 
-                e.to_app_error(anyhow!("Failed to update budget")))?;
-        }
-    }
-
-    db::transactions::update(&db_pool, transaction)
+    transactions
+        .update(transaction)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Failed to update transaction")))?;
 
@@ -213,42 +330,117 @@ pub async fn update(
     operation_id = "deleteTransaction"
 )]
 pub async fn delete(
-    State(db_pool): State<MySqlPool>,
+    State(ownership): State<Arc<dyn OwnershipRepository>>,
+    State(transactions): State<Arc<dyn TransactionRepository>>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(transaction_id): Path<Uuid>,
 ) -> Result<(), AppError> {
-    let budget = db::budgets::get_by_assignment_transaction_id(&db_pool, transaction_id)
+    ownership
+        .check_transaction(user.id, transaction_id, PlanRole::Editor)
         .await
-        .map_err(|e| e.to_app_error(anyhow!("Failed to get budget with transaction assignment")))?;
+        .map_err(|e| e.to_app_error(anyhow!("Failed to get transaction")))?;
 
-    if let Some(mut budget) = budget {
-        budget.assignments.retain(|assignment| !matches!(
-            assignment.source,
-            BudgetAssignmentSource::Transaction {from_transaction_id} if from_transaction_id == transaction_id));
+    transactions
+        .delete(transaction_id)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Failed to delete transaction")))?;
 
-        db::budgets::update(&db_pool, budget)
-            .await
-            .map_err(|e| e.to_app_error(anyhow!("Failed to update transaction")))?;
+    Ok(())
+}
+
+/// A transaction without a budget is income, which only makes sense for inflows
+pub(crate) fn validate_budget(budget_id: Option<Uuid>, amount: Decimal) -> Result<(), AppError> {
+    if budget_id.is_some_and(|budget_id| budget_id.is_nil()) {
+        return Err(AppError::BadRequest(anyhow!("Budget Id must not be empty")));
     }
 
-    db::transactions::delete(&db_pool, transaction_id)
-        .await
-        .map_err(|e| e.to_app_error(anyhow!("Failed to delete transaction")))?;
+    if budget_id.is_none() && amount < Decimal::ZERO {
+        return Err(AppError::BadRequest(anyhow!(
+            "Outflows must have a budget, only inflows can be income"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Money can't be assigned to an archived budget, so neither can a transaction
+pub(crate) fn validate_not_archived(budget: Option<&Budget>) -> Result<(), AppError> {
+    if let Some(budget) = budget.filter(|budget| budget.archived_at.is_some()) {
+        return Err(AppError::BadRequest(anyhow!(
+            "budget with id {} is archived",
+            budget.id
+        )));
+    }
 
     Ok(())
 }
 
+/// Being an editor of the payee's and the budget's plans isn't enough, they have to be in the
+/// bank account's plan too
+pub(crate) async fn validate_same_plan(
+    bank_accounts: &dyn BankAccountRepository,
+    payees: &dyn PayeeRepository,
+    bank_account_id: Uuid,
+    payee_id: Uuid,
+    budget: Option<&Budget>,
+) -> Result<(), AppError> {
+    let (plan_id_result, payee_result) = join!(
+        bank_accounts.get_plan_id(bank_account_id),
+        payees.get_single(payee_id)
+    );
+
+    let plan_id =
+        plan_id_result.map_err(|e| e.to_app_error(anyhow!("Could not get bank account")))?;
+    let payee =
+        payee_result.map_err(|e| e.to_app_error(anyhow!("Payee not found with id {payee_id}")))?;
+
+    if payee.plan_id != plan_id {
+        return Err(AppError::BadRequest(anyhow!(
+            "Payee is not in the same plan as the bank account"
+        )));
+    }
+
+    if budget.is_some_and(|budget| budget.plan_id != plan_id) {
+        return Err(AppError::BadRequest(anyhow!(
+            "Budget is not in the same plan as the bank account"
+        )));
+    }
+
+    Ok(())
+}
+
+async fn get_budget(
+    ownership: &dyn OwnershipRepository,
+    budgets: &dyn BudgetRepository,
+    user_id: Uuid,
+    budget_id: Option<Uuid>,
+) -> Result<Option<Budget>, Error> {
+    match budget_id {
+        Some(budget_id) => {
+            ownership.check_budget(user_id, budget_id, PlanRole::Editor).await?;
+            budgets.get_single(budget_id).await.map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{
-        Budget, BudgetAssignment, CreateBankAccountRequest, CreatePayeeRequest, User,
+    use crate::{
+        db::{self, TestPool},
+        models::{
+            Budget, BudgetAssignment, BudgetAssignmentSource, CreateBankAccountRequest,
+            CreatePayeeRequest, User,
+        },
+        AppState,
     };
-    use chrono::NaiveDate;
+    use chrono::{NaiveDate, Utc};
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
-    #[sqlx::test]
-    pub async fn delete_should_remove_transaction_assignment(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn delete_should_remove_transaction_assignment(db_pool: TestPool) {
         let payee_id = Uuid::new_v4();
         let bank_account_id = Uuid::new_v4();
         let transaction_id_1 = Uuid::new_v4();
@@ -275,7 +467,7 @@ mod tests {
             &db_pool,
             payee_id,
             CreatePayeeRequest {
-                user_id,
+                plan_id: user_id,
                 name: "payee".into(),
             },
         )
@@ -286,7 +478,7 @@ mod tests {
             bank_account_id,
             CreateBankAccountRequest {
                 name: "name".into(),
-                user_id,
+                plan_id: user_id,
                 initial_amount: Decimal::ZERO,
             },
         )
@@ -295,16 +487,20 @@ mod tests {
         let budget_1 = Budget {
             id: budget_id_1,
             name: "budget 1".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         let mut budget_2 = Budget {
             id: budget_id_2,
             name: "budget 2".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         db::budgets::create(&db_pool, budget_1).await.unwrap();
         db::budgets::create(&db_pool, budget_2.clone())
@@ -317,8 +513,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -330,8 +527,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -367,9 +565,15 @@ mod tests {
             .await
             .unwrap();
 
-        delete(State(db_pool.clone()), Path(transaction_id_1))
-            .await
-            .unwrap();
+        let state = AppState::new(db_pool.clone().into());
+        delete(
+            State(state.ownership.clone()),
+            State(state.transactions.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+            Path(transaction_id_1),
+        )
+        .await
+        .unwrap();
 
         let fetched_transaction = db::transactions::get_single(&db_pool, transaction_id_1).await;
         assert!(matches!(fetched_transaction, Err(db::Error::NotFound)));
@@ -385,8 +589,8 @@ mod tests {
         assert_eq!(fetched_budget, budget_2);
     }
 
-    #[sqlx::test]
-    pub async fn update_should_update_transaction_assignment(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_should_update_transaction_assignment(db_pool: TestPool) {
         let payee_id = Uuid::new_v4();
         let bank_account_id = Uuid::new_v4();
         let transaction_id_1 = Uuid::new_v4();
@@ -413,7 +617,7 @@ mod tests {
             &db_pool,
             payee_id,
             CreatePayeeRequest {
-                user_id,
+                plan_id: user_id,
                 name: "payee".into(),
             },
         )
@@ -424,7 +628,7 @@ mod tests {
             bank_account_id,
             CreateBankAccountRequest {
                 name: "name".into(),
-                user_id,
+                plan_id: user_id,
                 initial_amount: Decimal::ZERO,
             },
         )
@@ -433,16 +637,20 @@ mod tests {
         let budget_1 = Budget {
             id: budget_id_1,
             name: "budget 1".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         let mut budget_2 = Budget {
             id: budget_id_2,
             name: "budget 2".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         db::budgets::create(&db_pool, budget_1).await.unwrap();
         db::budgets::create(&db_pool, budget_2.clone())
@@ -455,8 +663,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -468,8 +677,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -505,14 +715,20 @@ mod tests {
             .await
             .unwrap();
 
+        let state = AppState::new(db_pool.clone().into());
         update(
-            State(db_pool.clone()),
+            State(state.ownership.clone()),
+            State(state.bank_accounts.clone()),
+            State(state.payees.clone()),
+            State(state.budgets.clone()),
+            State(state.transactions.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
             Path(transaction_id_1),
             Json(UpdateTransactionRequest {
                 amount: dec!(10),
                 date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
             }),
         )
         .await
@@ -528,8 +744,9 @@ mod tests {
                 amount: dec!(10),
                 date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
                 payee_id,
-                budget_id: budget_id_2,
-                bank_account_id
+                budget_id: Some(budget_id_2),
+                bank_account_id,
+                memo: None,
             }
         );
 
@@ -547,8 +764,8 @@ mod tests {
         assert_eq!(fetched_budget, budget_2);
     }
 
-    #[sqlx::test]
-    pub async fn update_should_remove_assignment_when_budget_changes(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_should_remove_assignment_when_budget_changes(db_pool: TestPool) {
         let payee_id = Uuid::new_v4();
         let bank_account_id = Uuid::new_v4();
         let transaction_id_1 = Uuid::new_v4();
@@ -575,7 +792,7 @@ mod tests {
             &db_pool,
             payee_id,
             CreatePayeeRequest {
-                user_id,
+                plan_id: user_id,
                 name: "payee".into(),
             },
         )
@@ -586,7 +803,7 @@ mod tests {
             bank_account_id,
             CreateBankAccountRequest {
                 name: "name".into(),
-                user_id,
+                plan_id: user_id,
                 initial_amount: Decimal::ZERO,
             },
         )
@@ -595,16 +812,20 @@ mod tests {
         let mut budget_1 = Budget {
             id: budget_id_1,
             name: "budget 1".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         let mut budget_2 = Budget {
             id: budget_id_2,
             name: "budget 2".into(),
-            user_id,
+            plan_id: user_id,
             target: None,
             assignments: vec![],
+            group_id: None,
+            archived_at: None,
         };
         db::budgets::create(&db_pool, budget_1.clone())
             .await
@@ -619,8 +840,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -632,8 +854,9 @@ mod tests {
                 amount: Decimal::ZERO,
                 bank_account_id,
                 payee_id,
-                budget_id: budget_id_2,
+                budget_id: Some(budget_id_2),
                 date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                memo: None,
             },
         )
         .await
@@ -669,14 +892,20 @@ mod tests {
             .await
             .unwrap();
 
+        let state = AppState::new(db_pool.clone().into());
         update(
-            State(db_pool.clone()),
+            State(state.ownership.clone()),
+            State(state.bank_accounts.clone()),
+            State(state.payees.clone()),
+            State(state.budgets.clone()),
+            State(state.transactions.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
             Path(transaction_id_1),
             Json(UpdateTransactionRequest {
                 amount: dec!(10),
                 date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
                 payee_id,
-                budget_id: budget_id_1,
+                budget_id: Some(budget_id_1),
             }),
         )
         .await
@@ -692,8 +921,9 @@ mod tests {
                 amount: dec!(10),
                 date: NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
                 payee_id,
-                budget_id: budget_id_1,
-                bank_account_id
+                budget_id: Some(budget_id_1),
+                bank_account_id,
+                memo: None,
             }
         );
 
@@ -725,4 +955,471 @@ mod tests {
         assert_eq!(fetched_budget_1, budget_1);
         assert_eq!(fetched_budget_2, budget_2);
     }
+
+    mod in_memory_tests {
+        use crate::{
+            db::in_memory::InMemoryRepository,
+            models::{BankAccount, Payee},
+        };
+
+        use super::*;
+
+        struct Setup {
+            repository: Arc<InMemoryRepository>,
+            user: User,
+            bank_account_id: Uuid,
+            payee_id: Uuid,
+            budget: Budget,
+        }
+
+        fn setup(role: PlanRole) -> Setup {
+            let repository = Arc::new(InMemoryRepository::default());
+            let user = User::new(Uuid::new_v4(), "name".into(), "email@email.com".into(), None);
+            let plan_id = Uuid::new_v4();
+            let bank_account_id = Uuid::new_v4();
+            let payee_id = Uuid::new_v4();
+            let budget =
+                Budget::new(Uuid::new_v4(), "budget".into(), None, plan_id, vec![], None, None);
+
+            repository.add_member(plan_id, user.id, role);
+            repository.add_bank_account(BankAccount::new(
+                bank_account_id,
+                "account".into(),
+                dec!(0),
+                plan_id,
+                dec!(0),
+            ));
+            repository.add_payee(Payee::new(payee_id, "payee".into(), plan_id));
+            repository.add_budget(budget.clone());
+
+            Setup {
+                repository,
+                user,
+                bank_account_id,
+                payee_id,
+                budget,
+            }
+        }
+
+        #[allow(clippy::type_complexity)]
+        fn states(
+            repository: &Arc<InMemoryRepository>,
+        ) -> (
+            State<Arc<dyn OwnershipRepository>>,
+            State<Arc<dyn BankAccountRepository>>,
+            State<Arc<dyn PayeeRepository>>,
+            State<Arc<dyn BudgetRepository>>,
+            State<Arc<dyn TransactionRepository>>,
+        ) {
+            (
+                State(repository.clone()),
+                State(repository.clone()),
+                State(repository.clone()),
+                State(repository.clone()),
+                State(repository.clone()),
+            )
+        }
+
+        #[tokio::test]
+        async fn create_should_assign_transaction_to_budget() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let date = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+
+            let (_, Json(id)) = create(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(setup.bank_account_id),
+                Json(CreateTransactionRequest {
+                    payee_id: setup.payee_id,
+                    amount: dec!(-12.5),
+                    date,
+                    budget_id: Some(setup.budget.id),
+                }),
+            )
+            .await
+            .unwrap();
+
+            let transaction = setup.repository.transaction(id).unwrap();
+            assert_eq!(transaction.budget_id, Some(setup.budget.id));
+
+            let budget = setup.repository.budget(setup.budget.id).unwrap();
+            assert_eq!(budget.assignments.len(), 1);
+            assert_eq!(budget.assignments[0].amount, dec!(-12.5));
+            assert_eq!(budget.assignments[0].date, date);
+            assert_eq!(
+                budget.assignments[0].source,
+                BudgetAssignmentSource::Transaction {
+                    from_transaction_id: id
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn create_should_reject_archived_budget() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            setup.repository.add_budget(Budget {
+                archived_at: Some(Utc::now()),
+                ..setup.budget.clone()
+            });
+
+            let result = create(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(setup.bank_account_id),
+                Json(CreateTransactionRequest {
+                    payee_id: setup.payee_id,
+                    amount: dec!(-12.5),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    budget_id: Some(setup.budget.id),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(setup
+                .repository
+                .budget(setup.budget.id)
+                .unwrap()
+                .assignments
+                .is_empty());
+        }
+
+        #[tokio::test]
+        async fn create_should_be_forbidden_for_viewers() {
+            let setup = setup(PlanRole::Viewer);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+
+            let result = create(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(setup.bank_account_id),
+                Json(CreateTransactionRequest {
+                    payee_id: setup.payee_id,
+                    amount: dec!(-12.5),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    budget_id: Some(setup.budget.id),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+            assert!(setup.repository.budget(setup.budget.id).unwrap().assignments.is_empty());
+        }
+
+        #[tokio::test]
+        async fn create_should_reject_budgets_in_other_plans() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let other_plan_id = Uuid::new_v4();
+            let other_budget = Budget::new(
+                Uuid::new_v4(),
+                "other budget".into(),
+                None,
+                other_plan_id,
+                vec![],
+                None,
+                None,
+            );
+            setup
+                .repository
+                .add_member(other_plan_id, setup.user.id, PlanRole::Editor);
+            setup.repository.add_budget(other_budget.clone());
+
+            let result = create(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(setup.bank_account_id),
+                Json(CreateTransactionRequest {
+                    payee_id: setup.payee_id,
+                    amount: dec!(-12.5),
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                    budget_id: Some(other_budget.id),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert!(setup
+                .repository
+                .budget(other_budget.id)
+                .unwrap()
+                .assignments
+                .is_empty());
+        }
+
+        #[tokio::test]
+        async fn update_should_reject_payees_in_other_plans() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let other_plan_id = Uuid::new_v4();
+            let other_payee_id = Uuid::new_v4();
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                amount: dec!(100),
+                date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                payee_id: setup.payee_id,
+                bank_account_id: setup.bank_account_id,
+                budget_id: None,
+                memo: None,
+            };
+            setup
+                .repository
+                .add_member(other_plan_id, setup.user.id, PlanRole::Editor);
+            setup.repository.add_payee(Payee::new(
+                other_payee_id,
+                "other payee".into(),
+                other_plan_id,
+            ));
+            setup.repository.add_transaction(transaction.clone());
+
+            let result = update(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(transaction.id),
+                Json(UpdateTransactionRequest {
+                    amount: dec!(100),
+                    payee_id: other_payee_id,
+                    budget_id: None,
+                    date: transaction.date,
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+            assert_eq!(
+                setup
+                    .repository
+                    .transaction(transaction.id)
+                    .unwrap()
+                    .payee_id,
+                setup.payee_id
+            );
+        }
+
+        #[tokio::test]
+        async fn update_from_income_should_assign_to_new_budget() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                amount: dec!(100),
+                date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                payee_id: setup.payee_id,
+                bank_account_id: setup.bank_account_id,
+                budget_id: None,
+                memo: None,
+            };
+            setup.repository.add_transaction(transaction.clone());
+
+            update(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(transaction.id),
+                Json(UpdateTransactionRequest {
+                    amount: dec!(-20),
+                    payee_id: setup.payee_id,
+                    budget_id: Some(setup.budget.id),
+                    date: transaction.date,
+                }),
+            )
+            .await
+            .unwrap();
+
+            let budget = setup.repository.budget(setup.budget.id).unwrap();
+            assert_eq!(budget.assignments.len(), 1);
+            assert_eq!(budget.assignments[0].amount, dec!(-20));
+            assert_eq!(
+                setup.repository.transaction(transaction.id).unwrap().budget_id,
+                Some(setup.budget.id)
+            );
+        }
+
+        #[tokio::test]
+        async fn update_to_income_should_remove_assignment() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                amount: dec!(-20),
+                date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                payee_id: setup.payee_id,
+                bank_account_id: setup.bank_account_id,
+                budget_id: Some(setup.budget.id),
+                memo: None,
+            };
+            let mut budget = setup.budget.clone();
+            budget.assign_from_transaction(&transaction);
+            setup.repository.add_budget(budget);
+            setup.repository.add_transaction(transaction.clone());
+
+            update(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(transaction.id),
+                Json(UpdateTransactionRequest {
+                    amount: dec!(20),
+                    payee_id: setup.payee_id,
+                    budget_id: None,
+                    date: transaction.date,
+                }),
+            )
+            .await
+            .unwrap();
+
+            assert!(setup.repository.budget(setup.budget.id).unwrap().assignments.is_empty());
+            assert_eq!(setup.repository.transaction(transaction.id).unwrap().budget_id, None);
+        }
+
+        #[tokio::test]
+        async fn update_should_keep_other_assignments() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                amount: dec!(-20),
+                date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                payee_id: setup.payee_id,
+                bank_account_id: setup.bank_account_id,
+                budget_id: Some(setup.budget.id),
+                memo: None,
+            };
+            let other_assignment = BudgetAssignment {
+                id: Uuid::new_v4(),
+                amount: dec!(50),
+                date: transaction.date,
+                source: BudgetAssignmentSource::OtherBudget {
+                    from_budget_id: Uuid::new_v4(),
+                    link_id: Uuid::new_v4(),
+                },
+            };
+            let mut budget = setup.budget.clone();
+            budget.assignments.push(other_assignment.clone());
+            budget.assign_from_transaction(&transaction);
+            setup.repository.add_budget(budget);
+            setup.repository.add_transaction(transaction.clone());
+
+            update(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(transaction.id),
+                Json(UpdateTransactionRequest {
+                    amount: dec!(-30),
+                    payee_id: setup.payee_id,
+                    budget_id: Some(setup.budget.id),
+                    date: transaction.date,
+                }),
+            )
+            .await
+            .unwrap();
+
+            let budget = setup.repository.budget(setup.budget.id).unwrap();
+            assert_eq!(budget.assignments.len(), 2);
+            assert_eq!(budget.assignments[0], other_assignment);
+            assert_eq!(budget.assignments[1].amount, dec!(-30));
+        }
+
+        #[tokio::test]
+        async fn update_should_reject_outflows_without_budget() {
+            let setup = setup(PlanRole::Editor);
+            let (ownership, bank_accounts, payees, budgets, transactions) =
+                states(&setup.repository);
+
+            let result = update(
+                ownership,
+                bank_accounts,
+                payees,
+                budgets,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(Uuid::new_v4()),
+                Json(UpdateTransactionRequest {
+                    amount: dec!(-20),
+                    payee_id: setup.payee_id,
+                    budget_id: None,
+                    date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                }),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
+
+        #[tokio::test]
+        async fn delete_should_not_find_transactions_in_other_plans() {
+            let setup = setup(PlanRole::Owner);
+            let other_plan_id = Uuid::new_v4();
+            let other_bank_account_id = Uuid::new_v4();
+            let (ownership, _, _, _, transactions) = states(&setup.repository);
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                amount: dec!(20),
+                date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                payee_id: setup.payee_id,
+                bank_account_id: other_bank_account_id,
+                budget_id: None,
+                memo: None,
+            };
+            setup.repository.add_bank_account(BankAccount::new(
+                other_bank_account_id,
+                "other account".into(),
+                dec!(0),
+                other_plan_id,
+                dec!(0),
+            ));
+            setup.repository.add_transaction(transaction.clone());
+
+            let result = delete(
+                ownership,
+                transactions,
+                AuthenticatedUser(setup.user),
+                Path(transaction.id),
+            )
+            .await;
+
+            assert!(matches!(result, Err(AppError::NotFound(_))));
+            assert!(setup.repository.transaction(transaction.id).is_some());
+        }
+    }
 }
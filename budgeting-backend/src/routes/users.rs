@@ -1,16 +1,12 @@
 use anyhow::anyhow;
-use axum::{
-    extract::{Path, State},
-    Json,
-};
-use email_address::EmailAddress;
+use axum::{extract::State, Json};
 use http::StatusCode;
-use sqlx::MySqlPool;
 use utoipa::OpenApi;
 use uuid::Uuid;
 
 use crate::{
-    db,
+    auth::{self, AuthenticatedUser},
+    db::{self, DbPool},
     models::{
         CreateUserRequest, Schedule, SchedulePeriod, SchedulePeriodType, UpdateScheduleRequest,
         UpdateUserRequest, User,
@@ -20,7 +16,7 @@ use crate::{
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(get, get_single, create, update),
+    paths(get_current, create, update),
     components(schemas(
         User,
         Schedule,
@@ -42,102 +38,59 @@ const API_TAG: &str = "Users";
         (status = CREATED, description = "Success", body = Uuid, content_type = "application/json")
     ),
     request_body = CreateUserRequest,
+    security(()),
     tag = API_TAG,
     operation_id = "createUser"
 )]
 pub async fn create(
-    State(db_pool): State<MySqlPool>,
+    State(db_pool): State<DbPool>,
     Json(request): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<Uuid>), AppError> {
-    if request.name.trim().is_empty() {
-        return Err(AppError::BadRequest(anyhow!("User name must not be empty")));
-    }
-
-    if !EmailAddress::is_valid(&request.email) {
-        return Err(AppError::BadRequest(anyhow!(
-            "\"{}\" is not a valid email address",
-            request.email
-        )));
-    }
-
-    let id = Uuid::new_v4();
-
-    db::users::create(
-        &db_pool,
-        User::new(
-            id,
-            request.name.trim().into(),
-            request.email.trim().into(),
-            None,
-        ),
-    )
-    .await
-    .map_err(|e| e.to_app_error(anyhow!("Could not create user")))?;
+    let id = auth::create_user(&db_pool, request).await?;
 
     Ok((StatusCode::CREATED, Json(id)))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/users",
-    responses(
-        (status = OK, description = "Success", body = Box<[User]>, content_type = "application/json")
-    ),
-    tag = API_TAG,
-    operation_id = "getUsers"
-)]
-pub async fn get(State(db_pool): State<MySqlPool>) -> Result<Json<Box<[User]>>, AppError> {
-    db::users::get(&db_pool)
-        .await
-        .map(Json)
-        .map_err(|e| e.to_app_error(anyhow!("Could not get users")))
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/users/{userId}",
+    path = "/api/users/me",
     responses(
         (status = OK, description = "Success", body = User, content_type = "application/json")
     ),
-    params(
-        ("userId" = Uuid, Path,)
-    ),
     tag = API_TAG,
-    operation_id = "getUser"
+    operation_id = "getCurrentUser"
 )]
-pub async fn get_single(
-    State(db_pool): State<MySqlPool>,
-    Path(user_id): Path<Uuid>,
-) -> Result<Json<User>, AppError> {
-    db::users::get_single(&db_pool, user_id)
-        .await
-        .map_err(|e| e.to_app_error(anyhow!("Could not get user")))
-        .map(Json)
+pub async fn get_current(AuthenticatedUser(user): AuthenticatedUser) -> Json<User> {
+    Json(user)
 }
 
 #[utoipa::path(
     put,
-    path = "/api/users/{userId}",
+    path = "/api/users/me",
     responses(
         (status = OK, description = "Success")
     ),
-    params(
-        ("userId" = Uuid, Path,)
-    ),
     request_body = UpdateUserRequest,
     tag = API_TAG,
     operation_id = "updateUser"
 )]
 pub async fn update(
-    Path(user_id): Path<Uuid>,
-    State(db_pool): State<MySqlPool>,
+    AuthenticatedUser(existing): AuthenticatedUser,
+    State(db_pool): State<DbPool>,
     Json(request): Json<UpdateUserRequest>,
 ) -> Result<StatusCode, AppError> {
     if request.name.trim().is_empty() {
         return Err(AppError::BadRequest(anyhow!("User name must not be empty")));
     }
 
-    let existing = db::users::get_single(&db_pool, user_id)
+    if let Some(pay_frequency) = &request.pay_frequency {
+        pay_frequency
+            .period
+            .validate()
+            .map_err(AppError::BadRequest)?;
+    }
+
+    let mut db_transaction = db::begin(&db_pool)
         .await
         .map_err(|e| e.to_app_error(anyhow!("Could not update user")))?;
 
@@ -148,7 +101,7 @@ pub async fn update(
                 id: existing_schedule.id,
                 period: updated_schedule.period,
             };
-            db::schedule::update(&db_pool, schedule.clone())
+            db::schedule::update(&mut db_transaction, schedule.clone())
                 .await
                 .map_err(|e| e.to_app_error(anyhow!("Failed to update user")))?;
 
@@ -160,7 +113,7 @@ pub async fn update(
                 id: Uuid::new_v4(),
                 period: updated_schedule.period,
             };
-            db::schedule::create(&db_pool, schedule.clone())
+            db::schedule::create(&mut db_transaction, schedule.clone())
                 .await
                 .map_err(|e| e.to_app_error(anyhow!("Failed to update user")))?;
 
@@ -172,18 +125,22 @@ pub async fn update(
     };
 
     db::users::update(
-        &db_pool,
+        &mut db_transaction,
         User::new(existing.id, request.name, existing.email, schedule),
     )
     .await
     .map_err(|e| e.to_app_error(anyhow!("Could not update user")))?;
 
     if let Some(schedule_id) = schedule_id_to_delete {
-        db::schedule::delete(&db_pool, schedule_id)
+        db::schedule::delete(&mut db_transaction, schedule_id)
             .await
             .map_err(|e| e.to_app_error(anyhow!("Could not update user")))?;
     }
 
+    db::commit(db_transaction)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not update user")))?;
+
     Ok(StatusCode::OK)
 }
 
@@ -193,12 +150,14 @@ mod tests {
 
     use chrono::NaiveDate;
 
+    use crate::db::TestPool;
+
     use super::*;
 
     static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
     static SCHEDULE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-    async fn init(db_pool: &MySqlPool) {
+    async fn init(db_pool: &TestPool) {
         db::schedule::create(
             db_pool,
             Schedule {
@@ -212,8 +171,8 @@ mod tests {
         .unwrap();
     }
 
-    #[sqlx::test]
-    pub async fn update_no_schedule(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_no_schedule(db_pool: TestPool) {
         init(&db_pool).await;
         let user_id = *USER_ID;
         db::users::create(
@@ -224,8 +183,8 @@ mod tests {
         .unwrap();
 
         let response = update(
-            Path(user_id),
-            State(db_pool.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+            State(db_pool.clone().into()),
             Json(UpdateUserRequest::new("new_name".into(), None)),
         )
         .await
@@ -241,8 +200,8 @@ mod tests {
         );
     }
 
-    #[sqlx::test]
-    pub async fn update_delete_schedule(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_delete_schedule(db_pool: TestPool) {
         init(&db_pool).await;
         let user_id = *USER_ID;
         db::users::create(
@@ -263,8 +222,8 @@ mod tests {
         .unwrap();
 
         let response = update(
-            Path(user_id),
-            State(db_pool.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+            State(db_pool.clone().into()),
             Json(UpdateUserRequest::new("new_name".into(), None)),
         )
         .await
@@ -280,8 +239,8 @@ mod tests {
         );
     }
 
-    #[sqlx::test]
-    pub async fn update_user_update_schedule(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_user_update_schedule(db_pool: TestPool) {
         init(&db_pool).await;
         let user_id = *USER_ID;
         db::users::create(
@@ -302,8 +261,8 @@ mod tests {
         .unwrap();
 
         let response = update(
-            Path(user_id),
-            State(db_pool.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+            State(db_pool.clone().into()),
             Json(UpdateUserRequest::new(
                 "new_name".into(),
                 Some(UpdateScheduleRequest {
@@ -336,8 +295,8 @@ mod tests {
         );
     }
 
-    #[sqlx::test]
-    pub async fn update_create_schedule(db_pool: MySqlPool) {
+    #[sqlx::test(migrator = "crate::migrations::TEST_MIGRATOR")]
+    pub async fn update_create_schedule(db_pool: TestPool) {
         init(&db_pool).await;
         let user_id = *USER_ID;
         db::users::create(
@@ -348,8 +307,8 @@ mod tests {
         .unwrap();
 
         let response = update(
-            Path(user_id),
-            State(db_pool.clone()),
+            AuthenticatedUser(db::users::get_single(&db_pool, user_id).await.unwrap()),
+            State(db_pool.clone().into()),
             Json(UpdateUserRequest::new(
                 "new_name".into(),
                 Some(UpdateScheduleRequest {
@@ -0,0 +1,60 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db::{self, DbPool},
+    import,
+    models::{PlanRole, YnabImportRequest},
+    AppError,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(import), components(schemas(YnabImportRequest)))]
+pub struct Api;
+
+const API_TAG: &str = "YNAB";
+
+/// Imports YNAB's register and plan CSV exports into the plan.
+/// Returns the ids of the bank accounts created for YNAB's accounts
+#[utoipa::path(
+    post,
+    path = "/api/plans/{planId}/imports/ynab",
+    responses(
+        (status = CREATED, description = "Success", body = Box<[Uuid]>, content_type = "application/json"),
+        (status = PAYLOAD_TOO_LARGE, description = "The file is larger than 50 MB")
+    ),
+    params(
+        ("planId" = Uuid, Path,)
+    ),
+    request_body(content = YnabImportRequest, description = "YNAB's exported files", content_type = "application/json"),
+    tag = API_TAG,
+    operation_id = "importYnab"
+)]
+pub async fn import(
+    State(db_pool): State<DbPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(plan_id): Path<Uuid>,
+    Json(request): Json<YnabImportRequest>,
+) -> Result<(StatusCode, Json<Box<[Uuid]>>), AppError> {
+    db::ownership::check_plan(&db_pool, user.id, plan_id, PlanRole::Editor)
+        .await
+        .map_err(|e| e.to_app_error(anyhow!("Could not get plan")))?;
+
+    let bank_account_ids = import::ynab::import(
+        &db_pool,
+        plan_id,
+        &request.register,
+        request.plan.as_deref(),
+        request.date_format.as_deref(),
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(bank_account_ids)))
+}
@@ -6,19 +6,18 @@ use rust_decimal_macros::dec;
 use std::sync::OnceLock;
 
 use budgeting_backend::{
-    db::{self, Error},
+    db::{self, TestPool, Error},
     models::{BankAccount, Budget, CreateBankAccountRequest, UpdateBankAccountRequest, User},
 };
 use common::*;
 use rust_decimal::{prelude::FromPrimitive, Decimal};
-use sqlx::MySqlPool;
 use uuid::Uuid;
 
 static USER_ID: OnceLock<Uuid> = OnceLock::new();
 static BANK_ACCOUNT_ID: OnceLock<Uuid> = OnceLock::new();
 static BUDGET_ID: OnceLock<Uuid> = OnceLock::new();
 
-async fn test_init(db_pool: &MySqlPool) {
+async fn test_init(db_pool: &TestPool) {
     let user_id = *USER_ID.get_or_init(Uuid::new_v4);
     let bank_account_id = *BANK_ACCOUNT_ID.get_or_init(Uuid::new_v4);
     let budget_id = *BUDGET_ID.get_or_init(Uuid::new_v4);
@@ -49,16 +48,17 @@ async fn test_init(db_pool: &MySqlPool) {
 
     db::budgets::create(
         db_pool,
-        Budget::new(budget_id, "Budget".into(), None, user_id, vec![]),
+        Budget::new(budget_id, "Budget".into(), None, user_id, vec![], None, None),
     )
     .await
     .unwrap();
 }
 
-#[sqlx::test]
-pub async fn create_bank_account(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let user_id = *USER_ID.unwrap();
 
@@ -67,7 +67,7 @@ pub async fn create_bank_account(db_pool: MySqlPool) {
         .json(&CreateBankAccountRequest {
             initial_amount: Decimal::from_f32(13.63).unwrap(),
             name: "My Bank Account".to_owned(),
-            user_id,
+            plan_id: user_id,
         })
         .await;
 
@@ -82,7 +82,7 @@ pub async fn create_bank_account(db_pool: MySqlPool) {
         found_bank_account,
         BankAccount {
             id: bank_account_id,
-            user_id,
+            plan_id: user_id,
             name: "My Bank Account".to_owned(),
             initial_amount: Decimal::from_f32(13.63).unwrap(),
             balance: Decimal::from_f32(13.63).unwrap()
@@ -90,17 +90,18 @@ pub async fn create_bank_account(db_pool: MySqlPool) {
     )
 }
 
-#[sqlx::test]
-pub async fn get_bank_account(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
     let user_id = *USER_ID.unwrap();
 
     let response = test_server
         .get(&format!(
-            "/api/bank-accounts/{bank_account_id}?user_id={user_id}"
+            "/api/bank-accounts/{bank_account_id}?plan_id={user_id}"
         ))
         .await;
 
@@ -114,10 +115,11 @@ pub async fn get_bank_account(db_pool: MySqlPool) {
     ));
 }
 
-#[sqlx::test]
-pub async fn update_bank_account(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let user_id = *USER_ID.unwrap();
     let id = Uuid::new_v4();
@@ -131,7 +133,7 @@ pub async fn update_bank_account(db_pool: MySqlPool) {
     .unwrap();
 
     let response = test_server
-        .put(&format!("/api/bank-accounts/{id}?user_id={user_id}"))
+        .put(&format!("/api/bank-accounts/{id}?plan_id={user_id}"))
         .json(&UpdateBankAccountRequest::new("newName".into()))
         .await;
 
@@ -146,10 +148,11 @@ pub async fn update_bank_account(db_pool: MySqlPool) {
     assert_eq!(get_result, expected);
 }
 
-#[sqlx::test]
-pub async fn delete_bank_account(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let user_id = *USER_ID.unwrap();
     let id = Uuid::new_v4();
@@ -163,7 +166,7 @@ pub async fn delete_bank_account(db_pool: MySqlPool) {
     .unwrap();
 
     let response = test_server
-        .delete(&format!("/api/bank-accounts/{id}?user_id={user_id}"))
+        .delete(&format!("/api/bank-accounts/{id}?plan_id={user_id}"))
         .await;
 
     response.assert_ok();
@@ -172,3 +175,126 @@ pub async fn delete_bank_account(db_pool: MySqlPool) {
 
     assert!(matches!(get_result, Err(Error::NotFound)));
 }
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_bank_accounts(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .get(&format!("/api/bank-accounts?plan_id={other_user_id}"))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_bank_account_in_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .post("/api/bank-accounts")
+        .json(&CreateBankAccountRequest::new(
+            "name".into(),
+            dec!(0),
+            other_user_id,
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, other_user_id)
+        .await
+        .unwrap();
+    assert!(bank_accounts.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let id = Uuid::new_v4();
+
+    db::bank_accounts::create(
+        &db_pool,
+        id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .get(&format!("/api/bank-accounts/{id}?plan_id={other_user_id}"))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let id = Uuid::new_v4();
+
+    db::bank_accounts::create(
+        &db_pool,
+        id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .put(&format!("/api/bank-accounts/{id}?plan_id={other_user_id}"))
+        .json(&UpdateBankAccountRequest::new("newName".into()))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::bank_accounts::get_single(&db_pool, id, other_user_id)
+        .await
+        .unwrap();
+
+    assert_eq!(fetched.name, "name");
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_bank_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let id = Uuid::new_v4();
+
+    db::bank_accounts::create(
+        &db_pool,
+        id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .delete(&format!("/api/bank-accounts/{id}?plan_id={other_user_id}"))
+        .await;
+
+    response.assert_status_not_found();
+
+    db::bank_accounts::get_single(&db_pool, id, other_user_id)
+        .await
+        .unwrap();
+}
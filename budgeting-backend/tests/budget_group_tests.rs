@@ -0,0 +1,233 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool},
+    models::{BudgetGroup, CreateBudgetGroupRequest, UpdateBudgetGroupRequest, User},
+};
+use common::*;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+async fn test_init(db_pool: &TestPool) {
+    let user_id = *USER_ID;
+
+    db::users::create(
+        db_pool,
+        User::new(
+            user_id,
+            "name".to_owned(),
+            "someone@somewhere.com".to_owned(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+
+    let response = test_server
+        .post("/api/budget-groups")
+        .json(&CreateBudgetGroupRequest::new(
+            "Bills".to_owned(),
+            2,
+            user_id,
+        ))
+        .await;
+
+    response.assert_created();
+    let group_id: Uuid = response.json();
+
+    let found_group = db::budget_groups::get_single(&db_pool, group_id)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        found_group,
+        BudgetGroup::new(group_id, "Bills".to_owned(), 2, user_id)
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_group_without_name(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server
+        .post("/api/budget-groups")
+        .json(&CreateBudgetGroupRequest::new(" ".to_owned(), 0, *USER_ID))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_budget_groups(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+    let bills = BudgetGroup::new(Uuid::new_v4(), "Bills".to_owned(), 1, user_id);
+    let everyday = BudgetGroup::new(Uuid::new_v4(), "Everyday".to_owned(), 0, user_id);
+
+    db::budget_groups::create(&db_pool, bills.clone())
+        .await
+        .unwrap();
+    db::budget_groups::create(&db_pool, everyday.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .get(&format!("/api/budget-groups?plan_id={user_id}"))
+        .await;
+
+    response.assert_ok();
+    response.assert_json(&[everyday, bills]);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_budget_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".to_owned(), 0, *USER_ID);
+    db::budget_groups::create(&db_pool, group.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .put(&format!("/api/budget-groups/{}", group.id))
+        .json(&UpdateBudgetGroupRequest::new("Monthly bills".into(), 4))
+        .await;
+
+    response.assert_ok();
+
+    let fetched = db::budget_groups::get_single(&db_pool, group.id)
+        .await
+        .unwrap();
+
+    assert_eq!(fetched.name, "Monthly bills");
+    assert_eq!(fetched.sort_order, 4);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_budget_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".to_owned(), 0, *USER_ID);
+    db::budget_groups::create(&db_pool, group.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .delete(&format!("/api/budget-groups/{}", group.id))
+        .await;
+
+    response.assert_ok();
+
+    let fetched = db::budget_groups::get_single(&db_pool, group.id).await;
+
+    assert!(matches!(fetched, Err(db::Error::NotFound)));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_budget_groups(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .get(&format!("/api/budget-groups?plan_id={other_user_id}"))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_group_in_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .post("/api/budget-groups")
+        .json(&CreateBudgetGroupRequest::new(
+            "Bills".to_owned(),
+            0,
+            other_user_id,
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let groups = db::budget_groups::get(&db_pool, other_user_id)
+        .await
+        .unwrap();
+    assert!(groups.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_budget_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".to_owned(), 0, other_user_id);
+    db::budget_groups::create(&db_pool, group.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .put(&format!("/api/budget-groups/{}", group.id))
+        .json(&UpdateBudgetGroupRequest::new("Monthly bills".into(), 4))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::budget_groups::get_single(&db_pool, group.id)
+        .await
+        .unwrap();
+
+    assert_eq!(fetched, group);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_budget_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".to_owned(), 0, other_user_id);
+    db::budget_groups::create(&db_pool, group.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .delete(&format!("/api/budget-groups/{}", group.id))
+        .await;
+
+    response.assert_status_not_found();
+
+    db::budget_groups::get_single(&db_pool, group.id)
+        .await
+        .unwrap();
+}
@@ -1,24 +1,27 @@
 mod common;
 
 use std::ops::Deref;
-use chrono::NaiveDate;
+use chrono::{Local, NaiveDate};
 use common::*;
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use std::sync::LazyLock;
 
 use budgeting_backend::{
-    db::{self, Error},
+    db::{self, TestPool, Error},
     models::{
         Budget, BudgetAssignment, BudgetTarget, CreateBudgetRequest, CreateBudgetTargetRequest,
         CreateScheduleRequest, RepeatingTargetType, Schedule, SchedulePeriod, SchedulePeriodType,
         UpdateBudgetRequest, UpdateBudgetTargetRequest, UpdateScheduleRequest, User,
-        BudgetAssignmentSource, TransferBudgetRequest
+        BudgetAssignmentSource, TransferBudgetRequest, CreateBankAccountRequest, CreatePayeeRequest,
+        Transaction
     },
 };
-use sqlx::MySqlPool;
 use uuid::Uuid;
-use budgeting_backend::models::GetBudgetResponse;
+use budgeting_backend::models::{
+    ApplySuggestedAssignmentsRequest, ArchiveBudgetRequest, BudgetGroup, BudgetGroupSummary, BudgetMonthSummary,
+    BudgetTargetStatus, CreateBudgetGroupRequest, GetBudgetResponse, GetBudgetsResponse, GetMonthResponse, GetReadyToAssignResponse, SuggestedAssignment, SuggestedAssignments,
+};
 
 static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 static OTHER_BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
@@ -27,12 +30,14 @@ static OTHER_BUDGET: LazyLock<Budget> = LazyLock::new(|| {
         id: *OTHER_BUDGET_ID,
         assignments: vec![],
         name: "name".into(),
-        user_id: *USER_ID,
-        target: None
+        plan_id: *USER_ID,
+        target: None,
+        group_id: None,
+        archived_at: None,
     }
 });
 
-async fn test_init(db_pool: &MySqlPool) {
+async fn test_init(db_pool: &TestPool) {
     let user_id = *USER_ID;
 
     db::users::create(
@@ -45,10 +50,11 @@ async fn test_init(db_pool: &MySqlPool) {
     db::budgets::create(db_pool, OTHER_BUDGET.deref().clone()).await.unwrap();
 }
 
-#[sqlx::test]
-pub async fn test_create_budget(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn test_create_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let user_id = *USER_ID;
 
@@ -61,12 +67,14 @@ pub async fn test_create_budget(db_pool: MySqlPool) {
                 repeating_type: RepeatingTargetType::RequireRepeating,
                 schedule: CreateScheduleRequest {
                     period: SchedulePeriod::Custom {
+                        starting_on: NaiveDate::from_ymd_opt(2024, 12, 1),
                         period: SchedulePeriodType::Monthly,
                         every_x_periods: 2,
                     },
                 },
             }),
             user_id,
+            None,
         ))
         .await;
 
@@ -85,26 +93,30 @@ pub async fn test_create_budget(db_pool: MySqlPool) {
     let expected_budget = Budget {
         id: budget_id,
         name: "name".into(),
-        user_id,
+        plan_id: user_id,
         target: Some(BudgetTarget::Repeating {
             target_amount: Decimal::from_f32(1.1).unwrap(),
             repeating_type: RepeatingTargetType::RequireRepeating,
             schedule,
         }),
         assignments: vec![],
+        group_id: None,
+        archived_at: None,
     };
 
     assert_eq!(budget, expected_budget);
 }
 
-#[sqlx::test]
-pub async fn test_get_budgets(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn test_get_budgets(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let schedule = Schedule {
         id: Uuid::new_v4(),
         period: SchedulePeriod::Custom {
+            starting_on: NaiveDate::from_ymd_opt(2024, 12, 1),
             period: SchedulePeriodType::Fortnightly,
             every_x_periods: 1,
         },
@@ -115,14 +127,17 @@ pub async fn test_get_budgets(db_pool: MySqlPool) {
 
     let user_id = *USER_ID;
 
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 0, user_id);
+    db::budget_groups::create(&db_pool, group.clone()).await.unwrap();
+
     let budget = Budget {
         id: Uuid::new_v4(),
-        user_id,
+        plan_id: user_id,
         name: "name".into(),
         target: Some(BudgetTarget::Repeating {
             target_amount: Decimal::from_f32(1.1).unwrap(),
             repeating_type: RepeatingTargetType::BuildUpTo,
-            schedule,
+            schedule: schedule.clone(),
         }),
         assignments: vec![BudgetAssignment {
             id: Uuid::new_v4(),
@@ -133,31 +148,58 @@ pub async fn test_get_budgets(db_pool: MySqlPool) {
                 link_id: Uuid::new_v4()
             }
         }],
+        group_id: Some(group.id),
+        archived_at: None,
     };
 
     db::budgets::create(&db_pool, budget.clone()).await.unwrap();
 
     let response = test_server
-        .get(&format!("/api/budgets?user_id={user_id}"))
+        .get(&format!("/api/budgets?plan_id={user_id}"))
         .await;
 
     response.assert_ok();
 
-    let mut fetched = response.json::<Vec<GetBudgetResponse>>();
+    let next_due_date = schedule.first_on_or_after(Local::now().date_naive());
+
+    let GetBudgetsResponse { budgets: mut fetched, groups } = response.json();
+
+    assert_eq!(groups, vec![BudgetGroupSummary {
+        id: group.id,
+        name: group.name,
+        sort_order: 0,
+        total_assigned: dec!(10),
+        target_needed: Decimal::ZERO,
+        targets_on_track: true,
+        next_due_date,
+    }]);
+
     let mut expected = vec![GetBudgetResponse {
         id: budget.id,
         total_assigned: dec!(10),
+        target_status: Some(BudgetTargetStatus {
+            funded: dec!(10),
+            needed: Decimal::ZERO,
+            percent_funded: dec!(100),
+            on_track: true,
+            next_due_date,
+        }),
         name: budget.name,
-        user_id: budget.user_id,
+        plan_id: budget.plan_id,
         assignments: budget.assignments,
-        target: budget.target
+        target: budget.target,
+        group_id: Some(group.id),
+        archived_at: None,
     }, GetBudgetResponse {
         target: None,
         assignments: vec![],
         name: OTHER_BUDGET.name.clone(),
-        user_id,
+        plan_id: user_id,
         id: *OTHER_BUDGET_ID,
-        total_assigned: Decimal::ZERO
+        total_assigned: Decimal::ZERO,
+        target_status: None,
+        group_id: None,
+        archived_at: None,
     }];
 
     fetched.sort_by_key(|x| x.id);
@@ -166,31 +208,18 @@ pub async fn test_get_budgets(db_pool: MySqlPool) {
     assert_eq!(fetched, expected);
 }
 
-#[sqlx::test]
-pub async fn delete_budget(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let user_id = *USER_ID;
     let id = Uuid::new_v4();
 
     db::budgets::create(
         &db_pool,
-        Budget::new(
-            id,
-            "name".into(),
-            None,
-            user_id,
-            vec![BudgetAssignment {
-                amount: dec!(10),
-                id: Uuid::new_v4(),
-                date: NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
-                source: BudgetAssignmentSource::OtherBudget {
-                    from_budget_id: *OTHER_BUDGET_ID,
-                    link_id: Uuid::new_v4()
-                }
-            }],
-        ),
+        Budget::new(id, "name".into(), None, user_id, vec![], None, None),
     )
     .await
     .unwrap();
@@ -206,10 +235,90 @@ pub async fn delete_budget(db_pool: MySqlPool) {
     assert!(matches!(dbg!(find_response), Err(Error::NotFound)));
 }
 
-#[sqlx::test]
-pub async fn update_budget(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_budget_with_assignments(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = Budget::new(
+        Uuid::new_v4(),
+        "name".into(),
+        None,
+        *USER_ID,
+        vec![BudgetAssignment {
+            amount: dec!(10),
+            id: Uuid::new_v4(),
+            date: NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
+            source: BudgetAssignmentSource::OtherBudget {
+                from_budget_id: *OTHER_BUDGET_ID,
+                link_id: Uuid::new_v4()
+            }
+        }],
+        None,
+        None,
+    );
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    let response = test_server
+        .delete(&format!("/api/budgets/{}", budget.id))
+        .await;
+
+    response.assert_status_bad_request();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    assert_eq!(fetched, budget);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_budget_with_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let payee_id = Uuid::new_v4();
+    let bank_account_id = Uuid::new_v4();
+
+    db::payees::create(&db_pool, payee_id, CreatePayeeRequest::new("payee".into(), *USER_ID))
+        .await
+        .unwrap();
+    db::bank_accounts::create(
+        &db_pool,
+        bank_account_id,
+        CreateBankAccountRequest::new("account".into(), Decimal::ZERO, *USER_ID),
+    )
+    .await
+    .unwrap();
+    db::transactions::create(
+        &db_pool,
+        Transaction::new(
+            Uuid::new_v4(),
+            payee_id,
+            NaiveDate::from_ymd_opt(2024, 11, 28).unwrap(),
+            dec!(-10),
+            bank_account_id,
+            Some(*OTHER_BUDGET_ID),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .delete(&format!("/api/budgets/{}", *OTHER_BUDGET_ID))
+        .await;
+
+    response.assert_status_bad_request();
+
+    let fetched = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+    assert_eq!(fetched, *OTHER_BUDGET);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let user_id = *USER_ID;
     let id = Uuid::new_v4();
@@ -226,7 +335,7 @@ pub async fn update_budget(db_pool: MySqlPool) {
 
     db::budgets::create(
         &db_pool,
-        Budget::new(id, "name".into(), None, user_id, assignments.clone()),
+        Budget::new(id, "name".into(), None, user_id, assignments.clone(), None, None),
     )
     .await
     .unwrap();
@@ -244,6 +353,7 @@ pub async fn update_budget(db_pool: MySqlPool) {
                     },
                 },
             }),
+            None,
         ))
         .await;
 
@@ -270,22 +380,65 @@ pub async fn update_budget(db_pool: MySqlPool) {
         }),
         user_id,
         assignments,
+        None,
+        None,
     );
 
     assert_eq!(find_response, expected);
 }
 
-#[sqlx::test]
-pub async fn transfer_between_budgets(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_budget_rolls_back_on_failure(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    // the new target's schedule is saved before the budget, so this fails part way through
+    fail_writes_to(&db_pool, "UPDATE", "Budgets", "TRUE").await;
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}", *OTHER_BUDGET_ID))
+        .json(&UpdateBudgetRequest::new(
+            "newName".into(),
+            Some(UpdateBudgetTargetRequest::Repeating {
+                target_amount: dec!(10),
+                repeating_type: RepeatingTargetType::BuildUpTo,
+                schedule: UpdateScheduleRequest {
+                    period: SchedulePeriod::Weekly {
+                        starting_on: NaiveDate::from_ymd_opt(2024, 10, 6).unwrap(),
+                    },
+                },
+            }),
+            None,
+        ))
+        .await;
+
+    response.assert_status_failure();
+
+    let fetched = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+    assert_eq!(fetched, *OTHER_BUDGET);
+
+    let schedules: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM Schedules")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(schedules, 0);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn transfer_between_budgets(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let budget = Budget {
         id: Uuid::new_v4(),
         name: "name".into(),
-        user_id: *USER_ID,
+        plan_id: *USER_ID,
         target: None,
-        assignments: vec![]
+        assignments: vec![],
+        group_id: None,
+        archived_at: None,
     };
 
     db::budgets::create(&db_pool, budget.clone()).await.unwrap();
@@ -350,3 +503,559 @@ pub async fn transfer_between_budgets(db_pool: MySqlPool) {
         ..OTHER_BUDGET.deref().clone()
     })
 }
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn transfer_between_budgets_rolls_back_on_failure(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = Budget {
+        id: Uuid::new_v4(),
+        name: "name".into(),
+        plan_id: *USER_ID,
+        target: None,
+        assignments: vec![],
+        group_id: None,
+        archived_at: None,
+    };
+
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    // the from budget is updated first, so only fail once the transfer reaches the other budget
+    fail_writes_to(
+        &db_pool,
+        "INSERT",
+        "BudgetAssignments",
+        &format!("NEW.budget_id = '{}'", OTHER_BUDGET_ID.simple()),
+    )
+    .await;
+
+    let response = test_server.put(&format!("/api/budgets/{}/transfer-to/{}", budget.id, *OTHER_BUDGET_ID))
+        .json(&TransferBudgetRequest {
+            amount: dec!(1),
+            date: NaiveDate::from_ymd_opt(2024, 11, 30).unwrap(),
+        })
+        .await;
+
+    response.assert_status_failure();
+
+    let fetched_1 = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    let fetched_2 = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+
+    assert_eq!(fetched_1, budget);
+    assert_eq!(fetched_2, *OTHER_BUDGET);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_budget_month(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let mut budget = Budget {
+        id: Uuid::new_v4(),
+        name: "name".into(),
+        plan_id: *USER_ID,
+        target: None,
+        assignments: vec![],
+        group_id: None,
+        archived_at: None,
+    };
+    let mut other_budget = OTHER_BUDGET.deref().clone();
+
+    Budget::move_between_budgets(&mut other_budget, &mut budget, dec!(20), NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+    Budget::move_between_budgets(&mut other_budget, &mut budget, dec!(5), NaiveDate::from_ymd_opt(2024, 11, 1).unwrap());
+
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+    db::budgets::update(&db_pool, other_budget.clone()).await.unwrap();
+
+    let response = test_server.get(&format!("/api/budgets/{}/months/2024-11", budget.id)).await;
+
+    response.assert_ok();
+    response.assert_json(&BudgetMonthSummary {
+        budget_id: budget.id,
+        month: NaiveDate::from_ymd_opt(2024, 11, 1).unwrap(),
+        carried_over: dec!(20),
+        assigned: dec!(5),
+        activity: Decimal::ZERO,
+        available: dec!(25),
+        overspent: Decimal::ZERO,
+    });
+
+    let response = test_server.get(&format!("/api/budgets/months/2024-11?plan_id={}", *USER_ID)).await;
+
+    response.assert_ok();
+    let month: GetMonthResponse = response.json();
+
+    assert_eq!(month.month, NaiveDate::from_ymd_opt(2024, 11, 1).unwrap());
+    assert_eq!(month.assigned, Decimal::ZERO);
+    // the other budget was overspent in october, which doesn't carry over into november
+    assert_eq!(month.available, dec!(20));
+    assert_eq!(month.overspent, dec!(5));
+    assert_eq!(month.budgets.len(), 2);
+
+    let response = test_server.get(&format!("/api/budgets/{}/months/november", budget.id)).await;
+
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn transfer_to_and_from_ready_to_assign(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server.put(&format!("/api/budgets/ready-to-assign/transfer-to/{}", *OTHER_BUDGET_ID))
+        .json(&TransferBudgetRequest {
+            amount: dec!(30),
+            date: NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+        })
+        .await;
+
+    response.assert_ok();
+
+    let response = test_server.put(&format!("/api/budgets/{}/transfer-to/ready-to-assign", *OTHER_BUDGET_ID))
+        .json(&TransferBudgetRequest {
+            amount: dec!(10),
+            date: NaiveDate::from_ymd_opt(2024, 12, 7).unwrap(),
+        })
+        .await;
+
+    response.assert_ok();
+
+    let mut fetched = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+    for assignment in &mut fetched.assignments {
+        assignment.id = Uuid::nil();
+    }
+
+    assert_eq!(fetched.assignments, vec![
+        BudgetAssignment {
+            id: Uuid::nil(),
+            amount: dec!(30),
+            date: NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+            source: BudgetAssignmentSource::ReadyToAssign,
+        },
+        BudgetAssignment {
+            id: Uuid::nil(),
+            amount: dec!(-10),
+            date: NaiveDate::from_ymd_opt(2024, 12, 7).unwrap(),
+            source: BudgetAssignmentSource::ReadyToAssign,
+        },
+    ]);
+
+    // there is no income yet, so more has been assigned than is available
+    let response = test_server.get(&format!("/api/plans/{}/ready-to-assign", *USER_ID)).await;
+
+    response.assert_ok();
+    response.assert_json(&GetReadyToAssignResponse { amount: dec!(-20) });
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn suggest_and_apply_assignments(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let today = Local::now().date_naive();
+
+    let pay_frequency = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Weekly { starting_on: today },
+    };
+    db::schedule::create(&db_pool, pay_frequency.clone()).await.unwrap();
+    db::users::update(
+        &db_pool,
+        User::new(*USER_ID, "name".into(), "email@email.com".into(), Some(pay_frequency)),
+    )
+    .await
+    .unwrap();
+
+    let budget = Budget {
+        id: Uuid::new_v4(),
+        name: "name".into(),
+        plan_id: *USER_ID,
+        target: Some(BudgetTarget::OneTime { target_amount: dec!(70) }),
+        assignments: vec![],
+        group_id: None,
+        archived_at: None,
+    };
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    let response = test_server.get(&format!("/api/budgets/suggested-assignments?plan_id={}", *USER_ID)).await;
+
+    response.assert_ok();
+    let suggested: SuggestedAssignments = response.json();
+
+    assert_eq!(suggested, SuggestedAssignments {
+        pay_date: today,
+        assignments: vec![SuggestedAssignment { budget_id: budget.id, amount: dec!(70) }],
+        total: dec!(70),
+    });
+
+    let response = test_server.post("/api/budgets/suggested-assignments")
+        .json(&ApplySuggestedAssignmentsRequest {
+            plan_id: *USER_ID,
+            date: suggested.pay_date,
+            assignments: suggested.assignments,
+        })
+        .await;
+
+    response.assert_ok();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+
+    assert_eq!(fetched.assignments.len(), 1);
+    assert_eq!(fetched.assignments[0].amount, dec!(70));
+    assert_eq!(fetched.assignments[0].date, today);
+    assert_eq!(fetched.assignments[0].source, BudgetAssignmentSource::ReadyToAssign);
+
+    // everything has been assigned now
+    let response = test_server.get(&format!("/api/budgets/suggested-assignments?plan_id={}", *USER_ID)).await;
+
+    response.assert_ok();
+    assert!(response.json::<SuggestedAssignments>().assignments.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn apply_suggested_assignments_to_unknown_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server.post("/api/budgets/suggested-assignments")
+        .json(&ApplySuggestedAssignmentsRequest {
+            plan_id: *USER_ID,
+            date: NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+            assignments: vec![
+                SuggestedAssignment { budget_id: *OTHER_BUDGET_ID, amount: dec!(10) },
+                SuggestedAssignment { budget_id: Uuid::new_v4(), amount: dec!(10) },
+            ],
+        })
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+    assert!(fetched.assignments.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_suggested_target(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let payee_id = Uuid::new_v4();
+    let bank_account_id = Uuid::new_v4();
+    db::payees::create(&db_pool, payee_id, CreatePayeeRequest::new("payee".into(), *USER_ID))
+        .await
+        .unwrap();
+    db::bank_accounts::create(&db_pool, bank_account_id, CreateBankAccountRequest::new("name".into(), Decimal::ZERO, *USER_ID))
+        .await
+        .unwrap();
+
+    for date in [
+        NaiveDate::from_ymd_opt(2024, 9, 15).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 10, 15).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 11, 15).unwrap(),
+    ] {
+        db::transactions::create(
+            &db_pool,
+            Transaction::new(Uuid::new_v4(), payee_id, date, dec!(-12.99), bank_account_id, Some(*OTHER_BUDGET_ID), None),
+        )
+        .await
+        .unwrap();
+    }
+
+    let response = test_server
+        .get(&format!("/api/budgets/{}/suggested-target?payee_id={payee_id}", *OTHER_BUDGET_ID))
+        .await;
+
+    response.assert_ok();
+    response.assert_json(&CreateBudgetTargetRequest::Repeating {
+        target_amount: dec!(12.99),
+        repeating_type: RepeatingTargetType::RequireRepeating,
+        schedule: CreateScheduleRequest {
+            period: SchedulePeriod::Monthly {
+                starting_on: NaiveDate::from_ymd_opt(2024, 11, 15).unwrap(),
+            },
+        },
+    });
+
+    // no payments to this payee
+    let response = test_server
+        .get(&format!("/api/budgets/{}/suggested-target?payee_id={}", *OTHER_BUDGET_ID, Uuid::new_v4()))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_suggested_target_for_unknown_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server
+        .get(&format!("/api/budgets/{}/suggested-target?payee_id={}", Uuid::new_v4(), Uuid::new_v4()))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_in_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+
+    let group_id: Uuid = test_server
+        .post("/api/budget-groups")
+        .json(&CreateBudgetGroupRequest::new("Bills".into(), 0, user_id))
+        .await
+        .json();
+
+    let response = test_server
+        .post("/api/budgets")
+        .json(&CreateBudgetRequest::new("Rent".into(), None, user_id, Some(group_id)))
+        .await;
+
+    response.assert_created();
+
+    let budget = db::budgets::get_single(&db_pool, response.json()).await.unwrap();
+    assert_eq!(budget.group_id, Some(group_id));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_in_missing_group(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server
+        .post("/api/budgets")
+        .json(&CreateBudgetRequest::new("Rent".into(), None, *USER_ID, Some(Uuid::new_v4())))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn archive_budget_moves_remaining_balance(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let date = NaiveDate::from_ymd_opt(2024, 12, 9).unwrap();
+    let mut budget = Budget::new(Uuid::new_v4(), "name".into(), None, *USER_ID, vec![], None, None);
+    budget.move_from_ready_to_assign(dec!(25), date);
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}/archive", budget.id))
+        .json(&ArchiveBudgetRequest::new(Some(*OTHER_BUDGET_ID), date))
+        .await;
+
+    response.assert_ok();
+
+    let archived = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    let other = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+
+    assert!(archived.archived_at.is_some());
+    assert_eq!(archived.total_assigned(), Decimal::ZERO);
+    assert_eq!(other.total_assigned(), dec!(25));
+
+    let response = test_server
+        .get(&format!("/api/budgets?plan_id={}", *USER_ID))
+        .await;
+    let GetBudgetsResponse { budgets, .. } = response.json();
+    assert_eq!(budgets.iter().map(|b| b.id).collect::<Vec<_>>(), vec![*OTHER_BUDGET_ID]);
+
+    let response = test_server
+        .get(&format!("/api/budgets?plan_id={}&include_archived=true", *USER_ID))
+        .await;
+    let GetBudgetsResponse { budgets, .. } = response.json();
+    assert_eq!(budgets.len(), 2);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn archive_budget_with_balance_requires_budget_to_move_to(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let date = NaiveDate::from_ymd_opt(2024, 12, 9).unwrap();
+    let mut budget = Budget::new(Uuid::new_v4(), "name".into(), None, *USER_ID, vec![], None, None);
+    budget.move_from_ready_to_assign(dec!(25), date);
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}/archive", budget.id))
+        .json(&ArchiveBudgetRequest::new(None, date))
+        .await;
+
+    response.assert_status_bad_request();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    assert_eq!(fetched.archived_at, None);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn unarchive_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let date = NaiveDate::from_ymd_opt(2024, 12, 9).unwrap();
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}/archive", *OTHER_BUDGET_ID))
+        .json(&ArchiveBudgetRequest::new(None, date))
+        .await;
+    response.assert_ok();
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}/unarchive", *OTHER_BUDGET_ID))
+        .await;
+    response.assert_ok();
+
+    let fetched = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+    assert_eq!(fetched.archived_at, None);
+}
+
+async fn create_other_users_budget(db_pool: &TestPool) -> Budget {
+    let other_user_id = create_other_user(db_pool).await;
+
+    let budget = Budget::new(Uuid::new_v4(), "name".into(), None, other_user_id, vec![], None, None);
+    db::budgets::create(db_pool, budget.clone()).await.unwrap();
+
+    budget
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_budgets(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+
+    test_server
+        .get(&format!("/api/budgets?plan_id={}", budget.plan_id))
+        .await
+        .assert_status_not_found();
+
+    test_server
+        .get(&format!("/api/budgets/{}/months/2024-11", budget.id))
+        .await
+        .assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_budget_in_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+
+    let response = test_server
+        .post("/api/budgets")
+        .json(&CreateBudgetRequest::new("name".into(), None, budget.plan_id, None))
+        .await;
+
+    response.assert_status_not_found();
+
+    let budgets = db::budgets::get(&db_pool, budget.plan_id, true).await.unwrap();
+    assert_eq!(budgets.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+
+    let response = test_server
+        .put(&format!("/api/budgets/{}", budget.id))
+        .json(&UpdateBudgetRequest::new("newName".into(), None, None))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    assert_eq!(fetched, budget);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+
+    test_server
+        .delete(&format!("/api/budgets/{}", budget.id))
+        .await
+        .assert_status_not_found();
+
+    db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn archive_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+
+    test_server
+        .put(&format!("/api/budgets/{}/archive", budget.id))
+        .json(&ArchiveBudgetRequest::new(None, NaiveDate::from_ymd_opt(2024, 12, 9).unwrap()))
+        .await
+        .assert_status_not_found();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    assert_eq!(fetched.archived_at, None);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn transfer_to_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let budget = create_other_users_budget(&db_pool).await;
+    let request = TransferBudgetRequest {
+        amount: dec!(1),
+        date: NaiveDate::from_ymd_opt(2024, 11, 30).unwrap(),
+    };
+
+    test_server.put(&format!("/api/budgets/{}/transfer-to/{}", *OTHER_BUDGET_ID, budget.id))
+        .json(&request)
+        .await
+        .assert_status_not_found();
+
+    test_server.put(&format!("/api/budgets/{}/transfer-to/{}", budget.id, *OTHER_BUDGET_ID))
+        .json(&request)
+        .await
+        .assert_status_not_found();
+
+    test_server.put(&format!("/api/budgets/ready-to-assign/transfer-to/{}", budget.id))
+        .json(&request)
+        .await
+        .assert_status_not_found();
+
+    let fetched = db::budgets::get_single(&db_pool, budget.id).await.unwrap();
+    let own = db::budgets::get_single(&db_pool, *OTHER_BUDGET_ID).await.unwrap();
+
+    assert!(fetched.assignments.is_empty());
+    assert!(own.assignments.is_empty());
+}
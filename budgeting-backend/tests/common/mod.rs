@@ -1,15 +1,81 @@
 use std::sync::{Once, OnceLock};
 
 use axum_test::{TestResponse, TestServer};
-use budgeting_backend::new_app;
-use http::StatusCode;
-use sqlx::MySqlPool;
+use budgeting_backend::{
+    auth,
+    db::{self, TestPool},
+    models::User,
+    new_app,
+};
+use http::{header::AUTHORIZATION, StatusCode};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use uuid::Uuid;
 
-pub fn integration_test_init(db_pool: MySqlPool) -> TestServer {
+pub fn integration_test_init(db_pool: TestPool) -> TestServer {
     init_test_logger();
 
-    TestServer::new(new_app(db_pool)).unwrap()
+    TestServer::new(new_app(db_pool.into())).unwrap()
+}
+
+/// Starts a session for `user_id` and sends its token with every following request
+#[allow(unused)]
+pub async fn sign_in(test_server: &mut TestServer, db_pool: &TestPool, user_id: Uuid) {
+    let session = auth::new_session(user_id);
+
+    db::sessions::create(db_pool, session.clone())
+        .await
+        .unwrap();
+
+    test_server.add_header(AUTHORIZATION, format!("Bearer {}", session.token));
+}
+
+/// Creates a user that tests aren't signed in as. Their default plan shares their id
+#[allow(unused)]
+pub async fn create_other_user(db_pool: &TestPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+
+    db::users::create(
+        db_pool,
+        User::new(
+            user_id,
+            "other".to_owned(),
+            "other@somewhere.com".to_owned(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    user_id
+}
+
+/// Makes `statement` (`INSERT`, `UPDATE` or `DELETE`) on `table` fail whenever `condition` holds
+/// for the row, so tests can check a handler doesn't leave a partial write behind
+#[allow(unused)]
+pub async fn fail_writes_to(db_pool: &TestPool, statement: &str, table: &str, condition: &str) {
+    let name = format!("fail_{table}_{}", statement.to_lowercase());
+    #[cfg(not(feature = "sqlite-tests"))]
+    let trigger = format!(
+        r"
+CREATE TRIGGER {name} BEFORE {statement} ON {table}
+FOR EACH ROW
+BEGIN
+    IF {condition} THEN
+        SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = 'Injected failure';
+    END IF;
+END"
+    );
+    #[cfg(feature = "sqlite-tests")]
+    let trigger = format!(
+        r"
+CREATE TRIGGER {name} BEFORE {statement} ON {table}
+FOR EACH ROW WHEN {condition}
+BEGIN
+    SELECT RAISE(ABORT, 'Injected failure');
+END"
+    );
+
+    sqlx::raw_sql(&trigger).execute(db_pool).await.unwrap();
 }
 
 static INITIALIZE_LOGGER: Once = Once::new();
@@ -0,0 +1,267 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool},
+    models::{
+        Budget, BudgetTarget, CreateBankAccountRequest, CreatePayeeRequest, PlanRole,
+        RepeatingTargetType, Schedule, SchedulePeriod, ScheduledTransaction, Transaction, User,
+        UserExport, EXPORT_VERSION,
+    },
+};
+use chrono::NaiveDate;
+use common::*;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BANK_ACCOUNT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+fn schedule() -> Schedule {
+    Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Monthly {
+            starting_on: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+        },
+    }
+}
+
+async fn test_init(db_pool: &TestPool) {
+    let plan_id = *USER_ID;
+    let date = NaiveDate::from_ymd_opt(2024, 12, 5).unwrap();
+
+    let pay_frequency = schedule();
+    db::schedule::create(db_pool, pay_frequency.clone())
+        .await
+        .unwrap();
+    db::users::create(
+        db_pool,
+        User::new(
+            *USER_ID,
+            "name".into(),
+            "email@email.com".into(),
+            Some(pay_frequency),
+        ),
+    )
+    .await
+    .unwrap();
+
+    db::bank_accounts::create(
+        db_pool,
+        *BANK_ACCOUNT_ID,
+        CreateBankAccountRequest::new("Checking".into(), dec!(500), plan_id),
+    )
+    .await
+    .unwrap();
+
+    db::payees::create(
+        db_pool,
+        *PAYEE_ID,
+        CreatePayeeRequest::new("Landlord".into(), plan_id),
+    )
+    .await
+    .unwrap();
+
+    let target_schedule = schedule();
+    db::schedule::create(db_pool, target_schedule.clone())
+        .await
+        .unwrap();
+
+    let mut budget = Budget::new(
+        *BUDGET_ID,
+        "Rent".into(),
+        Some(BudgetTarget::Repeating {
+            target_amount: dec!(400),
+            repeating_type: RepeatingTargetType::RequireRepeating,
+            schedule: target_schedule,
+        }),
+        plan_id,
+        vec![],
+        None,
+        None,
+    );
+    db::budgets::create(db_pool, budget.clone()).await.unwrap();
+
+    let transaction = Transaction::new(
+        Uuid::new_v4(),
+        *PAYEE_ID,
+        date,
+        dec!(-400),
+        *BANK_ACCOUNT_ID,
+        Some(*BUDGET_ID),
+        None,
+    );
+    db::transactions::create(db_pool, transaction.clone())
+        .await
+        .unwrap();
+
+    budget.move_from_ready_to_assign(dec!(450), date);
+    budget.assign_from_transaction(&transaction);
+    db::budgets::update(db_pool, budget).await.unwrap();
+
+    let scheduled_transaction_schedule = schedule();
+    db::schedule::create(db_pool, scheduled_transaction_schedule.clone())
+        .await
+        .unwrap();
+    db::scheduled_transactions::create(
+        db_pool,
+        ScheduledTransaction::new(
+            Uuid::new_v4(),
+            *PAYEE_ID,
+            dec!(-400),
+            *BANK_ACCOUNT_ID,
+            *BUDGET_ID,
+            scheduled_transaction_schedule,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn export_and_restore(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server.get("/api/users/me/export").await;
+
+    response.assert_ok();
+    let export: UserExport = response.json();
+    assert_eq!(export.version, EXPORT_VERSION);
+    assert_eq!(export.user.id, *USER_ID);
+
+    let [plan] = export.plans.as_slice() else {
+        panic!("expected a single plan");
+    };
+    assert_eq!(plan.plan.id, *USER_ID);
+    assert_eq!(plan.bank_accounts.len(), 1);
+    assert_eq!(plan.payees.len(), 1);
+    assert_eq!(plan.budgets.len(), 1);
+    assert_eq!(plan.transactions.len(), 1);
+    assert_eq!(plan.scheduled_transactions.len(), 1);
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let mut other_test_server = integration_test_init(db_pool.clone());
+    sign_in(&mut other_test_server, &db_pool, other_user_id).await;
+
+    let response = other_test_server
+        .post("/api/users/me/restore")
+        .json(&export)
+        .await;
+
+    response.assert_created();
+    let plan_ids: Vec<Uuid> = response.json();
+    let [plan_id] = plan_ids.as_slice() else {
+        panic!("expected a single plan");
+    };
+    assert_ne!(*plan_id, *USER_ID);
+
+    let plans = db::plans::get_owned(&db_pool, other_user_id).await.unwrap();
+    assert_eq!(plans.len(), 2);
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, *plan_id).await.unwrap();
+    let [bank_account] = &*bank_accounts else {
+        panic!("expected a single bank account");
+    };
+    assert_ne!(bank_account.id, *BANK_ACCOUNT_ID);
+    assert_eq!(bank_account.name, "Checking");
+    assert_eq!(bank_account.balance, dec!(100));
+
+    let budgets = db::budgets::get(&db_pool, *plan_id, true).await.unwrap();
+    let [budget] = &*budgets else {
+        panic!("expected a single budget");
+    };
+    assert_eq!(budget.total_assigned(), dec!(50));
+    assert!(matches!(
+        budget.target,
+        Some(BudgetTarget::Repeating {
+            target_amount,
+            ..
+        }) if target_amount == dec!(400)
+    ));
+
+    let transactions = db::transactions::get_all(&db_pool, bank_account.id)
+        .await
+        .unwrap();
+    assert_eq!(transactions[0].budget_id, Some(budget.id));
+
+    let scheduled_transactions = db::scheduled_transactions::get(&db_pool, bank_account.id)
+        .await
+        .unwrap();
+    assert_eq!(scheduled_transactions[0].budget_id, budget.id);
+
+    let ready_to_assign = db::ready_to_assign::get(&db_pool, *plan_id).await.unwrap();
+    assert_eq!(ready_to_assign, dec!(50));
+
+    let other_user = db::users::get_single(&db_pool, other_user_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        other_user.pay_frequency.map(|schedule| schedule.period),
+        Some(schedule().period)
+    );
+
+    // the original plan is untouched
+    let budgets = db::budgets::get(&db_pool, *USER_ID, true).await.unwrap();
+    assert_eq!(budgets[0].id, *BUDGET_ID);
+    assert_eq!(budgets[0].total_assigned(), dec!(50));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn restore_newer_version(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let mut export: UserExport = test_server.get("/api/users/me/export").await.json();
+    export.version = EXPORT_VERSION + 1;
+
+    test_server
+        .post("/api/users/me/restore")
+        .json(&export)
+        .await
+        .assert_status_bad_request();
+
+    let plans = db::plans::get_owned(&db_pool, *USER_ID).await.unwrap();
+    assert_eq!(plans.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn restore_missing_reference(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let mut export: UserExport = test_server.get("/api/users/me/export").await.json();
+    export.plans[0].transactions[0].payee_id = Uuid::new_v4();
+
+    test_server
+        .post("/api/users/me/restore")
+        .json(&export)
+        .await
+        .assert_status_bad_request();
+
+    let plans = db::plans::get_owned(&db_pool, *USER_ID).await.unwrap();
+    assert_eq!(plans.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn export_leaves_out_shared_plans(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    db::plan_members::create(&db_pool, other_user_id, *USER_ID, PlanRole::Editor)
+        .await
+        .unwrap();
+
+    let export: UserExport = test_server.get("/api/users/me/export").await.json();
+
+    let plan_ids: Vec<_> = export.plans.iter().map(|plan| plan.plan.id).collect();
+    assert_eq!(plan_ids, [*USER_ID]);
+}
@@ -0,0 +1,552 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool, Error},
+    models::{
+        Budget, CreateBankAccountRequest, CreatePayeeRequest, CsvMapping, ImportPreview,
+        ImportTransactionRequest, ImportTransactionsRequest, ImportedTransaction, Transaction,
+        User,
+    },
+};
+use chrono::NaiveDate;
+use common::*;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BANK_ACCOUNT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+const STATEMENT: &str = r"OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1>
+<STMTTRNRS>
+<STMTRS>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20241205
+<TRNAMT>-42.10
+<FITID>2024120501
+<NAME>CORNER SHOP
+<MEMO>Card payment
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20241215
+<TRNAMT>1500.00
+<FITID>2024121501
+<NAME>Employer
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS>
+</STMTTRNRS>
+</BANKMSGSRSV1>
+</OFX>
+";
+
+async fn test_init(db_pool: &TestPool) {
+    let plan_id = *USER_ID;
+
+    db::users::create(
+        db_pool,
+        User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+    )
+    .await
+    .unwrap();
+
+    db::payees::create(
+        db_pool,
+        *PAYEE_ID,
+        CreatePayeeRequest::new("Corner Shop".into(), plan_id),
+    )
+    .await
+    .unwrap();
+
+    db::bank_accounts::create(
+        db_pool,
+        *BANK_ACCOUNT_ID,
+        CreateBankAccountRequest::new("name".into(), dec!(0), plan_id),
+    )
+    .await
+    .unwrap();
+
+    db::budgets::create(
+        db_pool,
+        Budget::new(
+            *BUDGET_ID,
+            "Budget".into(),
+            None,
+            plan_id,
+            vec![],
+            None,
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+fn import_request() -> ImportTransactionsRequest {
+    ImportTransactionsRequest::new(vec![
+        ImportTransactionRequest::new(
+            Some("2024120501".into()),
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+            dec!(-42.10),
+            "CORNER SHOP".into(),
+            Some(*BUDGET_ID),
+            Some("Card payment".into()),
+        ),
+        ImportTransactionRequest::new(
+            Some("2024121501".into()),
+            NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+            dec!(1500),
+            "Employer".into(),
+            None,
+            None,
+        ),
+    ])
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn preview_ofx_import(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/imports/ofx/preview",
+            *BANK_ACCOUNT_ID
+        ))
+        .text(STATEMENT)
+        .await;
+
+    response.assert_ok();
+    response.assert_json(&ImportPreview::new(
+        vec![
+            ImportedTransaction::new(
+                Some("2024120501".into()),
+                NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+                dec!(-42.10),
+                "CORNER SHOP".into(),
+                Some(*PAYEE_ID),
+                Some("Card payment".into()),
+            ),
+            ImportedTransaction::new(
+                Some("2024121501".into()),
+                NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                dec!(1500),
+                "Employer".into(),
+                None,
+                None,
+            ),
+        ],
+        vec![],
+    ));
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn preview_invalid_file(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/imports/ofx/preview",
+            *BANK_ACCOUNT_ID
+        ))
+        .text("date,amount\n2024-12-05,-42.10")
+        .await
+        .assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let response = test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&import_request())
+        .await;
+
+    response.assert_created();
+    let ids: Vec<Uuid> = response.json();
+    assert_eq!(ids.len(), 2);
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 2);
+
+    let outflow = transactions.iter().find(|x| x.id == ids[0]).unwrap();
+    assert_eq!(outflow.payee_id, *PAYEE_ID);
+    assert_eq!(outflow.budget_id, Some(*BUDGET_ID));
+    assert_eq!(outflow.amount, dec!(-42.10));
+    assert_eq!(outflow.memo.as_deref(), Some("Card payment"));
+
+    let payees = db::payees::get(&db_pool, *USER_ID).await.unwrap();
+    assert_eq!(payees.len(), 2);
+    let employer = payees.iter().find(|x| x.name == "Employer").unwrap();
+
+    let inflow = transactions.iter().find(|x| x.id == ids[1]).unwrap();
+    assert_eq!(inflow.payee_id, employer.id);
+    assert_eq!(inflow.budget_id, None);
+
+    let budget = db::budgets::get_single(&db_pool, *BUDGET_ID).await.unwrap();
+    assert_eq!(budget.total_assigned(), dec!(-42.10));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn skip_already_imported_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&import_request())
+        .await
+        .assert_created();
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/imports/ofx/preview",
+            *BANK_ACCOUNT_ID
+        ))
+        .text(STATEMENT)
+        .await;
+
+    response.assert_ok();
+    let preview: ImportPreview = response.json();
+    assert!(preview.transactions.is_empty());
+    assert_eq!(preview.already_imported.len(), 2);
+
+    let response = test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&import_request())
+        .await;
+
+    response.assert_created();
+    let ids: Vec<Uuid> = response.json();
+    assert!(ids.is_empty());
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 2);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_outflow_without_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let mut request = import_request();
+    request.transactions[0].budget_id = None;
+
+    test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&request)
+        .await
+        .assert_status_bad_request();
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_into_other_users_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let other_bank_account_id = Uuid::new_v4();
+    db::bank_accounts::create(
+        &db_pool,
+        other_bank_account_id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    test_server
+        .post(&format!(
+            "/api/bank-accounts/{other_bank_account_id}/imports/ofx/preview"
+        ))
+        .text(STATEMENT)
+        .await
+        .assert_status_not_found();
+
+    // only the income, so the request fails on the account rather than the budget
+    let mut request = import_request();
+    request.transactions.remove(0);
+
+    test_server
+        .post(&format!(
+            "/api/bank-accounts/{other_bank_account_id}/imports"
+        ))
+        .json(&request)
+        .await
+        .assert_status_not_found();
+
+    let transactions = db::transactions::get_all(&db_pool, other_bank_account_id)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_with_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let other_budget_id = Uuid::new_v4();
+    db::budgets::create(
+        &db_pool,
+        Budget::new(
+            other_budget_id,
+            "Budget".into(),
+            None,
+            other_user_id,
+            vec![],
+            None,
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let mut request = import_request();
+    request.transactions[0].budget_id = Some(other_budget_id);
+
+    test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&request)
+        .await
+        .assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn save_csv_mapping(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    test_server
+        .get(&format!(
+            "/api/bank-accounts/{}/csv-mapping",
+            *BANK_ACCOUNT_ID
+        ))
+        .await
+        .assert_status_not_found();
+
+    let mapping = CsvMapping::new(
+        ';',
+        ',',
+        false,
+        0,
+        "%d.%m.%Y".into(),
+        None,
+        Some(2),
+        Some(3),
+        1,
+    );
+
+    test_server
+        .put(&format!(
+            "/api/bank-accounts/{}/csv-mapping",
+            *BANK_ACCOUNT_ID
+        ))
+        .json(&mapping)
+        .await
+        .assert_ok();
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{}/csv-mapping",
+            *BANK_ACCOUNT_ID
+        ))
+        .await;
+    response.assert_ok();
+    response.assert_json(&mapping);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn save_invalid_csv_mapping(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    test_server
+        .put(&format!(
+            "/api/bank-accounts/{}/csv-mapping",
+            *BANK_ACCOUNT_ID
+        ))
+        .json(&CsvMapping::new(
+            ',',
+            '.',
+            true,
+            0,
+            "%Y-%m-%d".into(),
+            Some(1),
+            Some(2),
+            None,
+            3,
+        ))
+        .await
+        .assert_status_bad_request();
+
+    let mapping = db::csv_mappings::get(&db_pool, *BANK_ACCOUNT_ID).await;
+    assert!(matches!(mapping, Err(Error::NotFound)));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn preview_csv_without_mapping(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/imports/csv/preview",
+            *BANK_ACCOUNT_ID
+        ))
+        .text("2024-12-05,Corner Shop,-42.10")
+        .await
+        .assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn preview_csv_import_with_duplicates(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    db::csv_mappings::set(
+        &db_pool,
+        *BANK_ACCOUNT_ID,
+        CsvMapping::new(',', '.', true, 0, "%d/%m/%Y".into(), Some(2), None, None, 1),
+    )
+    .await
+    .unwrap();
+
+    db::transactions::create(
+        &db_pool,
+        Transaction::new(
+            Uuid::new_v4(),
+            *PAYEE_ID,
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+            dec!(-42.10),
+            *BANK_ACCOUNT_ID,
+            Some(*BUDGET_ID),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/imports/csv/preview",
+            *BANK_ACCOUNT_ID
+        ))
+        .text(
+            "Date,Description,Amount
+05/12/2024,Corner Shop,-42.10
+06/12/2024,Corner Shop,-42.10
+15/12/2024,Employer,1500.00
+",
+        )
+        .await;
+
+    response.assert_ok();
+    response.assert_json(&ImportPreview::new(
+        vec![
+            ImportedTransaction::new(
+                None,
+                NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+                dec!(-42.10),
+                "Corner Shop".into(),
+                Some(*PAYEE_ID),
+                None,
+            ),
+            ImportedTransaction::new(
+                None,
+                NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                dec!(1500),
+                "Employer".into(),
+                None,
+                None,
+            ),
+        ],
+        vec![ImportedTransaction::new(
+            None,
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+            dec!(-42.10),
+            "Corner Shop".into(),
+            Some(*PAYEE_ID),
+            None,
+        )],
+    ));
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_skips_duplicates_without_external_id(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let request = ImportTransactionsRequest::new(vec![ImportTransactionRequest::new(
+        None,
+        NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+        dec!(-42.10),
+        "Corner Shop".into(),
+        Some(*BUDGET_ID),
+        None,
+    )]);
+
+    test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&request)
+        .await
+        .assert_created();
+
+    let response = test_server
+        .post(&format!("/api/bank-accounts/{}/imports", *BANK_ACCOUNT_ID))
+        .json(&request)
+        .await;
+
+    response.assert_created();
+    let ids: Vec<Uuid> = response.json();
+    assert!(ids.is_empty());
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+}
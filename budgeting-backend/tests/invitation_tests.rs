@@ -0,0 +1,365 @@
+mod common;
+use std::sync::LazyLock;
+
+use axum_test::TestServer;
+use budgeting_backend::{
+    db::{self, TestPool, Error},
+    models::{
+        AcceptInvitationRequest, CreateInvitationRequest, CreateInvitationResponse,
+        CreatePayeeRequest, Invitation, PlanMember, PlanRole, UpdatePlanMemberRequest,
+        UpdatePlanRequest, User,
+    },
+};
+use common::*;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+async fn test_init(db_pool: &TestPool) {
+    db::users::create(
+        db_pool,
+        User::new(
+            *USER_ID,
+            "name".to_owned(),
+            "someone@somewhere.com".to_owned(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+/// Adds the other user to the signed in user's plan and signs them in on their own server
+async fn add_member(db_pool: &TestPool, role: PlanRole) -> (Uuid, TestServer) {
+    let other_user_id = create_other_user(db_pool).await;
+
+    db::plan_members::create(db_pool, *USER_ID, other_user_id, role)
+        .await
+        .unwrap();
+
+    let mut other_server = integration_test_init(db_pool.clone());
+    sign_in(&mut other_server, db_pool, other_user_id).await;
+
+    (other_user_id, other_server)
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn invite_and_accept(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let other_user_id = create_other_user(&db_pool).await;
+    let mut other_server = integration_test_init(db_pool.clone());
+    sign_in(&mut other_server, &db_pool, other_user_id).await;
+
+    let response = test_server
+        .post(&format!("/api/plans/{plan_id}/invitations"))
+        .json(&CreateInvitationRequest::new(
+            "other@somewhere.com".into(),
+            PlanRole::Editor,
+        ))
+        .await;
+
+    response.assert_created();
+    let CreateInvitationResponse {
+        id: invitation_id,
+        token,
+    } = response.json();
+
+    let response = other_server.get("/api/invitations").await;
+    response.assert_ok();
+    response.assert_json(&[Invitation::new(
+        invitation_id,
+        plan_id,
+        "other@somewhere.com".into(),
+        PlanRole::Editor,
+        *USER_ID,
+    )]);
+
+    // knowing the id isn't enough to accept
+    other_server
+        .post(&format!("/api/invitations/{invitation_id}/accept"))
+        .json(&AcceptInvitationRequest::new("wrong".into()))
+        .await
+        .assert_status_not_found();
+
+    other_server
+        .post(&format!("/api/invitations/{invitation_id}/accept"))
+        .json(&AcceptInvitationRequest::new(token))
+        .await
+        .assert_ok();
+
+    let response = test_server
+        .get(&format!("/api/plans/{plan_id}/members"))
+        .await;
+    response.assert_ok();
+    response.assert_json(&[
+        PlanMember::new(
+            *USER_ID,
+            "name".into(),
+            "someone@somewhere.com".into(),
+            PlanRole::Owner,
+        ),
+        PlanMember::new(
+            other_user_id,
+            "other".into(),
+            "other@somewhere.com".into(),
+            PlanRole::Editor,
+        ),
+    ]);
+
+    let invitation = db::invitations::get_single(&db_pool, invitation_id).await;
+    assert!(matches!(invitation, Err(Error::NotFound)));
+
+    other_server
+        .get(&format!("/api/payees?plan_id={plan_id}"))
+        .await
+        .assert_ok();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn accept_someone_elses_invitation(db_pool: TestPool) {
+    test_init(&db_pool).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let mut other_server = integration_test_init(db_pool.clone());
+    sign_in(&mut other_server, &db_pool, other_user_id).await;
+
+    let invitation = Invitation::new(
+        Uuid::new_v4(),
+        *USER_ID,
+        "someone-else@somewhere.com".into(),
+        PlanRole::Owner,
+        *USER_ID,
+    );
+    db::invitations::create(&db_pool, invitation.clone(), "token")
+        .await
+        .unwrap();
+
+    other_server
+        .post(&format!("/api/invitations/{}/accept", invitation.id))
+        .json(&AcceptInvitationRequest::new("token".into()))
+        .await
+        .assert_status_not_found();
+
+    let members = db::plan_members::get(&db_pool, *USER_ID).await.unwrap();
+    assert_eq!(members.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn revoke_invitation(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let invitation = Invitation::new(
+        Uuid::new_v4(),
+        plan_id,
+        "other@somewhere.com".into(),
+        PlanRole::Viewer,
+        *USER_ID,
+    );
+    db::invitations::create(&db_pool, invitation.clone(), "token")
+        .await
+        .unwrap();
+
+    test_server
+        .delete(&format!(
+            "/api/plans/{plan_id}/invitations/{}",
+            invitation.id
+        ))
+        .await
+        .assert_ok();
+
+    let fetched = db::invitations::get_single(&db_pool, invitation.id).await;
+    assert!(matches!(fetched, Err(Error::NotFound)));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn invite_existing_member_or_twice(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    add_member(&db_pool, PlanRole::Viewer).await;
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/invitations"))
+        .json(&CreateInvitationRequest::new(
+            "other@somewhere.com".into(),
+            PlanRole::Editor,
+        ))
+        .await
+        .assert_status_bad_request();
+
+    let request = CreateInvitationRequest::new("new@somewhere.com".into(), PlanRole::Editor);
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/invitations"))
+        .json(&request)
+        .await
+        .assert_created();
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/invitations"))
+        .json(&request)
+        .await
+        .assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn invite_to_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    test_server
+        .post(&format!("/api/plans/{other_user_id}/invitations"))
+        .json(&CreateInvitationRequest::new(
+            "new@somewhere.com".into(),
+            PlanRole::Owner,
+        ))
+        .await
+        .assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn viewer_cannot_make_changes(db_pool: TestPool) {
+    test_init(&db_pool).await;
+
+    let plan_id = *USER_ID;
+    let (_, other_server) = add_member(&db_pool, PlanRole::Viewer).await;
+
+    other_server
+        .get(&format!("/api/budgets?plan_id={plan_id}"))
+        .await
+        .assert_ok();
+
+    other_server
+        .post("/api/payees")
+        .json(&CreatePayeeRequest::new("Payee".into(), plan_id))
+        .await
+        .assert_status_forbidden();
+
+    let payees = db::payees::get(&db_pool, plan_id).await.unwrap();
+    assert!(payees.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn editor_cannot_manage_plan(db_pool: TestPool) {
+    test_init(&db_pool).await;
+
+    let plan_id = *USER_ID;
+    let (_, other_server) = add_member(&db_pool, PlanRole::Editor).await;
+
+    other_server
+        .post("/api/payees")
+        .json(&CreatePayeeRequest::new("Payee".into(), plan_id))
+        .await
+        .assert_created();
+
+    other_server
+        .put(&format!("/api/plans/{plan_id}"))
+        .json(&UpdatePlanRequest::new("Renamed".into()))
+        .await
+        .assert_status_forbidden();
+
+    other_server
+        .post(&format!("/api/plans/{plan_id}/invitations"))
+        .json(&CreateInvitationRequest::new(
+            "new@somewhere.com".into(),
+            PlanRole::Owner,
+        ))
+        .await
+        .assert_status_forbidden();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn remove_member(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let (other_user_id, other_server) = add_member(&db_pool, PlanRole::Editor).await;
+
+    test_server
+        .delete(&format!("/api/plans/{plan_id}/members/{other_user_id}"))
+        .await
+        .assert_ok();
+
+    other_server
+        .get(&format!("/api/payees?plan_id={plan_id}"))
+        .await
+        .assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_member_role(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let (other_user_id, other_server) = add_member(&db_pool, PlanRole::Viewer).await;
+
+    other_server
+        .put(&format!("/api/plans/{plan_id}/members/{other_user_id}"))
+        .json(&UpdatePlanMemberRequest::new(PlanRole::Owner))
+        .await
+        .assert_status_forbidden();
+
+    test_server
+        .put(&format!("/api/plans/{plan_id}/members/{other_user_id}"))
+        .json(&UpdatePlanMemberRequest::new(PlanRole::Editor))
+        .await
+        .assert_ok();
+
+    other_server
+        .post("/api/payees")
+        .json(&CreatePayeeRequest::new("Payee".into(), plan_id))
+        .await
+        .assert_created();
+
+    // the last owner can't hand over the plan by demoting themselves
+    test_server
+        .put(&format!("/api/plans/{plan_id}/members/{}", *USER_ID))
+        .json(&UpdatePlanMemberRequest::new(PlanRole::Editor))
+        .await
+        .assert_status_bad_request();
+
+    let members = db::plan_members::get(&db_pool, plan_id).await.unwrap();
+    assert_eq!(
+        members
+            .iter()
+            .map(|member| (member.user_id, member.role))
+            .collect::<Vec<_>>(),
+        vec![
+            (*USER_ID, PlanRole::Owner),
+            (other_user_id, PlanRole::Editor)
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn last_owner_cannot_leave(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    test_server
+        .delete(&format!("/api/plans/{plan_id}/members/{}", *USER_ID))
+        .await
+        .assert_status_bad_request();
+
+    let members = db::plan_members::get(&db_pool, plan_id).await.unwrap();
+    assert_eq!(members.len(), 1);
+}
@@ -2,16 +2,15 @@ mod common;
 use std::sync::LazyLock;
 
 use budgeting_backend::{
-    db,
+    db::{self, TestPool},
     models::{CreatePayeeRequest, Payee, UpdatePayeeRequest, User},
 };
 use common::*;
-use sqlx::MySqlPool;
 use uuid::Uuid;
 
 static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
 
-async fn test_init(db_pool: &MySqlPool) {
+async fn test_init(db_pool: &TestPool) {
     let user_id = *USER_ID;
 
     db::users::create(
@@ -27,10 +26,11 @@ async fn test_init(db_pool: &MySqlPool) {
     .unwrap();
 }
 
-#[sqlx::test]
-pub async fn create_payee(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let user_id = *USER_ID;
 
@@ -50,10 +50,11 @@ pub async fn create_payee(db_pool: MySqlPool) {
     )
 }
 
-#[sqlx::test]
-pub async fn get_payees(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_payees(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let payee_id = Uuid::new_v4();
     let user_id = *USER_ID;
@@ -67,17 +68,18 @@ pub async fn get_payees(db_pool: MySqlPool) {
     .unwrap();
 
     let response = test_server
-        .get(&format!("/api/payees?user_id={user_id}"))
+        .get(&format!("/api/payees?plan_id={user_id}"))
         .await;
 
     response.assert_ok();
     response.assert_json(&[Payee::new(payee_id, "Name".to_owned(), user_id)]);
 }
 
-#[sqlx::test]
-pub async fn update_payee(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let payee_id = Uuid::new_v4();
     let user_id = *USER_ID;
@@ -102,10 +104,11 @@ pub async fn update_payee(db_pool: MySqlPool) {
     assert_eq!(fetched.name, "NewName");
 }
 
-#[sqlx::test]
-pub async fn delete_payee(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
 
     let id = Uuid::new_v4();
     let user_id = *USER_ID;
@@ -126,3 +129,90 @@ pub async fn delete_payee(db_pool: MySqlPool) {
 
     assert!(matches!(fetched, Err(db::Error::NotFound)));
 }
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_payees(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .get(&format!("/api/payees?plan_id={other_user_id}"))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_payee_in_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .post("/api/payees")
+        .json(&CreatePayeeRequest::new("Payee".to_owned(), other_user_id))
+        .await;
+
+    response.assert_status_not_found();
+
+    let payees = db::payees::get(&db_pool, other_user_id).await.unwrap();
+    assert!(payees.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let payee_id = Uuid::new_v4();
+
+    db::payees::create(
+        &db_pool,
+        payee_id,
+        CreatePayeeRequest::new("Name".to_owned(), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .put(&format!("/api/payees/{payee_id}"))
+        .json(&UpdatePayeeRequest::new("NewName".into()))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::payees::get_single(&db_pool, payee_id).await.unwrap();
+
+    assert_eq!(fetched.name, "Name");
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let payee_id = Uuid::new_v4();
+
+    db::payees::create(
+        &db_pool,
+        payee_id,
+        CreatePayeeRequest::new("Name".to_owned(), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server.delete(&format!("/api/payees/{payee_id}")).await;
+
+    response.assert_status_not_found();
+
+    db::payees::get_single(&db_pool, payee_id).await.unwrap();
+}
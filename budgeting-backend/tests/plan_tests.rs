@@ -0,0 +1,276 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool},
+    models::{
+        Budget, BudgetGroup, BudgetTarget, CopyPlanRequest, CreateBankAccountRequest,
+        CreatePlanRequest, GetBudgetsResponse, GetReadyToAssignResponse, Plan, PlanRole,
+        RepeatingTargetType, Schedule, SchedulePeriod, UpdatePlanRequest, User,
+    },
+};
+use chrono::NaiveDate;
+use common::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+async fn test_init(db_pool: &TestPool) {
+    let user_id = *USER_ID;
+
+    db::users::create(
+        db_pool,
+        User::new(
+            user_id,
+            "name".to_owned(),
+            "someone@somewhere.com".to_owned(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+
+    let response = test_server
+        .post("/api/plans")
+        .json(&CreatePlanRequest::new("Household".to_owned()))
+        .await;
+
+    response.assert_created();
+    let plan_id: Uuid = response.json();
+
+    let found_plan = db::plans::get_single(&db_pool, plan_id).await.unwrap();
+
+    assert_eq!(found_plan, Plan::new(plan_id, "Household".to_owned()));
+
+    let members = db::plan_members::get(&db_pool, plan_id).await.unwrap();
+    assert_eq!(
+        members.iter().map(|m| (m.user_id, m.role)).collect::<Vec<_>>(),
+        vec![(user_id, PlanRole::Owner)]
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_plan_signed_out(db_pool: TestPool) {
+    let test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+
+    let response = test_server
+        .post("/api/plans")
+        .json(&CreatePlanRequest::new("Household".to_owned()))
+        .await;
+
+    response.assert_status_unauthorized();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_plans(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+    let household = Plan::new(Uuid::new_v4(), "Household".to_owned());
+    db::plans::create(&db_pool, household.clone(), user_id)
+        .await
+        .unwrap();
+
+    let response = test_server.get("/api/plans").await;
+
+    response.assert_ok();
+    response.assert_json(&[household, Plan::new(user_id, "My Plan".to_owned())]);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let user_id = *USER_ID;
+
+    let response = test_server
+        .put(&format!("/api/plans/{user_id}"))
+        .json(&UpdatePlanRequest::new("Personal".into()))
+        .await;
+
+    response.assert_ok();
+
+    let fetched = db::plans::get_single(&db_pool, user_id).await.unwrap();
+
+    assert_eq!(fetched.name, "Personal");
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .put(&format!("/api/plans/{other_user_id}"))
+        .json(&UpdatePlanRequest::new("Mine now".into()))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::plans::get_single(&db_pool, other_user_id)
+        .await
+        .unwrap();
+
+    assert_eq!(fetched.name, "My Plan");
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn copy_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let date = NaiveDate::from_ymd_opt(2024, 12, 10).unwrap();
+
+    let group = BudgetGroup::new(Uuid::new_v4(), "Bills".into(), 0, plan_id);
+    db::budget_groups::create(&db_pool, group.clone())
+        .await
+        .unwrap();
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Weekly { starting_on: date },
+    };
+    db::schedule::create(&db_pool, schedule.clone())
+        .await
+        .unwrap();
+
+    let target = BudgetTarget::Repeating {
+        target_amount: dec!(50),
+        repeating_type: RepeatingTargetType::BuildUpTo,
+        schedule,
+    };
+    let mut budget = Budget::new(
+        Uuid::new_v4(),
+        "Groceries".into(),
+        Some(target),
+        plan_id,
+        vec![],
+        Some(group.id),
+        None,
+    );
+    budget.move_from_ready_to_assign(dec!(30), date);
+    db::budgets::create(&db_pool, budget.clone()).await.unwrap();
+
+    db::bank_accounts::create(
+        &db_pool,
+        Uuid::new_v4(),
+        CreateBankAccountRequest::new("Account".into(), dec!(100), plan_id),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .post(&format!("/api/plans/{plan_id}/copy"))
+        .json(&CopyPlanRequest::new("Household".into()))
+        .await;
+
+    response.assert_created();
+    let new_plan_id: Uuid = response.json();
+
+    let new_plan = db::plans::get_single(&db_pool, new_plan_id).await.unwrap();
+    assert_eq!(new_plan.name, "Household");
+
+    let members = db::plan_members::get(&db_pool, new_plan_id).await.unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].user_id, *USER_ID);
+    assert_eq!(members[0].role, PlanRole::Owner);
+
+    let response = test_server
+        .get(&format!("/api/budgets?plan_id={new_plan_id}"))
+        .await;
+    response.assert_ok();
+
+    let GetBudgetsResponse { budgets, groups } = response.json();
+
+    let [copied_group] = groups.as_slice() else {
+        panic!("expected a single group, got {groups:?}");
+    };
+    assert_eq!(copied_group.name, "Bills");
+
+    let [copied_budget] = budgets.as_slice() else {
+        panic!("expected a single budget, got {budgets:?}");
+    };
+    assert_eq!(copied_budget.name, "Groceries");
+    assert_eq!(copied_budget.group_id, Some(copied_group.id));
+    assert_eq!(copied_budget.total_assigned, Decimal::ZERO);
+    let Some(BudgetTarget::Repeating {
+        target_amount,
+        schedule,
+        ..
+    }) = &copied_budget.target
+    else {
+        panic!("expected a repeating target");
+    };
+    assert_eq!(*target_amount, dec!(50));
+    assert_eq!(
+        schedule.period,
+        SchedulePeriod::Weekly { starting_on: date }
+    );
+
+    // bank accounts and the money in them stay with the original plan
+    let response = test_server
+        .get(&format!("/api/plans/{new_plan_id}/ready-to-assign"))
+        .await;
+    response.assert_ok();
+    response.assert_json(&GetReadyToAssignResponse::new(Decimal::ZERO));
+
+    let response = test_server
+        .get(&format!("/api/plans/{plan_id}/ready-to-assign"))
+        .await;
+    response.assert_json(&GetReadyToAssignResponse::new(dec!(70)));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn copy_other_users_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .post(&format!("/api/plans/{other_user_id}/copy"))
+        .json(&CopyPlanRequest::new("Mine now".into()))
+        .await;
+
+    response.assert_status_not_found();
+
+    let plans = db::plans::get(&db_pool, *USER_ID).await.unwrap();
+    assert_eq!(plans.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_ready_to_assign(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+
+    let response = test_server
+        .get(&format!("/api/plans/{other_user_id}/ready-to-assign"))
+        .await;
+
+    response.assert_status_not_found();
+}
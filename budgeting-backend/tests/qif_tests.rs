@@ -0,0 +1,318 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool},
+    models::{Budget, CreateBankAccountRequest, CreatePayeeRequest, Transaction, User},
+    UPLOAD_BODY_LIMIT,
+};
+use chrono::NaiveDate;
+use common::*;
+use http::StatusCode;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+const QIF: &str = "!Account
+NChecking
+TBank
+^
+!Type:Bank
+D12/01/2024
+T100.00
+POpening Balance
+L[Checking]
+^
+D12/05/2024
+T-42.10
+PCORNER SHOP
+LFood:Groceries
+^
+D12/06/2024
+T-15.00
+PCinema
+LEntertainment
+^
+D12/07/2024
+T-5.00
+PCorner Shop
+^
+D12/15/2024
+T1500.00
+PEmployer
+^
+";
+
+async fn test_init(db_pool: &TestPool) {
+    let plan_id = *USER_ID;
+
+    db::users::create(
+        db_pool,
+        User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+    )
+    .await
+    .unwrap();
+
+    db::payees::create(
+        db_pool,
+        *PAYEE_ID,
+        CreatePayeeRequest::new("Corner Shop".into(), plan_id),
+    )
+    .await
+    .unwrap();
+
+    db::budgets::create(
+        db_pool,
+        Budget::new(
+            *BUDGET_ID,
+            "Groceries".into(),
+            None,
+            plan_id,
+            vec![],
+            None,
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_qif(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    let response = test_server
+        .post(&format!("/api/plans/{plan_id}/imports/qif"))
+        .text(QIF)
+        .await;
+
+    response.assert_created();
+    let bank_account_ids: Vec<Uuid> = response.json();
+    assert_eq!(bank_account_ids.len(), 1);
+
+    let bank_account = db::bank_accounts::get_single(&db_pool, bank_account_ids[0], plan_id)
+        .await
+        .unwrap();
+    assert_eq!(bank_account.name, "Checking");
+    assert_eq!(bank_account.initial_amount, dec!(100));
+    assert_eq!(bank_account.balance, dec!(1537.90));
+
+    let payees = db::payees::get(&db_pool, plan_id).await.unwrap();
+    let mut payee_names: Vec<_> = payees.iter().map(|payee| payee.name.as_str()).collect();
+    payee_names.sort_unstable();
+    assert_eq!(payee_names, ["Cinema", "Corner Shop", "Employer"]);
+
+    let budgets = db::budgets::get(&db_pool, plan_id, false).await.unwrap();
+    let mut budget_names: Vec<_> = budgets.iter().map(|budget| budget.name.as_str()).collect();
+    budget_names.sort_unstable();
+    assert_eq!(
+        budget_names,
+        ["Entertainment", "Groceries", "Uncategorised"]
+    );
+
+    let groceries = budgets.iter().find(|x| x.id == *BUDGET_ID).unwrap();
+    assert_eq!(groceries.total_assigned(), dec!(-42.10));
+
+    let transactions = db::transactions::get_all(&db_pool, bank_account_ids[0])
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 4);
+
+    let corner_shop = transactions
+        .iter()
+        .filter(|x| x.payee_id == *PAYEE_ID)
+        .count();
+    assert_eq!(corner_shop, 2);
+
+    let income = transactions.iter().find(|x| x.amount > dec!(0)).unwrap();
+    assert_eq!(income.budget_id, None);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_qif_without_account_name(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let qif = "!Type:Bank
+D12/15/2024
+T1500.00
+PEmployer
+^
+";
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/qif"))
+        .text(qif)
+        .await
+        .assert_status_bad_request();
+
+    let response = test_server
+        .post(&format!(
+            "/api/plans/{plan_id}/imports/qif?account_name=Savings"
+        ))
+        .text(qif)
+        .await;
+
+    response.assert_created();
+    let bank_account_ids: Vec<Uuid> = response.json();
+
+    let bank_account = db::bank_accounts::get_single(&db_pool, bank_account_ids[0], plan_id)
+        .await
+        .unwrap();
+    assert_eq!(bank_account.name, "Savings");
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_invalid_qif(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/qif"))
+        .text("!Type:Bank\nT-42.10\nPCorner Shop\n^\n")
+        .await
+        .assert_status_bad_request();
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, plan_id).await.unwrap();
+    assert!(bank_accounts.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_large_qif(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    // larger than axum's default limit, so only read because of the upload limit
+    let transaction = "T-42.10\nPCorner Shop\n^\n";
+    let contents = format!("!Type:Bank\n{}", transaction.repeat(200_000));
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/qif"))
+        .text(contents)
+        .await
+        .assert_status_bad_request();
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/qif"))
+        .text("!Type:Bank\n".repeat(UPLOAD_BODY_LIMIT / 10))
+        .await
+        .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn export_qif(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+    let bank_account_id = Uuid::new_v4();
+    db::bank_accounts::create(
+        &db_pool,
+        bank_account_id,
+        CreateBankAccountRequest::new("Checking".into(), dec!(100), plan_id),
+    )
+    .await
+    .unwrap();
+
+    for transaction in [
+        Transaction::new(
+            Uuid::new_v4(),
+            *PAYEE_ID,
+            NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+            dec!(20),
+            bank_account_id,
+            None,
+            None,
+        ),
+        Transaction::new(
+            Uuid::new_v4(),
+            *PAYEE_ID,
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+            dec!(-42.10),
+            bank_account_id,
+            Some(*BUDGET_ID),
+            None,
+        ),
+    ] {
+        db::transactions::create(&db_pool, transaction)
+            .await
+            .unwrap();
+    }
+
+    let response = test_server
+        .get(&format!("/api/bank-accounts/{bank_account_id}/qif"))
+        .await;
+
+    response.assert_ok();
+    assert_eq!(
+        response.text(),
+        "!Account
+NChecking
+TBank
+^
+!Type:Bank
+D12/05/2024
+T100.00
+POpening Balance
+L[Checking]
+^
+D12/05/2024
+T-42.10
+PCorner Shop
+LGroceries
+^
+D12/15/2024
+T20.00
+PCorner Shop
+^
+"
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_and_export_other_users_qif(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    let other_bank_account_id = Uuid::new_v4();
+    db::bank_accounts::create(
+        &db_pool,
+        other_bank_account_id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    test_server
+        .post(&format!("/api/plans/{other_user_id}/imports/qif"))
+        .text(QIF)
+        .await
+        .assert_status_not_found();
+
+    test_server
+        .get(&format!("/api/bank-accounts/{other_bank_account_id}/qif"))
+        .await
+        .assert_status_not_found();
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, other_user_id)
+        .await
+        .unwrap();
+    assert_eq!(bank_accounts.len(), 1);
+}
@@ -0,0 +1,480 @@
+mod common;
+
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool, Error},
+    models::{
+        Budget, BudgetAssignmentSource, CreateBankAccountRequest, CreatePayeeRequest,
+        CreateScheduleRequest, CreateScheduledTransactionRequest, Schedule, SchedulePeriod,
+        ScheduledTransaction, UpdateScheduleRequest, UpdateScheduledTransactionRequest, User,
+    },
+};
+use chrono::{Local, NaiveDate};
+use common::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BANK_ACCOUNT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static PAYEE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+static BUDGET_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+async fn test_init(db_pool: &TestPool) {
+    let user_id = *USER_ID;
+
+    db::users::create(
+        db_pool,
+        User::new(user_id, "name".into(), "email@email.com".into(), None),
+    )
+    .await
+    .unwrap();
+
+    db::payees::create(
+        db_pool,
+        *PAYEE_ID,
+        CreatePayeeRequest::new("name".into(), user_id),
+    )
+    .await
+    .unwrap();
+
+    db::bank_accounts::create(
+        db_pool,
+        *BANK_ACCOUNT_ID,
+        CreateBankAccountRequest::new("name".into(), Decimal::ZERO, user_id),
+    )
+    .await
+    .unwrap();
+
+    db::budgets::create(
+        db_pool,
+        Budget::new(*BUDGET_ID, "Budget".into(), None, user_id, vec![], None, None),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_scheduled_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{bank_account_id}/scheduled-transactions"
+        ))
+        .json(&CreateScheduledTransactionRequest::new(
+            *PAYEE_ID,
+            dec!(-15),
+            *BUDGET_ID,
+            CreateScheduleRequest {
+                period: SchedulePeriod::Weekly {
+                    starting_on: NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+                },
+            },
+        ))
+        .await;
+
+    response.assert_created();
+    let id: Uuid = response.json();
+
+    let mut fetched = db::scheduled_transactions::get_single(&db_pool, id)
+        .await
+        .unwrap();
+    fetched.schedule.id = Uuid::nil();
+
+    assert_eq!(
+        fetched,
+        ScheduledTransaction {
+            id,
+            payee_id: *PAYEE_ID,
+            amount: dec!(-15),
+            bank_account_id,
+            budget_id: *BUDGET_ID,
+            schedule: Schedule {
+                id: Uuid::nil(),
+                period: SchedulePeriod::Weekly {
+                    starting_on: NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+                },
+            },
+            next_occurrence: NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+        }
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_scheduled_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Monthly {
+            starting_on: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        },
+    };
+    db::schedule::create(&db_pool, schedule.clone())
+        .await
+        .unwrap();
+
+    let id = Uuid::new_v4();
+    db::scheduled_transactions::create(
+        &db_pool,
+        ScheduledTransaction::new(
+            id,
+            *PAYEE_ID,
+            dec!(-15),
+            *BANK_ACCOUNT_ID,
+            *BUDGET_ID,
+            schedule.clone(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server
+        .delete(&format!(
+            "/api/bank-accounts/{}/scheduled-transactions/{id}",
+            *BANK_ACCOUNT_ID
+        ))
+        .await;
+
+    response.assert_ok();
+
+    let fetched = db::scheduled_transactions::get_single(&db_pool, id).await;
+    assert!(matches!(fetched, Err(Error::NotFound)));
+
+    let fetched_schedule = db::schedule::get_single(&db_pool, schedule.id).await;
+    assert!(matches!(fetched_schedule, Err(Error::NotFound)));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn post_due_creates_transactions_once(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let today = Local::now().date_naive();
+    let starting_on = today - chrono::Days::new(14);
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Weekly { starting_on },
+    };
+    db::schedule::create(&db_pool, schedule.clone())
+        .await
+        .unwrap();
+
+    let id = Uuid::new_v4();
+    db::scheduled_transactions::create(
+        &db_pool,
+        ScheduledTransaction::new(
+            id,
+            *PAYEE_ID,
+            dec!(-15),
+            *BANK_ACCOUNT_ID,
+            *BUDGET_ID,
+            schedule,
+            starting_on,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let url = format!(
+        "/api/bank-accounts/{}/scheduled-transactions/post-due",
+        *BANK_ACCOUNT_ID
+    );
+
+    let response = test_server.post(&url).await;
+    response.assert_ok();
+    let created_ids: Vec<Uuid> = response.json();
+
+    // starting_on, one week later and today
+    assert_eq!(created_ids.len(), 3);
+
+    let mut transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap()
+        .into_vec();
+    transactions.sort_by_key(|t| t.date);
+
+    assert_eq!(
+        transactions.iter().map(|t| t.date).collect::<Vec<_>>(),
+        vec![starting_on, starting_on + chrono::Days::new(7), today]
+    );
+    assert!(transactions.iter().all(|t| t.amount == dec!(-15)));
+
+    let budget = db::budgets::get_single(&db_pool, *BUDGET_ID).await.unwrap();
+    assert_eq!(budget.assignments.len(), 3);
+    assert!(budget.assignments.iter().all(|a| matches!(
+        a.source,
+        BudgetAssignmentSource::Transaction { from_transaction_id } if created_ids.contains(&from_transaction_id)
+    )));
+
+    let scheduled_transaction = db::scheduled_transactions::get_single(&db_pool, id)
+        .await
+        .unwrap();
+    assert_eq!(
+        scheduled_transaction.next_occurrence,
+        today + chrono::Days::new(7)
+    );
+
+    // posting again shouldn't create anything new
+    let response = test_server.post(&url).await;
+    response.assert_ok();
+    response.assert_json(&Vec::<Uuid>::new());
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 3);
+}
+
+async fn create_other_users_scheduled_transaction(db_pool: &TestPool) -> ScheduledTransaction {
+    let other_user_id = create_other_user(db_pool).await;
+    let payee_id = Uuid::new_v4();
+    let bank_account_id = Uuid::new_v4();
+    let budget_id = Uuid::new_v4();
+
+    db::payees::create(
+        db_pool,
+        payee_id,
+        CreatePayeeRequest::new("name".into(), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    db::bank_accounts::create(
+        db_pool,
+        bank_account_id,
+        CreateBankAccountRequest::new("name".into(), Decimal::ZERO, other_user_id),
+    )
+    .await
+    .unwrap();
+
+    db::budgets::create(
+        db_pool,
+        Budget::new(budget_id, "Budget".into(), None, other_user_id, vec![], None, None),
+    )
+    .await
+    .unwrap();
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Monthly {
+            starting_on: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        },
+    };
+    db::schedule::create(db_pool, schedule.clone())
+        .await
+        .unwrap();
+
+    let scheduled_transaction = ScheduledTransaction::new(
+        Uuid::new_v4(),
+        payee_id,
+        dec!(-15),
+        bank_account_id,
+        budget_id,
+        schedule,
+        NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+    );
+    db::scheduled_transactions::create(db_pool, scheduled_transaction.clone())
+        .await
+        .unwrap();
+
+    scheduled_transaction
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_scheduled_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let scheduled_transaction = create_other_users_scheduled_transaction(&db_pool).await;
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{}/scheduled-transactions",
+            scheduled_transaction.bank_account_id
+        ))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_scheduled_transaction_in_other_users_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let scheduled_transaction = create_other_users_scheduled_transaction(&db_pool).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/scheduled-transactions",
+            scheduled_transaction.bank_account_id
+        ))
+        .json(&CreateScheduledTransactionRequest::new(
+            scheduled_transaction.payee_id,
+            dec!(-15),
+            scheduled_transaction.budget_id,
+            CreateScheduleRequest {
+                period: SchedulePeriod::Weekly {
+                    starting_on: NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+                },
+            },
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let scheduled_transactions =
+        db::scheduled_transactions::get(&db_pool, scheduled_transaction.bank_account_id)
+            .await
+            .unwrap();
+    assert_eq!(scheduled_transactions.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_scheduled_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let scheduled_transaction = create_other_users_scheduled_transaction(&db_pool).await;
+
+    // neither the other user's account nor the user's own account can reach it
+    for bank_account_id in [scheduled_transaction.bank_account_id, *BANK_ACCOUNT_ID] {
+        let response = test_server
+            .put(&format!(
+                "/api/bank-accounts/{bank_account_id}/scheduled-transactions/{}",
+                scheduled_transaction.id
+            ))
+            .json(&UpdateScheduledTransactionRequest::new(
+                *PAYEE_ID,
+                dec!(-100),
+                *BUDGET_ID,
+                UpdateScheduleRequest {
+                    period: scheduled_transaction.schedule.period.clone(),
+                },
+            ))
+            .await;
+
+        response.assert_status_not_found();
+    }
+
+    let fetched = db::scheduled_transactions::get_single(&db_pool, scheduled_transaction.id)
+        .await
+        .unwrap();
+    assert_eq!(fetched, scheduled_transaction);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_scheduled_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let scheduled_transaction = create_other_users_scheduled_transaction(&db_pool).await;
+
+    for bank_account_id in [scheduled_transaction.bank_account_id, *BANK_ACCOUNT_ID] {
+        let response = test_server
+            .delete(&format!(
+                "/api/bank-accounts/{bank_account_id}/scheduled-transactions/{}",
+                scheduled_transaction.id
+            ))
+            .await;
+
+        response.assert_status_not_found();
+    }
+
+    db::scheduled_transactions::get_single(&db_pool, scheduled_transaction.id)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn post_due_concurrently_creates_transactions_once(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let today = Local::now().date_naive();
+    let starting_on = today - chrono::Days::new(14);
+
+    let schedule = Schedule {
+        id: Uuid::new_v4(),
+        period: SchedulePeriod::Weekly { starting_on },
+    };
+    db::schedule::create(&db_pool, schedule.clone())
+        .await
+        .unwrap();
+
+    db::scheduled_transactions::create(
+        &db_pool,
+        ScheduledTransaction::new(
+            Uuid::new_v4(),
+            *PAYEE_ID,
+            dec!(-15),
+            *BANK_ACCOUNT_ID,
+            *BUDGET_ID,
+            schedule,
+            starting_on,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let url = format!(
+        "/api/bank-accounts/{}/scheduled-transactions/post-due",
+        *BANK_ACCOUNT_ID
+    );
+
+    let (first, second) = tokio::join!(test_server.post(&url), test_server.post(&url));
+    first.assert_ok();
+    second.assert_ok();
+
+    let mut created_ids: Vec<Uuid> = first.json();
+    created_ids.extend(second.json::<Vec<Uuid>>());
+    assert_eq!(created_ids.len(), 3);
+
+    let transactions = db::transactions::get_all(&db_pool, *BANK_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 3);
+
+    let budget = db::budgets::get_single(&db_pool, *BUDGET_ID).await.unwrap();
+    assert_eq!(budget.assignments.len(), 3);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn post_due_in_other_users_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let scheduled_transaction = create_other_users_scheduled_transaction(&db_pool).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/scheduled-transactions/post-due",
+            scheduled_transaction.bank_account_id
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let transactions = db::transactions::get_all(&db_pool, scheduled_transaction.bank_account_id)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+}
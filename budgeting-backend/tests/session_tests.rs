@@ -0,0 +1,119 @@
+mod common;
+
+use budgeting_backend::{
+    auth,
+    db::{self, TestPool},
+    models::{Session, SignInRequest, User},
+};
+use chrono::{TimeDelta, Utc};
+use common::*;
+use uuid::Uuid;
+
+async fn test_init(db_pool: &TestPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+
+    db::users::create(
+        db_pool,
+        User::new(
+            user_id,
+            "Someone".into(),
+            "someone@somewhere.com".into(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    db::users::set_password_hash(
+        db_pool,
+        user_id,
+        &auth::hash_password("correct horse").unwrap(),
+    )
+    .await
+    .unwrap();
+
+    user_id
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn sign_in_and_out(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    let user_id = test_init(&db_pool).await;
+
+    let response = test_server
+        .post("/api/sessions")
+        .json(&SignInRequest::new(
+            "someone@somewhere.com".into(),
+            "correct horse".into(),
+        ))
+        .await;
+
+    response.assert_created();
+    let session: Session = response.json();
+    assert_eq!(session.user_id, user_id);
+
+    test_server.add_header(
+        http::header::AUTHORIZATION,
+        format!("Bearer {}", session.token),
+    );
+
+    let response = test_server.get("/api/users/me").await;
+    response.assert_ok();
+    assert_eq!(response.json::<User>().id, user_id);
+
+    test_server.delete("/api/sessions").await.assert_ok();
+
+    test_server
+        .get("/api/users/me")
+        .await
+        .assert_status_unauthorized();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn sign_in_with_wrong_password(db_pool: TestPool) {
+    let test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+
+    let response = test_server
+        .post("/api/sessions")
+        .json(&SignInRequest::new(
+            "someone@somewhere.com".into(),
+            "wrong horse".into(),
+        ))
+        .await;
+
+    response.assert_status_unauthorized();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn sign_in_unknown_email(db_pool: TestPool) {
+    let test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+
+    let response = test_server
+        .post("/api/sessions")
+        .json(&SignInRequest::new(
+            "nobody@somewhere.com".into(),
+            "correct horse".into(),
+        ))
+        .await;
+
+    response.assert_status_unauthorized();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn expired_session(db_pool: TestPool) {
+    let test_server = integration_test_init(db_pool.clone());
+    let user_id = test_init(&db_pool).await;
+
+    let session = Session::new("a".repeat(64), user_id, Utc::now() - TimeDelta::days(1));
+    db::sessions::create(&db_pool, session.clone())
+        .await
+        .unwrap();
+
+    test_server
+        .get("/api/users/me")
+        .authorization_bearer(&session.token)
+        .await
+        .assert_status_unauthorized();
+}
@@ -4,17 +4,17 @@ use std::sync::OnceLock;
 
 use budgeting_backend::models::{BudgetAssignment, BudgetAssignmentSource};
 use budgeting_backend::{
-    db::{self, Error},
+    db::{self, TestPool, Error},
     models::{
         Budget, CreateBankAccountRequest, CreatePayeeRequest, CreateTransactionRequest,
-        Transaction, UpdateTransactionRequest, User,
+        Transaction, TransactionPage, TransactionSearchPage, TransactionSearchResult,
+        TransactionTotals, UpdateTransactionRequest, User,
     },
 };
 use chrono::NaiveDate;
 use common::*;
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use rust_decimal_macros::dec;
-use sqlx::MySqlPool;
 use uuid::Uuid;
 
 static USER_ID: OnceLock<Uuid> = OnceLock::new();
@@ -22,7 +22,7 @@ static BANK_ACCOUNT_ID: OnceLock<Uuid> = OnceLock::new();
 static PAYEE_ID: OnceLock<Uuid> = OnceLock::new();
 static BUDGET_ID: OnceLock<Uuid> = OnceLock::new();
 
-async fn test_init(db_pool: &MySqlPool) {
+async fn test_init(db_pool: &TestPool) {
     let user_id = *USER_ID.get_or_init(Uuid::new_v4);
     let bank_account_id = *BANK_ACCOUNT_ID.get_or_init(Uuid::new_v4);
     let payee_id = *PAYEE_ID.get_or_init(Uuid::new_v4);
@@ -53,16 +53,17 @@ async fn test_init(db_pool: &MySqlPool) {
 
     db::budgets::create(
         db_pool,
-        Budget::new(budget_id, "Budget".into(), None, user_id, vec![]),
+        Budget::new(budget_id, "Budget".into(), None, user_id, vec![], None, None),
     )
     .await
     .unwrap();
 }
 
-#[sqlx::test]
-pub async fn create_transaction(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
     let payee_id = *PAYEE_ID.unwrap();
@@ -77,14 +78,14 @@ pub async fn create_transaction(db_pool: MySqlPool) {
             payee_id,
             Decimal::from_f32(10.15).unwrap(),
             NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
-            budget_id,
+            Some(budget_id),
         ))
         .await;
 
     response.assert_created();
     let transaction_id: Uuid = response.json();
 
-    let transactions = db::transactions::get(&db_pool, bank_account_id)
+    let transactions = db::transactions::get_all(&db_pool, bank_account_id)
         .await
         .unwrap();
 
@@ -113,17 +114,109 @@ pub async fn create_transaction(db_pool: MySqlPool) {
         NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
         Decimal::from_f32(10.15).unwrap(),
         bank_account_id,
-        budget_id,
+        Some(budget_id),
+        None,
     )]
     .into_boxed_slice();
 
     assert_eq!(transactions, expected);
 }
 
-#[sqlx::test]
-pub async fn get_transactions(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_income_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/transactions",
+            bank_account_id
+        ))
+        .json(&CreateTransactionRequest::new(
+            *PAYEE_ID.unwrap(),
+            dec!(100),
+            NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+            None,
+        ))
+        .await;
+
+    response.assert_created();
+
+    let budget = db::budgets::get_single(&db_pool, *BUDGET_ID.unwrap()).await.unwrap();
+    assert!(budget.assignments.is_empty());
+
+    let ready_to_assign = db::ready_to_assign::get(&db_pool, *USER_ID.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(ready_to_assign, dec!(100));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_outflow_without_budget_fails(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/transactions",
+            *BANK_ACCOUNT_ID.unwrap()
+        ))
+        .json(&CreateTransactionRequest::new(
+            *PAYEE_ID.unwrap(),
+            dec!(-100),
+            NaiveDate::from_ymd_opt(2024, 12, 6).unwrap(),
+            None,
+        ))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_transaction_rolls_back_on_failure(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let budget_id = *BUDGET_ID.unwrap();
+
+    // the transaction is inserted before the budget assignment, so this fails part way through
+    fail_writes_to(&db_pool, "INSERT", "BudgetAssignments", "TRUE").await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/transactions",
+            bank_account_id
+        ))
+        .json(&CreateTransactionRequest::new(
+            *PAYEE_ID.unwrap(),
+            dec!(10.15),
+            NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
+            Some(budget_id),
+        ))
+        .await;
+
+    response.assert_status_failure();
+
+    let transactions = db::transactions::get_all(&db_pool, bank_account_id)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+
+    let budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
+    assert!(budget.assignments.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let payee_id = *PAYEE_ID.unwrap();
     let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
@@ -135,7 +228,8 @@ pub async fn get_transactions(db_pool: MySqlPool) {
         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         Decimal::from_f32(1.3).unwrap(),
         bank_account_id,
-        budget_id,
+        Some(budget_id),
+        None,
     );
     db::transactions::create(&db_pool, transaction.clone())
         .await
@@ -148,13 +242,319 @@ pub async fn get_transactions(db_pool: MySqlPool) {
         .await;
 
     response.assert_ok();
-    response.assert_json(&vec![transaction])
+    response.assert_json(&TransactionPage {
+        transactions: vec![transaction.clone()].into_boxed_slice(),
+        next_cursor: None,
+        totals: TransactionTotals {
+            count: 1,
+            inflow: transaction.amount,
+            outflow: Decimal::ZERO,
+        },
+    })
+}
+
+/// Creates transactions on consecutive days from 2024-01-01, one for each amount, with
+/// payee "Other payee" from the 3rd on
+async fn create_transactions(db_pool: &TestPool, amounts: &[Decimal]) -> Vec<Transaction> {
+    let other_payee_id = Uuid::new_v4();
+    db::payees::create(
+        db_pool,
+        other_payee_id,
+        CreatePayeeRequest::new("Other payee".into(), *USER_ID.unwrap()),
+    )
+    .await
+    .unwrap();
+
+    let mut transactions = vec![];
+    for (day, amount) in (1..).zip(amounts) {
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            if day < 3 { *PAYEE_ID.unwrap() } else { other_payee_id },
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            *amount,
+            *BANK_ACCOUNT_ID.unwrap(),
+            Some(*BUDGET_ID.unwrap()),
+            None,
+        );
+        db::transactions::create(db_pool, transaction.clone())
+            .await
+            .unwrap();
+        transactions.push(transaction);
+    }
+
+    transactions
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_transactions_filtered(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let transactions =
+        create_transactions(&db_pool, &[dec!(-10), dec!(25), dec!(-3.5), dec!(-40), dec!(8)])
+            .await;
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{bank_account_id}/transactions\
+            ?flow=Outflow&from_date=2024-01-02&search=other%20PAY&sort=Amount&order=Ascending"
+        ))
+        .await;
+
+    response.assert_ok();
+    response.assert_json(&TransactionPage {
+        transactions: vec![transactions[3].clone(), transactions[2].clone()].into_boxed_slice(),
+        next_cursor: None,
+        totals: TransactionTotals {
+            count: 2,
+            inflow: Decimal::ZERO,
+            outflow: dec!(-43.5),
+        },
+    });
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{bank_account_id}/transactions?min_amount=-5&max_amount=10"
+        ))
+        .await;
+
+    response.assert_ok();
+    let page: TransactionPage = response.json();
+    assert_eq!(
+        page.transactions.as_ref(),
+        [transactions[4].clone(), transactions[2].clone()]
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_transactions_searches_memos(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    create_transactions(&db_pool, &[dec!(-10), dec!(25)]).await;
+    let transaction = Transaction::new(
+        Uuid::new_v4(),
+        *PAYEE_ID.unwrap(),
+        NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(),
+        dec!(-12),
+        bank_account_id,
+        Some(*BUDGET_ID.unwrap()),
+        Some("Friday pizza".into()),
+    );
+    db::transactions::create(&db_pool, transaction.clone())
+        .await
+        .unwrap();
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{bank_account_id}/transactions?search=PIZZA"
+        ))
+        .await;
+
+    response.assert_ok();
+    let page: TransactionPage = response.json();
+    assert_eq!(page.transactions.as_ref(), [transaction]);
 }
 
-#[sqlx::test]
-pub async fn update_transaction(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_transactions_in_pages(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let transactions =
+        create_transactions(&db_pool, &[dec!(-10), dec!(25), dec!(-3.5), dec!(-40), dec!(8)])
+            .await;
+    let totals = TransactionTotals {
+        count: 5,
+        inflow: dec!(33),
+        outflow: dec!(-53.5),
+    };
+
+    let mut cursor = None;
+    let mut fetched = vec![];
+    for expected_len in [2, 2, 1] {
+        let cursor_param = cursor.map(|cursor| format!("&cursor={cursor}")).unwrap_or_default();
+        let response = test_server
+            .get(&format!(
+                "/api/bank-accounts/{bank_account_id}/transactions?limit=2{cursor_param}"
+            ))
+            .await;
+
+        response.assert_ok();
+        let page: TransactionPage = response.json();
+        assert_eq!(page.transactions.len(), expected_len);
+        assert_eq!(page.totals, totals);
+
+        fetched.extend(page.transactions.into_vec());
+        cursor = page.next_cursor;
+    }
+
+    assert_eq!(cursor, None);
+    assert_eq!(
+        fetched,
+        transactions.into_iter().rev().collect::<Vec<_>>(),
+        "newest first by default"
+    );
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_transactions_rejects_bad_queries(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+
+    for query in [
+        "cursor=nonsense",
+        "sort=Amount&cursor=2024-01-01_00000000000000000000000000000000",
+        "limit=0",
+        "limit=501",
+        "min_amount=5&max_amount=1",
+        "from_date=2024-02-01&to_date=2024-01-01",
+    ] {
+        let response = test_server
+            .get(&format!(
+                "/api/bank-accounts/{bank_account_id}/transactions?{query}"
+            ))
+            .await;
+
+        response.assert_status_bad_request();
+    }
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn search_transactions_across_bank_accounts(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let user_id = *USER_ID.unwrap();
+    let savings_id = Uuid::new_v4();
+    let other_user_id = Uuid::new_v4();
+    let other_bank_account_id = Uuid::new_v4();
+
+    db::bank_accounts::create(
+        &db_pool,
+        savings_id,
+        CreateBankAccountRequest::new("Savings".into(), Decimal::ZERO, user_id),
+    )
+    .await
+    .unwrap();
+
+    db::users::create(
+        &db_pool,
+        User::new(other_user_id, "other".into(), "other@email.com".into(), None),
+    )
+    .await
+    .unwrap();
+    db::bank_accounts::create(
+        &db_pool,
+        other_bank_account_id,
+        CreateBankAccountRequest::new("Not mine".into(), Decimal::ZERO, other_user_id),
+    )
+    .await
+    .unwrap();
+
+    let transactions = create_transactions(&db_pool, &[dec!(-10), dec!(25)]).await;
+    let income = Transaction::new(
+        Uuid::new_v4(),
+        *PAYEE_ID.unwrap(),
+        NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        dec!(100),
+        savings_id,
+        None,
+        None,
+    );
+    db::transactions::create(&db_pool, income.clone())
+        .await
+        .unwrap();
+
+    let other_payee_id = Uuid::new_v4();
+    db::payees::create(
+        &db_pool,
+        other_payee_id,
+        CreatePayeeRequest::new("Other user's payee".into(), other_user_id),
+    )
+    .await
+    .unwrap();
+    db::transactions::create(
+        &db_pool,
+        Transaction::new(
+            Uuid::new_v4(),
+            other_payee_id,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            dec!(-99),
+            other_bank_account_id,
+            None,
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+
+    let response = test_server.get("/api/transactions").await;
+
+    response.assert_ok();
+    response.assert_json(&TransactionSearchPage {
+        transactions: vec![
+            TransactionSearchResult {
+                transaction: income,
+                bank_account_name: "Savings".into(),
+                payee_name: "name".into(),
+                budget_name: None,
+            },
+            TransactionSearchResult {
+                transaction: transactions[1].clone(),
+                bank_account_name: "name".into(),
+                payee_name: "name".into(),
+                budget_name: Some("Budget".into()),
+            },
+            TransactionSearchResult {
+                transaction: transactions[0].clone(),
+                bank_account_name: "name".into(),
+                payee_name: "name".into(),
+                budget_name: Some("Budget".into()),
+            },
+        ]
+        .into_boxed_slice(),
+        next_cursor: None,
+        totals: TransactionTotals {
+            count: 3,
+            inflow: dec!(125),
+            outflow: dec!(-10),
+        },
+    });
+
+    let response = test_server
+        .get("/api/transactions?flow=Inflow&sort=Amount&order=Ascending&limit=1")
+        .await;
+
+    response.assert_ok();
+    let page: TransactionSearchPage = response.json();
+    assert_eq!(page.transactions.len(), 1);
+    assert_eq!(page.transactions[0].transaction, transactions[1]);
+    assert!(page.next_cursor.is_some());
+    assert_eq!(page.totals.count, 2);
+
+    let response = test_server
+        .get("/api/transactions?min_amount=5&max_amount=1")
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let user_id = *USER_ID.unwrap();
     let payee_id = *PAYEE_ID.unwrap();
@@ -173,7 +573,7 @@ pub async fn update_transaction(db_pool: MySqlPool) {
     .unwrap();
 
     let mut budget1 = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
-    let budget2 = Budget::new(budget_id_2, "name".into(), None, user_id, vec![]);
+    let budget2 = Budget::new(budget_id_2, "name".into(), None, user_id, vec![], None, None);
 
     db::budgets::create(&db_pool, budget2.clone())
         .await
@@ -185,7 +585,8 @@ pub async fn update_transaction(db_pool: MySqlPool) {
         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         dec!(1.3),
         bank_account_id,
-        budget_id,
+        Some(budget_id),
+        None,
     );
     db::transactions::create(&db_pool, transaction.clone())
         .await
@@ -209,7 +610,7 @@ pub async fn update_transaction(db_pool: MySqlPool) {
         .json(&UpdateTransactionRequest::new(
             dec!(-1.2),
             payee_id_2,
-            budget_id_2,
+            Some(budget_id_2),
             NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
         ))
         .await;
@@ -217,7 +618,7 @@ pub async fn update_transaction(db_pool: MySqlPool) {
     transaction.amount = dec!(-1.2);
     transaction.payee_id = payee_id_2;
     transaction.date = NaiveDate::from_ymd_opt(2024, 10, 5).unwrap();
-    transaction.budget_id = budget_id_2;
+    transaction.budget_id = Some(budget_id_2);
 
     response.assert_ok();
 
@@ -247,10 +648,11 @@ pub async fn update_transaction(db_pool: MySqlPool) {
     )
 }
 
-#[sqlx::test]
-pub async fn delete_transaction(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
     test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
 
     let payee_id = *PAYEE_ID.unwrap();
     let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
@@ -263,7 +665,8 @@ pub async fn delete_transaction(db_pool: MySqlPool) {
         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         Decimal::from_f32(1.3).unwrap(),
         bank_account_id,
-        budget_id,
+        Some(budget_id),
+        None,
     );
     db::transactions::create(&db_pool, transaction.clone())
         .await
@@ -289,3 +692,298 @@ pub async fn delete_transaction(db_pool: MySqlPool) {
     let fetched_budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
     assert!(fetched_budget.assignments.is_empty());
 }
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_transaction_rolls_back_on_failure(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let user_id = *USER_ID.unwrap();
+    let payee_id = *PAYEE_ID.unwrap();
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let budget_id = *BUDGET_ID.unwrap();
+
+    let budget_2 = Budget::new(Uuid::new_v4(), "name".into(), None, user_id, vec![], None, None);
+    db::budgets::create(&db_pool, budget_2.clone()).await.unwrap();
+
+    let transaction = Transaction::new(
+        Uuid::new_v4(),
+        payee_id,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        dec!(-1.3),
+        bank_account_id,
+        Some(budget_id),
+        None,
+    );
+    db::transactions::create(&db_pool, transaction.clone())
+        .await
+        .unwrap();
+
+    let mut budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
+    budget.assign_from_transaction(&transaction);
+    db::budgets::update(&db_pool, budget.clone()).await.unwrap();
+
+    // the budgets are saved before the transaction, so this fails part way through
+    fail_writes_to(&db_pool, "UPDATE", "Transactions", "TRUE").await;
+
+    let response = test_server
+        .put(&format!("/api/transactions/{}", transaction.id))
+        .json(&UpdateTransactionRequest::new(
+            dec!(-1.2),
+            payee_id,
+            Some(budget_2.id),
+            NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
+        ))
+        .await;
+
+    response.assert_status_failure();
+
+    let fetched_transaction = db::transactions::get_single(&db_pool, transaction.id)
+        .await
+        .unwrap();
+    let fetched_budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
+    let fetched_budget_2 = db::budgets::get_single(&db_pool, budget_2.id).await.unwrap();
+
+    assert_eq!(fetched_transaction, transaction);
+    assert_eq!(fetched_budget, budget);
+    assert_eq!(fetched_budget_2, budget_2);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_transaction_rolls_back_on_failure(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let budget_id = *BUDGET_ID.unwrap();
+
+    let transaction = Transaction::new(
+        Uuid::new_v4(),
+        *PAYEE_ID.unwrap(),
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        dec!(-1.3),
+        *BANK_ACCOUNT_ID.unwrap(),
+        Some(budget_id),
+        None,
+    );
+    db::transactions::create(&db_pool, transaction.clone())
+        .await
+        .unwrap();
+
+    let mut budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
+    budget.assign_from_transaction(&transaction);
+    db::budgets::update(&db_pool, budget.clone()).await.unwrap();
+
+    // the assignment is removed before the transaction, so this fails part way through
+    fail_writes_to(&db_pool, "DELETE", "Transactions", "TRUE").await;
+
+    let response = test_server
+        .delete(&format!("/api/transactions/{}", transaction.id))
+        .await;
+
+    response.assert_status_failure();
+
+    let fetched_transaction = db::transactions::get_single(&db_pool, transaction.id)
+        .await
+        .unwrap();
+    let fetched_budget = db::budgets::get_single(&db_pool, budget_id).await.unwrap();
+
+    assert_eq!(fetched_transaction, transaction);
+    assert_eq!(fetched_budget, budget);
+}
+
+async fn create_other_users_transaction(db_pool: &TestPool) -> Transaction {
+    let other_user_id = create_other_user(db_pool).await;
+    let payee_id = Uuid::new_v4();
+    let bank_account_id = Uuid::new_v4();
+    let budget_id = Uuid::new_v4();
+
+    db::payees::create(
+        db_pool,
+        payee_id,
+        CreatePayeeRequest::new("name".into(), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    db::bank_accounts::create(
+        db_pool,
+        bank_account_id,
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    db::budgets::create(
+        db_pool,
+        Budget::new(budget_id, "Budget".into(), None, other_user_id, vec![], None, None),
+    )
+    .await
+    .unwrap();
+
+    let transaction = Transaction::new(
+        Uuid::new_v4(),
+        payee_id,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        dec!(-1.3),
+        bank_account_id,
+        Some(budget_id),
+        None,
+    );
+    db::transactions::create(db_pool, transaction.clone())
+        .await
+        .unwrap();
+
+    transaction
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_other_users_transactions(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .get(&format!(
+            "/api/bank-accounts/{}/transactions",
+            transaction.bank_account_id
+        ))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_transaction_in_other_users_account(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{}/transactions",
+            transaction.bank_account_id
+        ))
+        .json(&CreateTransactionRequest::new(
+            transaction.payee_id,
+            dec!(-5),
+            NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
+            transaction.budget_id,
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let transactions = db::transactions::get_all(&db_pool, transaction.bank_account_id)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_transaction_with_other_users_payee(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{bank_account_id}/transactions"
+        ))
+        .json(&CreateTransactionRequest::new(
+            transaction.payee_id,
+            dec!(-5),
+            NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
+            Some(*BUDGET_ID.unwrap()),
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let transactions = db::transactions::get_all(&db_pool, bank_account_id)
+        .await
+        .unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_transaction_with_other_users_budget(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let bank_account_id = *BANK_ACCOUNT_ID.unwrap();
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .post(&format!(
+            "/api/bank-accounts/{bank_account_id}/transactions"
+        ))
+        .json(&CreateTransactionRequest::new(
+            *PAYEE_ID.unwrap(),
+            dec!(-5),
+            NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
+            transaction.budget_id,
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let budget = db::budgets::get_single(&db_pool, transaction.budget_id.unwrap())
+        .await
+        .unwrap();
+    assert!(budget.assignments.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_other_users_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .put(&format!("/api/transactions/{}", transaction.id))
+        .json(&UpdateTransactionRequest::new(
+            dec!(-100),
+            *PAYEE_ID.unwrap(),
+            Some(*BUDGET_ID.unwrap()),
+            NaiveDate::from_ymd_opt(2024, 10, 5).unwrap(),
+        ))
+        .await;
+
+    response.assert_status_not_found();
+
+    let fetched = db::transactions::get_single(&db_pool, transaction.id)
+        .await
+        .unwrap();
+    assert_eq!(fetched, transaction);
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn delete_other_users_transaction(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID.unwrap()).await;
+
+    let transaction = create_other_users_transaction(&db_pool).await;
+
+    let response = test_server
+        .delete(&format!("/api/transactions/{}", transaction.id))
+        .await;
+
+    response.assert_status_not_found();
+
+    db::transactions::get_single(&db_pool, transaction.id)
+        .await
+        .unwrap();
+}
@@ -1,18 +1,18 @@
 mod common;
 
 use budgeting_backend::{
-    db,
+    auth,
+    db::{self, TestPool},
     models::{
         CreateUserRequest, Schedule, SchedulePeriod, UpdateScheduleRequest, UpdateUserRequest, User,
     },
 };
 use chrono::NaiveDate;
 use common::*;
-use sqlx::MySqlPool;
-use uuid::{uuid, Uuid};
+use uuid::Uuid;
 
-#[sqlx::test]
-pub async fn create_users(db_pool: MySqlPool) {
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_users(db_pool: TestPool) {
     let server = integration_test_init(db_pool.clone());
 
     let response = server
@@ -20,6 +20,7 @@ pub async fn create_users(db_pool: MySqlPool) {
         .json(&CreateUserRequest {
             email: "someone@somewhere.com".to_owned(),
             name: "Someone".to_owned(),
+            password: "correct horse".to_owned(),
         })
         .await;
 
@@ -36,59 +37,64 @@ pub async fn create_users(db_pool: MySqlPool) {
             name: "Someone".to_owned(),
             pay_frequency: None
         }
-    )
+    );
+
+    let (_, password_hash) = db::users::get_password_hash(&db_pool, "someone@somewhere.com")
+        .await
+        .unwrap();
+    assert!(auth::verify_password(
+        "correct horse",
+        &password_hash.unwrap()
+    ));
 }
 
-#[sqlx::test]
-pub async fn get_users(db_pool: MySqlPool) {
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_user_with_registered_email(db_pool: TestPool) {
     let server = integration_test_init(db_pool.clone());
 
-    let schedule = Schedule {
-        id: Uuid::new_v4(),
-        period: SchedulePeriod::Weekly {
-            starting_on: NaiveDate::from_ymd_opt(2024, 11, 24).unwrap(),
-        },
-    };
+    db::users::create(
+        &db_pool,
+        User::new(
+            Uuid::new_v4(),
+            "Someone".into(),
+            "someone@somewhere.com".into(),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
 
-    db::schedule::create(&db_pool, schedule.clone())
-        .await
-        .unwrap();
+    let response = server
+        .post("/api/users")
+        .json(&CreateUserRequest {
+            email: "Someone@Somewhere.com".to_owned(),
+            name: "Someone else".to_owned(),
+            password: "correct horse".to_owned(),
+        })
+        .await;
 
-    let mut users = vec![
-        // seeded user
-        User {
-            id: uuid!("33e96445-9e8a-4ca1-b8d6-390cdfde698f"),
-            name: "super-user".into(),
-            email: "super.user@email.com".into(),
-            pay_frequency: None,
-        },
-        User {
-            id: Uuid::new_v4(),
+    response.assert_status_bad_request();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn create_user_with_short_password(db_pool: TestPool) {
+    let server = integration_test_init(db_pool.clone());
+
+    let response = server
+        .post("/api/users")
+        .json(&CreateUserRequest {
             email: "someone@somewhere.com".to_owned(),
             name: "Someone".to_owned(),
-            pay_frequency: Some(schedule),
-        },
-        User {
-            id: Uuid::new_v4(),
-            email: "someone+1@somewhere.com".to_owned(),
-            name: "Someone else".to_owned(),
-            pay_frequency: None,
-        },
-    ];
-    users.sort_by_key(|x| x.id);
-
-    for user in users.iter().filter(|u| u.email != "super.user@email.com") {
-        db::users::create(&db_pool, user.clone()).await.unwrap();
-    }
+            password: "short".to_owned(),
+        })
+        .await;
 
-    let response = server.get("/api/users").await;
-    response.assert_ok();
-    response.assert_json(&users);
+    response.assert_status_bad_request();
 }
 
-#[sqlx::test]
-pub async fn get_user(db_pool: MySqlPool) {
-    let test_server = integration_test_init(db_pool.clone());
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_current_user(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
 
     let schedule_id = Uuid::new_v4();
     let schedule = Schedule {
@@ -118,16 +124,27 @@ pub async fn get_user(db_pool: MySqlPool) {
 
     db::users::update(&db_pool, user.clone()).await.unwrap();
 
-    let response = test_server.get(&format!("/api/users/{user_id}")).await;
+    sign_in(&mut test_server, &db_pool, user_id).await;
+
+    let response = test_server.get("/api/users/me").await;
 
     response.assert_ok();
     response.assert_json(&user);
 }
 
-#[sqlx::test]
-pub async fn update_user(db_pool: MySqlPool) {
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn get_current_user_signed_out(db_pool: TestPool) {
     let test_server = integration_test_init(db_pool.clone());
 
+    let response = test_server.get("/api/users/me").await;
+
+    response.assert_status_unauthorized();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn update_user(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+
     let user_id = Uuid::new_v4();
 
     db::users::create(
@@ -137,8 +154,10 @@ pub async fn update_user(db_pool: MySqlPool) {
     .await
     .unwrap();
 
+    sign_in(&mut test_server, &db_pool, user_id).await;
+
     let response = test_server
-        .put(&format!("/api/users/{user_id}"))
+        .put("/api/users/me")
         .json(&UpdateUserRequest::new(
             "new name".into(),
             Some(UpdateScheduleRequest {
@@ -0,0 +1,184 @@
+mod common;
+use std::sync::LazyLock;
+
+use budgeting_backend::{
+    db::{self, TestPool},
+    models::{CreateBankAccountRequest, User, YnabImportRequest},
+};
+use common::*;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+static USER_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+const REGISTER: &str = "\u{feff}\"Account\",\"Flag\",\"Date\",\"Payee\",\"Category Group/Category\",\"Category Group\",\"Category\",\"Memo\",\"Outflow\",\"Inflow\",\"Cleared\"
+\"Checking\",\"\",\"11/01/2024\",\"Employer\",\"Inflow: Ready to Assign\",\"Inflow\",\"Ready to Assign\",\"\",\"$0.00\",\"$1,500.00\",\"Cleared\"
+\"Checking\",\"\",\"11/10/2024\",\"Corner Shop\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"\",\"$250.00\",\"$0.00\",\"Cleared\"
+\"Checking\",\"\",\"12/01/2024\",\"Landlord\",\"Bills: Rent\",\"Bills\",\"Rent\",\"\",\"$800.00\",\"$0.00\",\"Cleared\"
+\"Checking\",\"\",\"12/05/2024\",\"Corner Shop\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"\",\"$42.10\",\"$0.00\",\"Cleared\"
+\"Checking\",\"\",\"12/20/2024\",\"Transfer : Savings\",\"\",\"\",\"\",\"\",\"$100.00\",\"$0.00\",\"Cleared\"
+\"Savings\",\"\",\"12/20/2024\",\"Transfer : Checking\",\"\",\"\",\"\",\"\",\"$0.00\",\"$100.00\",\"Cleared\"
+";
+
+const PLAN: &str = "\u{feff}\"Month\",\"Category Group/Category\",\"Category Group\",\"Category\",\"Assigned\",\"Activity\",\"Available\"
+\"Nov 2024\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"$200.00\",\"-$250.00\",\"-$50.00\"
+\"Nov 2024\",\"Bills: Rent\",\"Bills\",\"Rent\",\"$0.00\",\"$0.00\",\"$0.00\"
+\"Dec 2024\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"$300.00\",\"-$42.10\",\"$257.90\"
+\"Dec 2024\",\"Bills: Rent\",\"Bills\",\"Rent\",\"$800.00\",\"-$800.00\",\"$0.00\"
+";
+
+async fn test_init(db_pool: &TestPool) {
+    db::users::create(
+        db_pool,
+        User::new(*USER_ID, "name".into(), "email@email.com".into(), None),
+    )
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_ynab(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    let response = test_server
+        .post(&format!("/api/plans/{plan_id}/imports/ynab"))
+        .json(&YnabImportRequest::new(
+            REGISTER.into(),
+            Some(PLAN.into()),
+            None,
+        ))
+        .await;
+
+    response.assert_created();
+    let bank_account_ids: Vec<Uuid> = response.json();
+    assert_eq!(bank_account_ids.len(), 2);
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, plan_id).await.unwrap();
+    let checking = bank_accounts.iter().find(|x| x.name == "Checking").unwrap();
+    let savings = bank_accounts.iter().find(|x| x.name == "Savings").unwrap();
+    assert_eq!(checking.balance, dec!(307.90));
+    assert_eq!(savings.balance, dec!(100));
+
+    let payees = db::payees::get(&db_pool, plan_id).await.unwrap();
+    let mut payee_names: Vec<_> = payees.iter().map(|payee| payee.name.as_str()).collect();
+    payee_names.sort_unstable();
+    assert_eq!(
+        payee_names,
+        [
+            "Corner Shop",
+            "Employer",
+            "Landlord",
+            "Transfer : Checking",
+            "Transfer : Savings"
+        ]
+    );
+
+    let groups = db::budget_groups::get(&db_pool, plan_id).await.unwrap();
+    let mut group_names: Vec<_> = groups.iter().map(|group| group.name.as_str()).collect();
+    group_names.sort_unstable();
+    assert_eq!(group_names, ["Bills", "Everyday"]);
+
+    let budgets = db::budgets::get(&db_pool, plan_id, false).await.unwrap();
+    assert_eq!(budgets.len(), 3);
+
+    let groceries = budgets.iter().find(|x| x.name == "Groceries").unwrap();
+    let everyday = groups.iter().find(|x| x.name == "Everyday").unwrap();
+    assert_eq!(groceries.group_id, Some(everyday.id));
+    assert_eq!(groceries.total_assigned(), dec!(257.90));
+
+    let rent = budgets.iter().find(|x| x.name == "Rent").unwrap();
+    assert_eq!(rent.total_assigned(), dec!(0));
+
+    // the transfer out of checking has no category
+    let uncategorised = budgets.iter().find(|x| x.name == "Uncategorised").unwrap();
+    assert_eq!(uncategorised.group_id, None);
+    assert_eq!(uncategorised.total_assigned(), dec!(-100));
+
+    let ready_to_assign = db::ready_to_assign::get(&db_pool, plan_id).await.unwrap();
+    assert_eq!(ready_to_assign, dec!(250));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_ynab_without_plan(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/ynab"))
+        .json(&YnabImportRequest::new(REGISTER.into(), None, None))
+        .await
+        .assert_created();
+
+    let budgets = db::budgets::get(&db_pool, plan_id, false).await.unwrap();
+    let groceries = budgets.iter().find(|x| x.name == "Groceries").unwrap();
+    assert_eq!(groceries.total_assigned(), dec!(-292.10));
+
+    let ready_to_assign = db::ready_to_assign::get(&db_pool, plan_id).await.unwrap();
+    assert_eq!(ready_to_assign, dec!(1600));
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_invalid_ynab(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let plan_id = *USER_ID;
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/ynab"))
+        .json(&YnabImportRequest::new(
+            REGISTER.into(),
+            Some("Month,Category Group,Category,Assigned,Available\nDecember,Everyday,Groceries,$1.00,$1.00\n".into()),
+            None,
+        ))
+        .await
+        .assert_status_bad_request();
+
+    test_server
+        .post(&format!("/api/plans/{plan_id}/imports/ynab"))
+        .json(&YnabImportRequest::new("Date,Amount\n".into(), None, None))
+        .await
+        .assert_status_bad_request();
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, plan_id).await.unwrap();
+    assert!(bank_accounts.is_empty());
+}
+
+#[sqlx::test(migrator = "budgeting_backend::migrations::TEST_MIGRATOR")]
+pub async fn import_other_users_ynab(db_pool: TestPool) {
+    let mut test_server = integration_test_init(db_pool.clone());
+    test_init(&db_pool).await;
+    sign_in(&mut test_server, &db_pool, *USER_ID).await;
+
+    let other_user_id = create_other_user(&db_pool).await;
+    db::bank_accounts::create(
+        &db_pool,
+        Uuid::new_v4(),
+        CreateBankAccountRequest::new("name".into(), dec!(0), other_user_id),
+    )
+    .await
+    .unwrap();
+
+    test_server
+        .post(&format!("/api/plans/{other_user_id}/imports/ynab"))
+        .json(&YnabImportRequest::new(
+            REGISTER.into(),
+            Some(PLAN.into()),
+            None,
+        ))
+        .await
+        .assert_status_not_found();
+
+    let bank_accounts = db::bank_accounts::get(&db_pool, other_user_id)
+        .await
+        .unwrap();
+    assert_eq!(bank_accounts.len(), 1);
+}